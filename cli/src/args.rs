@@ -1,24 +1,83 @@
-use clap::Parser;
+use build_cleaner_core::{AnonymizeMode, FreeSpaceThreshold, GroupBy, Preset, RetentionPolicy};
+use clap::{ArgGroup, Parser, Subcommand};
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
-/// 命令行参数结构
+/// 命令行入口：不带子命令时直接清理，否则执行子命令
 #[derive(Parser, Debug)]
 #[command(
     name = "bc",
     about = "A fast tool for batch cleaning temporary directories and files in projects",
     long_about = None,
-    version = env!("CARGO_PKG_VERSION")
+    version = env!("CARGO_PKG_VERSION"),
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    /// 子命令
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 不带子命令时的清理参数
+    #[command(flatten)]
+    pub args: Args,
+}
+
+/// 子命令
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Keep running and re-clean the given paths every INTERVAL until interrupted with Ctrl-C
+    Watch(WatchArgs),
+}
+
+/// `watch` 子命令的参数
+#[derive(clap::Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// Time between the start of two runs (e.g. 30m, 1h)
+    #[arg(long = "interval", value_name = "INTERVAL", value_parser = parse_duration, conflicts_with_all = ["interactive", "confirm_each_root", "require_phrase", "from", "explain", "emit", "resume"])]
+    pub interval: Duration,
+
+    /// 每一轮使用的清理参数
+    #[command(flatten)]
+    pub args: Args,
+}
+
+/// 命令行参数结构
+#[derive(Parser, Debug, Clone)]
+#[command(
+    name = "bc",
+    about = "A fast tool for batch cleaning temporary directories and files in projects",
+    long_about = None,
+    version = env!("CARGO_PKG_VERSION"),
+    group(ArgGroup::new("preview").args(["dry_run", "scan_only"]).multiple(true))
 )]
 pub struct Args {
-    /// List of paths to search (required, at least one)
-    #[arg(required = true, num_args = 1..)]
+    /// List of paths to search (required unless --from or --explain is given)
+    #[arg(required_unless_present_any = ["from", "explain"], num_args = 1..)]
     pub paths: Vec<PathBuf>,
 
-    /// Cleanup pattern list (folders end with /, files use wildcards)
+    /// Cleanup pattern list (folders end with /, files use wildcards; prefix with re: to match names by regex)
     #[arg(long = "clean", num_args = 1..)]
     pub clean_patterns: Vec<String>,
 
-    /// Configuration file path (optional, supports YAML and JSON formats)
+    /// YAML/JSON file listing cleanup patterns under separate `folders` and `files` keys (no trailing-slash guessing)
+    #[arg(long = "clean-patterns-file", value_name = "FILE")]
+    pub clean_patterns_file: Option<PathBuf>,
+
+    /// Use a built-in pattern set instead of the project-type defaults (partials: stale *.part, *.download, *.tmp-*, leftover *.lock; defaults to min_age_days 1)
+    #[arg(long = "preset", value_name = "NAME", value_parser = Preset::from_str)]
+    pub preset: Option<Preset>,
+
+    /// Disable a configured pattern for this run (repeatable)
+    #[arg(long = "disable-pattern")]
+    pub disable_patterns: Vec<String>,
+
+    /// Restrict this run to the named configured pattern (repeatable)
+    #[arg(long = "only-pattern")]
+    pub only_patterns: Vec<String>,
+
+    /// Configuration file path (optional, supports YAML and JSON formats; falls back to the BC_CONFIG environment variable)
     #[arg(long = "config")]
     pub config_file: Option<PathBuf>,
 
@@ -30,17 +89,341 @@ pub struct Args {
     #[arg(long = "interactive", short = 'i')]
     pub interactive: bool,
 
+    /// Allow scanning the home directory, `/` or system directories, which are refused by default
+    #[arg(long = "force")]
+    pub force: bool,
+
+    /// Require typing the number of items before deleting; with =<ITEMS>, only for plans of at least that many items
+    #[arg(
+        long = "require-phrase",
+        value_name = "ITEMS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "0"
+    )]
+    pub require_phrase: Option<usize>,
+
+    /// Only ask for confirmation for items at or above this size (e.g. 100MB); smaller items are deleted without prompting
+    #[arg(long = "confirm-above", value_parser = parse_size)]
+    pub confirm_above: Option<u64>,
+
+    /// With several paths, confirm or skip each root's matches as a unit before moving on to the next root
+    #[arg(long = "confirm-each-root", conflicts_with_all = ["interactive", "confirm_above", "dry_run", "scan_only"])]
+    pub confirm_each_root: bool,
+
+    /// In interactive mode, ask about the largest items first (items inside a planned directory still follow it)
+    #[arg(long = "largest-first")]
+    pub largest_first: bool,
+
     /// Enable verbose output (shows detailed cleanup report)
     #[arg(long = "verbose", short = 'v')]
     pub verbose: bool,
 
-    /// Enable quiet mode (minimal output, only shows errors)
+    /// Enable quiet mode (no progress or per-item output, only errors and the final one-line summary)
     #[arg(long = "quiet", short = 'q')]
     pub quiet: bool,
 
+    /// Print nothing on stdout, not even the final summary (implies --quiet)
+    #[arg(long = "silent")]
+    pub silent: bool,
+
     /// Enable debug mode (shows debug logs)
     #[arg(long = "debug")]
     pub debug: bool,
+
+    /// Restore the modification time of parent directories after deleting files inside them
+    #[arg(long = "preserve-parent-mtime")]
+    pub preserve_parent_mtime: bool,
+
+    /// List scanned items that were not selected for cleaning, with a reason code for each
+    #[arg(long = "explain-kept")]
+    pub explain_kept: bool,
+
+    /// Explain step by step whether this path would be cleaned and why, without scanning or deleting
+    #[arg(long = "explain", value_name = "PATH", conflicts_with_all = ["from", "save", "scan_only"])]
+    pub explain: Option<PathBuf>,
+
+    /// Print the configured patterns as a `find` command or `rsync` exclude rules instead of scanning
+    #[arg(long = "emit", value_name = "FORMAT", value_parser = ["find", "rsync"], conflicts_with_all = ["from", "explain"])]
+    pub emit: Option<String>,
+
+    /// Abort the scan once this many traversal errors occur (0 = unlimited)
+    #[arg(long = "max-walk-errors")]
+    pub max_walk_errors: Option<usize>,
+
+    /// Retry a file's metadata lookup this many times on transient errors (e.g. network filesystems)
+    #[arg(long = "stat-retries", value_name = "N")]
+    pub stat_retries: Option<u32>,
+
+    /// Also account for physical disk usage (blocks), showing logical and physical space freed
+    #[arg(long = "physical-size")]
+    pub physical_size: bool,
+
+    /// Also count the inodes freed (files, directories and links removed), for filesystems that run out of inodes before space
+    #[arg(long = "count-inodes")]
+    pub count_inodes: bool,
+
+    /// Skip matched directories smaller than this size (e.g. 100MB)
+    #[arg(long = "exclude-dirs-smaller-than", value_parser = parse_size)]
+    pub exclude_dirs_smaller_than: Option<u64>,
+
+    /// Skip matched directories larger than this size (e.g. 50GB)
+    #[arg(long = "exclude-dirs-larger-than", value_parser = parse_size)]
+    pub exclude_dirs_larger_than: Option<u64>,
+
+    /// Only walk this many levels deep when computing directory sizes (sizes become lower bounds)
+    #[arg(long = "size-max-depth")]
+    pub size_max_depth: Option<usize>,
+
+    /// When a matched folder contains excluded paths, delete everything else inside it instead of skipping it
+    #[arg(long = "clean-around-excludes")]
+    pub clean_around_excludes: bool,
+
+    /// Also clean the global caches of Cargo, npm, pip and Go (located via env vars or the tools themselves)
+    #[arg(long = "clean-tool-caches", conflicts_with = "from")]
+    pub clean_tool_caches: bool,
+
+    /// Clean symlinks to directories whose name matches a folder pattern (removes the link, not its target)
+    #[arg(long = "include-symlinked-dirs")]
+    pub include_symlinked_dirs: bool,
+
+    /// Whether a scan root that is itself a symlink is followed (default: true; interior links are governed by follow_symlinks)
+    #[arg(
+        long = "dereference-root",
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub dereference_root: Option<bool>,
+
+    /// Keep the newest matching file in each directory (e.g. the latest log) and clean the rest
+    #[arg(long = "keep-newest-per-dir")]
+    pub keep_newest_per_dir: bool,
+
+    /// In each directory, keep matches modified in the last N days and the newest M matches (e.g. 7d,5); clean the rest
+    #[arg(long = "retention", value_name = "DAYSd,COUNT", value_parser = RetentionPolicy::from_str)]
+    pub retention: Option<RetentionPolicy>,
+
+    /// Keep walking into matched folders so file patterns also match files inside them (same as `on_overlap: collect-both`)
+    #[arg(long = "descend-into-matches")]
+    pub descend_into_matches: bool,
+
+    /// Compute the sizes of matched directories in parallel on a thread pool
+    #[arg(long = "parallel-size-compute")]
+    pub parallel_size_compute: bool,
+
+    /// Scan the entries directly under each path in parallel on a thread pool
+    #[arg(long = "parallel-scan")]
+    pub parallel_scan: bool,
+
+    /// Do not clean paths ignored by .gitignore files found while scanning (.bcignore files are always honored)
+    #[arg(long = "respect-gitignore")]
+    pub respect_gitignore: bool,
+
+    /// Number of worker threads shared by all parallel stages (default: number of CPUs; 1 runs everything serially)
+    #[arg(long = "jobs", short = 'j', value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Ask for confirmation instead of skipping directories outside the size thresholds
+    #[arg(long = "confirm-out-of-range-dirs")]
+    pub confirm_out_of_range_dirs: bool,
+
+    /// Check that the trash's filesystem has room for each item first; on shortage either warn or skip the item
+    #[arg(long = "check-trash-space", value_name = "ACTION", value_parser = ["warn", "skip"])]
+    pub check_trash_space: Option<String>,
+
+    /// When undoing a deletion whose original location is occupied again, skip it (leave it in the trash) or restore it as NAME.restored
+    #[arg(long = "on-restore-conflict", value_name = "ACTION", value_parser = ["skip", "rename"])]
+    pub on_restore_conflict: Option<String>,
+
+    /// Try to clear the immutable attribute (chattr -i) before deleting items that have it (Linux, usually needs root)
+    #[arg(long = "clear-immutable")]
+    pub clear_immutable: bool,
+
+    /// Rename each directory to a hidden sibling first so it leaves its location at once, then move the renamed copy to the trash
+    #[arg(long = "rename-first")]
+    pub rename_first: bool,
+
+    /// Delete all items under one subtree before moving to the next (deepest first) instead of all files, then all directories
+    #[arg(long = "locality-order")]
+    pub locality_order: bool,
+
+    /// Remove matched directories permanently, entry by entry (deepest first), instead of in one step: slower and bypasses the trash, but shows progress and can be cancelled part-way
+    #[arg(long = "piecewise-delete")]
+    pub piecewise_delete: bool,
+
+    /// Limit deletion to this many items per second to reduce disk I/O pressure
+    #[arg(long = "throttle", value_name = "ITEMS_PER_SEC")]
+    pub throttle: Option<u32>,
+
+    /// Only clean files and directories owned by the current user (Unix only)
+    #[arg(long = "only-mine")]
+    pub only_mine: bool,
+
+    /// Only delete matched directories that contain no source files (see `source_extensions`)
+    #[arg(long = "verify-generated")]
+    pub verify_generated: bool,
+
+    /// Show the free space of each root's filesystem before and after cleaning
+    #[arg(long = "verify-free-space")]
+    pub verify_free_space: bool,
+
+    /// Only clean roots whose filesystem has less free space than this size or percentage (e.g. 10%, 20G)
+    #[arg(long = "min-free", alias = "min-free-space", value_name = "SIZE|PERCENT", value_parser = parse_free_space_threshold, conflicts_with_all = ["from", "explain", "emit"])]
+    pub min_free: Option<FreeSpaceThreshold>,
+
+    /// In dry-run mode, cross-check computed sizes of a sample of directories against `du`
+    #[arg(long = "audit-sizes", requires = "dry_run")]
+    pub audit_sizes: bool,
+
+    /// In dry-run or scan-only mode, also print N randomly chosen matched paths to spot-check the patterns
+    #[arg(
+        long = "sample",
+        value_name = "N",
+        requires = "preview",
+        conflicts_with = "json"
+    )]
+    pub sample: Option<usize>,
+
+    /// Seed for --sample so the same sample can be reproduced (random by default)
+    #[arg(long = "seed", requires = "sample")]
+    pub seed: Option<u64>,
+
+    /// Only scan and list matched items, never delete anything
+    #[arg(long = "scan-only")]
+    pub scan_only: bool,
+
+    /// Print the scan inventory as JSON (requires --scan-only)
+    #[arg(long = "json", requires = "scan_only")]
+    pub json: bool,
+
+    /// Report format, repeatable: `report` (default), `status` (a single terse line like `bc: 34d 1.2kf 4.8G 2.1s` for prompts and status bars), or a machine-readable summary as `json` or `csv`
+    #[arg(long = "format", value_name = "FORMAT", value_parser = ["report", "status", "json", "csv"], conflicts_with = "scan_only")]
+    pub format: Vec<String>,
+
+    /// Write the JSON summary to this file (implies `--format json`)
+    #[arg(
+        long = "summary-json-to",
+        value_name = "FILE",
+        conflicts_with = "scan_only"
+    )]
+    pub summary_json_to: Option<PathBuf>,
+
+    /// Write the CSV summary to this file (implies `--format csv`)
+    #[arg(
+        long = "summary-csv-to",
+        value_name = "FILE",
+        conflicts_with = "scan_only"
+    )]
+    pub summary_csv_to: Option<PathBuf>,
+
+    /// Group deleted items in the verbose report by scan root, matched pattern or project type
+    #[arg(long = "group-by", value_name = "root|pattern|type", value_parser = GroupBy::from_str)]
+    pub group_by: Option<GroupBy>,
+
+    /// Anonymize paths in the report and JSON output: home becomes ~ and parent directory names are redacted (default) or hashed
+    #[arg(
+        long = "anonymize",
+        value_name = "redact|hash",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "redact",
+        value_parser = AnonymizeMode::from_str
+    )]
+    pub anonymize: Option<AnonymizeMode>,
+
+    /// Write the report to this file instead of stdout (progress still goes to stderr)
+    #[arg(long = "output", short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// Save the scan result to this file so it can be reviewed and later deleted with --from
+    #[arg(long = "save")]
+    pub save: Option<PathBuf>,
+
+    /// Delete exactly the items in a scan result saved with --save, without scanning again
+    #[arg(long = "from", conflicts_with_all = ["save", "scan_only", "cache_file"])]
+    pub from: Option<PathBuf>,
+
+    /// Scan cache file; directories with an unchanged mtime reuse their cached size
+    #[arg(long = "cache")]
+    pub cache_file: Option<PathBuf>,
+
+    /// Record every successfully deleted item in this manifest file
+    #[arg(long = "manifest", conflicts_with = "resume")]
+    pub manifest: Option<PathBuf>,
+
+    /// Resume an interrupted run: skip items already recorded in this manifest and keep appending to it
+    #[arg(long = "resume")]
+    pub resume: Option<PathBuf>,
+
+    /// Stop scanning and deleting once this much time has passed (e.g. 90s, 10m, 1h) and report partial results
+    #[arg(long = "max-total-time", value_name = "DURATION", value_parser = parse_duration)]
+    pub max_total_time: Option<Duration>,
+}
+
+/// 解析带单位的大小字符串（如 `100MB`、`1.5G`、`4096`），返回字节数
+///
+/// 支持的单位：B、K/KB、M/MB、G/GB、T/TB（不区分大小写，按 1024 进制）
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let trimmed = input.trim();
+    let split_at = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split_at);
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size: {}", input))?;
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        "T" | "TB" => 1024 * 1024 * 1024 * 1024,
+        _ => return Err(format!("Invalid size unit: {}", input)),
+    };
+
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// 解析可用空间阈值：以 `%` 结尾时为占总大小的百分比（如 `10%`），否则为带单位的大小
+pub fn parse_free_space_threshold(input: &str) -> Result<FreeSpaceThreshold, String> {
+    match input.trim().strip_suffix('%') {
+        Some(percent) => {
+            let value: f64 = percent
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid percentage: {}", input))?;
+            if !(0.0..=100.0).contains(&value) {
+                return Err(format!("Percentage must be between 0 and 100: {}", input));
+            }
+            Ok(FreeSpaceThreshold::Percent(value))
+        }
+        None => parse_size(input).map(FreeSpaceThreshold::Bytes),
+    }
+}
+
+/// 解析带单位的时长字符串（如 `500ms`、`90s`、`10m`、`1.5h`），没有单位时按秒计算
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let trimmed = input.trim();
+    let split_at = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split_at);
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration: {}", input))?;
+    let seconds = match unit.trim().to_lowercase().as_str() {
+        "ms" => value / 1000.0,
+        "" | "s" | "sec" => value,
+        "m" | "min" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("Invalid duration unit: {}", input)),
+    };
+
+    Ok(Duration::from_secs_f64(seconds))
 }
 
 #[cfg(test)]
@@ -50,7 +433,7 @@ mod tests {
     #[test]
     fn test_args_parsing() {
         // 测试基本参数解析
-        let args = Args::try_parse_from(&["bc", "."]).unwrap();
+        let args = Args::try_parse_from(["bc", "."]).unwrap();
         assert_eq!(args.paths.len(), 1);
         assert!(!args.dry_run);
         assert!(!args.interactive);
@@ -63,7 +446,7 @@ mod tests {
     fn test_args_with_options() {
         // 测试带选项的参数解析
         // 注意：--clean 需要单独的参数，路径必须在最后
-        let args = Args::try_parse_from(&[
+        let args = Args::try_parse_from([
             "bc",
             ".",
             "--dry-run",
@@ -84,14 +467,14 @@ mod tests {
     #[test]
     fn test_args_multiple_paths() {
         // 测试多个路径
-        let args = Args::try_parse_from(&["bc", ".", "~/project1", "~/project2"]).unwrap();
+        let args = Args::try_parse_from(["bc", ".", "~/project1", "~/project2"]).unwrap();
         assert_eq!(args.paths.len(), 3);
     }
 
     #[test]
     fn test_args_multiple_clean_patterns() {
         // 测试多个清理模式（多次使用 --clean 选项）
-        let args = Args::try_parse_from(&[
+        let args = Args::try_parse_from([
             "bc",
             ".",
             "--clean",
@@ -109,19 +492,205 @@ mod tests {
         assert_eq!(args.paths.len(), 1);
     }
 
+    #[test]
+    fn test_args_pattern_filters() {
+        let args = Args::try_parse_from([
+            "bc",
+            ".",
+            "--disable-pattern",
+            "node_modules",
+            "--disable-pattern",
+            "*.log",
+            "--only-pattern",
+            "target/",
+        ])
+        .unwrap();
+        assert_eq!(args.disable_patterns, vec!["node_modules", "*.log"]);
+        assert_eq!(args.only_patterns, vec!["target/"]);
+    }
+
+    #[test]
+    fn test_args_json_requires_scan_only() {
+        assert!(Args::try_parse_from(["bc", ".", "--json"]).is_err());
+        let args = Args::try_parse_from(["bc", ".", "--scan-only", "--json"]).unwrap();
+        assert!(args.scan_only);
+        assert!(args.json);
+    }
+
+    #[test]
+    fn test_args_sample_requires_preview() {
+        assert!(Args::try_parse_from(["bc", ".", "--sample", "3"]).is_err());
+        assert!(Args::try_parse_from(["bc", ".", "--dry-run", "--sample", "3"]).is_ok());
+        let args = Args::try_parse_from(["bc", ".", "--scan-only", "--sample", "3"]).unwrap();
+        assert_eq!(args.sample, Some(3));
+        // 抽样列表会破坏 JSON 输出
+        assert!(
+            Args::try_parse_from(["bc", ".", "--scan-only", "--json", "--sample", "3"]).is_err()
+        );
+        // 作为子命令的参数时同样生效
+        assert!(
+            Cli::try_parse_from(["bc", "watch", ".", "--interval", "1h", "--sample", "3"]).is_err()
+        );
+    }
+
     #[test]
     fn test_args_short_options() {
         // 测试短选项
-        let args = Args::try_parse_from(&["bc", "-i", "-v", "-q", "."]).unwrap();
+        let args = Args::try_parse_from(["bc", "-i", "-v", "-q", "."]).unwrap();
         assert!(args.interactive);
         assert!(args.verbose);
         assert!(args.quiet);
     }
 
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("10B"), Ok(10));
+        assert_eq!(parse_size("2k"), Ok(2048));
+        assert_eq!(parse_size("100MB"), Ok(100 * 1024 * 1024));
+        assert_eq!(parse_size("1.5G"), Ok(1536 * 1024 * 1024));
+        assert!(parse_size("abc").is_err());
+        assert!(parse_size("10XB").is_err());
+
+        let args =
+            Args::try_parse_from(["bc", ".", "--exclude-dirs-smaller-than", "100MB"]).unwrap();
+        assert_eq!(args.exclude_dirs_smaller_than, Some(100 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_parse_free_space_threshold() {
+        assert_eq!(
+            parse_free_space_threshold("10%"),
+            Ok(FreeSpaceThreshold::Percent(10.0))
+        );
+        assert_eq!(
+            parse_free_space_threshold("20G"),
+            Ok(FreeSpaceThreshold::Bytes(20 * 1024 * 1024 * 1024))
+        );
+        assert!(parse_free_space_threshold("150%").is_err());
+        assert!(parse_free_space_threshold("abc%").is_err());
+
+        let args = Args::try_parse_from(["bc", ".", "--min-free", "10%"]).unwrap();
+        assert_eq!(args.min_free, Some(FreeSpaceThreshold::Percent(10.0)));
+        // 与从保存的扫描结果删除冲突（没有要查询的根路径）
+        assert!(Args::try_parse_from(["bc", "--from", "scan.json", "--min-free", "10%"]).is_err());
+    }
+
     #[test]
     fn test_args_config_file() {
         // 测试配置文件选项
-        let args = Args::try_parse_from(&["bc", "--config", ".bc.yaml", "."]).unwrap();
+        let args = Args::try_parse_from(["bc", "--config", ".bc.yaml", "."]).unwrap();
         assert_eq!(args.config_file, Some(PathBuf::from(".bc.yaml")));
     }
+
+    #[test]
+    fn test_args_from_without_paths() {
+        // 从保存的扫描结果删除时不需要路径
+        let args = Args::try_parse_from(["bc", "--from", "scan.json"]).unwrap();
+        assert_eq!(args.from, Some(PathBuf::from("scan.json")));
+        assert!(args.paths.is_empty());
+
+        // 没有 --from 时仍然必须指定路径
+        assert!(Args::try_parse_from(["bc"]).is_err());
+    }
+
+    #[test]
+    fn test_args_require_phrase() {
+        // 不带值时总是要求确认短语，路径不会被当作值
+        let args = Args::try_parse_from(["bc", "--require-phrase", "."]).unwrap();
+        assert_eq!(args.require_phrase, Some(0));
+        assert_eq!(args.paths, vec![PathBuf::from(".")]);
+
+        let args = Args::try_parse_from(["bc", "--require-phrase=500", "."]).unwrap();
+        assert_eq!(args.require_phrase, Some(500));
+
+        let args = Args::try_parse_from(["bc", "."]).unwrap();
+        assert_eq!(args.require_phrase, None);
+    }
+
+    #[test]
+    fn test_args_anonymize() {
+        // 不带值时隐藏目录名称，路径不会被当作值
+        let args = Args::try_parse_from(["bc", "--anonymize", "."]).unwrap();
+        assert_eq!(args.anonymize, Some(AnonymizeMode::Redact));
+        assert_eq!(args.paths, vec![PathBuf::from(".")]);
+
+        let args = Args::try_parse_from(["bc", "--anonymize=hash", "."]).unwrap();
+        assert_eq!(args.anonymize, Some(AnonymizeMode::Hash));
+
+        assert!(Args::try_parse_from(["bc", "--anonymize=scramble", "."]).is_err());
+    }
+
+    #[test]
+    fn test_args_dereference_root() {
+        let args = Args::try_parse_from(["bc", "."]).unwrap();
+        assert_eq!(args.dereference_root, None);
+
+        // 不带值时启用，路径不会被当作值
+        let args = Args::try_parse_from(["bc", "--dereference-root", "."]).unwrap();
+        assert_eq!(args.dereference_root, Some(true));
+        assert_eq!(args.paths, vec![PathBuf::from(".")]);
+
+        let args = Args::try_parse_from(["bc", "--dereference-root=false", "."]).unwrap();
+        assert_eq!(args.dereference_root, Some(false));
+    }
+
+    #[test]
+    fn test_args_preset() {
+        let args = Args::try_parse_from(["bc", "--preset", "partials", "."]).unwrap();
+        assert_eq!(args.preset, Some(Preset::Partials));
+        assert!(Args::try_parse_from(["bc", "--preset", "downloads", "."]).is_err());
+    }
+
+    #[test]
+    fn test_args_on_restore_conflict() {
+        let args = Args::try_parse_from(["bc", "--on-restore-conflict", "rename", "."]).unwrap();
+        assert_eq!(args.on_restore_conflict.as_deref(), Some("rename"));
+        let args = Args::try_parse_from(["bc", "."]).unwrap();
+        assert_eq!(args.on_restore_conflict, None);
+        assert!(Args::try_parse_from(["bc", "--on-restore-conflict", "overwrite", "."]).is_err());
+    }
+
+    #[test]
+    fn test_cli_watch_subcommand() {
+        // 不带子命令时直接清理
+        let cli = Cli::try_parse_from(["bc", "."]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.args.paths, vec![PathBuf::from(".")]);
+
+        // watch 子命令接受与直接清理相同的参数
+        let cli = Cli::try_parse_from([
+            "bc",
+            "watch",
+            "/projects",
+            "--interval",
+            "1h",
+            "--clean",
+            "target/",
+        ])
+        .unwrap();
+        let Some(Command::Watch(watch)) = cli.command else {
+            panic!("expected the watch subcommand");
+        };
+        assert_eq!(watch.interval, Duration::from_secs(3600));
+        assert_eq!(watch.args.paths, vec![PathBuf::from("/projects")]);
+        assert_eq!(watch.args.clean_patterns, vec!["target/"]);
+
+        // 必须指定间隔，且不能与需要逐项确认的选项一起使用
+        assert!(Cli::try_parse_from(["bc", "watch", "/projects"]).is_err());
+        assert!(
+            Cli::try_parse_from(["bc", "watch", "/projects", "--interval", "1h", "-i"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_duration("1.5h"), Ok(Duration::from_secs(5400)));
+        assert!(parse_duration("10 days").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
 }
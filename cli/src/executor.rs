@@ -1,7 +1,75 @@
 use crate::args::Args;
-use build_cleaner_core::{CleanError, ConfigLoader, DeleteEngine, ReportGenerator, SearchEngine};
+use crate::interactive::{ItemAction, ProjectTypeChoice};
+use crate::output::{DeleteProgress, ProgressLine, ReportLevel};
+use build_cleaner_core::{
+    CancelFlag, CleanError, Config, ConfigLoader, DeleteEngine, DeleteManifest, DeleteOptions,
+    DeletePlan, DeleteResult, DirSizeAction, DiskSpace, ExcludedChildAction, Explanation,
+    FileScanCache, FreeSpaceThreshold, OverlapPolicy, PathAnonymizer, ProjectType, ReportGenerator,
+    RestoreConflict, SearchEngine, SearchResult, Stats, SystemTrash, ToolCacheDiscovery, TrashOps,
+    TrashSpaceAction, Verdict, CONFIG_ENV_VAR,
+};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+/// 一次运行的结果摘要，便于嵌入和测试时检查执行情况
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    /// 统计信息
+    pub stats: Stats,
+    /// 删除结果（预览模式下为将要删除的内容）
+    pub delete_result: DeleteResult,
+    /// 是否为预览模式（包括只扫描模式）
+    pub dry_run: bool,
+    /// 用户是否在确认过程中取消了操作
+    pub cancelled: bool,
+    /// 是否因超出 `--max-total-time` 时间预算而提前结束（结果不完整）
+    pub timed_out: bool,
+}
+
+impl RunSummary {
+    /// 有项目删除失败时的退出码
+    pub const EXIT_FAILED_ITEMS: i32 = 2;
+    /// 超出时间预算、结果不完整时的退出码
+    pub const EXIT_TIMED_OUT: i32 = 3;
+
+    /// 根据运行结果确定进程退出码：超出时间预算时为 `EXIT_TIMED_OUT`，
+    /// 实际删除时有项目失败为 `EXIT_FAILED_ITEMS`，否则为 0
+    pub fn exit_code(&self) -> i32 {
+        let failed = !self.delete_result.failed_files.is_empty()
+            || !self.delete_result.failed_dirs.is_empty();
+        if self.timed_out {
+            Self::EXIT_TIMED_OUT
+        } else if failed && !self.dry_run {
+            Self::EXIT_FAILED_ITEMS
+        } else {
+            0
+        }
+    }
+
+    /// 用户取消操作时的结果
+    fn cancelled() -> Self {
+        RunSummary {
+            cancelled: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// 机器可读统计摘要的格式（`json` 或 `csv`）及其输出目标
+type SummarySink = (&'static str, Box<dyn Write>);
+
+/// 一个扫描根路径下的删除计划，用于按根路径确认
+struct RootPlan {
+    /// 扫描根路径
+    root: PathBuf,
+    /// 该根路径下要删除的项目
+    plan: DeletePlan,
+    /// 该根路径下要删除的总大小（字节）
+    size: u64,
+}
+
 /// 命令执行器，负责执行清理命令的完整流程
 pub struct CommandExecutor;
 
@@ -20,30 +88,260 @@ impl CommandExecutor {
     /// * `args` - 命令行参数
     ///
     /// # 返回
-    /// 如果执行成功返回 `Ok(())`，否则返回错误
-    pub fn execute(args: Args) -> Result<(), CleanError> {
+    /// 如果执行成功返回进程退出码（见 `RunSummary::exit_code`），否则返回错误（用户取消也视为错误）
+    pub fn execute(args: Args) -> Result<i32, CleanError> {
+        let summary = Self::execute_with_result(args)?;
+        if summary.cancelled {
+            return Err(CleanError::Cancelled);
+        }
+        Ok(summary.exit_code())
+    }
+
+    /// 执行清理命令并返回结果摘要，流程与 `execute` 相同
+    ///
+    /// # 参数
+    /// * `args` - 命令行参数
+    ///
+    /// # 返回
+    /// 运行结果摘要，用户取消时 `cancelled` 为 `true`
+    pub fn execute_with_result(mut args: Args) -> Result<RunSummary, CleanError> {
         let start_time = Instant::now();
+        // --silent 在静默模式的基础上连单行摘要也不输出
+        args.quiet |= args.silent;
 
-        // 展开并验证所有路径
-        let mut expanded_paths = Vec::new();
-        for path in &args.paths {
-            let expanded = if path.to_string_lossy().starts_with('~') {
-                ConfigLoader::expand_path(&path.to_string_lossy())
+        // 从保存的扫描结果删除或解释单个路径时可以不指定路径
+        let mut expanded_paths =
+            if (args.from.is_some() || args.explain.is_some()) && args.paths.is_empty() {
+                Vec::new()
             } else {
-                path.clone()
+                Self::expand_paths(&args.paths)?
             };
-            ConfigLoader::validate_path(&expanded)?;
-            expanded_paths.push(expanded);
+
+        // 在扫描开始前拒绝主目录、根目录和系统目录，而不是等到删除时才逐项拦截
+        Self::check_scan_roots(
+            &expanded_paths,
+            ConfigLoader::home_dir().as_deref(),
+            args.force,
+        )?;
+
+        // 只在磁盘空间不足时清理：只清理可用空间低于阈值的根路径，都高于阈值时直接结束
+        if let Some(threshold) = &args.min_free {
+            let spaces = Self::query_free_space(&expanded_paths);
+            if spaces.is_empty() {
+                return Err(CleanError::Other(
+                    "Could not determine free space for any root, refusing to clean with --min-free"
+                        .to_string(),
+                ));
+            }
+            let (low, healthy) = Self::split_low_space_roots(spaces, threshold);
+            if !args.quiet {
+                for (root, space) in &healthy {
+                    println!(
+                        "💽 {}: {}",
+                        root.display(),
+                        ReportGenerator::format_free_space_skip(space, threshold)
+                    );
+                }
+            }
+            if low.is_empty() {
+                return Ok(RunSummary::default());
+            }
+            expanded_paths = low;
         }
 
-        let config = ConfigLoader::load_config(
-            &expanded_paths[0],
+        // 没有指定路径时，按被解释路径所在的目录检测项目类型
+        let explain_dir = args
+            .explain
+            .as_deref()
+            .and_then(|path| path.parent())
+            .filter(|parent| !parent.as_os_str().is_empty());
+        let mut config = ConfigLoader::load_config_with_preset(
+            expanded_paths.first().map(|p| p.as_path()).or(explain_dir),
             args.config_file.as_deref(),
             &args.clean_patterns,
+            args.preset,
         )?;
 
+        // 交互模式下没有配置文件和预设且无法识别项目类型时，询问用户要使用的项目类型，
+        // 而不是静默地使用覆盖面较广的通用默认配置
+        if args.interactive
+            && !args.quiet
+            && args.preset.is_none()
+            && args.from.is_none()
+            && args.config_file.is_none()
+            && std::env::var_os(CONFIG_ENV_VAR).is_none()
+        {
+            if let Some(path) = expanded_paths.first() {
+                let detected = ConfigLoader::detect_project_type(path);
+                if detected == ProjectType::Unknown {
+                    let choice = crate::interactive::prompt_project_type()?;
+                    config = Self::resolve_project_config(
+                        config,
+                        &detected,
+                        choice,
+                        &args.clean_patterns,
+                    )?;
+                }
+            }
+        }
+
+        // 模式文件明确区分文件夹和文件，与命令行模式一样合并到配置中
+        if let Some(patterns_file) = &args.clean_patterns_file {
+            ConfigLoader::validate_path(patterns_file)?;
+            let patterns = ConfigLoader::parse_clean_patterns_file(patterns_file)?;
+            ConfigLoader::merge_clean_patterns(&mut config, &patterns);
+            ConfigLoader::validate_config(&config)?;
+        }
+
+        // 在合并后的配置上启用或禁用单个模式，便于排查而无需修改配置文件
+        if !args.only_patterns.is_empty() || !args.disable_patterns.is_empty() {
+            if !args.only_patterns.is_empty() {
+                ConfigLoader::only_patterns(&mut config, &args.only_patterns);
+            }
+            ConfigLoader::disable_patterns(&mut config, &args.disable_patterns);
+            ConfigLoader::validate_config(&config)?;
+        }
+
+        // 命令行选项覆盖配置文件中的选项
+        if args.preserve_parent_mtime {
+            config.options.preserve_parent_mtime = true;
+        }
+        if args.explain_kept {
+            config.options.explain = true;
+        }
+        if let Some(max_walk_errors) = args.max_walk_errors {
+            config.options.max_walk_errors = max_walk_errors;
+        }
+        if let Some(stat_retries) = args.stat_retries {
+            config.options.stat_retries = stat_retries;
+        }
+        if args.physical_size {
+            config.options.physical_size = true;
+        }
+        if args.count_inodes {
+            config.options.count_inodes = true;
+        }
+        if args.exclude_dirs_smaller_than.is_some() {
+            config.options.dir_min_size = args.exclude_dirs_smaller_than;
+        }
+        if args.exclude_dirs_larger_than.is_some() {
+            config.options.dir_max_size = args.exclude_dirs_larger_than;
+        }
+        if args.throttle.is_some() {
+            config.options.throttle = args.throttle;
+        }
+        #[cfg(unix)]
+        if args.only_mine {
+            // SAFETY: getuid 总是成功且没有副作用
+            config.options.only_owned_by = Some(unsafe { libc::getuid() });
+        }
+        if args.verify_generated {
+            config.options.verify_generated = true;
+        }
+        if args.size_max_depth.is_some() {
+            config.options.size_max_depth = args.size_max_depth;
+        }
+        if args.clean_around_excludes {
+            config.options.excluded_child_action = ExcludedChildAction::Partial;
+        }
+        if args.descend_into_matches {
+            config.options.on_overlap = OverlapPolicy::CollectBoth;
+        }
+        if args.keep_newest_per_dir {
+            config.options.keep_newest_per_dir = true;
+        }
+        if let Some(retention) = args.retention {
+            config.options.retention = Some(retention);
+        }
+        if args.include_symlinked_dirs {
+            config.options.include_symlinked_dirs = true;
+        }
+        if let Some(dereference_root) = args.dereference_root {
+            config.options.dereference_root = dereference_root;
+        }
+        if args.parallel_size_compute {
+            config.options.parallel_size_compute = true;
+        }
+        if args.parallel_scan {
+            config.options.parallel_scan = true;
+        }
+        if args.respect_gitignore {
+            config.options.respect_gitignore = true;
+        }
+        if args.jobs.is_some() {
+            config.options.jobs = args.jobs;
+        }
+        if args.confirm_out_of_range_dirs {
+            config.options.dir_size_action = DirSizeAction::Confirm;
+        }
+        if args.clear_immutable {
+            config.options.clear_immutable = true;
+        }
+        if args.rename_first {
+            config.options.rename_first = true;
+        }
+        if args.locality_order {
+            config.options.locality_order = true;
+        }
+        if args.piecewise_delete {
+            config.options.piecewise_delete = true;
+        }
+        if let Some(action) = args.check_trash_space.as_deref() {
+            config.options.trash_space_check = Some(match action {
+                "skip" => TrashSpaceAction::Skip,
+                _ => TrashSpaceAction::Warn,
+            });
+        }
+        if let Some(action) = args.on_restore_conflict.as_deref() {
+            config.options.restore_conflict = match action {
+                "rename" => RestoreConflict::Rename,
+                _ => RestoreConflict::Skip,
+            };
+        }
+
+        // 转换模式：只把配置中的模式转换为其他工具的语法，不访问文件系统
+        if let Some(format) = args.emit.as_deref() {
+            let emitted = match format {
+                "rsync" => ReportGenerator::emit_rsync(&config),
+                _ => ReportGenerator::emit_find(&config),
+            };
+            println!("{}", emitted);
+            return Ok(RunSummary {
+                dry_run: true,
+                ..Default::default()
+            });
+        }
+
+        // 解释模式：只判定单个路径，不扫描也不删除
+        if let Some(path) = &args.explain {
+            let explanation = SearchEngine::explain(path, &expanded_paths, &config)?;
+            Self::print_explanation(&explanation);
+            return Ok(RunSummary {
+                dry_run: true,
+                ..Default::default()
+            });
+        }
+
+        let mut delete_options: DeleteOptions = (&config.options).into();
+        delete_options.manifest = args.resume.clone().or_else(|| args.manifest.clone());
+        // 详细模式下按扩展名汇总释放的空间，在计算项目大小时一并统计
+        delete_options.extension_breakdown = args.verbose;
+        // 超出时间预算后设置取消标志，扫描和删除都会在下一个项目之前结束
+        let cancel = args
+            .max_total_time
+            .map(|budget| CancelFlag::with_deadline(start_time + budget));
+        delete_options.cancel = cancel.clone();
+
+        // 报告输出目标，提前创建以便在扫描前发现无法写入的文件
+        Self::check_format_sinks(&args)?;
+        let mut report_sink = crate::output::open_report_sink(args.output.as_deref())?;
+        let mut summary_sinks = Self::open_summary_sinks(&args)?;
+
+        // 只扫描模式的匹配清单可能写到标准输出，扫描期间不输出其他信息
+        let quiet = args.quiet || args.scan_only;
+
         // 显示扫描开始信息（即使非 verbose 模式也显示，避免用户以为程序卡住）
-        if !args.quiet {
+        if !quiet && args.from.is_none() {
             crate::output::print_scanning_start(args.dry_run);
         }
 
@@ -61,54 +359,218 @@ impl CommandExecutor {
             format!("{:.2} {}", size, UNITS[unit_idx])
         }
 
-        // 设置进度回调
-        let progress_callback = if !args.quiet {
-            Some(
-                |files_scanned: usize,
-                 dirs_scanned: usize,
-                 files_matched: usize,
-                 dirs_matched: usize,
-                 total_size: u64| {
-                    // 格式化大小
-                    let size_str = format_size(total_size);
-                    eprint!(
-                        "\r📊 Scanning... Files: {}, Dirs: {}, Matched: {} files, {} dirs, Size: {}",
-                        files_scanned, dirs_scanned, files_matched, dirs_matched, size_str
-                    );
-                    use std::io::Write;
-                    let _ = std::io::stderr().flush();
-                },
-            )
-        } else {
-            None
-        };
+        // 设置进度回调，进度行写到标准错误，结束（或出错）时总会换行
+        let progress =
+            (!quiet).then(|| std::cell::RefCell::new(ProgressLine::new(std::io::stderr())));
+        let progress_callback = progress.as_ref().map(|progress| {
+            move |files_scanned: usize,
+                  dirs_scanned: usize,
+                  files_matched: usize,
+                  dirs_matched: usize,
+                  total_size: u64| {
+                // 格式化大小
+                let size_str = format_size(total_size);
+                progress.borrow_mut().update(&format!(
+                    "📊 Scanning... Files: {}, Dirs: {}, Matched: {} files, {} dirs, Size: {}",
+                    files_scanned, dirs_scanned, files_matched, dirs_matched, size_str
+                ));
+            }
+        });
 
-        let search_result =
-            SearchEngine::search_with_progress(&expanded_paths, &config, progress_callback)?;
+        let mut search_result = match (&args.from, &args.cache_file) {
+            (Some(from), _) => Self::load_saved_result(from, quiet)?,
+            (None, Some(cache_file)) => {
+                let mut cache = FileScanCache::load(cache_file)?;
+                let result = SearchEngine::search_with_cancel(
+                    &expanded_paths,
+                    &config,
+                    Some(&mut cache),
+                    progress_callback,
+                    cancel.clone(),
+                )?;
+                cache.prune();
+                cache.save(cache_file)?;
+                result
+            }
+            (None, None) => SearchEngine::search_with_cancel(
+                &expanded_paths,
+                &config,
+                None,
+                progress_callback,
+                cancel.clone(),
+            )?,
+        };
 
         // 清除进度行并换行
-        if !args.quiet {
-            eprintln!("\r✅ Scanning completed");
+        if let Some(progress) = &progress {
+            if args.from.is_none() {
+                progress.borrow_mut().finish("✅ Scanning completed");
+            }
+        }
+        if args.verbose && !quiet && !search_result.special_files.is_empty() {
+            println!(
+                "ℹ️  Skipped {} special files (FIFOs, sockets or devices)",
+                search_result.special_files.len()
+            );
+        }
+        if !quiet && !search_result.case_duplicates.is_empty() {
+            crate::output::print_warning(&format!(
+                "{} matches differ only in case from another match on a case-insensitive filesystem and were collapsed: {}",
+                search_result.case_duplicates.len(),
+                search_result
+                    .case_duplicates
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !quiet && !search_result.symlinked_dirs.is_empty() {
+            println!(
+                "🔗 Skipped {} symlinked directories matching folder patterns (use --include-symlinked-dirs to remove the links)",
+                search_result.symlinked_dirs.len()
+            );
+            if args.verbose {
+                for link in &search_result.symlinked_dirs {
+                    println!("   - {}", link.display());
+                }
+            }
+        }
+
+        if args.clean_tool_caches {
+            Self::add_tool_caches(&mut search_result, &expanded_paths, &config, quiet);
+        }
+
+        // 只扫描模式：输出匹配清单后直接返回，不进行任何删除
+        if args.scan_only {
+            Self::execute_scan_only(&args, &search_result, &mut *report_sink)?;
+            return Ok(RunSummary {
+                dry_run: true,
+                timed_out: Self::report_time_budget(&args, cancel.as_ref()),
+                ..Default::default()
+            });
+        }
+
+        if let Some(save) = &args.save {
+            search_result.save(save)?;
+            if !args.quiet {
+                println!("💾 Scan result saved to {}", save.display());
+            }
+        }
+
+        if !args.dry_run && !args.quiet && config.options.piecewise_delete {
+            crate::output::print_warning(
+                "matched directories are removed permanently entry by entry and cannot be restored from the trash",
+            );
         }
 
         if args.dry_run {
             // 在 dry-run 模式下，文件大小和目录大小都已经在搜索阶段计算完成了
             // 直接使用 SearchResult 中的 total_size，避免重复计算
-            let delete_plan = DeleteEngine::create_delete_plan(&search_result);
-            let delete_result = DeleteEngine::execute_deletion(&delete_plan, true);
+            let delete_result = DeleteEngine::execute_deletion_from_search_with_options(
+                &search_result,
+                true,
+                &delete_options,
+            );
             let stats = ReportGenerator::collect_stats(&search_result, &delete_result, start_time);
-            let report = ReportGenerator::format_report(&stats, &delete_result, args.verbose);
-            println!("{}", report);
-            if !args.verbose {
-                println!("ℹ️  Run without --dry-run to actually clean");
+            if let Some(report) = Self::human_report(&args, &stats, &search_result, &delete_result)
+            {
+                crate::output::write_report(&mut *report_sink, &report)?;
             }
-            return Ok(());
+            Self::write_summaries(&mut summary_sinks, &stats)?;
+            if !args.verbose && !args.quiet && Self::human_format(&args) == Some("report") {
+                crate::output::write_report(
+                    &mut *report_sink,
+                    "ℹ️  Run without --dry-run to actually clean",
+                )?;
+            }
+            if args.explain_kept {
+                Self::write_rejected(&mut *report_sink, &search_result)?;
+            }
+            if args.audit_sizes {
+                Self::audit_sizes(&search_result, args.quiet);
+            }
+            if let Some(n) = args.sample {
+                Self::write_sample(&mut *report_sink, &search_result, n, args.seed)?;
+            }
+            let timed_out = Self::report_time_budget(&args, cancel.as_ref());
+            return Ok(RunSummary {
+                stats,
+                delete_result,
+                dry_run: true,
+                cancelled: false,
+                timed_out,
+            });
         }
 
-        let delete_plan = DeleteEngine::create_delete_plan(&search_result);
+        // 非交互模式下，大小超出阈值的目录仍需逐个确认（交互模式下本来就会逐个确认）
+        // 指定 --confirm-above 时也逐个处理，只是小项目不再询问
+        let interactive = args.interactive || args.confirm_above.is_some();
+        if !interactive && !search_result.needs_confirmation.is_empty() {
+            match Self::confirm_out_of_range_dirs(&mut search_result, args.quiet) {
+                Err(CleanError::Cancelled) => return Ok(RunSummary::cancelled()),
+                result => result?,
+            }
+        }
+
+        let mut delete_plan = DeleteEngine::create_delete_plan(&search_result);
+
+        // 恢复模式：跳过清单中已经完成删除的项目
+        if let Some(resume) = &args.resume {
+            let committed = DeleteManifest::load_committed(resume)?;
+            let (remaining, skipped) = DeleteManifest::reconcile(&delete_plan, &committed);
+            if !args.quiet {
+                println!(
+                    "⏩ Resuming from {}: skipped {} already deleted items, {} items remaining",
+                    resume.display(),
+                    skipped.len(),
+                    remaining.files.len() + remaining.dirs.len()
+                );
+            }
+            delete_plan = remaining;
+        }
+
+        // 删除大量内容前要求输入确认短语，防止习惯性确认
+        let item_count = delete_plan.files.len() + delete_plan.dirs.len();
+        if let Some(min_items) = args.require_phrase {
+            if item_count > 0
+                && item_count >= min_items
+                && !crate::interactive::confirm_with_phrase(item_count, search_result.total_size)?
+            {
+                if !args.quiet {
+                    println!("  ❌ Confirmation phrase did not match, nothing was deleted");
+                }
+                return Ok(RunSummary::cancelled());
+            }
+        }
+
+        // 记录每个根路径所在文件系统在清理前的可用空间
+        let free_space_before = args
+            .verify_free_space
+            .then(|| Self::query_free_space(&expanded_paths));
+
+        // 按根路径确认时，用户在某个根路径选择退出后不再处理剩余的根路径
+        let mut quit_early = false;
 
         // 交互模式下，直接逐个确认删除（不再显示批量确认，避免重复）
-        let delete_result = if args.interactive {
+        let delete_result = if args.confirm_each_root {
+            let root_plans = Self::plans_by_root(&delete_plan, &search_result, &expanded_paths);
+            let (result, quit) = Self::execute_deletion_per_root(
+                &root_plans,
+                args.quiet,
+                |root_plan| {
+                    crate::interactive::confirm_root_deletion(
+                        &root_plan.root,
+                        root_plan.plan.dirs.len(),
+                        root_plan.plan.files.len(),
+                        root_plan.size,
+                    )
+                },
+                |plan| DeleteEngine::execute_deletion_with_options(plan, false, &delete_options),
+            )?;
+            quit_early = quit;
+            result
+        } else if interactive {
             if !args.quiet {
                 let total_items = delete_plan.files.len() + delete_plan.dirs.len();
                 println!(
@@ -120,26 +582,84 @@ impl CommandExecutor {
                 println!(
                     "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
                 );
-                println!(
-                    "⚠️  You will be prompted for each item. Options: y=yes, N=skip, a=all, q=quit"
-                );
+                match args.confirm_above {
+                    Some(threshold) => println!(
+                        "⚠️  You will be prompted for items of {} or more; smaller items are deleted automatically. Options: y=yes, N=skip, a=all, q=quit, u=undo last",
+                        format_size(threshold)
+                    ),
+                    None => println!(
+                        "⚠️  You will be prompted for each item. Options: y=yes, N=skip, a=all, q=quit, u=undo last"
+                    ),
+                }
                 println!(
                     "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
                 );
             }
-            Self::execute_deletion_interactive(&delete_plan, args.quiet)?
+            match Self::execute_deletion_interactive(
+                &delete_plan,
+                &delete_options,
+                args.quiet,
+                args.confirm_above,
+                args.largest_first
+                    .then_some(search_result.item_sizes.as_slice()),
+                &SystemTrash,
+                crate::interactive::confirm_item_deletion,
+            ) {
+                Err(CleanError::Cancelled) => return Ok(RunSummary::cancelled()),
+                result => result?,
+            }
         } else {
             // 非交互模式下，显示清理开始信息
             if args.verbose && !args.quiet {
                 println!("🧹 Cleaning...");
             }
-            DeleteEngine::execute_deletion(&delete_plan, false)
+            // 删除进度和预计剩余时间写到标准错误，删除很快结束时不显示
+            let progress = (!args.quiet)
+                .then(|| std::cell::RefCell::new(DeleteProgress::new(std::io::stderr())));
+            let progress_callback = progress.as_ref().map(|progress| {
+                Box::new(move |done: usize, total: usize, _path: &Path| {
+                    progress.borrow_mut().update(done, total);
+                }) as Box<dyn FnMut(usize, usize, &Path) + '_>
+            });
+            let result = DeleteEngine::execute_deletion_with_progress(
+                &delete_plan,
+                false,
+                &delete_options,
+                progress_callback,
+            );
+            if let Some(progress) = &progress {
+                progress.borrow_mut().finish();
+            }
+            result
         };
 
         let stats = ReportGenerator::collect_stats(&search_result, &delete_result, start_time);
 
-        let report = ReportGenerator::format_report(&stats, &delete_result, args.verbose);
-        crate::output::print_report(&report, args.quiet);
+        if let Some(report) = Self::human_report(&args, &stats, &search_result, &delete_result) {
+            crate::output::write_report(&mut *report_sink, &report)?;
+        }
+        // 机器可读的统计摘要在静默模式下也总是输出
+        Self::write_summaries(&mut summary_sinks, &stats)?;
+        let level = ReportLevel::new(args.quiet, args.silent, args.output.is_some());
+        if let Some(before) = free_space_before.filter(|_| level != ReportLevel::Silent) {
+            let after = Self::query_free_space(&expanded_paths);
+            let roots: Vec<_> = before
+                .into_iter()
+                .filter_map(|(root, before)| {
+                    after
+                        .iter()
+                        .find(|(path, _)| *path == root)
+                        .map(|(_, after)| (root, before, *after))
+                })
+                .collect();
+            crate::output::write_report(
+                &mut *report_sink,
+                &ReportGenerator::format_free_space(&roots),
+            )?;
+        }
+        if args.explain_kept {
+            Self::write_rejected(&mut *report_sink, &search_result)?;
+        }
 
         // 显示完成信息
         if args.verbose && !args.quiet {
@@ -154,169 +674,1377 @@ impl CommandExecutor {
             ));
         }
 
-        Ok(())
+        let timed_out = Self::report_time_budget(&args, cancel.as_ref());
+        Ok(RunSummary {
+            stats,
+            delete_result,
+            dry_run: false,
+            cancelled: quit_early,
+            timed_out,
+        })
     }
 
-    /// 交互式执行删除操作，逐个确认每个文件/目录
-    fn execute_deletion_interactive(
-        plan: &build_cleaner_core::delete::DeletePlan,
+    /// 将删除计划按发现各项目的扫描根路径拆分，按根路径的顺序排列，没有项目的根路径不包括在内
+    fn plans_by_root(
+        plan: &DeletePlan,
+        search_result: &SearchResult,
+        roots: &[PathBuf],
+    ) -> Vec<RootPlan> {
+        let origins: HashMap<&PathBuf, &PathBuf> = search_result
+            .item_origins
+            .iter()
+            .map(|origin| (&origin.path, &origin.root))
+            .collect();
+        let sizes: HashMap<&PathBuf, u64> = search_result
+            .item_sizes
+            .iter()
+            .map(|(path, size)| (path, *size))
+            .collect();
+
+        let mut root_plans: Vec<RootPlan> = roots
+            .iter()
+            .map(|root| RootPlan {
+                root: root.clone(),
+                plan: DeletePlan::default(),
+                size: 0,
+            })
+            .collect();
+        for (path, is_dir) in plan.items() {
+            // 没有来源记录时（如从旧的扫描结果加载）按路径前缀归属
+            let idx = origins
+                .get(&path)
+                .and_then(|root| roots.iter().position(|r| r == *root))
+                .or_else(|| roots.iter().position(|root| path.starts_with(root)))
+                .unwrap_or(0);
+            let Some(root_plan) = root_plans.get_mut(idx) else {
+                continue;
+            };
+            root_plan.size += sizes.get(&path).copied().unwrap_or(0);
+            if plan.is_permanent(&path) {
+                root_plan.plan.permanent.insert(path.clone());
+            }
+            if is_dir {
+                root_plan.plan.dirs.push(path);
+            } else {
+                root_plan.plan.files.push(path);
+            }
+        }
+        root_plans.retain(|root_plan| !root_plan.plan.items().is_empty());
+        root_plans
+    }
+
+    /// 逐个根路径确认并删除
+    ///
+    /// # 参数
+    /// * `root_plans` - 每个根路径的删除计划
+    /// * `quiet` - 是否为静默模式
+    /// * `prompt` - 询问用户如何处理一个根路径的函数
+    /// * `delete` - 删除一个根路径下所有项目的函数
+    ///
+    /// # 返回
+    /// 合并后的删除结果，以及用户是否选择了退出（退出后剩余的根路径不会被删除）
+    fn execute_deletion_per_root<P, D>(
+        root_plans: &[RootPlan],
         quiet: bool,
-    ) -> Result<build_cleaner_core::delete::DeleteResult, CleanError> {
-        use build_cleaner_core::delete::{DeleteEngine, DeleteResult};
-        use std::fs;
-        use trash;
-
-        let mut deleted_files = Vec::new();
-        let mut deleted_dirs = Vec::new();
-        let mut failed_files = Vec::new();
-        let mut failed_dirs = Vec::new();
-        let mut total_size = 0u64;
+        mut prompt: P,
+        mut delete: D,
+    ) -> Result<(DeleteResult, bool), CleanError>
+    where
+        P: FnMut(&RootPlan) -> Result<ItemAction, CleanError>,
+        D: FnMut(&DeletePlan) -> DeleteResult,
+    {
+        let mut delete_result = DeleteResult::default();
         let mut confirm_all = false;
 
-        // 删除文件
-        for file in &plan.files {
-            match DeleteEngine::check_safety(file) {
-                Ok(_) => {
-                    let file_size = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
-
-                    if !confirm_all {
-                        match crate::interactive::confirm_item_deletion(file, false, file_size) {
-                            Ok(true) => {
-                                // 用户确认删除
-                            }
-                            Ok(false) => {
-                                if !quiet {
-                                    println!("  ⏭️  Skipped: {}", file.display());
-                                }
-                                continue;
-                            }
-                            Err(ref e) if e == "all" => {
-                                confirm_all = true;
-                                if !quiet {
-                                    println!("  ✅ All remaining items will be deleted");
-                                }
-                            }
-                            Err(ref e) if e == "quit" => {
-                                if !quiet {
-                                    println!("  ❌ Operation cancelled by user");
-                                }
-                                return Err(CleanError::Other("User cancelled".to_string()));
-                            }
-                            Err(e) => {
-                                if !quiet {
-                                    println!("  ❌ Error: {}", e);
-                                }
-                                return Err(CleanError::Other(e));
-                            }
+        for root_plan in root_plans {
+            if !confirm_all {
+                match prompt(root_plan)? {
+                    ItemAction::Delete => {}
+                    ItemAction::All => confirm_all = true,
+                    ItemAction::Quit => {
+                        if !quiet {
+                            println!("  ❌ Remaining roots were not cleaned");
                         }
+                        return Ok((delete_result, true));
                     }
-
-                    match trash::delete(file) {
-                        Ok(_) => {
-                            total_size += file_size;
-                            deleted_files.push(file.clone());
-                            if !quiet {
-                                println!("  ✅ Deleted: {}", file.display());
-                            }
-                        }
-                        Err(e) => {
-                            failed_files.push((file.clone(), e.to_string()));
-                            if !quiet {
-                                println!("  ❌ Failed: {} - {}", file.display(), e);
-                            }
+                    ItemAction::Skip | ItemAction::Open | ItemAction::Undo => {
+                        if !quiet {
+                            println!("  ⏭️  Skipped: {}", root_plan.root.display());
                         }
+                        continue;
                     }
                 }
+            }
+            let result = delete(&root_plan.plan);
+            if !quiet {
+                println!(
+                    "  ✅ Cleaned {}: {} items",
+                    root_plan.root.display(),
+                    result.deleted_dirs.len() + result.deleted_files.len()
+                );
+            }
+            delete_result.merge(result);
+        }
+        Ok((delete_result, false))
+    }
+
+    /// 超出时间预算时提示结果不完整
+    ///
+    /// # 返回
+    /// 如果超出了时间预算返回 `true`
+    fn report_time_budget(args: &Args, cancel: Option<&CancelFlag>) -> bool {
+        let timed_out = cancel.is_some_and(|cancel| cancel.is_cancelled());
+        if timed_out && !args.quiet {
+            if let Some(budget) = args.max_total_time {
+                crate::output::print_warning(&format!(
+                    "Time budget of {:?} exceeded, stopped early; results are partial",
+                    budget
+                ));
+            }
+        }
+        timed_out
+    }
+
+    /// 输出单个路径的逐步判定过程
+    fn print_explanation(explanation: &Explanation) {
+        println!("🔍 Explaining {}", explanation.path.display());
+        println!("   Scan root: {}", explanation.root.display());
+        if explanation.matched_patterns.is_empty() {
+            println!("   Matched patterns: none");
+        } else {
+            println!(
+                "   Matched patterns: {}",
+                explanation.matched_patterns.join(", ")
+            );
+        }
+        for (ancestor, reason) in &explanation.ancestor_rejections {
+            println!(
+                "   Enclosing {} matched but was rejected: {}",
+                ancestor.display(),
+                reason.code()
+            );
+        }
+        let icon = match explanation.verdict {
+            Verdict::WouldClean { .. } => "✅",
+            Verdict::Protected(_) => "🛡️ ",
+            Verdict::Rejected(_) | Verdict::NotReached => "❌",
+        };
+        println!("{} Verdict: {}", icon, explanation.verdict);
+    }
+
+    /// 只扫描并输出匹配清单（JSON 或文本），不删除任何内容
+    fn execute_scan_only(
+        args: &Args,
+        search_result: &SearchResult,
+        sink: &mut dyn Write,
+    ) -> Result<(), CleanError> {
+        let mut inventory = ReportGenerator::scan_inventory_from_result(search_result);
+        if let Some(mode) = args.anonymize {
+            PathAnonymizer::new(mode).anonymize_inventory(&mut inventory);
+        }
+
+        let report = if args.json {
+            serde_json::to_string_pretty(&inventory)
+                .map_err(|e| CleanError::Other(format!("Failed to serialize inventory: {}", e)))?
+        } else {
+            let mut lines: Vec<String> = inventory
+                .items
+                .iter()
+                .map(|item| {
+                    // 估算的大小只是下限
+                    let size_prefix = if item.size_is_estimate { ">=" } else { "" };
+                    format!(
+                        "{}\t{}{}\t{}\t{}",
+                        item.kind, size_prefix, item.size, item.pattern, item.path
+                    )
+                })
+                .collect();
+            lines.push(format!(
+                "📊 {} directories, {} files, total {} bytes",
+                inventory.dirs_matched, inventory.files_matched, inventory.total_size
+            ));
+            lines.join("\n")
+        };
+        crate::output::write_report(sink, &report)?;
+        if let Some(n) = args.sample {
+            Self::write_sample(sink, search_result, n, args.seed)?;
+        }
+        Ok(())
+    }
+
+    /// 抽样核对匹配目录的计算大小与 `du` 报告的大小，差异过大时输出警告
+    fn audit_sizes(search_result: &SearchResult, quiet: bool) {
+        // 抽样的目录数量和允许的相对误差
+        const SAMPLE_SIZE: usize = 10;
+        const TOLERANCE: f64 = 0.05;
+
+        let mut audited = 0;
+        let mut diverged = 0;
+        for dir in search_result.folders.iter().take(SAMPLE_SIZE) {
+            let Some(reported) = DeleteEngine::du_size(&dir) else {
+                continue;
+            };
+            audited += 1;
+            let (computed, _) = DeleteEngine::calculate_dir_size(&dir);
+            if DeleteEngine::sizes_diverge(computed, reported, TOLERANCE) {
+                diverged += 1;
+                crate::output::print_warning(&format!(
+                    "Size mismatch for {}: computed {} bytes, du reports {} bytes",
+                    dir.display(),
+                    computed,
+                    reported
+                ));
+            }
+        }
+
+        if audited == 0 && !search_result.folders.is_empty() {
+            crate::output::print_warning("Size audit skipped: `du` is not available");
+        } else if audited > 0 && diverged == 0 && !quiet {
+            println!("✅ Size audit: {} directories agree with du", audited);
+        }
+    }
+
+    /// 查询每个根路径所在文件系统的可用空间，查询失败的路径会被忽略
+    fn query_free_space(paths: &[PathBuf]) -> Vec<(PathBuf, DiskSpace)> {
+        paths
+            .iter()
+            .filter_map(|path| match DiskSpace::query(path) {
+                Ok(space) => Some((path.clone(), space)),
                 Err(e) => {
-                    failed_files.push((file.clone(), e.to_string()));
-                    if !quiet {
-                        println!("  ⚠️  Safety check failed: {} - {}", file.display(), e);
-                    }
+                    crate::output::print_warning(&e.to_string());
+                    None
                 }
+            })
+            .collect()
+    }
+
+    /// 按可用空间阈值拆分根路径
+    ///
+    /// # 参数
+    /// * `spaces` - 每个根路径所在文件系统的可用空间
+    /// * `threshold` - 可用空间阈值
+    ///
+    /// # 返回
+    /// 可用空间低于阈值、需要清理的根路径，以及空间充足、跳过的根路径及其可用空间
+    fn split_low_space_roots(
+        spaces: Vec<(PathBuf, DiskSpace)>,
+        threshold: &FreeSpaceThreshold,
+    ) -> (Vec<PathBuf>, Vec<(PathBuf, DiskSpace)>) {
+        let (low, healthy): (Vec<_>, Vec<_>) = spaces
+            .into_iter()
+            .partition(|(_, space)| threshold.is_low(space));
+        (low.into_iter().map(|(root, _)| root).collect(), healthy)
+    }
+
+    /// 根据项目类型的检测结果和用户的选择确定使用的配置
+    ///
+    /// 只在无法识别项目类型时才考虑用户的选择：选择某个项目类型时使用该类型的默认配置
+    /// （仍然合并命令行模式），选择通用配置时保持不变，选择退出时取消操作。
+    ///
+    /// # 参数
+    /// * `config` - 按检测结果合并得到的配置
+    /// * `detected` - 检测到的项目类型
+    /// * `choice` - 用户的选择
+    /// * `cli_patterns` - 命令行传入的清理模式
+    ///
+    /// # 返回
+    /// 要使用的配置，用户选择退出时返回 `CleanError::Cancelled`
+    fn resolve_project_config(
+        config: Config,
+        detected: &ProjectType,
+        choice: ProjectTypeChoice,
+        cli_patterns: &[String],
+    ) -> Result<Config, CleanError> {
+        if *detected != ProjectType::Unknown {
+            return Ok(config);
+        }
+        match choice {
+            ProjectTypeChoice::Pick(project_type) => {
+                let default = ConfigLoader::load_default_config(&project_type);
+                let config = ConfigLoader::merge_configs(&default, None, cli_patterns);
+                ConfigLoader::validate_config(&config)?;
+                Ok(config)
             }
+            ProjectTypeChoice::Generic => Ok(config),
+            ProjectTypeChoice::Quit => Err(CleanError::Cancelled),
         }
+    }
 
-        // 删除目录（需要计算目录大小）
-        for dir in &plan.dirs {
-            match DeleteEngine::check_safety(dir) {
-                Ok(_) => {
-                    // 计算目录大小
-                    let dir_size = {
-                        use walkdir::WalkDir;
-                        let mut size = 0u64;
-                        for entry in WalkDir::new(dir).into_iter().flatten() {
-                            if entry.file_type().is_file() {
-                                if let Ok(metadata) = entry.metadata() {
-                                    size += metadata.len();
-                                }
-                            }
-                        }
-                        size
-                    };
-
-                    if !confirm_all {
-                        match crate::interactive::confirm_item_deletion(dir, true, dir_size) {
-                            Ok(true) => {
-                                // 用户确认删除
-                            }
-                            Ok(false) => {
-                                if !quiet {
-                                    println!("  ⏭️  Skipped: {}", dir.display());
-                                }
-                                continue;
-                            }
-                            Err(ref e) if e == "all" => {
-                                confirm_all = true;
-                                if !quiet {
-                                    println!("  ✅ All remaining items will be deleted");
-                                }
-                            }
-                            Err(ref e) if e == "quit" => {
-                                if !quiet {
-                                    println!("  ❌ Operation cancelled by user");
-                                }
-                                return Err(CleanError::Other("User cancelled".to_string()));
-                            }
-                            Err(e) => {
-                                if !quiet {
-                                    println!("  ❌ Error: {}", e);
-                                }
-                                return Err(CleanError::Other(e));
-                            }
-                        }
-                    }
+    /// 展开并验证所有路径
+    ///
+    /// # 参数
+    /// * `paths` - 命令行传入的路径列表
+    ///
+    /// # 返回
+    /// 展开后的路径列表，如果没有提供路径或路径无效则返回错误
+    fn expand_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>, CleanError> {
+        if paths.is_empty() {
+            return Err(CleanError::NoPaths);
+        }
 
-                    match trash::delete(dir) {
-                        Ok(_) => {
-                            total_size += dir_size;
-                            deleted_dirs.push(dir.clone());
-                            if !quiet {
-                                println!("  ✅ Deleted: {}", dir.display());
-                            }
-                        }
-                        Err(e) => {
-                            failed_dirs.push((dir.clone(), e.to_string()));
-                            if !quiet {
-                                println!("  ❌ Failed: {} - {}", dir.display(), e);
-                            }
-                        }
+        let mut expanded_paths = Vec::new();
+        for path in paths {
+            let expanded = if path.to_string_lossy().starts_with('~') {
+                ConfigLoader::expand_path(&path.to_string_lossy())
+            } else {
+                path.clone()
+            };
+            ConfigLoader::validate_path(&expanded)?;
+            expanded_paths.push(expanded);
+        }
+        Ok(expanded_paths)
+    }
+
+    /// 检查所有扫描根目录，指定 `--force` 时跳过检查
+    ///
+    /// # 参数
+    /// * `paths` - 扫描的根目录
+    /// * `home` - 用户主目录
+    /// * `force` - 是否允许扫描主目录、根目录和系统目录
+    fn check_scan_roots(
+        paths: &[PathBuf],
+        home: Option<&std::path::Path>,
+        force: bool,
+    ) -> Result<(), CleanError> {
+        if force {
+            return Ok(());
+        }
+        for path in paths {
+            DeleteEngine::check_scan_root(path, home)?;
+        }
+        Ok(())
+    }
+
+    /// 逐个确认大小超出阈值的目录，用户拒绝的目录会从搜索结果中移除
+    fn confirm_out_of_range_dirs(
+        search_result: &mut SearchResult,
+        quiet: bool,
+    ) -> Result<(), CleanError> {
+        if !quiet {
+            println!(
+                "\n⚠️  {} matched directories are outside the configured size range and need confirmation.",
+                search_result.needs_confirmation.len()
+            );
+        }
+
+        let pending = std::mem::take(&mut search_result.needs_confirmation);
+        for (dir, size) in &pending {
+            match crate::interactive::confirm_item_deletion(dir, true, *size) {
+                Ok(true) => {}
+                Ok(false) => {
+                    if !quiet {
+                        println!("  ⏭️  Skipped: {}", dir.display());
                     }
+                    search_result.folders.retain(|folder| folder != dir);
+                    search_result.item_sizes.retain(|(path, _)| path != dir);
+                    search_result.total_size = search_result.total_size.saturating_sub(*size);
                 }
-                Err(e) => {
-                    failed_dirs.push((dir.clone(), e.to_string()));
+                Err(ref e) if e == "all" => break,
+                Err(ref e) if e == "quit" => {
                     if !quiet {
-                        println!("  ⚠️  Safety check failed: {} - {}", dir.display(), e);
+                        println!("  ❌ Operation cancelled by user");
                     }
+                    return Err(CleanError::Cancelled);
                 }
+                Err(e) => return Err(CleanError::Other(e)),
             }
         }
 
-        Ok(DeleteResult {
-            deleted_files,
-            deleted_dirs,
-            failed_files,
-            failed_dirs,
-            total_size,
-        })
+        Ok(())
+    }
+
+    /// 将随机抽取的匹配路径写入报告输出目标，未指定种子时随机选择并一起输出，以便复现
+    fn write_sample(
+        sink: &mut dyn Write,
+        search_result: &SearchResult,
+        n: usize,
+        seed: Option<u64>,
+    ) -> Result<(), CleanError> {
+        let seed = seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos() as u64)
+                .unwrap_or(0)
+        });
+        let sample = search_result.sample(n, seed);
+        let mut text = format!(
+            "\n🎲 Random sample of {} out of {} matches (--seed {}):",
+            sample.len(),
+            search_result.folders.len() + search_result.files.len(),
+            seed
+        );
+        for path in &sample {
+            text.push_str(&format!("\n   - {}", path.display()));
+        }
+        crate::output::write_report(sink, &text)
+    }
+
+    /// 将被扫描但未被选中清理的条目（反向报告）写入报告输出目标
+    fn write_rejected(
+        sink: &mut dyn Write,
+        search_result: &SearchResult,
+    ) -> Result<(), CleanError> {
+        if search_result.rejected.is_empty() {
+            return Ok(());
+        }
+        crate::output::write_report(
+            sink,
+            &format!(
+                "\n🛡️  Kept items:\n{}",
+                ReportGenerator::format_rejected(&search_result.rejected)
+            ),
+        )
+    }
+
+    /// 将与项目类型相关的工具全局缓存加入搜索结果（排除路径中的缓存除外）
+    fn add_tool_caches(
+        search_result: &mut SearchResult,
+        paths: &[PathBuf],
+        config: &Config,
+        quiet: bool,
+    ) {
+        // 每个根路径可能属于不同的项目类型，分别查找其工具缓存
+        let mut project_types: Vec<ProjectType> = Vec::new();
+        for path in paths {
+            let project_type = ConfigLoader::detect_project_type(path);
+            if !project_types.contains(&project_type) {
+                project_types.push(project_type);
+            }
+        }
+        if project_types.is_empty() {
+            project_types.push(ProjectType::Unknown);
+        }
+        let caches = project_types
+            .iter()
+            .flat_map(ToolCacheDiscovery::discover_tool_caches);
+        for cache in caches {
+            if SearchEngine::should_exclude(&cache, &config.exclude) {
+                continue;
+            }
+            if search_result.add_extra_folder(&cache, "tool-cache") && !quiet {
+                println!("🧰 Including tool cache: {}", cache.display());
+            }
+        }
+    }
+
+    /// 是否使用单行状态格式（`--format status`）
+    fn status_format(args: &Args) -> bool {
+        Self::human_format(args) == Some("status")
+    }
+
+    /// 人类可读报告的格式（`report` 或 `status`）
+    ///
+    /// 没有指定 `--format` 时默认输出 `report`；只指定了机器可读的格式时不输出人类可读报告。
+    fn human_format(args: &Args) -> Option<&str> {
+        if args.format.is_empty() {
+            return Some("report");
+        }
+        args.format
+            .iter()
+            .map(String::as_str)
+            .find(|format| matches!(*format, "report" | "status"))
+    }
+
+    /// 要输出的机器可读统计摘要格式及其输出文件（为 `None` 时输出到标准输出）
+    ///
+    /// 指定 `--summary-json-to`/`--summary-csv-to` 时即使没有对应的 `--format` 也会输出。
+    fn summary_formats(args: &Args) -> Vec<(&'static str, Option<&Path>)> {
+        [
+            ("json", args.summary_json_to.as_deref()),
+            ("csv", args.summary_csv_to.as_deref()),
+        ]
+        .into_iter()
+        .filter(|(format, target)| target.is_some() || args.format.iter().any(|f| f == format))
+        .collect()
+    }
+
+    /// 检查请求的输出格式能否各自写到独立的输出目标
+    ///
+    /// `report` 和 `status` 不能同时使用；多种格式都要写到标准输出时会互相混在一起，
+    /// 此时要求为其余格式指定输出文件（`--output`、`--summary-json-to`、`--summary-csv-to`）。
+    fn check_format_sinks(args: &Args) -> Result<(), CleanError> {
+        let requested = |format: &str| args.format.iter().any(|f| f == format);
+        if requested("report") && requested("status") {
+            return Err(CleanError::Other(
+                "--format report and --format status cannot be combined".to_string(),
+            ));
+        }
+
+        let mut stdout_formats: Vec<&str> = Self::summary_formats(args)
+            .into_iter()
+            .filter(|(_, target)| target.is_none())
+            .map(|(format, _)| format)
+            .collect();
+        if let Some(human) = Self::human_format(args).filter(|_| args.output.is_none()) {
+            stdout_formats.insert(0, human);
+        }
+        if stdout_formats.len() > 1 {
+            return Err(CleanError::Other(format!(
+                "Formats {} would all be written to stdout; send all but one to a file with --output, --summary-json-to or --summary-csv-to",
+                stdout_formats.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// 打开每种机器可读统计摘要的输出目标
+    fn open_summary_sinks(args: &Args) -> Result<Vec<SummarySink>, CleanError> {
+        Self::summary_formats(args)
+            .into_iter()
+            .map(|(format, target)| Ok((format, crate::output::open_report_sink(target)?)))
+            .collect()
+    }
+
+    /// 将统计摘要按各自的格式写入输出目标
+    fn write_summaries(sinks: &mut [SummarySink], stats: &Stats) -> Result<(), CleanError> {
+        for (format, sink) in sinks.iter_mut() {
+            let summary = match *format {
+                "csv" => ReportGenerator::format_summary_csv(stats),
+                _ => ReportGenerator::format_summary_json(stats),
+            };
+            crate::output::write_report(&mut **sink, &summary)?;
+        }
+        Ok(())
+    }
+
+    /// 要写到报告输出目标的人类可读报告
+    ///
+    /// 指定了输出文件时总是写入完整报告；写到标准输出时，静默模式下只输出单行摘要，
+    /// `--silent` 时不输出。只指定了机器可读的格式时不输出人类可读报告。
+    fn human_report(
+        args: &Args,
+        stats: &Stats,
+        search_result: &SearchResult,
+        delete_result: &DeleteResult,
+    ) -> Option<String> {
+        Self::human_format(args)?;
+        match ReportLevel::new(args.quiet, args.silent, args.output.is_some()) {
+            ReportLevel::Full => Some(Self::format_report(
+                args,
+                stats,
+                search_result,
+                delete_result,
+            )),
+            ReportLevel::Summary => Some(ReportGenerator::format_status_line(stats)),
+            ReportLevel::Silent => None,
+        }
+    }
+
+    /// 格式化清理报告，指定 `--group-by` 时将已删除的项目分组显示，指定 `--format status` 时只输出单行状态，
+    /// 指定 `--anonymize` 时匿名化报告中的路径
+    fn format_report(
+        args: &Args,
+        stats: &Stats,
+        search_result: &SearchResult,
+        delete_result: &DeleteResult,
+    ) -> String {
+        if Self::status_format(args) {
+            return ReportGenerator::format_status_line(stats);
+        }
+        let mut groups = args
+            .group_by
+            .map(|group_by| ReportGenerator::group_items(search_result, delete_result, group_by));
+        let anonymizer = args.anonymize.map(PathAnonymizer::new);
+        let anonymized = anonymizer.as_ref().map(|anonymizer| {
+            if let (Some(groups), Some(group_by)) = (groups.as_mut(), args.group_by) {
+                anonymizer.anonymize_groups(groups, group_by);
+            }
+            anonymizer.anonymize_delete_result(delete_result)
+        });
+        ReportGenerator::format_report_with_groups(
+            stats,
+            anonymized.as_ref().unwrap_or(delete_result),
+            args.verbose,
+            args.group_by.zip(groups.as_deref()),
+        )
+    }
+
+    /// 加载之前保存的扫描结果，并移除扫描之后已经消失的路径
+    ///
+    /// # 参数
+    /// * `path` - 扫描结果文件路径
+    /// * `quiet` - 是否为静默模式
+    ///
+    /// # 返回
+    /// 只包含仍然存在的路径的扫描结果
+    fn load_saved_result(path: &std::path::Path, quiet: bool) -> Result<SearchResult, CleanError> {
+        let mut search_result = SearchResult::load(path)?;
+        let vanished = search_result.retain_existing();
+        if !quiet {
+            println!(
+                "📂 Loaded scan result from {} ({} items)",
+                path.display(),
+                search_result.folders.len() + search_result.files.len()
+            );
+            for path in &vanished {
+                crate::output::print_warning(&format!(
+                    "Skipping {}: it no longer exists",
+                    path.display()
+                ));
+            }
+        }
+        Ok(search_result)
+    }
+
+    /// 交互式执行删除操作，逐个确认每个文件/目录
+    ///
+    /// # 参数
+    /// * `plan` - 删除计划
+    /// * `options` - 删除选项
+    /// * `quiet` - 是否为静默模式
+    /// * `confirm_above` - 只询问大小不小于该值的项目，更小的项目直接删除（为 `None` 时每项都询问）
+    /// * `sizes` - 每个项目的大小，指定时按大小从大到小依次询问（为 `None` 时按计划顺序）
+    /// * `trash` - 移到回收站和从回收站恢复的操作
+    /// * `prompt` - 询问用户是否删除单个项目的函数
+    ///
+    /// 用户在询问时选择撤销，会从回收站恢复最近删除的一个项目，然后重新询问当前项目。
+    fn execute_deletion_interactive<P>(
+        plan: &DeletePlan,
+        options: &DeleteOptions,
+        quiet: bool,
+        confirm_above: Option<u64>,
+        sizes: Option<&[(PathBuf, u64)]>,
+        trash: &dyn TrashOps,
+        prompt: P,
+    ) -> Result<DeleteResult, CleanError>
+    where
+        P: FnMut(&Path, bool, u64) -> Result<bool, String>,
+    {
+        let mut confirmer = crate::interactive::PromptConfirmer::new(prompt, quiet);
+        DeleteEngine::execute_deletion_interactive(
+            plan,
+            options,
+            confirm_above,
+            sizes,
+            trash,
+            &mut confirmer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_execute_deletion_per_root() {
+        let root_plans: Vec<RootPlan> = ["/a", "/b", "/c", "/d"]
+            .iter()
+            .map(|root| RootPlan {
+                root: PathBuf::from(root),
+                plan: DeletePlan {
+                    files: vec![PathBuf::from(root).join("x.log")],
+                    dirs: vec![],
+                    ..Default::default()
+                },
+                size: 10,
+            })
+            .collect();
+
+        // 按给定的回答依次处理，返回被删除的根路径、被询问的次数和是否退出
+        let run = |answers: Vec<ItemAction>| {
+            let mut answers = answers.into_iter();
+            let mut asked = 0;
+            let mut deleted = Vec::new();
+            let (result, quit) = CommandExecutor::execute_deletion_per_root(
+                &root_plans,
+                true,
+                |_| {
+                    asked += 1;
+                    Ok(answers.next().unwrap())
+                },
+                |plan| {
+                    deleted.push(plan.files[0].parent().unwrap().to_path_buf());
+                    DeleteResult {
+                        deleted_files: plan.files.clone(),
+                        total_size: 10,
+                        ..Default::default()
+                    }
+                },
+            )
+            .unwrap();
+            assert_eq!(result.deleted_files.len(), deleted.len());
+            assert_eq!(result.total_size, 10 * deleted.len() as u64);
+            (deleted, asked, quit)
+        };
+
+        // 是、否、退出：只删除第一个根路径，退出后不再询问
+        let (deleted, asked, quit) =
+            run(vec![ItemAction::Delete, ItemAction::Skip, ItemAction::Quit]);
+        assert_eq!(deleted, vec![PathBuf::from("/a")]);
+        assert_eq!(asked, 3);
+        assert!(quit);
+
+        // 否、全部：跳过第一个，删除剩余的根路径且不再询问
+        let (deleted, asked, quit) = run(vec![ItemAction::Skip, ItemAction::All]);
+        assert_eq!(
+            deleted,
+            vec![
+                PathBuf::from("/b"),
+                PathBuf::from("/c"),
+                PathBuf::from("/d")
+            ]
+        );
+        assert_eq!(asked, 2);
+        assert!(!quit);
+
+        // 第一个根路径就退出时什么都不删除
+        let (deleted, _, quit) = run(vec![ItemAction::Quit]);
+        assert!(deleted.is_empty());
+        assert!(quit);
+    }
+
+    #[test]
+    fn test_plans_by_root() {
+        let root_a = tempfile::TempDir::new().unwrap();
+        let root_b = tempfile::TempDir::new().unwrap();
+        let root_c = tempfile::TempDir::new().unwrap();
+        for root in [&root_a, &root_b] {
+            std::fs::create_dir(root.path().join("node_modules")).unwrap();
+            std::fs::write(root.path().join("node_modules").join("a.js"), "abc").unwrap();
+            std::fs::write(root.path().join("debug.log"), "log").unwrap();
+        }
+        let roots = vec![
+            root_a.path().to_path_buf(),
+            root_b.path().to_path_buf(),
+            root_c.path().to_path_buf(),
+        ];
+        let mut config = ConfigLoader::load_config(None, None, &[]).unwrap();
+        config.clean.folders = vec!["node_modules".to_string()];
+        config.clean.files = vec!["*.log".to_string()];
+        let search_result = SearchEngine::search(&roots, &config).unwrap();
+        let plan = DeleteEngine::create_delete_plan(&search_result);
+
+        // 没有匹配项的根路径不包括在内
+        let root_plans = CommandExecutor::plans_by_root(&plan, &search_result, &roots);
+        assert_eq!(root_plans.len(), 2);
+        for (root_plan, root) in root_plans.iter().zip(&roots) {
+            assert_eq!(&root_plan.root, root);
+            assert_eq!(root_plan.plan.dirs, vec![root.join("node_modules")]);
+            assert_eq!(root_plan.plan.files, vec![root.join("debug.log")]);
+            assert_eq!(root_plan.size, 6);
+        }
+    }
+
+    #[test]
+    fn test_check_scan_roots() {
+        let home = tempfile::TempDir::new().unwrap();
+        let project = home.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let home_arg = home.path().to_string_lossy().into_owned();
+
+        // 默认拒绝扫描主目录
+        let args = Args::try_parse_from(["bc", home_arg.as_str(), "--clean", "*"]).unwrap();
+        let err = CommandExecutor::check_scan_roots(&args.paths, Some(home.path()), args.force)
+            .unwrap_err();
+        assert!(err.to_string().contains("home directory"));
+
+        // 指定 --force 后继续
+        let args = Args::try_parse_from(["bc", home_arg.as_str(), "--force"]).unwrap();
+        assert!(
+            CommandExecutor::check_scan_roots(&args.paths, Some(home.path()), args.force).is_ok()
+        );
+
+        // 主目录下的项目不受影响，根目录同样被拒绝
+        assert!(CommandExecutor::check_scan_roots(&[project], Some(home.path()), false).is_ok());
+        assert!(
+            CommandExecutor::check_scan_roots(&[PathBuf::from("/")], Some(home.path()), false)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_project_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cli_patterns = vec!["*.log".to_string()];
+        let generic =
+            ConfigLoader::load_config(Some(temp_dir.path()), None, &cli_patterns).unwrap();
+        assert!(generic.clean.folders.contains(&"node_modules".to_string()));
+
+        // 无法识别项目类型时，选择 Rust 使用 Rust 的默认配置并保留命令行模式
+        let config = CommandExecutor::resolve_project_config(
+            generic.clone(),
+            &ProjectType::Unknown,
+            ProjectTypeChoice::Pick(ProjectType::Rust),
+            &cli_patterns,
+        )
+        .unwrap();
+        assert_eq!(config.clean.folders, vec!["target"]);
+        assert_eq!(config.clean.files, vec!["*.log"]);
+
+        // 选择通用配置时保持不变
+        let config = CommandExecutor::resolve_project_config(
+            generic.clone(),
+            &ProjectType::Unknown,
+            ProjectTypeChoice::Generic,
+            &cli_patterns,
+        )
+        .unwrap();
+        assert_eq!(config, generic);
+
+        // 选择退出时取消操作
+        assert!(matches!(
+            CommandExecutor::resolve_project_config(
+                generic.clone(),
+                &ProjectType::Unknown,
+                ProjectTypeChoice::Quit,
+                &cli_patterns,
+            ),
+            Err(CleanError::Cancelled)
+        ));
+
+        // 已识别项目类型时忽略选择
+        let config = CommandExecutor::resolve_project_config(
+            generic.clone(),
+            &ProjectType::NodeJs,
+            ProjectTypeChoice::Pick(ProjectType::Rust),
+            &cli_patterns,
+        )
+        .unwrap();
+        assert_eq!(config, generic);
+    }
+
+    #[test]
+    fn test_execute_writes_report_to_output_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+        let output = temp_dir.path().join("report.txt");
+
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "*.log".as_ref(),
+            "--dry-run".as_ref(),
+            "--verbose".as_ref(),
+            "--quiet".as_ref(),
+            "--sample".as_ref(),
+            "1".as_ref(),
+            "--seed".as_ref(),
+            "7".as_ref(),
+            "--output".as_ref(),
+            output.as_os_str(),
+        ])
+        .unwrap();
+        CommandExecutor::execute(args).unwrap();
+
+        let report = std::fs::read_to_string(&output).unwrap();
+        assert!(report.contains("Cleanup Report"));
+        assert!(report.contains("Files matched: 1"));
+        // 抽样列表也写入报告文件
+        assert!(report.contains("Random sample of 1 out of 1 matches (--seed 7)"));
+        // 预览模式不删除文件
+        assert!(project.join("debug.log").exists());
+    }
+
+    #[test]
+    fn test_execute_multiple_formats() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+        let output = temp_dir.path().join("report.txt");
+        let csv = temp_dir.path().join("summary.csv");
+
+        // 人类可读报告写到 --output，CSV 摘要写到 --summary-csv-to
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "*.log".as_ref(),
+            "--dry-run".as_ref(),
+            "--quiet".as_ref(),
+            "--format".as_ref(),
+            "report".as_ref(),
+            "--format".as_ref(),
+            "csv".as_ref(),
+            "--output".as_ref(),
+            output.as_os_str(),
+            "--summary-csv-to".as_ref(),
+            csv.as_os_str(),
+        ])
+        .unwrap();
+        CommandExecutor::execute(args).unwrap();
+
+        let report = std::fs::read_to_string(&output).unwrap();
+        assert!(report.contains("Cleaned 0 directories, 1 files"));
+        let summary = std::fs::read_to_string(&csv).unwrap();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let columns: Vec<&str> = lines[0].split(',').collect();
+        let values: Vec<&str> = lines[1].split(',').collect();
+        let files_deleted = columns.iter().position(|c| *c == "files_deleted").unwrap();
+        assert_eq!(values[files_deleted], "1");
+
+        // 两种格式都写到标准输出时拒绝执行
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--format".as_ref(),
+            "report".as_ref(),
+            "--format".as_ref(),
+            "json".as_ref(),
+        ])
+        .unwrap();
+        let err = CommandExecutor::check_format_sinks(&args).unwrap_err();
+        assert!(err.to_string().contains("report, json"));
+        // 只请求 JSON 时写到标准输出
+        let args = Args::try_parse_from(["bc", ".", "--format", "json"]).unwrap();
+        assert!(CommandExecutor::check_format_sinks(&args).is_ok());
+        assert_eq!(CommandExecutor::human_format(&args), None);
+        // report 和 status 不能同时使用
+        let args = Args::try_parse_from([
+            "bc", ".", "--format", "report", "--format", "status", "-o", "r.txt",
+        ])
+        .unwrap();
+        assert!(CommandExecutor::check_format_sinks(&args).is_err());
+    }
+
+    #[test]
+    fn test_execute_scan_only_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(project.join("target")).unwrap();
+        std::fs::write(project.join("target").join("app.o"), vec![0u8; 100]).unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+        let output = temp_dir.path().join("scan.json");
+
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "target/".as_ref(),
+            "*.log".as_ref(),
+            "--scan-only".as_ref(),
+            "--json".as_ref(),
+            "--output".as_ref(),
+            output.as_os_str(),
+        ])
+        .unwrap();
+        CommandExecutor::execute(args).unwrap();
+
+        let inventory: build_cleaner_core::ScanInventory =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(inventory.items.len(), 2);
+        assert_eq!(inventory.total_size, 103);
+        // 只扫描模式不删除任何内容
+        assert!(project.join("target").exists());
+        assert!(project.join("debug.log").exists());
+    }
+
+    #[test]
+    fn test_split_low_space_roots() {
+        let low = DiskSpace {
+            total: 1000,
+            available: 50,
+        };
+        let healthy = DiskSpace {
+            total: 1000,
+            available: 500,
+        };
+        let spaces = vec![
+            (PathBuf::from("/full"), low),
+            (PathBuf::from("/roomy"), healthy),
+        ];
+
+        // 只清理空间不足的根路径，空间充足的根路径被跳过
+        let (to_clean, skipped) =
+            CommandExecutor::split_low_space_roots(spaces, &FreeSpaceThreshold::Percent(10.0));
+        assert_eq!(to_clean, vec![PathBuf::from("/full")]);
+        assert_eq!(skipped, vec![(PathBuf::from("/roomy"), healthy)]);
+    }
+
+    #[test]
+    fn test_execute_scan_only_matches_dry_run() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let now = std::time::SystemTime::now();
+        for (i, name) in ["a.log", "b.log", "c.log"].iter().enumerate() {
+            let path = project.join(name);
+            std::fs::write(&path, vec![0u8; 10]).unwrap();
+            // c.log 最新
+            let modified = now - std::time::Duration::from_secs(3600 * (3 - i as u64));
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        // Rust 项目只查找 Cargo 的缓存，扫描期间不会被其他工具改动
+        std::fs::write(project.join("Cargo.toml"), "").unwrap();
+        let output = temp_dir.path().join("scan.json");
+
+        let args_for = |mode: &[&str]| {
+            let mut args = vec![
+                "bc".as_ref(),
+                project.as_os_str(),
+                "--clean".as_ref(),
+                "*.log".as_ref(),
+                "--keep-newest-per-dir".as_ref(),
+                "--clean-tool-caches".as_ref(),
+                "--quiet".as_ref(),
+            ];
+            args.extend(mode.iter().map(std::ffi::OsStr::new));
+            Args::try_parse_from(args).unwrap()
+        };
+
+        let summary = CommandExecutor::execute_with_result(args_for(&["--dry-run"])).unwrap();
+        CommandExecutor::execute(args_for(&[
+            "--scan-only",
+            "--json",
+            "--output",
+            output.to_str().unwrap(),
+        ]))
+        .unwrap();
+        let inventory: build_cleaner_core::ScanInventory =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+
+        // 只扫描模式列出的正是实际运行会删除的内容，包括工具缓存，不包括保留的最新文件
+        assert_eq!(summary.stats.files_deleted, 2);
+        assert_eq!(inventory.files_matched, summary.stats.files_deleted);
+        assert_eq!(inventory.dirs_matched, summary.stats.dirs_deleted);
+        assert_eq!(inventory.total_size, summary.stats.space_freed);
+        let listed: Vec<&str> = inventory
+            .items
+            .iter()
+            .map(|item| item.path.as_str())
+            .collect();
+        assert!(!listed.iter().any(|path| path.ends_with("c.log")));
+        let project_type = ConfigLoader::detect_project_type(&project);
+        for cache in ToolCacheDiscovery::discover_tool_caches(&project_type) {
+            assert!(
+                listed.iter().any(|path| cache.starts_with(path)),
+                "{} missing from {:?}",
+                cache.display(),
+                listed
+            );
+        }
+    }
+
+    #[test]
+    fn test_execute_with_result_summary() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(project.join("dist")).unwrap();
+        std::fs::write(project.join("dist").join("bundle.js"), vec![0u8; 1000]).unwrap();
+        std::fs::write(project.join("debug.log"), vec![0u8; 24]).unwrap();
+        std::fs::write(project.join("main.rs"), "fn main() {}").unwrap();
+
+        let args_for = |dry_run: bool| {
+            let mut args = vec![
+                "bc".as_ref(),
+                project.as_os_str(),
+                "--clean".as_ref(),
+                "*.log".as_ref(),
+                "--quiet".as_ref(),
+            ];
+            if dry_run {
+                args.push("--dry-run".as_ref());
+            }
+            Args::try_parse_from(args).unwrap()
+        };
+
+        // 预览模式返回将要删除的内容，不删除任何文件
+        let summary = CommandExecutor::execute_with_result(args_for(true)).unwrap();
+        assert!(summary.dry_run);
+        assert!(!summary.cancelled);
+        assert_eq!(summary.stats.dirs_deleted, 1);
+        assert_eq!(summary.stats.files_deleted, 1);
+        assert_eq!(summary.stats.space_freed, 1024);
+        assert!(project.join("dist").exists());
+
+        // 实际执行后返回删除结果
+        let summary = CommandExecutor::execute_with_result(args_for(false)).unwrap();
+        assert!(!summary.dry_run);
+        assert_eq!(
+            summary.delete_result.deleted_dirs,
+            vec![project.join("dist")]
+        );
+        assert_eq!(
+            summary.delete_result.deleted_files,
+            vec![project.join("debug.log")]
+        );
+        assert_eq!(summary.stats.space_freed, 1024);
+        assert!(!project.join("dist").exists());
+        assert!(project.join("main.rs").exists());
+    }
+
+    #[test]
+    fn test_execute_resume_skips_committed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(project.join("dist")).unwrap();
+        std::fs::write(project.join("dist").join("bundle.js"), "js").unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+
+        // 上一次运行在删除 debug.log 后被中断
+        let manifest_path = temp_dir.path().join("manifest.jsonl");
+        let mut manifest = DeleteManifest::open(&manifest_path).unwrap();
+        manifest.commit(&project.join("debug.log"), false).unwrap();
+        drop(manifest);
+
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "*.log".as_ref(),
+            "--quiet".as_ref(),
+            "--resume".as_ref(),
+            manifest_path.as_os_str(),
+        ])
+        .unwrap();
+        let summary = CommandExecutor::execute_with_result(args).unwrap();
+
+        // 已完成的项目被跳过，剩余项目被删除并追加到清单
+        assert!(summary.delete_result.deleted_files.is_empty());
+        assert_eq!(
+            summary.delete_result.deleted_dirs,
+            vec![project.join("dist")]
+        );
+        assert!(project.join("debug.log").exists());
+        let committed = DeleteManifest::load_committed(&manifest_path).unwrap();
+        assert!(committed.contains(&project.join("debug.log")));
+        assert!(committed.contains(&project.join("dist")));
+    }
+
+    #[test]
+    fn test_confirm_above_prompts_only_large_items() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let small_file = temp_dir.path().join("small.log");
+        let large_file = temp_dir.path().join("large.log");
+        let small_dir = temp_dir.path().join("small_dist");
+        let large_dir = temp_dir.path().join("large_dist");
+        std::fs::write(&small_file, vec![0u8; 10]).unwrap();
+        std::fs::write(&large_file, vec![0u8; 2000]).unwrap();
+        std::fs::create_dir(&small_dir).unwrap();
+        std::fs::write(small_dir.join("a.js"), vec![0u8; 10]).unwrap();
+        std::fs::create_dir(&large_dir).unwrap();
+        std::fs::write(large_dir.join("a.js"), vec![0u8; 2000]).unwrap();
+
+        let plan = build_cleaner_core::DeletePlan {
+            files: vec![small_file.clone(), large_file.clone()],
+            dirs: vec![small_dir.clone(), large_dir.clone()],
+            ..Default::default()
+        };
+
+        // 记录被询问的项目：同意删除大目录，拒绝删除大文件
+        let mut prompted = Vec::new();
+        let result = CommandExecutor::execute_deletion_interactive(
+            &plan,
+            &DeleteOptions::default(),
+            true,
+            Some(1000),
+            None,
+            &SystemTrash,
+            |path: &std::path::Path, is_dir: bool, _size: u64| {
+                prompted.push(path.to_path_buf());
+                Ok(is_dir)
+            },
+        )
+        .unwrap();
+
+        assert_eq!(prompted, vec![large_file.clone(), large_dir.clone()]);
+        assert_eq!(result.deleted_files, vec![small_file.clone()]);
+        assert_eq!(
+            result.deleted_dirs,
+            vec![small_dir.clone(), large_dir.clone()]
+        );
+        assert!(large_file.exists());
+    }
+
+    #[test]
+    fn test_interactive_undo_restores_last_item() {
+        /// 只记录操作、不真正移动文件的回收站
+        #[derive(Default)]
+        struct RecordingTrash {
+            trashed: std::cell::RefCell<Vec<PathBuf>>,
+            restored: std::cell::RefCell<Vec<PathBuf>>,
+        }
+
+        impl TrashOps for RecordingTrash {
+            fn trash(
+                &self,
+                path: &Path,
+                _is_dir: bool,
+                _: &DeleteOptions,
+            ) -> Result<PathBuf, String> {
+                self.trashed.borrow_mut().push(path.to_path_buf());
+                Ok(path.to_path_buf())
+            }
+
+            fn restore(
+                &self,
+                path: &Path,
+                _trashed: &Path,
+                _conflict: RestoreConflict,
+            ) -> Result<build_cleaner_core::RestoreOutcome, String> {
+                self.restored.borrow_mut().push(path.to_path_buf());
+                Ok(build_cleaner_core::RestoreOutcome::Restored)
+            }
+        }
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let files: Vec<PathBuf> = ["a.log", "b.log", "c.log"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for file in &files {
+            std::fs::write(file, vec![0u8; 10]).unwrap();
+        }
+        let manifest_path = temp_dir.path().join("manifest.jsonl");
+        let options = DeleteOptions {
+            manifest: Some(manifest_path.clone()),
+            ..Default::default()
+        };
+        let plan = build_cleaner_core::DeletePlan {
+            files: files.clone(),
+            dirs: vec![],
+            ..Default::default()
+        };
+
+        // a：删除；b：撤销（恢复 a）后删除；c：撤销（恢复 b），再次撤销时已没有可撤销的项目，然后删除
+        let mut answers = vec![
+            Ok(true),
+            Err("undo".to_string()),
+            Ok(true),
+            Err("undo".to_string()),
+            Err("undo".to_string()),
+            Ok(true),
+        ]
+        .into_iter();
+        let mut prompted = Vec::new();
+        let trash = RecordingTrash::default();
+        let result = CommandExecutor::execute_deletion_interactive(
+            &plan,
+            &options,
+            true,
+            None,
+            None,
+            &trash,
+            |path: &std::path::Path, _is_dir: bool, _size: u64| {
+                prompted.push(path.file_name().unwrap().to_string_lossy().into_owned());
+                answers.next().unwrap()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            prompted,
+            vec!["a.log", "b.log", "b.log", "c.log", "c.log", "c.log"]
+        );
+        assert_eq!(*trash.trashed.borrow(), files);
+        assert_eq!(
+            *trash.restored.borrow(),
+            vec![files[0].clone(), files[1].clone()]
+        );
+        assert_eq!(result.deleted_files, vec![files[2].clone()]);
+        assert_eq!(result.total_size, 10);
+        // 已恢复的项目在清单中被撤销，恢复删除时不会跳过
+        let committed = DeleteManifest::load_committed(&manifest_path).unwrap();
+        assert_eq!(committed, [files[2].clone()].into_iter().collect());
+    }
+
+    #[test]
+    fn test_execute_from_saved_scan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(project.join("dist")).unwrap();
+        std::fs::write(project.join("dist").join("bundle.js"), "js").unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+        std::fs::write(project.join("other.log"), "log").unwrap();
+        let saved = temp_dir.path().join("scan.json");
+
+        // 预览并保存扫描结果
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "*.log".as_ref(),
+            "--dry-run".as_ref(),
+            "--quiet".as_ref(),
+            "--save".as_ref(),
+            saved.as_os_str(),
+        ])
+        .unwrap();
+        CommandExecutor::execute_with_result(args).unwrap();
+
+        // 编辑保存的结果：移除 other.log，并模拟 dist 在扫描之后被删除
+        let mut result = SearchResult::load(&saved).unwrap();
+        result.files.retain(|path| !path.ends_with("other.log"));
+        result.save(&saved).unwrap();
+        std::fs::remove_dir_all(project.join("dist")).unwrap();
+
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            "--quiet".as_ref(),
+            "--from".as_ref(),
+            saved.as_os_str(),
+        ])
+        .unwrap();
+        let summary = CommandExecutor::execute_with_result(args).unwrap();
+
+        // 只删除保存结果中仍然存在的项目，不重新扫描
+        assert_eq!(
+            summary.delete_result.deleted_files,
+            vec![project.join("debug.log")]
+        );
+        assert!(summary.delete_result.deleted_dirs.is_empty());
+        assert!(summary.delete_result.failed_dirs.is_empty());
+        assert!(project.join("other.log").exists());
+    }
+
+    #[test]
+    fn test_execute_stops_after_time_budget() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+
+        // 预算在开始前就已用完：扫描和删除都立即结束，不删除任何内容
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "*.log".as_ref(),
+            "--quiet".as_ref(),
+            "--max-total-time".as_ref(),
+            "0s".as_ref(),
+        ])
+        .unwrap();
+        let summary = CommandExecutor::execute_with_result(args).unwrap();
+        assert!(summary.timed_out);
+        assert!(!summary.cancelled);
+        assert!(summary.delete_result.deleted_files.is_empty());
+        assert!(project.join("debug.log").exists());
+
+        // 预算充足时正常完成
+        let args = Args::try_parse_from([
+            "bc".as_ref(),
+            project.as_os_str(),
+            "--clean".as_ref(),
+            "*.log".as_ref(),
+            "--quiet".as_ref(),
+            "--max-total-time".as_ref(),
+            "1h".as_ref(),
+        ])
+        .unwrap();
+        let summary = CommandExecutor::execute_with_result(args).unwrap();
+        assert!(!summary.timed_out);
+        assert_eq!(
+            summary.delete_result.deleted_files,
+            vec![project.join("debug.log")]
+        );
+    }
+
+    #[test]
+    fn test_expand_paths_empty() {
+        // 没有路径时返回错误而不是 panic
+        let result = CommandExecutor::expand_paths(&[]);
+        assert!(matches!(result, Err(CleanError::NoPaths)));
     }
 }
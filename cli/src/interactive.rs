@@ -1,6 +1,9 @@
 use build_cleaner_core::error::CleanError;
 use build_cleaner_core::search::SearchResult;
-use std::io::{self, Write};
+use build_cleaner_core::{Confirmation, Confirmer, DeleteEvent, DeleteItem, ProjectType};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::Command;
 
 /// 格式化文件大小
 fn format_size(bytes: u64) -> String {
@@ -94,6 +97,58 @@ pub fn confirm_deletion(search_result: &SearchResult, verbose: bool) -> Result<b
     Ok(input.trim().to_lowercase() == "y" || input.trim().to_lowercase() == "yes")
 }
 
+/// 单个项目确认时用户输入对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemAction {
+    /// 删除当前项目
+    Delete,
+    /// 跳过当前项目
+    Skip,
+    /// 删除所有剩余项目
+    All,
+    /// 取消操作
+    Quit,
+    /// 在文件管理器中打开，然后重新询问
+    Open,
+    /// 从回收站恢复上一个删除的项目，然后重新询问
+    Undo,
+}
+
+/// 将用户输入解析为对应的操作（不区分大小写，无法识别的输入视为跳过）
+pub fn classify_input(input: &str) -> ItemAction {
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => ItemAction::Delete,
+        "a" | "all" => ItemAction::All,
+        "q" | "quit" => ItemAction::Quit,
+        "o" | "open" => ItemAction::Open,
+        "u" | "undo" => ItemAction::Undo,
+        _ => ItemAction::Skip,
+    }
+}
+
+/// 在文件管理器中打开路径的接口，便于在测试中替换
+pub trait Opener {
+    /// 打开指定路径
+    fn open(&self, path: &Path) -> io::Result<()>;
+}
+
+/// 使用系统文件管理器（Finder/Explorer/xdg-open）打开路径
+pub struct SystemOpener;
+
+impl Opener for SystemOpener {
+    fn open(&self, path: &Path) -> io::Result<()> {
+        let program = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(target_os = "windows") {
+            "explorer"
+        } else {
+            "xdg-open"
+        };
+        // 只启动文件管理器，不等待其退出
+        Command::new(program).arg(path).spawn().map(|_| ())
+    }
+}
+
 /// 确认单个项目的删除
 ///
 /// # 参数
@@ -106,50 +161,336 @@ pub fn confirm_deletion(search_result: &SearchResult, verbose: bool) -> Result<b
 /// - `Ok(false)` - 用户跳过
 /// - `Err("all")` - 用户选择删除所有剩余项目
 /// - `Err("quit")` - 用户取消操作
-pub fn confirm_item_deletion(
-    path: &std::path::Path,
+/// - `Err("undo")` - 用户要求恢复上一个删除的项目
+pub fn confirm_item_deletion(path: &Path, is_dir: bool, size: u64) -> Result<bool, String> {
+    confirm_item_deletion_with_opener(path, is_dir, size, &SystemOpener)
+}
+
+/// 确认单个项目的删除，使用指定的打开方式处理 `o` 操作
+///
+/// 用户选择 `o` 时在文件管理器中打开该路径，然后重新询问。
+/// 打开失败（如系统没有可用的文件管理器）时只输出提示，不中断确认流程。
+pub fn confirm_item_deletion_with_opener(
+    path: &Path,
     is_dir: bool,
     size: u64,
+    opener: &dyn Opener,
 ) -> Result<bool, String> {
     let item_type = if is_dir { "Directory" } else { "File" };
     let size_str = format_size(size);
 
+    loop {
+        print!(
+            "\n🗑️  {}: {} (Size: {})\n   Delete? (y/N/a=all/q=quit/o=open/u=undo last): ",
+            item_type,
+            path.display(),
+            size_str
+        );
+        io::stdout().flush().map_err(|e| e.to_string())?;
+
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .map_err(|e| e.to_string())?;
+
+        match classify_input(&input) {
+            ItemAction::Delete => return Ok(true),
+            ItemAction::Skip => return Ok(false),
+            ItemAction::All => return Err("all".to_string()),
+            ItemAction::Quit => return Err("quit".to_string()),
+            ItemAction::Undo => return Err("undo".to_string()),
+            ItemAction::Open => {
+                open_in_file_manager(path, opener);
+            }
+        }
+    }
+}
+
+/// 通过提示函数（默认为标准输入）逐项确认删除，并在终端显示删除进度
+pub struct PromptConfirmer<P> {
+    /// 询问用户是否删除单个项目的函数，返回值与 `confirm_item_deletion` 相同
+    prompt: P,
+    /// 是否为静默模式（不显示进度）
+    quiet: bool,
+}
+
+impl<P> PromptConfirmer<P>
+where
+    P: FnMut(&Path, bool, u64) -> Result<bool, String>,
+{
+    /// 创建使用指定提示函数的确认方
+    pub fn new(prompt: P, quiet: bool) -> Self {
+        PromptConfirmer { prompt, quiet }
+    }
+}
+
+impl<P> Confirmer for PromptConfirmer<P>
+where
+    P: FnMut(&Path, bool, u64) -> Result<bool, String>,
+{
+    fn confirm(&mut self, item: &DeleteItem) -> Result<Confirmation, CleanError> {
+        match (self.prompt)(&item.path, item.is_dir, item.size) {
+            Ok(true) => Ok(Confirmation::Yes),
+            Ok(false) => Ok(Confirmation::No),
+            Err(ref e) if e == "all" => Ok(Confirmation::All),
+            Err(ref e) if e == "quit" => Ok(Confirmation::Quit),
+            Err(ref e) if e == "undo" => Ok(Confirmation::Undo),
+            Err(e) => {
+                if !self.quiet {
+                    println!("  ❌ Error: {}", e);
+                }
+                Err(CleanError::Other(e))
+            }
+        }
+    }
+
+    fn notify(&mut self, event: &DeleteEvent) {
+        // 警告在静默模式下也显示
+        if let DeleteEvent::Warning(message) = event {
+            crate::output::print_warning(message);
+            return;
+        }
+        if self.quiet {
+            return;
+        }
+        match event {
+            DeleteEvent::AlreadyDeleted { path, parent } => println!(
+                "  ⏭️  Already deleted with {}: {}",
+                parent.display(),
+                path.display()
+            ),
+            DeleteEvent::SafetyCheckFailed { path, error } => {
+                println!("  ⚠️  Safety check failed: {} - {}", path.display(), error)
+            }
+            DeleteEvent::Skipped { path, reason: None } => {
+                println!("  ⏭️  Skipped: {}", path.display())
+            }
+            DeleteEvent::Skipped {
+                path,
+                reason: Some(reason),
+            } => println!("  ⏭️  Skipped: {} - {}", path.display(), reason),
+            DeleteEvent::AllConfirmed => println!("  ✅ All remaining items will be deleted"),
+            DeleteEvent::Cancelled => println!("  ❌ Operation cancelled by user"),
+            DeleteEvent::Deleted(path) => println!("  ✅ Deleted: {}", path.display()),
+            DeleteEvent::Failed { path, error } => {
+                println!("  ❌ Failed: {} - {}", path.display(), error)
+            }
+            DeleteEvent::Restored(path) => println!("  ↩️  Restored: {}", path.display()),
+            DeleteEvent::RestoredAs { path, renamed } => println!(
+                "  ↩️  Restored: {} as {} (original location is occupied)",
+                path.display(),
+                renamed.display()
+            ),
+            DeleteEvent::RestoreConflict(path) => println!(
+                "  ⚠️  Not restored: {} already exists; the item is still in the trash",
+                path.display()
+            ),
+            DeleteEvent::RestoreFailed { path, error } => {
+                println!("  ❌ Could not restore {}: {}", path.display(), error)
+            }
+            DeleteEvent::NothingToUndo => println!("  ⚠️  Nothing to undo"),
+            DeleteEvent::Warning(_) => {}
+        }
+    }
+}
+
+/// 确认一个根路径下的所有项目，作为一个整体删除或跳过
+///
+/// 用户选择 `o` 时在文件管理器中打开该根路径，然后重新询问。
+///
+/// # 参数
+/// * `root` - 扫描根路径
+/// * `dirs` - 该根路径下要删除的目录数量
+/// * `files` - 该根路径下要删除的文件数量
+/// * `size` - 该根路径下要删除的总大小（字节）
+///
+/// # 返回
+/// 用户的选择（不会是 `ItemAction::Open` 或 `ItemAction::Undo`）
+pub fn confirm_root_deletion(
+    root: &Path,
+    dirs: usize,
+    files: usize,
+    size: u64,
+) -> Result<ItemAction, CleanError> {
+    loop {
+        print!(
+            "\n📂 {}: {} directories, {} files ({})\n   Delete these items? (y/N/a=all roots/q=quit/o=open): ",
+            root.display(),
+            dirs,
+            files,
+            format_size(size)
+        );
+        io::stdout()
+            .flush()
+            .map_err(|e| CleanError::Other(e.to_string()))?;
+
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .map_err(|e| CleanError::Other(e.to_string()))?;
+
+        match classify_input(&input) {
+            ItemAction::Open => {
+                open_in_file_manager(root, &SystemOpener);
+            }
+            ItemAction::Undo => {
+                println!("   ⚠️  Undo is not available when confirming whole roots");
+            }
+            action => return Ok(action),
+        }
+    }
+}
+
+/// 要求用户输入的确认短语：即将删除的项目数量
+///
+/// 需要输入具体数字而不是 `y`，避免习惯性地按回车或粘贴命令时误删大量内容。
+pub fn confirmation_phrase(item_count: usize) -> String {
+    item_count.to_string()
+}
+
+/// 检查用户输入是否与确认短语一致（忽略首尾空白）
+pub fn verify_phrase(expected: &str, input: &str) -> bool {
+    input.trim() == expected
+}
+
+/// 要求用户输入确认短语后才继续删除
+///
+/// # 参数
+/// * `item_count` - 即将删除的项目数量
+/// * `total_size` - 即将删除的总大小（字节）
+///
+/// # 返回
+/// 输入与短语一致时返回 `Ok(true)`，否则返回 `Ok(false)`
+pub fn confirm_with_phrase(item_count: usize, total_size: u64) -> Result<bool, CleanError> {
+    confirm_with_phrase_from(&mut io::stdin().lock(), item_count, total_size)
+}
+
+/// 从指定输入读取确认短语，便于在测试中替换标准输入
+pub fn confirm_with_phrase_from(
+    reader: &mut dyn BufRead,
+    item_count: usize,
+    total_size: u64,
+) -> Result<bool, CleanError> {
+    let phrase = confirmation_phrase(item_count);
     print!(
-        "\n🗑️  {}: {} (Size: {})\n   Delete? (y/N/a=all/q=quit): ",
-        item_type,
-        path.display(),
-        size_str
+        "\n⚠️  About to move {} items ({}) to trash. Type {} to confirm: ",
+        item_count,
+        format_size(total_size),
+        phrase
     );
-    io::stdout().flush().map_err(|e| e.to_string())?;
+    io::stdout()
+        .flush()
+        .map_err(|e| CleanError::Other(e.to_string()))?;
 
     let mut input = String::new();
-    io::stdin()
+    reader
         .read_line(&mut input)
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CleanError::Other(e.to_string()))?;
+    Ok(verify_phrase(&phrase, &input))
+}
+
+/// 无法识别项目类型时可供选择的项目类型，按提示中的编号排列
+const KNOWN_PROJECT_TYPES: [ProjectType; 5] = [
+    ProjectType::NodeJs,
+    ProjectType::Rust,
+    ProjectType::Python,
+    ProjectType::Go,
+    ProjectType::Java,
+];
+
+/// 无法识别项目类型时用户的选择
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectTypeChoice {
+    /// 使用所选项目类型的默认配置
+    Pick(ProjectType),
+    /// 使用通用的默认配置
+    Generic,
+    /// 取消操作
+    Quit,
+}
+
+/// 将用户输入解析为项目类型的选择（编号或名称，不区分大小写），无法识别时返回 `None`
+pub fn classify_project_type_input(input: &str) -> Option<ProjectTypeChoice> {
+    let input = input.trim().to_lowercase();
+    match input.as_str() {
+        "" | "g" | "generic" => return Some(ProjectTypeChoice::Generic),
+        "q" | "quit" => return Some(ProjectTypeChoice::Quit),
+        _ => {}
+    }
+    if let Ok(number) = input.parse::<usize>() {
+        return number
+            .checked_sub(1)
+            .and_then(|idx| KNOWN_PROJECT_TYPES.get(idx))
+            .map(|project_type| ProjectTypeChoice::Pick(project_type.clone()));
+    }
+    KNOWN_PROJECT_TYPES
+        .iter()
+        .find(|project_type| project_type.name().to_lowercase() == input)
+        .map(|project_type| ProjectTypeChoice::Pick(project_type.clone()))
+}
+
+/// 告诉用户无法识别项目类型，询问要使用的项目类型
+///
+/// # 返回
+/// 用户的选择，输入结束（如标准输入被关闭）时使用通用的默认配置
+pub fn prompt_project_type() -> Result<ProjectTypeChoice, CleanError> {
+    prompt_project_type_from(&mut io::stdin().lock())
+}
+
+/// 从指定输入读取项目类型的选择，便于在测试中替换标准输入
+pub fn prompt_project_type_from(reader: &mut dyn BufRead) -> Result<ProjectTypeChoice, CleanError> {
+    println!("\n❓ Could not detect the project type.");
+    for (idx, project_type) in KNOWN_PROJECT_TYPES.iter().enumerate() {
+        println!("   {}. {}", idx + 1, project_type.name());
+    }
+    loop {
+        print!("   Pick a project type (1-5), Enter for generic defaults, q to quit: ");
+        io::stdout()
+            .flush()
+            .map_err(|e| CleanError::Other(e.to_string()))?;
+
+        let mut input = String::new();
+        let read = reader
+            .read_line(&mut input)
+            .map_err(|e| CleanError::Other(e.to_string()))?;
+        if read == 0 {
+            return Ok(ProjectTypeChoice::Generic);
+        }
+        match classify_project_type_input(&input) {
+            Some(choice) => return Ok(choice),
+            None => println!("   ⚠️  Unrecognized choice: {}", input.trim()),
+        }
+    }
+}
 
-    let trimmed = input.trim().to_lowercase();
-    match trimmed.as_str() {
-        "y" | "yes" => Ok(true),
-        "a" | "all" => Err("all".to_string()),
-        "q" | "quit" => Err("quit".to_string()),
-        _ => Ok(false),
+/// 在文件管理器中打开路径，失败时输出提示
+fn open_in_file_manager(path: &Path, opener: &dyn Opener) -> bool {
+    match opener.open(path) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("⚠️  Could not open {}: {}", path.display(), e);
+            false
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use build_cleaner_core::search::SearchResult;
+    use std::cell::RefCell;
     use std::path::PathBuf;
 
     #[test]
     fn test_confirm_deletion_format() {
         // 测试确认信息的格式
         let search_result = SearchResult {
-            folders: vec![PathBuf::from("/test/dir1")],
-            files: vec![PathBuf::from("/test/file1.txt")],
+            folders: vec![PathBuf::from("/test/dir1")].into(),
+            files: vec![PathBuf::from("/test/file1.txt")].into(),
             total_size: 1024,
             total_dirs_scanned: 1,
             total_files_scanned: 1,
+            ..Default::default()
         };
 
         // 这个测试主要验证函数不会 panic
@@ -157,4 +498,101 @@ mod tests {
         let _ = search_result.folders.len();
         let _ = search_result.files.len();
     }
+
+    #[test]
+    fn test_confirm_with_phrase() {
+        // 输入正确的项目数量时继续
+        let mut input = io::Cursor::new("1234\n");
+        assert!(confirm_with_phrase_from(&mut input, 1234, 0).unwrap());
+        assert!(verify_phrase("1234", "  1234 \r\n"));
+
+        // 习惯性的 y、回车或错误的数量都会中止
+        for wrong in ["y\n", "\n", "123\n", "yes 1234\n", ""] {
+            let mut input = io::Cursor::new(wrong);
+            assert!(!confirm_with_phrase_from(&mut input, 1234, 0).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_prompt_project_type() {
+        assert_eq!(
+            classify_project_type_input("2\n"),
+            Some(ProjectTypeChoice::Pick(ProjectType::Rust))
+        );
+        assert_eq!(
+            classify_project_type_input("node.js"),
+            Some(ProjectTypeChoice::Pick(ProjectType::NodeJs))
+        );
+        assert_eq!(
+            classify_project_type_input("\n"),
+            Some(ProjectTypeChoice::Generic)
+        );
+        assert_eq!(
+            classify_project_type_input("Q"),
+            Some(ProjectTypeChoice::Quit)
+        );
+        assert_eq!(classify_project_type_input("0"), None);
+        assert_eq!(classify_project_type_input("6"), None);
+
+        // 无法识别的输入会重新询问
+        let mut input = io::Cursor::new("kotlin\n5\n");
+        assert_eq!(
+            prompt_project_type_from(&mut input).unwrap(),
+            ProjectTypeChoice::Pick(ProjectType::Java)
+        );
+        // 输入结束时使用通用的默认配置
+        let mut input = io::Cursor::new("");
+        assert_eq!(
+            prompt_project_type_from(&mut input).unwrap(),
+            ProjectTypeChoice::Generic
+        );
+    }
+
+    #[test]
+    fn test_classify_input() {
+        assert_eq!(classify_input("y\n"), ItemAction::Delete);
+        assert_eq!(classify_input("YES"), ItemAction::Delete);
+        assert_eq!(classify_input("a"), ItemAction::All);
+        assert_eq!(classify_input("q"), ItemAction::Quit);
+        assert_eq!(classify_input(""), ItemAction::Skip);
+        // o 为重新询问前的打开操作，而不是跳过
+        assert_eq!(classify_input("o\n"), ItemAction::Open);
+        assert_eq!(classify_input("Open"), ItemAction::Open);
+        assert_eq!(classify_input("u\n"), ItemAction::Undo);
+    }
+
+    #[test]
+    fn test_open_in_file_manager() {
+        struct RecordingOpener {
+            opened: RefCell<Vec<PathBuf>>,
+        }
+
+        impl Opener for RecordingOpener {
+            fn open(&self, path: &Path) -> io::Result<()> {
+                self.opened.borrow_mut().push(path.to_path_buf());
+                Ok(())
+            }
+        }
+
+        struct MissingOpener;
+
+        impl Opener for MissingOpener {
+            fn open(&self, _path: &Path) -> io::Result<()> {
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "xdg-open not found",
+                ))
+            }
+        }
+
+        let opener = RecordingOpener {
+            opened: RefCell::new(Vec::new()),
+        };
+        let path = PathBuf::from("/test/node_modules");
+        assert!(open_in_file_manager(&path, &opener));
+        assert_eq!(*opener.opened.borrow(), vec![path.clone()]);
+
+        // 打开器不可用时不会返回错误，只输出提示
+        assert!(!open_in_file_manager(&path, &MissingOpener));
+    }
 }
@@ -2,27 +2,40 @@ mod args;
 mod executor;
 mod interactive;
 mod output;
+mod watch;
 
-use args::Args;
+use args::{Cli, Command};
 use build_cleaner_core::log;
 use clap::Parser;
 use executor::CommandExecutor;
 
 fn main() {
-    let args = Args::parse();
+    let cli = Cli::parse();
+    let (args, watch_interval) = match cli.command {
+        Some(Command::Watch(watch)) => (watch.args, Some(watch.interval)),
+        None => (cli.args, None),
+    };
 
     if args.debug {
         log::init_logger(::log::LevelFilter::Debug);
     } else if args.verbose {
         log::init_logger(::log::LevelFilter::Info);
-    } else if args.quiet {
+    } else if args.quiet || args.silent {
         log::init_logger(::log::LevelFilter::Error);
     } else {
         log::init_logger(::log::LevelFilter::Warn);
     }
 
-    if let Err(e) = CommandExecutor::execute(args) {
-        output::print_error(&e.to_string());
-        std::process::exit(1);
+    let result = match watch_interval {
+        Some(interval) => watch::run(args, interval).map(|_| 0),
+        None => CommandExecutor::execute(args),
+    };
+    match result {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            output::print_error(&e.to_string());
+            std::process::exit(1);
+        }
     }
 }
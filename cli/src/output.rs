@@ -1,11 +1,235 @@
-/// 打印清理报告
+use build_cleaner_core::CleanError;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 删除进度行两次刷新之间的最短间隔，删除很快结束时也不会显示进度
+const DELETE_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 报告的详细程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportLevel {
+    /// 完整报告
+    Full,
+    /// 只输出单行摘要（`--quiet`）
+    Summary,
+    /// 什么都不输出（`--silent`）
+    Silent,
+}
+
+impl ReportLevel {
+    /// 根据命令行选项确定详细程度：写到文件时总是输出完整报告，否则 `silent` 优先于 `quiet`
+    pub fn new(quiet: bool, silent: bool, to_file: bool) -> Self {
+        if to_file {
+            ReportLevel::Full
+        } else if silent {
+            ReportLevel::Silent
+        } else if quiet {
+            ReportLevel::Summary
+        } else {
+            ReportLevel::Full
+        }
+    }
+}
+
+/// 打开报告输出目标：指定文件时创建（或截断）该文件，否则使用标准输出
 ///
 /// # 参数
+/// * `output` - 可选的报告文件路径
+///
+/// # 返回
+/// 报告输出目标，如果无法创建文件则返回错误
+pub fn open_report_sink(output: Option<&Path>) -> Result<Box<dyn Write>, CleanError> {
+    match output {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| {
+                CleanError::Other(format!(
+                    "Failed to create output file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            Ok(Box::new(io::BufWriter::new(file)))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+/// 将清理报告写入输出目标，报告总是以恰好一个换行符结尾
+///
+/// # 参数
+/// * `sink` - 报告输出目标
 /// * `report` - 报告内容
-/// * `quiet` - 是否为静默模式（静默模式下不输出）
-pub fn print_report(report: &str, quiet: bool) {
-    if !quiet {
-        println!("{}", report);
+///
+/// # 返回
+/// 如果写入成功返回 `Ok(())`，否则返回错误
+pub fn write_report(sink: &mut dyn Write, report: &str) -> Result<(), CleanError> {
+    writeln!(sink, "{}", report.trim_end_matches(['\n', '\r']))
+        .and_then(|_| sink.flush())
+        .map_err(|e| CleanError::Other(format!("Failed to write report: {}", e)))
+}
+
+/// 原地刷新的单行进度输出（通常写到标准错误）
+///
+/// 每次更新用 `\r` 回到行首并覆盖上一次的内容。结束时（包括出错提前返回时被丢弃）
+/// 总会换行并刷新，之后写到标准输出的报告不会与未结束的进度行混在一起。
+pub struct ProgressLine<W: Write> {
+    writer: W,
+    /// 上一次输出的字符数，用于清除较短的新内容之后残留的字符
+    width: usize,
+    /// 是否有尚未换行的进度行
+    active: bool,
+}
+
+impl<W: Write> ProgressLine<W> {
+    /// 创建写入指定目标的进度行
+    pub fn new(writer: W) -> Self {
+        ProgressLine {
+            writer,
+            width: 0,
+            active: false,
+        }
+    }
+
+    /// 用新内容覆盖当前进度行
+    pub fn update(&mut self, text: &str) {
+        self.overwrite(text);
+        self.active = true;
+        let _ = self.writer.flush();
+    }
+
+    /// 用最终消息覆盖进度行并换行
+    pub fn finish(&mut self, message: &str) {
+        self.overwrite(message);
+        let _ = writeln!(self.writer);
+        let _ = self.writer.flush();
+        self.active = false;
+        self.width = 0;
+    }
+
+    /// 回到行首写入内容，不足上一次长度的部分用空格覆盖
+    fn overwrite(&mut self, text: &str) {
+        let width = text.chars().count();
+        let padding = self.width.saturating_sub(width);
+        let _ = write!(self.writer, "\r{}{}", text, " ".repeat(padding));
+        self.width = width;
+    }
+}
+
+impl<W: Write> Drop for ProgressLine<W> {
+    fn drop(&mut self) {
+        // 没有正常结束时（如扫描出错）也要结束进度行，避免错误信息接在进度后面
+        if self.active {
+            let _ = writeln!(self.writer);
+            let _ = self.writer.flush();
+        }
+    }
+}
+
+/// 删除进度显示：按已完成项目的速度估计剩余时间，刷新频率受限
+pub struct DeleteProgress<W: Write> {
+    line: ProgressLine<W>,
+    start: Instant,
+    /// 上一次刷新进度行的时间，尚未显示过时为 `None`
+    last_update: Option<Instant>,
+}
+
+impl<W: Write> DeleteProgress<W> {
+    /// 创建写入指定目标的删除进度显示，从现在开始计时
+    pub fn new(writer: W) -> Self {
+        DeleteProgress {
+            line: ProgressLine::new(writer),
+            start: Instant::now(),
+            last_update: None,
+        }
+    }
+
+    /// 报告删除进度，距开始或上一次刷新不足刷新间隔时忽略
+    ///
+    /// # 参数
+    /// * `done` - 已处理的项目数
+    /// * `total` - 项目总数
+    pub fn update(&mut self, done: usize, total: usize) {
+        let now = Instant::now();
+        let since = now.duration_since(self.last_update.unwrap_or(self.start));
+        if since < DELETE_PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_update = Some(now);
+        self.line.update(&format_delete_progress(
+            done,
+            total,
+            now.duration_since(self.start),
+        ));
+    }
+
+    /// 结束删除进度行，从未显示过进度时不输出任何内容
+    pub fn finish(&mut self) {
+        if self.last_update.is_some() {
+            self.line.finish("✅ Deleting completed");
+        }
+    }
+}
+
+/// 按目前为止的平均速度估计剩余时间
+///
+/// # 参数
+/// * `done` - 已处理的项目数
+/// * `total` - 项目总数
+/// * `elapsed` - 已用时间
+///
+/// # 返回
+/// 估计的剩余时间；尚无进度（无法计算速度）时返回 `None`
+pub fn estimate_remaining(done: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if done == 0 || elapsed.is_zero() {
+        return None;
+    }
+    let remaining = total.saturating_sub(done);
+    Some(elapsed.mul_f64(remaining as f64 / done as f64))
+}
+
+/// 格式化删除进度，如 `🗑️  Deleting 4,200/50,000 (~3m remaining)`
+///
+/// # 参数
+/// * `done` - 已处理的项目数
+/// * `total` - 项目总数
+/// * `elapsed` - 已用时间
+pub fn format_delete_progress(done: usize, total: usize, elapsed: Duration) -> String {
+    let progress = format!(
+        "🗑️  Deleting {}/{}",
+        format_count(done),
+        format_count(total)
+    );
+    match estimate_remaining(done, total, elapsed) {
+        Some(remaining) => format!("{} (~{} remaining)", progress, format_eta(remaining)),
+        None => progress,
+    }
+}
+
+/// 用千位分隔符格式化数量，如 `50,000`
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// 格式化剩余时间，保留一个最大的单位，如 `45s`、`3m`、`1h20m`
+fn format_eta(remaining: Duration) -> String {
+    let secs = remaining.as_secs_f64().round() as u64;
+    if secs < 60 {
+        format!("{}s", secs.max(1))
+    } else if secs < 3600 {
+        format!("{}m", (secs + 30) / 60)
+    } else {
+        let minutes = (secs + 30) / 60;
+        format!("{}h{}m", minutes / 60, minutes % 60)
     }
 }
 
@@ -51,17 +275,13 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_print_report_quiet() {
-        // 测试静默模式不输出
-        // 这个测试主要验证函数不会 panic
-        print_report("test report", true);
-    }
-
-    #[test]
-    fn test_print_report_verbose() {
-        // 测试正常模式输出
-        // 这个测试主要验证函数不会 panic
-        print_report("test report", false);
+    fn test_report_level() {
+        assert_eq!(ReportLevel::new(false, false, false), ReportLevel::Full);
+        assert_eq!(ReportLevel::new(true, false, false), ReportLevel::Summary);
+        assert_eq!(ReportLevel::new(false, true, false), ReportLevel::Silent);
+        assert_eq!(ReportLevel::new(true, true, false), ReportLevel::Silent);
+        // 写到文件时总是输出完整报告
+        assert_eq!(ReportLevel::new(true, true, true), ReportLevel::Full);
     }
 
     #[test]
@@ -78,6 +298,88 @@ mod tests {
         print_warning("test warning");
     }
 
+    #[test]
+    fn test_progress_line_terminated_before_report() {
+        let mut stderr = Vec::new();
+        let mut stdout = Vec::new();
+        {
+            let mut progress = ProgressLine::new(&mut stderr);
+            progress.update("📊 Scanning... Files: 1200, Dirs: 30");
+            progress.update("📊 Scanning... Files: 9");
+            progress.finish("✅ Scanning completed");
+        }
+        write_report(&mut stdout, "Cleaned 1 directories\n\n").unwrap();
+
+        // 进度行以换行结束，最后一行没有残留的 \r 内容；较短的内容覆盖了之前的字符
+        let stderr = String::from_utf8(stderr).unwrap();
+        assert!(stderr.ends_with('\n'));
+        let last_line = stderr.trim_end_matches('\n').rsplit('\r').next().unwrap();
+        assert_eq!(last_line.trim_end(), "✅ Scanning completed");
+        // 报告以恰好一个换行符结尾
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            "Cleaned 1 directories\n"
+        );
+
+        // 出错提前返回（没有调用 finish）时，丢弃进度行也会换行
+        let mut stderr = Vec::new();
+        {
+            let mut progress = ProgressLine::new(&mut stderr);
+            progress.update("📊 Scanning... Files: 3");
+        }
+        assert!(String::from_utf8(stderr).unwrap().ends_with('\n'));
+
+        // 从未更新过的进度行被丢弃时不输出任何内容
+        let mut stderr = Vec::new();
+        drop(ProgressLine::new(&mut stderr));
+        assert!(stderr.is_empty());
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        // 尚无进度时无法估计
+        assert_eq!(estimate_remaining(0, 50_000, Duration::from_secs(5)), None);
+        assert_eq!(estimate_remaining(10, 100, Duration::ZERO), None);
+
+        // 完成 4,200 项用时 20 秒，剩余 45,800 项约 218 秒
+        let remaining = estimate_remaining(4_200, 50_000, Duration::from_secs(20)).unwrap();
+        assert_eq!(remaining.as_secs(), 218);
+        assert_eq!(
+            format_delete_progress(4_200, 50_000, Duration::from_secs(20)),
+            "🗑️  Deleting 4,200/50,000 (~4m remaining)"
+        );
+
+        // 一半用时 1 小时，剩余同样 1 小时
+        assert_eq!(
+            estimate_remaining(500, 1_000, Duration::from_secs(3600)),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            format_delete_progress(500, 1_000, Duration::from_secs(4800)),
+            "🗑️  Deleting 500/1,000 (~1h20m remaining)"
+        );
+
+        // 接近完成时剩余时间很短，但不会显示为 0 秒
+        assert_eq!(
+            estimate_remaining(999, 1_000, Duration::from_secs(100)).unwrap(),
+            Duration::from_secs(100).mul_f64(1.0 / 999.0)
+        );
+        assert_eq!(
+            format_delete_progress(999, 1_000, Duration::from_secs(100)),
+            "🗑️  Deleting 999/1,000 (~1s remaining)"
+        );
+        assert_eq!(
+            estimate_remaining(1_000, 1_000, Duration::from_secs(100)),
+            Some(Duration::ZERO)
+        );
+
+        // 没有进度时只显示计数
+        assert_eq!(
+            format_delete_progress(0, 1_234_567, Duration::from_secs(1)),
+            "🗑️  Deleting 0/1,234,567"
+        );
+    }
+
     #[test]
     fn test_print_scanning_start() {
         // 测试扫描开始信息
@@ -0,0 +1,272 @@
+use crate::args::Args;
+use crate::executor::{CommandExecutor, RunSummary};
+use build_cleaner_core::{CleanError, ReportGenerator};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 收到 Ctrl-C 后设置，监视模式在当前一轮结束后退出
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// 等待下一轮时每次休眠的最长时间，以便及时响应 Ctrl-C
+const SLEEP_STEP: Duration = Duration::from_millis(250);
+
+/// 监视模式使用的时钟，便于在测试中替换
+pub trait Clock {
+    /// 当前时间
+    fn now(&self) -> Instant;
+    /// 休眠指定的时间
+    fn sleep(&self, duration: Duration);
+}
+
+/// 系统时钟
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// 按固定间隔重复执行清理，直到收到 Ctrl-C
+///
+/// 第一次按 Ctrl-C 时不会中断正在进行的一轮，而是在其结束后退出；再按一次立即退出。
+///
+/// # 参数
+/// * `args` - 命令行参数，每一轮使用相同的参数
+/// * `interval` - 两轮开始之间的间隔
+pub fn run(args: Args, interval: Duration) -> Result<(), CleanError> {
+    install_interrupt_handler();
+    let quiet = args.quiet || args.silent;
+    if !quiet {
+        println!(
+            "👀 Watching, cleaning every {} (press Ctrl-C to stop)",
+            format_interval(interval)
+        );
+    }
+
+    let runs = watch_loop(
+        &SystemClock,
+        interval,
+        || INTERRUPTED.load(Ordering::Relaxed),
+        |iteration| {
+            let result = CommandExecutor::execute_with_result(args.clone());
+            log_run(iteration, &result, quiet);
+            result
+        },
+    );
+
+    if !quiet {
+        println!("👋 Watch stopped after {} runs", runs);
+    }
+    Ok(())
+}
+
+/// 监视模式的调度循环
+///
+/// 每一轮在上一轮结束后才开始，不会同时进行两轮。某一轮耗时超过间隔时，
+/// 错过的计划时间点被跳过，下一轮在其后第一个计划时间点开始。
+///
+/// # 参数
+/// * `clock` - 时钟
+/// * `interval` - 两轮开始之间的间隔
+/// * `stopped` - 返回 `true` 时结束循环，在每一轮之前和等待期间检查
+/// * `run_once` - 执行一轮清理，参数为从 1 开始的轮次
+///
+/// # 返回
+/// 执行的轮数
+pub fn watch_loop<C, S, R>(clock: &C, interval: Duration, stopped: S, mut run_once: R) -> usize
+where
+    C: Clock,
+    S: Fn() -> bool,
+    R: FnMut(usize) -> Result<RunSummary, CleanError>,
+{
+    let interval = interval.max(Duration::from_millis(1));
+    let mut runs = 0;
+    let mut next_run = clock.now();
+
+    while !stopped() {
+        runs += 1;
+        // 单轮失败（如某个路径暂时不可用）不结束监视，错误已在 run_once 中记录
+        let _ = run_once(runs);
+
+        // 跳过上一轮运行期间错过的计划时间点
+        next_run += interval;
+        let now = clock.now();
+        if next_run < now {
+            let missed = (now - next_run).as_nanos() / interval.as_nanos() + 1;
+            log::info!(
+                "Skipped {} scheduled runs while the previous run was still running",
+                missed
+            );
+            next_run += interval * missed as u32;
+        }
+
+        // 分段休眠，等待期间也能及时响应 Ctrl-C
+        loop {
+            if stopped() {
+                return runs;
+            }
+            let now = clock.now();
+            if now >= next_run {
+                break;
+            }
+            clock.sleep((next_run - now).min(SLEEP_STEP));
+        }
+    }
+    runs
+}
+
+/// 输出一轮清理的摘要
+fn log_run(iteration: usize, result: &Result<RunSummary, CleanError>, quiet: bool) {
+    match result {
+        Ok(summary) if summary.cancelled => {
+            if !quiet {
+                println!("🔁 Run {}: cancelled", iteration);
+            }
+        }
+        Ok(summary) => {
+            if !quiet {
+                println!(
+                    "🔁 Run {}: {}",
+                    iteration,
+                    ReportGenerator::format_status_line(&summary.stats)
+                );
+            }
+        }
+        Err(e) => crate::output::print_error(&format!("Run {} failed: {}", iteration, e)),
+    }
+}
+
+/// 格式化间隔，如 `1h`、`30m`、`90s`
+fn format_interval(interval: Duration) -> String {
+    let secs = interval.as_secs();
+    if secs == 0 {
+        format!("{}ms", interval.as_millis())
+    } else if secs.is_multiple_of(3600) {
+        format!("{}h", secs / 3600)
+    } else if secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// 安装 Ctrl-C 处理：第一次只设置标志，第二次立即退出
+#[cfg(unix)]
+fn install_interrupt_handler() {
+    extern "C" fn on_interrupt(_signal: libc::c_int) {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            // SAFETY: _exit 是异步信号安全的
+            unsafe { libc::_exit(130) };
+        }
+    }
+    // SAFETY: 处理函数只访问原子变量和异步信号安全的函数
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+/// 非 Unix 平台保持默认的 Ctrl-C 行为（立即退出）
+#[cfg(not(unix))]
+fn install_interrupt_handler() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// 模拟时钟，休眠时直接推进时间
+    struct MockClock {
+        start: Instant,
+        elapsed: Cell<Duration>,
+    }
+
+    impl MockClock {
+        fn new() -> Self {
+            MockClock {
+                start: Instant::now(),
+                elapsed: Cell::new(Duration::ZERO),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.elapsed.set(self.elapsed.get() + duration);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.advance(duration);
+        }
+    }
+
+    #[test]
+    fn test_watch_loop_schedule() {
+        let hour = Duration::from_secs(3600);
+
+        // 每轮耗时 1 分钟，间隔 1 小时：在 0h、1h、2h 开始，3h 时停止
+        let clock = MockClock::new();
+        let mut starts = Vec::new();
+        let runs = watch_loop(
+            &clock,
+            hour,
+            || clock.elapsed.get() >= hour * 3,
+            |_| {
+                starts.push(clock.elapsed.get());
+                clock.advance(Duration::from_secs(60));
+                Ok(RunSummary::default())
+            },
+        );
+        assert_eq!(runs, 3);
+        assert_eq!(starts, vec![Duration::ZERO, hour, hour * 2]);
+
+        // 第一轮耗时 2.5 小时：不会同时运行，错过的 1h、2h 被跳过，下一轮在 3h 开始
+        let clock = MockClock::new();
+        let mut starts = Vec::new();
+        let runs = watch_loop(
+            &clock,
+            hour,
+            || clock.elapsed.get() >= hour * 4,
+            |iteration| {
+                starts.push(clock.elapsed.get());
+                let took = if iteration == 1 {
+                    hour * 5 / 2
+                } else {
+                    Duration::from_secs(1)
+                };
+                clock.advance(took);
+                Err(CleanError::Other(
+                    "failed run does not stop watching".to_string(),
+                ))
+            },
+        );
+        assert_eq!(runs, 2);
+        assert_eq!(starts, vec![Duration::ZERO, hour * 3]);
+
+        // 已经停止时一轮也不运行
+        let clock = MockClock::new();
+        assert_eq!(
+            watch_loop(&clock, hour, || true, |_| Ok(RunSummary::default())),
+            0
+        );
+    }
+
+    #[test]
+    fn test_format_interval() {
+        assert_eq!(format_interval(Duration::from_secs(3600)), "1h");
+        assert_eq!(format_interval(Duration::from_secs(1800)), "30m");
+        assert_eq!(format_interval(Duration::from_secs(90)), "90s");
+        assert_eq!(format_interval(Duration::from_millis(500)), "500ms");
+    }
+}
@@ -0,0 +1,36 @@
+use std::process::Command;
+
+#[test]
+fn test_exit_code_reflects_run_summary() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    std::fs::create_dir(&project).unwrap();
+    std::fs::write(project.join("debug.log"), "log").unwrap();
+
+    let run_bc = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_bc"))
+            .arg(&project)
+            .args(["--clean", "*.log", "--dry-run", "--quiet"])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    // 正常完成的运行退出码为 0
+    let output = run_bc(&[]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    // 时间预算立即耗尽时结果不完整，退出码为 3
+    let output = run_bc(&["--max-total-time", "0s"]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    assert!(project.join("debug.log").exists());
+
+    // 只扫描模式同样遵守时间预算
+    let output = Command::new(env!("CARGO_BIN_EXE_bc"))
+        .arg(&project)
+        .args(["--clean", "*.log", "--scan-only", "--quiet"])
+        .args(["--max-total-time", "0s"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+}
@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::process::Command;
+
+/// 在示例项目上以预览模式运行 `bc`，返回标准输出
+fn run_bc(project: &Path, flag: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_bc"))
+        .arg(project)
+        .args(["--clean", "*.log", "--dry-run", flag])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_quiet_prints_only_summary_line() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    std::fs::create_dir(&project).unwrap();
+    std::fs::write(project.join("debug.log"), "log").unwrap();
+    std::fs::write(project.join("main.rs"), "fn main() {}").unwrap();
+
+    // --quiet 只输出最终的单行摘要
+    let stdout = run_bc(&project, "--quiet");
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "{:?}", stdout);
+    assert!(lines[0].starts_with("bc: 0d 1f "), "{:?}", stdout);
+
+    // --silent 什么都不输出
+    assert_eq!(run_bc(&project, "--silent"), "");
+    assert!(project.join("debug.log").exists());
+}
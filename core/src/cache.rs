@@ -0,0 +1,198 @@
+use crate::error::CleanError;
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 扫描缓存条目，记录目录在某个修改时间下的计算结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// 目录修改时间（秒）
+    pub mtime_secs: i64,
+    /// 目录修改时间（纳秒部分）
+    pub mtime_nanos: u32,
+    /// 目录的逻辑大小（字节）
+    pub size: u64,
+    /// 目录的物理大小（字节）
+    pub physical_size: u64,
+}
+
+impl CacheEntry {
+    /// 检查条目是否与给定的修改时间一致
+    pub fn is_fresh(&self, mtime: FileTime) -> bool {
+        self.mtime_secs == mtime.unix_seconds() && self.mtime_nanos == mtime.nanoseconds()
+    }
+}
+
+/// 扫描缓存接口，按目录路径和修改时间复用已计算的目录大小
+///
+/// 注意：目录的修改时间只反映直接子项的增删，不反映深层文件内容的变化，
+/// 因此缓存的大小在深层文件被修改后可能过期。
+pub trait ScanCache {
+    /// 查询目录的缓存条目，只有修改时间一致时才返回
+    fn get(&mut self, dir: &Path, mtime: FileTime) -> Option<CacheEntry>;
+
+    /// 写入或更新目录的缓存条目
+    fn insert(&mut self, dir: &Path, entry: CacheEntry);
+}
+
+/// 基于 JSON 文件的扫描缓存
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FileScanCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+impl FileScanCache {
+    /// 从文件加载缓存，文件不存在时返回空缓存
+    ///
+    /// # 参数
+    /// * `path` - 缓存文件路径
+    ///
+    /// # 返回
+    /// 加载的缓存，如果文件存在但无法解析则返回错误
+    pub fn load(path: &Path) -> Result<Self, CleanError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to read cache file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to parse cache file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 将缓存保存到文件
+    ///
+    /// # 参数
+    /// * `path` - 缓存文件路径
+    ///
+    /// # 返回
+    /// 如果保存成功返回 `Ok(())`，否则返回错误
+    pub fn save(&self, path: &Path) -> Result<(), CleanError> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| CleanError::Other(format!("Failed to serialize cache: {}", e)))?;
+        fs::write(path, content).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to write cache file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 移除目录已不存在的条目（例如已被清理的目录），避免缓存文件无限增长
+    ///
+    /// # 返回
+    /// 移除的条目数量
+    pub fn prune(&mut self) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|dir, _| dir.is_dir());
+        before - self.entries.len()
+    }
+
+    /// 缓存中的条目数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl ScanCache for FileScanCache {
+    fn get(&mut self, dir: &Path, mtime: FileTime) -> Option<CacheEntry> {
+        match self.entries.get(dir) {
+            Some(entry) if entry.is_fresh(mtime) => Some(entry.clone()),
+            Some(_) => {
+                // 修改时间不一致，使条目失效
+                self.entries.remove(dir);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, dir: &Path, entry: CacheEntry) {
+        self.entries.insert(dir.to_path_buf(), entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(mtime: FileTime, size: u64) -> CacheEntry {
+        CacheEntry {
+            mtime_secs: mtime.unix_seconds(),
+            mtime_nanos: mtime.nanoseconds(),
+            size,
+            physical_size: size,
+        }
+    }
+
+    #[test]
+    fn test_file_scan_cache_invalidation() {
+        let mut cache = FileScanCache::default();
+        let dir = PathBuf::from("/project/target");
+        let mtime = FileTime::from_unix_time(1_600_000_000, 0);
+
+        cache.insert(&dir, entry(mtime, 1024));
+        assert_eq!(cache.get(&dir, mtime).unwrap().size, 1024);
+
+        // 修改时间变化后条目失效并被移除
+        let newer = FileTime::from_unix_time(1_600_000_100, 0);
+        assert!(cache.get(&dir, newer).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_file_scan_cache_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_path = temp_dir.path().join("cache.json");
+
+        // 文件不存在时返回空缓存
+        let mut cache = FileScanCache::load(&cache_path).unwrap();
+        assert!(cache.is_empty());
+
+        let dir = PathBuf::from("/project/node_modules");
+        let mtime = FileTime::from_unix_time(1_600_000_000, 42);
+        cache.insert(&dir, entry(mtime, 2048));
+        cache.save(&cache_path).unwrap();
+
+        let mut loaded = FileScanCache::load(&cache_path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get(&dir, mtime), Some(entry(mtime, 2048)));
+    }
+
+    #[test]
+    fn test_file_scan_cache_prune() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("target");
+        fs::create_dir(&kept).unwrap();
+        let removed = temp_dir.path().join("node_modules");
+        let mtime = FileTime::from_unix_time(1_600_000_000, 0);
+
+        let mut cache = FileScanCache::default();
+        cache.insert(&kept, entry(mtime, 1024));
+        cache.insert(&removed, entry(mtime, 2048));
+
+        // 只移除目录已不存在的条目
+        assert_eq!(cache.prune(), 1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&kept, mtime), Some(entry(mtime, 1024)));
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 取消标志，用于提前结束扫描和删除
+///
+/// 可以在多个线程之间共享，任意一方调用 `cancel` 后所有持有者都会看到。设置了截止时间时，
+/// 超过截止时间后第一次检查会自动设置取消标志。
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelFlag {
+    /// 创建未取消的标志
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建在指定时间之后自动取消的标志
+    ///
+    /// # 参数
+    /// * `deadline` - 截止时间
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancelFlag {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// 设置取消标志
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 检查是否已取消，已超过截止时间时设置取消标志
+    ///
+    /// # 返回
+    /// 如果已取消返回 `true`
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.cancel();
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cancel_flag() {
+        // 克隆的标志共享取消状态
+        let flag = CancelFlag::new();
+        let shared = flag.clone();
+        assert!(!shared.is_cancelled());
+        flag.cancel();
+        assert!(shared.is_cancelled());
+
+        // 超过截止时间后自动取消
+        let expired = CancelFlag::with_deadline(Instant::now());
+        assert!(expired.is_cancelled());
+        let later = CancelFlag::with_deadline(Instant::now() + Duration::from_secs(3600));
+        assert!(!later.is_cancelled());
+    }
+}
@@ -0,0 +1,336 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 不在任何根路径下、按完整路径保存的条目使用的根路径序号
+const NO_ROOT: u32 = u32::MAX;
+
+/// 按（根路径序号, 相对路径）保存的路径列表，用于 `SearchResult` 的 `folders`/`files`
+///
+/// 大量匹配项通常共享少数几个扫描根路径，只保存一份根路径可以省去每个路径中重复的前缀。
+/// 不在任何根路径下的路径（如工具的全局缓存）按完整路径保存。读取时重新拼接出完整路径，
+/// 与保存前的路径完全相同；序列化为完整路径的列表，与 `Vec<PathBuf>` 的格式相同。
+#[derive(Clone, Default)]
+pub struct CompactPaths {
+    /// 根路径表，前缀最长的根路径在前
+    roots: Vec<PathBuf>,
+    /// 每个条目的根路径序号和相对路径（没有多余容量）
+    entries: Vec<(u32, Box<Path>)>,
+}
+
+impl CompactPaths {
+    /// 创建空列表
+    ///
+    /// # 参数
+    /// * `roots` - 路径共享的根路径，前缀最长的根路径优先
+    pub fn new(roots: &[PathBuf]) -> Self {
+        let mut paths = CompactPaths::default();
+        paths.set_roots(roots);
+        paths
+    }
+
+    /// 更换根路径表，已有的条目按新的根路径重新保存
+    ///
+    /// # 参数
+    /// * `roots` - 路径共享的根路径
+    pub fn set_roots(&mut self, roots: &[PathBuf]) {
+        let paths = self.to_paths();
+        let mut roots = roots.to_vec();
+        roots.sort_by(|a, b| {
+            b.as_os_str()
+                .len()
+                .cmp(&a.as_os_str().len())
+                .then_with(|| a.cmp(b))
+        });
+        roots.dedup();
+        self.roots = roots;
+        self.entries.clear();
+        self.extend(paths);
+        self.shrink_to_fit();
+    }
+
+    /// 追加一个路径
+    pub fn push(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let entry = self
+            .roots
+            .iter()
+            .enumerate()
+            .find_map(|(index, root)| {
+                let relative = path.strip_prefix(root).ok()?;
+                // strip_prefix 按组件比较，拼接回去与原路径不完全相同（如多余的 `.` 或分隔符）时按完整路径保存
+                (Self::join(root, relative).as_os_str() == path.as_os_str())
+                    .then(|| (index as u32, Box::from(relative)))
+            })
+            .unwrap_or_else(|| (NO_ROOT, Box::from(path)));
+        self.entries.push(entry);
+    }
+
+    /// 条目数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否没有条目
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 重新拼接第 `index` 个条目的完整路径
+    pub fn get(&self, index: usize) -> Option<PathBuf> {
+        self.entries
+            .get(index)
+            .map(|(root, relative)| self.resolve(*root, relative))
+    }
+
+    /// 按添加顺序依次重新拼接出完整路径
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = PathBuf> + ExactSizeIterator + '_ {
+        self.entries
+            .iter()
+            .map(|(root, relative)| self.resolve(*root, relative))
+    }
+
+    /// 重新拼接出全部完整路径
+    pub fn to_paths(&self) -> Vec<PathBuf> {
+        self.iter().collect()
+    }
+
+    /// 是否包含指定的路径
+    pub fn contains(&self, path: &Path) -> bool {
+        self.iter().any(|item| item == path)
+    }
+
+    /// 只保留满足条件的路径，保持原有顺序
+    pub fn retain(&mut self, mut keep: impl FnMut(&Path) -> bool) {
+        let roots = &self.roots;
+        self.entries
+            .retain(|(root, relative)| keep(&Self::resolve_with(roots, *root, relative)));
+    }
+
+    /// 按完整路径排序
+    pub fn sort(&mut self) {
+        let mut paths = self.to_paths();
+        paths.sort();
+        self.entries.clear();
+        self.extend(paths);
+    }
+
+    /// 释放多余的容量，添加完所有路径后调用
+    pub fn shrink_to_fit(&mut self) {
+        self.entries.shrink_to_fit();
+    }
+
+    /// 估算占用的堆内存（字节），包括根路径表和所有条目
+    pub fn heap_size(&self) -> usize {
+        self.roots.capacity() * std::mem::size_of::<PathBuf>()
+            + self
+                .roots
+                .iter()
+                .map(|root| root.as_os_str().len())
+                .sum::<usize>()
+            + self.entries.capacity() * std::mem::size_of::<(u32, Box<Path>)>()
+            + self
+                .entries
+                .iter()
+                .map(|(_, relative)| relative.as_os_str().len())
+                .sum::<usize>()
+    }
+
+    fn resolve(&self, root: u32, relative: &Path) -> PathBuf {
+        Self::resolve_with(&self.roots, root, relative)
+    }
+
+    fn resolve_with(roots: &[PathBuf], root: u32, relative: &Path) -> PathBuf {
+        match roots.get(root as usize) {
+            Some(root) => Self::join(root, relative),
+            None => relative.to_path_buf(),
+        }
+    }
+
+    /// 拼接根路径和相对路径；相对路径为空（根路径本身）时不追加分隔符
+    fn join(root: &Path, relative: &Path) -> PathBuf {
+        if relative.as_os_str().is_empty() {
+            root.to_path_buf()
+        } else {
+            root.join(relative)
+        }
+    }
+}
+
+impl<P: AsRef<Path>> Extend<P> for CompactPaths {
+    fn extend<I: IntoIterator<Item = P>>(&mut self, paths: I) {
+        for path in paths {
+            self.push(path);
+        }
+    }
+}
+
+impl<P: AsRef<Path>> FromIterator<P> for CompactPaths {
+    fn from_iter<I: IntoIterator<Item = P>>(paths: I) -> Self {
+        let mut compact = CompactPaths::default();
+        compact.extend(paths);
+        compact
+    }
+}
+
+impl From<Vec<PathBuf>> for CompactPaths {
+    fn from(paths: Vec<PathBuf>) -> Self {
+        paths.into_iter().collect()
+    }
+}
+
+impl From<CompactPaths> for Vec<PathBuf> {
+    fn from(paths: CompactPaths) -> Self {
+        paths.to_paths()
+    }
+}
+
+impl<'a> IntoIterator for &'a CompactPaths {
+    type Item = PathBuf;
+    type IntoIter = Box<dyn Iterator<Item = PathBuf> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+/// 按完整路径比较，与根路径表无关
+impl PartialEq for CompactPaths {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for CompactPaths {}
+
+impl PartialEq<Vec<PathBuf>> for CompactPaths {
+    fn eq(&self, other: &Vec<PathBuf>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter().cloned())
+    }
+}
+
+impl fmt::Debug for CompactPaths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Serialize for CompactPaths {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactPaths {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<PathBuf>::deserialize(deserializer).map(CompactPaths::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 估算 `Vec<PathBuf>` 占用的堆内存（字节）
+    fn vec_heap_size(paths: &[PathBuf]) -> usize {
+        std::mem::size_of_val(paths) + paths.iter().map(|path| path.capacity()).sum::<usize>()
+    }
+
+    #[test]
+    fn test_compact_paths_round_trip() {
+        let roots = vec![
+            PathBuf::from("/home/user/projects"),
+            PathBuf::from("/home/user/projects/app"),
+            PathBuf::from("/"),
+        ];
+        let paths: Vec<PathBuf> = [
+            "/home/user/projects/app/node_modules",
+            "/home/user/projects/lib/target",
+            "/home/user/projects",
+            "/var/cache/tool",
+            "relative/dist",
+            "/home/user/projects/./odd",
+            "/home/user/projects/trailing/",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let mut compact = CompactPaths::new(&roots);
+        compact.extend(&paths);
+        assert_eq!(compact.len(), paths.len());
+        // 重新拼接的路径逐字节与原路径相同
+        for (index, path) in paths.iter().enumerate() {
+            assert_eq!(
+                compact.get(index).unwrap().as_os_str(),
+                path.as_os_str(),
+                "{}",
+                path.display()
+            );
+        }
+        assert_eq!(compact, paths);
+        assert_eq!(compact.get(paths.len()), None);
+        // 前缀最长的根路径优先
+        assert_eq!(compact.entries[0].0, 0);
+        assert_eq!(compact.roots[0], PathBuf::from("/home/user/projects/app"));
+
+        // 更换根路径表后路径不变，比较时与根路径表无关
+        let plain = CompactPaths::from(paths.clone());
+        assert_eq!(plain.entries[0].0, NO_ROOT);
+        assert_eq!(plain, compact);
+        let mut rerooted = plain.clone();
+        rerooted.set_roots(&roots);
+        assert_eq!(rerooted.entries, compact.entries);
+
+        // 序列化为完整路径的列表
+        let json = serde_json::to_string(&compact).unwrap();
+        assert_eq!(json, serde_json::to_string(&paths).unwrap());
+        let loaded: CompactPaths = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, paths);
+    }
+
+    #[test]
+    fn test_compact_paths_retain_and_sort() {
+        let root = PathBuf::from("/p");
+        let mut compact = CompactPaths::new(std::slice::from_ref(&root));
+        compact.extend(["/p/c", "/p/a", "/other/b"]);
+
+        compact.retain(|path| path != Path::new("/p/a"));
+        assert_eq!(
+            compact,
+            vec![PathBuf::from("/p/c"), PathBuf::from("/other/b")]
+        );
+        assert!(compact.contains(Path::new("/other/b")));
+        assert!(!compact.contains(Path::new("/p/a")));
+
+        compact.sort();
+        assert_eq!(
+            compact,
+            vec![PathBuf::from("/other/b"), PathBuf::from("/p/c")]
+        );
+    }
+
+    #[test]
+    fn test_compact_paths_memory() {
+        // 合成的大型扁平扫描：十万个匹配项共享同一个较长的根路径
+        let root = PathBuf::from("/home/someone/work/monorepo/packages");
+        let paths: Vec<PathBuf> = (0..100_000)
+            .map(|i| root.join(format!("pkg{}/dist/chunk-{}.js", i % 500, i)))
+            .collect();
+
+        let mut compact = CompactPaths::new(std::slice::from_ref(&root));
+        compact.extend(&paths);
+        compact.shrink_to_fit();
+        assert_eq!(compact, paths);
+
+        let expanded = vec_heap_size(&paths);
+        let compacted = compact.heap_size();
+        assert!(
+            compacted * 10 < expanded * 7,
+            "compact {} bytes vs expanded {} bytes for {} items",
+            compacted,
+            expanded,
+            paths.len()
+        );
+    }
+}
@@ -1,11 +1,15 @@
 use crate::error::CleanError;
+use crate::search::GlobDotPolicy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
 
 /// 清理配置，包含清理目标、排除路径和搜索选项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
     /// 清理配置，定义要清理的文件夹和文件
     pub clean: CleanConfig,
@@ -15,17 +19,128 @@ pub struct Config {
     pub options: Options,
 }
 
+impl Config {
+    /// 将配置序列化为 YAML 字符串
+    ///
+    /// # 返回
+    /// YAML 格式的配置，如果序列化失败则返回错误
+    pub fn to_yaml(&self) -> Result<String, CleanError> {
+        serde_yaml::to_string(self)
+            .map_err(|e| CleanError::ConfigParseError(format!("Failed to serialize YAML: {}", e)))
+    }
+
+    /// 将配置序列化为格式化的 JSON 字符串
+    ///
+    /// # 返回
+    /// JSON 格式的配置，如果序列化失败则返回错误
+    pub fn to_json(&self) -> Result<String, CleanError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| CleanError::ConfigParseError(format!("Failed to serialize JSON: {}", e)))
+    }
+}
+
 /// 清理配置，定义要清理的目标
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// 配置文件中的每个模式可以是字符串，也可以是带有删除方式的对象，如
+/// `{ pattern: "*.tmp", mode: permanent }`。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawCleanConfig", into = "RawCleanConfig")]
 pub struct CleanConfig {
     /// 要清理的文件夹名称列表（如 node_modules/, dist/）
     pub folders: Vec<String>,
     /// 要清理的文件模式列表（如 *.log, *.tmp）
     pub files: Vec<String>,
+    /// 单独指定了删除方式的模式，其他模式使用全局的 `delete_mode`
+    pub modes: BTreeMap<String, DeleteMode>,
+}
+
+impl CleanConfig {
+    /// 查找模式的删除方式
+    ///
+    /// # 参数
+    /// * `pattern` - 匹配到的清理模式
+    /// * `default` - 模式没有单独指定时使用的删除方式
+    pub fn mode_for(&self, pattern: &str, default: DeleteMode) -> DeleteMode {
+        self.modes.get(pattern).copied().unwrap_or(default)
+    }
+}
+
+/// 配置文件中的一个清理模式：字符串，或带有删除方式的对象
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PatternEntry {
+    Plain(String),
+    WithMode { pattern: String, mode: DeleteMode },
+}
+
+/// 清理配置在配置文件中的表示
+#[derive(Serialize, Deserialize)]
+struct RawCleanConfig {
+    folders: Vec<PatternEntry>,
+    files: Vec<PatternEntry>,
+}
+
+impl From<RawCleanConfig> for CleanConfig {
+    fn from(raw: RawCleanConfig) -> Self {
+        let mut modes = BTreeMap::new();
+        let mut patterns = |entries: Vec<PatternEntry>| -> Vec<String> {
+            entries
+                .into_iter()
+                .map(|entry| match entry {
+                    PatternEntry::Plain(pattern) => pattern,
+                    PatternEntry::WithMode { pattern, mode } => {
+                        modes.insert(pattern.clone(), mode);
+                        pattern
+                    }
+                })
+                .collect()
+        };
+        let folders = patterns(raw.folders);
+        let files = patterns(raw.files);
+        CleanConfig {
+            folders,
+            files,
+            modes,
+        }
+    }
+}
+
+impl From<CleanConfig> for RawCleanConfig {
+    fn from(config: CleanConfig) -> Self {
+        let CleanConfig {
+            folders,
+            files,
+            modes,
+        } = config;
+        let entries = |patterns: Vec<String>| -> Vec<PatternEntry> {
+            patterns
+                .into_iter()
+                .map(|pattern| match modes.get(&pattern) {
+                    Some(&mode) => PatternEntry::WithMode { pattern, mode },
+                    None => PatternEntry::Plain(pattern),
+                })
+                .collect()
+        };
+        RawCleanConfig {
+            folders: entries(folders),
+            files: entries(files),
+        }
+    }
+}
+
+/// 删除匹配项的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    /// 移到回收站，可以恢复
+    #[default]
+    Trash,
+    /// 直接永久删除，不经过回收站
+    Permanent,
 }
 
 /// 搜索和删除选项
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Options {
     /// 是否递归搜索子目录
     #[serde(default = "default_true")]
@@ -41,12 +156,349 @@ pub struct Options {
     pub min_age_days: Option<u32>,
     /// 最大文件年龄（天数），大于此年龄的文件不清理
     pub max_age_days: Option<u32>,
+    /// 计算年龄时使用的当前时间（`None` 表示使用系统时间），用于固定时间的测试，不从配置文件读取
+    #[serde(skip)]
+    pub now: Option<SystemTime>,
+    /// 删除文件后是否恢复其父目录的修改时间（仅对单个文件删除生效）
+    #[serde(default)]
+    pub preserve_parent_mtime: bool,
+    /// 是否记录被扫描但未被选中清理的条目及原因
+    #[serde(default)]
+    pub explain: bool,
+    /// 遍历错误数量上限，超过后中止扫描（0 表示不限制）
+    #[serde(default = "default_max_walk_errors")]
+    pub max_walk_errors: usize,
+    /// 读取文件元数据遇到可重试的错误（如网络文件系统的暂时故障）时的重试次数
+    #[serde(default = "default_stat_retries")]
+    pub stat_retries: u32,
+    /// 是否同时统计实际占用的磁盘空间（物理大小，考虑透明压缩和稀疏文件）
+    #[serde(default)]
+    pub physical_size: bool,
+    /// 是否同时统计释放的 inode 数量（删除的文件、目录和链接的总数）
+    #[serde(default)]
+    pub count_inodes: bool,
+    /// 是否同时使用符号链接目标的名称匹配文件模式（默认只匹配链接自身的名称）
+    #[serde(default)]
+    pub match_symlink_targets: bool,
+    /// 是否清理名称匹配目录模式的目录符号链接（只删除链接本身，不删除目标）
+    ///
+    /// 默认只报告这类链接而不清理，避免误删链接指向的内容。与 `follow_symlinks` 无关，
+    /// 后者只控制遍历时是否进入链接指向的目录。
+    #[serde(default)]
+    pub include_symlinked_dirs: bool,
+    /// 扫描根路径本身是符号链接时是否进入链接指向的目录
+    ///
+    /// 默认进入，如 `bc ~/current-project` 指向真正的项目目录时照常扫描。
+    /// 根路径以下的链接仍由 `follow_symlinks` 决定；启用 `follow_symlinks` 时总会进入。
+    #[serde(default = "default_true")]
+    pub dereference_root: bool,
+    /// 删除速率上限（每秒项目数），为 `None` 时不限制
+    #[serde(default)]
+    pub throttle: Option<u32>,
+    /// 通配符是否可以匹配隐藏文件开头的 `.`（默认不匹配，与 shell 一致）
+    #[serde(default)]
+    pub glob_dot_policy: GlobDotPolicy,
+    /// 只清理属于该用户（uid）的文件和目录（仅 Unix，目录按其自身的所有者判断）
+    #[serde(default)]
+    pub only_owned_by: Option<u32>,
+    /// 是否只删除看起来完全由构建生成的目录（不包含源文件）
+    #[serde(default)]
+    pub verify_generated: bool,
+    /// 源文件扩展名列表，启用 `verify_generated` 时包含这些文件的目录不会被删除
+    #[serde(default = "default_source_extensions")]
+    pub source_extensions: Vec<String>,
+    /// 计算目录大小时的最大遍历深度，超出部分不计入大小（结果标记为估算值）
+    #[serde(default)]
+    pub size_max_depth: Option<usize>,
+    /// 是否在线程池中并行计算匹配目录的大小（主遍历同时继续进行）
+    #[serde(default)]
+    pub parallel_size_compute: bool,
+    /// 是否将每个根路径下的直接子条目分配到线程池中并行遍历（使用扫描缓存时仍然串行扫描）
+    #[serde(default)]
+    pub parallel_scan: bool,
+    /// 是否排除扫描中遇到的 `.gitignore` 忽略的路径（`.bcignore` 总是生效）
+    #[serde(default)]
+    pub respect_gitignore: bool,
+    /// 所有并行阶段使用的线程数（为 `None` 时使用 CPU 数量，1 表示串行执行）
+    #[serde(default)]
+    pub jobs: Option<usize>,
+    /// 匹配目录中的文件同时匹配文件模式时的处理方式（默认只匹配目录，跳过其内容）
+    #[serde(default)]
+    pub on_overlap: OverlapPolicy,
+    /// 匹配目录后是否继续遍历其内容以匹配其中的文件，等同于 `on_overlap: collect-both`（保留以兼容旧配置）
+    #[serde(default)]
+    pub descend_into_matches: bool,
+    /// 是否对匹配目录也应用 `min_age_days`/`max_age_days`，目录年龄按其中最新文件的修改时间计算
+    /// （目录自身的修改时间只反映条目的增删，不反映其中文件的写入）
+    #[serde(default)]
+    pub dir_age_from_contents: bool,
+    /// 是否在每个目录中保留最新的一个匹配文件（如保留最近的一份日志用于排查），其余照常清理
+    #[serde(default)]
+    pub keep_newest_per_dir: bool,
+    /// 同一目录中匹配项的保留策略（如保留最近 7 天的全部和最新的 5 个），其余照常清理
+    #[serde(default)]
+    pub retention: Option<RetentionPolicy>,
+    /// 匹配目录至少包含的直接子条目数量，条目更少的目录不清理
+    #[serde(default)]
+    pub min_dir_entries: Option<usize>,
+    /// 匹配目录的最小大小（字节），小于此大小的目录按 `dir_size_action` 处理
+    #[serde(default)]
+    pub dir_min_size: Option<u64>,
+    /// 匹配目录的最大大小（字节），大于此大小的目录按 `dir_size_action` 处理
+    #[serde(default)]
+    pub dir_max_size: Option<u64>,
+    /// 匹配目录大小超出范围时的处理方式
+    #[serde(default)]
+    pub dir_size_action: DirSizeAction,
+    /// 匹配目录中包含排除路径时的处理方式
+    #[serde(default)]
+    pub excluded_child_action: ExcludedChildAction,
+    /// 移到回收站前检查回收站所在文件系统的可用空间，空间不足时的处理方式（为 `None` 时不检查）
+    #[serde(default)]
+    pub trash_space_check: Option<TrashSpaceAction>,
+    /// 删除前是否尝试清除不可变属性（`chattr -i`，仅 Linux，通常需要 root 权限）
+    #[serde(default)]
+    pub clear_immutable: bool,
+    /// 删除目录前是否先将其重命名为同级的临时名称，使其立即从原位置消失
+    #[serde(default)]
+    pub rename_first: bool,
+    /// 是否按目录聚集删除：处理完一个子树中的所有项目再处理下一个，减少机械硬盘和网络存储上的来回寻址
+    #[serde(default)]
+    pub locality_order: bool,
+    /// 是否逐项删除匹配的目录：由深到浅逐个永久删除其中的条目（不经过回收站），可以显示进度并在中途取消，但速度较慢
+    #[serde(default)]
+    pub piecewise_delete: bool,
+    /// 没有单独指定删除方式的清理模式使用的删除方式
+    #[serde(default)]
+    pub delete_mode: DeleteMode,
+    /// 从回收站恢复时原位置已被占用（如重新安装了 `node_modules`）的处理方式
+    #[serde(default)]
+    pub restore_conflict: RestoreConflict,
+    /// 文件模式带有内容条件（如 `*.log content:^BUILD LOG`）时读取的文件开头字节数
+    #[serde(default = "default_content_sniff_bytes")]
+    pub content_sniff_bytes: usize,
+}
+
+/// 匹配目录大小超出 `dir_min_size`/`dir_max_size` 范围时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirSizeAction {
+    /// 跳过该目录，不进行清理
+    #[default]
+    Skip,
+    /// 仍然清理，但删除前必须经过交互式确认
+    Confirm,
+}
+
+/// 回收站所在文件系统的可用空间不足以容纳要删除的项目时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashSpaceAction {
+    /// 输出警告，仍然尝试移到回收站
+    Warn,
+    /// 跳过该项目，记录为删除失败
+    Skip,
+}
+
+/// 从回收站恢复时原位置已被占用的处理方式
+///
+/// 两种方式都不会覆盖原位置现有的内容。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreConflict {
+    /// 不恢复，项目留在回收站中，并报告冲突
+    #[default]
+    Skip,
+    /// 恢复为同级的另一个名称（如 `node_modules.restored`）
+    Rename,
+}
+
+/// 目录模式与文件模式重叠时的优先规则
+///
+/// 例如 `folders` 中有 `node_modules`、`files` 中有 `*`，`node_modules` 中的文件同时匹配两者。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlapPolicy {
+    /// 目录匹配优先：整个目录作为一个匹配项，不再遍历其内容
+    #[default]
+    FolderWins,
+    /// 仍然匹配目录，同时继续遍历其内容，单独收集其中匹配文件模式的文件（嵌套的目录不再单独匹配）
+    CollectBoth,
+}
+
+/// 同一目录中匹配项（如按日期命名的日志或构建产物目录）的保留策略
+///
+/// 两个条件取并集：在 `keep_days` 天内修改过的项目，以及最新的 `keep_newest` 个项目都会保留，
+/// 其余的项目照常清理。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// 保留最近多少天内修改过的项目
+    #[serde(default)]
+    pub keep_days: Option<u32>,
+    /// 至少保留最新的多少个项目
+    #[serde(default)]
+    pub keep_newest: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// 将同一目录中的项目分为保留和删除两部分
+    ///
+    /// # 参数
+    /// * `items` - 项目及其修改时间
+    /// * `now` - 计算年龄时使用的当前时间
+    ///
+    /// # 返回
+    /// (保留的项目, 删除的项目)，各自保持输入顺序
+    pub fn partition<T: Clone>(
+        &self,
+        items: &[(T, SystemTime)],
+        now: SystemTime,
+    ) -> (Vec<T>, Vec<T>) {
+        // 按修改时间从新到旧排名，时间相同时按输入顺序
+        let mut by_age: Vec<usize> = (0..items.len()).collect();
+        by_age.sort_by(|&a, &b| items[b].1.cmp(&items[a].1).then(a.cmp(&b)));
+        let mut rank = vec![0; items.len()];
+        for (position, &index) in by_age.iter().enumerate() {
+            rank[index] = position;
+        }
+
+        let mut kept = Vec::new();
+        let mut deleted = Vec::new();
+        for (index, (item, modified)) in items.iter().enumerate() {
+            // 修改时间晚于当前时间（如时钟偏差）视为刚修改
+            let age_days = now
+                .duration_since(*modified)
+                .map(|age| age.as_secs() / 86400)
+                .unwrap_or(0);
+            let recent = self.keep_days.is_some_and(|days| age_days < days as u64);
+            let newest = self.keep_newest.is_some_and(|count| rank[index] < count);
+            if recent || newest {
+                kept.push(item.clone());
+            } else {
+                deleted.push(item.clone());
+            }
+        }
+        (kept, deleted)
+    }
+}
+
+impl FromStr for RetentionPolicy {
+    type Err = String;
+
+    /// 解析 `7d,5` 形式的保留策略：`<N>d` 为保留天数，单独的数字为保留数量，两者都可以省略其一
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid retention: {} (expected DAYSd, COUNT or both, e.g. 7d,5)",
+                s
+            )
+        };
+        let mut policy = RetentionPolicy::default();
+        for part in s.split(',').map(str::trim) {
+            match part.strip_suffix(['d', 'D']) {
+                Some(days) if policy.keep_days.is_none() => {
+                    policy.keep_days = Some(days.parse().map_err(|_| invalid())?);
+                }
+                None if policy.keep_newest.is_none() => {
+                    policy.keep_newest = Some(part.parse().map_err(|_| invalid())?);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(policy)
+    }
+}
+
+/// 匹配目录中包含排除路径（如匹配了 `node_modules` 但排除了 `node_modules/.cache`）时的处理方式
+///
+/// 排除规则总是优先：无论哪种方式，排除的路径都不会被删除。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExcludedChildAction {
+    /// 不删除整个目录，继续遍历其内容，只清理其中单独匹配的条目
+    #[default]
+    Skip,
+    /// 删除目录中除排除路径（及其上级目录）以外的所有内容
+    Partial,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            recursive: true,
+            follow_symlinks: false,
+            min_size: None,
+            max_size: None,
+            min_age_days: None,
+            max_age_days: None,
+            now: None,
+            preserve_parent_mtime: false,
+            explain: false,
+            max_walk_errors: default_max_walk_errors(),
+            stat_retries: default_stat_retries(),
+            physical_size: false,
+            count_inodes: false,
+            match_symlink_targets: false,
+            include_symlinked_dirs: false,
+            dereference_root: true,
+            throttle: None,
+            glob_dot_policy: GlobDotPolicy::Explicit,
+            only_owned_by: None,
+            verify_generated: false,
+            source_extensions: default_source_extensions(),
+            size_max_depth: None,
+            parallel_size_compute: false,
+            parallel_scan: false,
+            respect_gitignore: false,
+            jobs: None,
+            on_overlap: OverlapPolicy::FolderWins,
+            descend_into_matches: false,
+            dir_age_from_contents: false,
+            keep_newest_per_dir: false,
+            retention: None,
+            min_dir_entries: None,
+            dir_min_size: None,
+            dir_max_size: None,
+            dir_size_action: DirSizeAction::Skip,
+            excluded_child_action: ExcludedChildAction::Skip,
+            trash_space_check: None,
+            clear_immutable: false,
+            rename_first: false,
+            locality_order: false,
+            piecewise_delete: false,
+            delete_mode: DeleteMode::Trash,
+            restore_conflict: RestoreConflict::Skip,
+            content_sniff_bytes: default_content_sniff_bytes(),
+        }
+    }
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_max_walk_errors() -> usize {
+    10_000
+}
+
+fn default_stat_retries() -> u32 {
+    2
+}
+
+fn default_content_sniff_bytes() -> usize {
+    4096
+}
+
+fn default_source_extensions() -> Vec<String> {
+    [
+        "rs", "c", "cc", "cpp", "h", "hpp", "go", "java", "kt", "py", "js", "ts", "jsx", "tsx",
+        "swift", "cs", "rb", "php", "scala",
+    ]
+    .iter()
+    .map(|ext| ext.to_string())
+    .collect()
+}
+
 /// 项目类型枚举
 #[derive(Debug, Clone, PartialEq)]
 pub enum ProjectType {
@@ -64,6 +516,84 @@ pub enum ProjectType {
     Unknown,
 }
 
+impl ProjectType {
+    /// 返回项目类型的显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectType::NodeJs => "Node.js",
+            ProjectType::Rust => "Rust",
+            ProjectType::Python => "Python",
+            ProjectType::Go => "Go",
+            ProjectType::Java => "Java",
+            ProjectType::Unknown => "Unknown",
+        }
+    }
+}
+
+/// 内置的清理预设，用一组固定的模式代替按项目类型加载的默认配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// 中断的下载和工具遗留的临时文件（`*.part`、`*.download`、`*.tmp-*`、残留的 `*.lock` 等）
+    Partials,
+}
+
+impl Preset {
+    /// 返回预设的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Partials => "partials",
+        }
+    }
+
+    /// 返回预设的清理模式
+    ///
+    /// `*.lock` 只匹配空的或只包含进程号的锁文件，不会匹配 `Cargo.lock`、`yarn.lock`
+    /// 这类需要提交到版本库的依赖锁定文件。
+    pub fn clean_config(&self) -> CleanConfig {
+        match self {
+            Preset::Partials => CleanConfig {
+                folders: vec![],
+                files: [
+                    "*.part",
+                    "*.partial",
+                    "*.crdownload",
+                    "*.download",
+                    "*.tmp-*",
+                    r"*.lock content:\A\s*[0-9]*\s*\z",
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// 返回预设默认的最小年龄（天数）
+    ///
+    /// 正在进行的下载和正在运行的工具持有的锁文件与残留的文件无法区分，
+    /// 只清理一段时间没有修改过的文件。配置中设置了 `min_age_days` 时使用配置的值。
+    pub fn min_age_days(&self) -> Option<u32> {
+        match self {
+            Preset::Partials => Some(1),
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "partials" => Ok(Preset::Partials),
+            _ => Err(format!("Unknown preset: {} (expected partials)", s)),
+        }
+    }
+}
+
+/// 包含配置内容（JSON 或 YAML，而不是路径）的环境变量，在未指定配置文件时使用
+pub const CONFIG_ENV_VAR: &str = "BC_CONFIG";
+
 /// 配置加载器，负责加载、解析和合并配置
 pub struct ConfigLoader;
 
@@ -78,10 +608,7 @@ impl ConfigLoader {
     pub fn expand_path(path: &str) -> PathBuf {
         if path.starts_with('~') {
             if path == "~" || path.starts_with("~/") {
-                let home = env::var("HOME")
-                    .or_else(|_| env::var("USERPROFILE"))
-                    .unwrap_or_else(|_| ".".to_string());
-                let home_path = PathBuf::from(home);
+                let home_path = Self::home_dir().unwrap_or_else(|| PathBuf::from("."));
                 if path == "~" {
                     home_path
                 } else {
@@ -95,6 +622,13 @@ impl ConfigLoader {
         }
     }
 
+    /// 当前用户的主目录（`HOME`，Windows 上为 `USERPROFILE`），都未设置时返回 `None`
+    pub fn home_dir() -> Option<PathBuf> {
+        env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+    }
+
     /// 验证路径是否存在和可访问
     ///
     /// # 参数
@@ -118,33 +652,96 @@ impl ConfigLoader {
     /// 加载配置，合并默认配置、配置文件（如果存在）和命令行参数
     ///
     /// # 参数
-    /// * `path` - 项目根路径，用于识别项目类型
+    /// * `path` - 可选的项目根路径，用于识别项目类型（为 `None` 时使用通用默认配置）
     /// * `config_file` - 可选的配置文件路径（YAML 或 JSON）
     /// * `cli_patterns` - 命令行传入的清理模式列表
     ///
     /// # 返回
     /// 返回合并后的配置，如果配置无效则返回错误
     pub fn load_config(
-        path: &Path,
+        path: Option<&Path>,
+        config_file: Option<&Path>,
+        cli_patterns: &[String],
+    ) -> Result<Config, CleanError> {
+        Self::load_config_with_preset(path, config_file, cli_patterns, None)
+    }
+
+    /// 加载配置，指定预设时用预设的模式代替按项目类型加载的默认配置
+    ///
+    /// 配置文件和命令行的模式照常合并到预设的模式中。预设带有默认的最小年龄时，
+    /// 只在合并后的配置没有设置 `min_age_days` 时使用；注意年龄条件对本次运行的所有文件模式
+    /// 都生效，而不只是预设的模式。
+    ///
+    /// # 参数
+    /// * `path` - 可选的项目根路径，用于识别项目类型（为 `None` 时使用通用默认配置）
+    /// * `config_file` - 可选的配置文件路径（YAML 或 JSON）
+    /// * `cli_patterns` - 命令行传入的清理模式列表
+    /// * `preset` - 可选的内置预设
+    ///
+    /// # 返回
+    /// 返回合并后的配置，如果配置无效则返回错误
+    pub fn load_config_with_preset(
+        path: Option<&Path>,
         config_file: Option<&Path>,
         cli_patterns: &[String],
+        preset: Option<Preset>,
     ) -> Result<Config, CleanError> {
-        // 验证路径
-        Self::validate_path(path)?;
+        Self::load_config_with_env(
+            path,
+            config_file,
+            cli_patterns,
+            preset,
+            env::var(CONFIG_ENV_VAR).ok(),
+        )
+    }
 
-        let project_type = Self::detect_project_type(path);
-        let default_config = Self::load_default_config(&project_type);
+    /// 加载配置，未指定配置文件时使用给定的配置内容（通常来自 `BC_CONFIG` 环境变量）
+    ///
+    /// # 参数
+    /// * `path` - 可选的项目根路径，用于识别项目类型（为 `None` 时使用通用默认配置）
+    /// * `config_file` - 可选的配置文件路径（YAML 或 JSON）
+    /// * `cli_patterns` - 命令行传入的清理模式列表
+    /// * `preset` - 可选的内置预设
+    /// * `env_content` - 可选的配置内容（JSON 或 YAML），指定了配置文件时忽略
+    ///
+    /// # 返回
+    /// 返回合并后的配置，如果配置无效则返回错误
+    pub fn load_config_with_env(
+        path: Option<&Path>,
+        config_file: Option<&Path>,
+        cli_patterns: &[String],
+        preset: Option<Preset>,
+        env_content: Option<String>,
+    ) -> Result<Config, CleanError> {
+        let project_type = match path {
+            Some(path) => {
+                // 验证路径
+                Self::validate_path(path)?;
+                Self::detect_project_type(path)
+            }
+            None => ProjectType::Unknown,
+        };
+        let default_config = match preset {
+            Some(preset) => Self::load_preset_config(preset),
+            None => Self::load_default_config(&project_type),
+        };
 
         let file_config = if let Some(config_path) = config_file {
             // 验证配置文件路径
             Self::validate_path(config_path)?;
             Some(Self::parse_config_file(config_path)?)
+        } else if let Some(content) = env_content {
+            // 没有指定配置文件时，使用环境变量中的配置内容
+            Some(Self::parse_config_str(&content)?)
         } else {
             None
         };
 
-        let merged_config =
+        let mut merged_config =
             Self::merge_configs(&default_config, file_config.as_ref(), cli_patterns);
+        if merged_config.options.min_age_days.is_none() {
+            merged_config.options.min_age_days = preset.and_then(|preset| preset.min_age_days());
+        }
         Self::validate_config(&merged_config)?;
 
         Ok(merged_config)
@@ -214,39 +811,271 @@ impl ConfigLoader {
         };
 
         Config {
-            clean: CleanConfig { folders, files },
-            exclude: vec![],
-            options: Options {
-                recursive: true,
-                follow_symlinks: false,
-                min_size: None,
-                max_size: None,
-                min_age_days: None,
-                max_age_days: None,
+            clean: CleanConfig {
+                folders,
+                files,
+                ..Default::default()
             },
+            exclude: vec![],
+            options: Options::default(),
+        }
+    }
+
+    /// 加载预设的配置
+    ///
+    /// # 参数
+    /// * `preset` - 内置预设
+    ///
+    /// # 返回
+    /// 只包含预设模式的配置（最小年龄在合并配置文件之后由 `load_config_with_preset` 设置）
+    pub fn load_preset_config(preset: Preset) -> Config {
+        Config {
+            clean: preset.clean_config(),
+            exclude: vec![],
+            options: Options::default(),
         }
     }
 
     /// 解析配置文件（支持 YAML 和 JSON 格式）
     ///
+    /// 顶层的 `include` 列出要先加载的其他配置文件（相对路径相对于当前文件所在目录，
+    /// 支持 `~`），被引用的文件也可以继续 `include`。按列出的顺序合并被引用的文件，
+    /// 最后合并当前文件：列表（如 `clean.folders`、`exclude`）依次追加并去重，
+    /// 其他值由后合并的文件覆盖。循环引用返回错误。
+    ///
     /// # 参数
     /// * `path` - 配置文件路径
     ///
     /// # 返回
     /// 解析后的配置，如果解析失败则返回错误
     pub fn parse_config_file(path: &Path) -> Result<Config, CleanError> {
-        let content = fs::read_to_string(path).map_err(|e| {
+        #[derive(Deserialize)]
+        struct Includes {
+            #[serde(default)]
+            include: Vec<String>,
+        }
+
+        let content = Self::read_config_text(path)?;
+        let includes: Includes = if Self::is_yaml_path(path) {
+            serde_yaml::from_str(&content)?
+        } else {
+            serde_json::from_str(&content)?
+        };
+        // 没有 include 时直接解析，错误信息保留行列位置
+        if includes.include.is_empty() {
+            return if Self::is_yaml_path(path) {
+                Ok(serde_yaml::from_str(&content)?)
+            } else {
+                Ok(serde_json::from_str(&content)?)
+            };
+        }
+
+        let merged = Self::load_config_value(path, &mut Vec::new())?;
+        serde_json::from_value(merged).map_err(|e| {
+            CleanError::ConfigParseError(format!(
+                "Invalid config {} after applying includes: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 加载配置文件及其递归引用的文件，返回合并后的内容
+    ///
+    /// # 参数
+    /// * `path` - 配置文件路径
+    /// * `chain` - 正在加载的文件（规范化路径），用于检测循环引用
+    fn load_config_value(
+        path: &Path,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<serde_json::Value, CleanError> {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Some(start) = chain.iter().position(|loaded| *loaded == canonical) {
+            let cycle: Vec<String> = chain[start..]
+                .iter()
+                .chain([&canonical])
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(CleanError::ConfigParseError(format!(
+                "Config include cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let content = Self::read_config_text(path)?;
+        let mut value: serde_json::Value = if Self::is_yaml_path(path) {
+            serde_yaml::from_str(&content)?
+        } else {
+            serde_json::from_str(&content)?
+        };
+        let includes: Vec<String> = match value.as_object_mut().and_then(|o| o.remove("include")) {
+            Some(includes) => serde_json::from_value(includes).map_err(|e| {
+                CleanError::ConfigParseError(format!(
+                    "Invalid include in {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?,
+            None => Vec::new(),
+        };
+
+        chain.push(canonical);
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut merged = serde_json::Value::Object(serde_json::Map::new());
+        for include in &includes {
+            let include_path = base_dir.join(Self::expand_path(include));
+            if !include_path.is_file() {
+                return Err(CleanError::ConfigParseError(format!(
+                    "Included config file {} not found (included from {})",
+                    include_path.display(),
+                    path.display()
+                )));
+            }
+            let included = Self::load_config_value(&include_path, chain)?;
+            Self::merge_config_values(&mut merged, included);
+        }
+        chain.pop();
+
+        Self::merge_config_values(&mut merged, value);
+        Ok(merged)
+    }
+
+    /// 将 `overlay` 合并到 `base` 中：对象逐键合并，列表追加并去重，其他值被覆盖
+    fn merge_config_values(base: &mut serde_json::Value, overlay: serde_json::Value) {
+        use serde_json::Value;
+        match (base, overlay) {
+            (Value::Object(base), Value::Object(overlay)) => {
+                for (key, value) in overlay {
+                    match base.get_mut(&key) {
+                        Some(existing) => Self::merge_config_values(existing, value),
+                        None => {
+                            base.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (Value::Array(base), Value::Array(overlay)) => {
+                for item in overlay {
+                    if !base.contains(&item) {
+                        base.push(item);
+                    }
+                }
+            }
+            (base, overlay) => *base = overlay,
+        }
+    }
+
+    /// 解析清理模式文件（支持 YAML 和 JSON 格式）
+    ///
+    /// 文件内容相当于配置文件中单独的 `clean` 部分，用 `folders` 和 `files` 两个键
+    /// 明确区分文件夹模式和文件模式，不依赖末尾的 `/` 判断。两个键都可以省略；
+    /// 文件夹模式末尾的 `/` 会被去掉。
+    ///
+    /// # 参数
+    /// * `path` - 模式文件路径
+    ///
+    /// # 返回
+    /// 解析后的清理模式，如果解析失败（包括出现其他键）则返回错误
+    pub fn parse_clean_patterns_file(path: &Path) -> Result<CleanConfig, CleanError> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct PatternsFile {
+            #[serde(default)]
+            folders: Vec<String>,
+            #[serde(default)]
+            files: Vec<String>,
+        }
+
+        let content = Self::read_config_text(path)?;
+        let patterns: PatternsFile = if Self::is_yaml_path(path) {
+            serde_yaml::from_str(&content)?
+        } else {
+            serde_json::from_str(&content)?
+        };
+        Ok(CleanConfig {
+            folders: patterns
+                .folders
+                .iter()
+                .map(|folder| folder.trim_end_matches('/').to_string())
+                .collect(),
+            files: patterns.files,
+            ..Default::default()
+        })
+    }
+
+    /// 读取配置文件的文本内容，去掉开头的 UTF-8 BOM
+    fn read_config_text(path: &Path) -> Result<String, CleanError> {
+        let bytes = fs::read(path).map_err(|e| {
             CleanError::ConfigParseError(format!("Failed to read config file: {}", e))
         })?;
+        // 部分编辑器（如 Windows 记事本）会在文件开头写入 UTF-8 BOM，serde_json 无法解析
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+        std::str::from_utf8(bytes).map(str::to_string).map_err(|e| {
+            CleanError::ConfigParseError(format!(
+                "Config file {} is not valid UTF-8 (invalid byte at offset {})",
+                path.display(),
+                e.valid_up_to()
+            ))
+        })
+    }
 
-        if path.extension().and_then(|s| s.to_str()) == Some("yaml")
-            || path.extension().and_then(|s| s.to_str()) == Some("yml")
-        {
-            serde_yaml::from_str(&content)
-                .map_err(|e| CleanError::ConfigParseError(format!("Failed to parse YAML: {}", e)))
+    /// 按扩展名判断是否为 YAML 文件（`.yaml`/`.yml`），其他按 JSON 解析
+    fn is_yaml_path(path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|s| s.to_str()),
+            Some("yaml") | Some("yml")
+        )
+    }
+
+    /// 将配置写入文件，根据扩展名选择格式（`.yaml`/`.yml` 为 YAML，其他为 JSON）
+    ///
+    /// 先写入同目录下的临时文件再重命名，避免中途失败时留下不完整的配置文件。
+    ///
+    /// # 参数
+    /// * `config` - 要写入的配置
+    /// * `path` - 配置文件路径
+    ///
+    /// # 返回
+    /// 如果写入成功返回 `Ok(())`，否则返回错误
+    pub fn write_config(config: &Config, path: &Path) -> Result<(), CleanError> {
+        let content = match path.extension().and_then(|s| s.to_str()) {
+            Some("yaml") | Some("yml") => config.to_yaml()?,
+            _ => config.to_json()?,
+        };
+
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_name);
+
+        fs::write(&tmp_path, content).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to write config file {}: {}",
+                tmp_path.display(),
+                e
+            ))
+        })?;
+        fs::rename(&tmp_path, path).map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            CleanError::Other(format!(
+                "Failed to write config file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 解析配置内容，根据第一个非空白字符判断格式（`{` 为 JSON，否则为 YAML）
+    ///
+    /// # 参数
+    /// * `content` - 配置内容
+    ///
+    /// # 返回
+    /// 解析后的配置，如果解析失败则返回错误
+    pub fn parse_config_str(content: &str) -> Result<Config, CleanError> {
+        if content.trim_start().starts_with('{') {
+            Ok(serde_json::from_str(content)?)
         } else {
-            serde_json::from_str(&content)
-                .map_err(|e| CleanError::ConfigParseError(format!("Failed to parse JSON: {}", e)))
+            Ok(serde_yaml::from_str(content)?)
         }
     }
 
@@ -269,6 +1098,7 @@ impl ConfigLoader {
         if let Some(file_cfg) = file_config {
             merged.clean.folders.extend(file_cfg.clean.folders.clone());
             merged.clean.files.extend(file_cfg.clean.files.clone());
+            merged.clean.modes.extend(file_cfg.clean.modes.clone());
             merged.exclude.extend(file_cfg.exclude.clone());
             merged.options = file_cfg.options.clone();
         }
@@ -287,6 +1117,70 @@ impl ConfigLoader {
         merged
     }
 
+    /// 将清理模式文件中的模式合并到配置中，与命令行模式一样跳过已存在的模式
+    ///
+    /// # 参数
+    /// * `config` - 合并后的配置
+    /// * `patterns` - 清理模式文件的内容
+    pub fn merge_clean_patterns(config: &mut Config, patterns: &CleanConfig) {
+        for folder in &patterns.folders {
+            if !config.clean.folders.contains(folder) {
+                config.clean.folders.push(folder.clone());
+            }
+        }
+        for file in &patterns.files {
+            if !config.clean.files.contains(file) {
+                config.clean.files.push(file.clone());
+            }
+        }
+        for (pattern, mode) in &patterns.modes {
+            config.clean.modes.entry(pattern.clone()).or_insert(*mode);
+        }
+    }
+
+    /// 从合并后的配置中移除指定的清理模式
+    ///
+    /// 模式按名称精确匹配，命令行和配置中文件夹模式末尾的 `/` 都可省略。
+    ///
+    /// # 参数
+    /// * `config` - 合并后的配置
+    /// * `patterns` - 要禁用的模式列表
+    pub fn disable_patterns(config: &mut Config, patterns: &[String]) {
+        let names: Vec<&str> = patterns.iter().map(|p| Self::pattern_name(p)).collect();
+        config
+            .clean
+            .folders
+            .retain(|folder| !names.contains(&Self::pattern_name(folder)));
+        config
+            .clean
+            .files
+            .retain(|file| !names.contains(&Self::pattern_name(file)));
+    }
+
+    /// 将合并后的配置限制为只包含指定的清理模式
+    ///
+    /// 模式按名称精确匹配，命令行和配置中文件夹模式末尾的 `/` 都可省略。
+    ///
+    /// # 参数
+    /// * `config` - 合并后的配置
+    /// * `patterns` - 要保留的模式列表
+    pub fn only_patterns(config: &mut Config, patterns: &[String]) {
+        let names: Vec<&str> = patterns.iter().map(|p| Self::pattern_name(p)).collect();
+        config
+            .clean
+            .folders
+            .retain(|folder| names.contains(&Self::pattern_name(folder)));
+        config
+            .clean
+            .files
+            .retain(|file| names.contains(&Self::pattern_name(file)));
+    }
+
+    /// 用于按名称比较的模式，去掉文件夹模式末尾的 `/`
+    fn pattern_name(pattern: &str) -> &str {
+        pattern.trim_end_matches('/')
+    }
+
     /// 验证配置的有效性
     ///
     /// # 参数
@@ -300,6 +1194,26 @@ impl ConfigLoader {
                 "At least one folder or file pattern must be specified".to_string(),
             ));
         }
+        for pattern in config.clean.folders.iter().chain(&config.clean.files) {
+            let name_pattern = crate::search::SearchEngine::split_content_pattern(pattern).0;
+            if let Some(Err(e)) = crate::search::SearchEngine::compile_regex_pattern(name_pattern) {
+                return Err(CleanError::ConfigParseError(format!(
+                    "Invalid regex pattern '{}': {}",
+                    pattern, e
+                )));
+            }
+        }
+        for pattern in &config.clean.files {
+            if let (_, Some(content)) = crate::search::SearchEngine::split_content_pattern(pattern)
+            {
+                regex::bytes::Regex::new(content).map_err(|e| {
+                    CleanError::ConfigParseError(format!(
+                        "Invalid content pattern in '{}': {}",
+                        pattern, e
+                    ))
+                })?;
+            }
+        }
         Ok(())
     }
 }
@@ -314,6 +1228,59 @@ impl From<&Options> for crate::search::SearchOptions {
             max_size: options.max_size,
             min_age_days: options.min_age_days,
             max_age_days: options.max_age_days,
+            now: options.now,
+            explain: options.explain,
+            max_walk_errors: options.max_walk_errors,
+            stat_retries: options.stat_retries,
+            physical_size: options.physical_size,
+            contents_first: false,
+            dir_min_size: options.dir_min_size,
+            dir_max_size: options.dir_max_size,
+            match_symlink_targets: options.match_symlink_targets,
+            include_symlinked_dirs: options.include_symlinked_dirs,
+            dereference_root: options.dereference_root,
+            glob_dot_policy: options.glob_dot_policy,
+            only_owned_by: options.only_owned_by,
+            size_max_depth: options.size_max_depth,
+            parallel_size_compute: options.parallel_size_compute,
+            parallel_scan: options.parallel_scan,
+            respect_gitignore: options.respect_gitignore,
+            runtime: crate::runtime::RuntimeConfig::new(options.jobs),
+            on_overlap: if options.descend_into_matches {
+                OverlapPolicy::CollectBoth
+            } else {
+                options.on_overlap
+            },
+            dir_age_from_contents: options.dir_age_from_contents,
+            min_dir_entries: options.min_dir_entries,
+            dir_size_action: options.dir_size_action,
+            excluded_child_action: options.excluded_child_action,
+            content_sniff_bytes: options.content_sniff_bytes,
+        }
+    }
+}
+
+impl From<&Options> for crate::delete::DeleteOptions {
+    fn from(options: &Options) -> Self {
+        crate::delete::DeleteOptions {
+            preserve_parent_mtime: options.preserve_parent_mtime,
+            physical_size: options.physical_size,
+            count_inodes: options.count_inodes,
+            extension_breakdown: false,
+            generated_only: options
+                .verify_generated
+                .then(|| crate::delete::SafetyPolicy {
+                    source_extensions: options.source_extensions.clone(),
+                }),
+            throttle: options.throttle,
+            manifest: None,
+            trash_space_check: options.trash_space_check,
+            clear_immutable: options.clear_immutable,
+            rename_first: options.rename_first,
+            locality_order: options.locality_order,
+            piecewise_delete: options.piecewise_delete,
+            restore_conflict: options.restore_conflict,
+            cancel: None,
         }
     }
 }
@@ -430,6 +1397,58 @@ mod tests {
         assert!(config.clean.files.contains(&"*.pyc".to_string()));
     }
 
+    #[test]
+    fn test_parse_config_delete_modes() {
+        // 字符串模式和带有删除方式的模式可以混合使用
+        let yaml_config = r#"clean:
+  folders:
+    - node_modules/
+    - pattern: .cache
+      mode: permanent
+  files:
+    - pattern: "*.tmp"
+      mode: permanent
+    - "*.log"
+exclude: []
+options:
+  delete_mode: trash"#;
+        let config = ConfigLoader::parse_config_str(yaml_config).unwrap();
+        assert_eq!(config.clean.folders, vec!["node_modules/", ".cache"]);
+        assert_eq!(config.clean.files, vec!["*.tmp", "*.log"]);
+        let default = config.options.delete_mode;
+        assert_eq!(default, DeleteMode::Trash);
+        assert_eq!(
+            config.clean.mode_for("node_modules/", default),
+            DeleteMode::Trash
+        );
+        assert_eq!(
+            config.clean.mode_for(".cache", default),
+            DeleteMode::Permanent
+        );
+        assert_eq!(
+            config.clean.mode_for("*.tmp", default),
+            DeleteMode::Permanent
+        );
+        // 字符串模式使用全局的删除方式
+        assert_eq!(
+            config.clean.mode_for("*.log", DeleteMode::Permanent),
+            DeleteMode::Permanent
+        );
+
+        // 序列化后只有单独指定了删除方式的模式写成对象，可以原样解析回来
+        let json = config.to_json().unwrap();
+        assert!(json.contains(r#""*.log""#));
+        assert_eq!(ConfigLoader::parse_config_str(&json).unwrap(), config);
+        assert_eq!(
+            ConfigLoader::parse_config_str(&config.to_yaml().unwrap()).unwrap(),
+            config
+        );
+
+        // 未知的删除方式无法解析
+        let invalid = r#"{"clean": {"folders": [], "files": [{"pattern": "*.tmp", "mode": "shred"}]}, "exclude": [], "options": {}}"#;
+        assert!(ConfigLoader::parse_config_str(invalid).is_err());
+    }
+
     #[test]
     fn test_parse_config_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -483,6 +1502,7 @@ options:
             clean: CleanConfig {
                 folders: vec!["default_folder".to_string()],
                 files: vec![],
+                ..Default::default()
             },
             exclude: vec![],
             options: Options {
@@ -492,6 +1512,7 @@ options:
                 max_size: None,
                 min_age_days: None,
                 max_age_days: None,
+                ..Options::default()
             },
         };
 
@@ -499,6 +1520,7 @@ options:
             clean: CleanConfig {
                 folders: vec!["file_folder".to_string()],
                 files: vec!["*.log".to_string()],
+                ..Default::default()
             },
             exclude: vec![],
             options: Options {
@@ -508,6 +1530,7 @@ options:
                 max_size: None,
                 min_age_days: None,
                 max_age_days: None,
+                ..Options::default()
             },
         };
 
@@ -534,6 +1557,7 @@ options:
             clean: CleanConfig {
                 folders: vec!["test".to_string()],
                 files: vec![],
+                ..Default::default()
             },
             exclude: vec![],
             options: Options {
@@ -543,6 +1567,7 @@ options:
                 max_size: None,
                 min_age_days: None,
                 max_age_days: None,
+                ..Options::default()
             },
         };
         assert!(ConfigLoader::validate_config(&valid_config).is_ok());
@@ -552,6 +1577,7 @@ options:
             clean: CleanConfig {
                 folders: vec![],
                 files: vec![],
+                ..Default::default()
             },
             exclude: vec![],
             options: Options {
@@ -561,8 +1587,441 @@ options:
                 max_size: None,
                 min_age_days: None,
                 max_age_days: None,
+                ..Options::default()
             },
         };
         assert!(ConfigLoader::validate_config(&invalid_config).is_err());
+
+        // 无效的正则表达式模式在加载配置时报错，而不是静默地不匹配
+        for (folders, files) in [
+            (vec!["re:(".to_string()], vec![]),
+            (vec![], vec!["re:[a-".to_string()]),
+        ] {
+            let config = Config {
+                clean: CleanConfig {
+                    folders,
+                    files,
+                    ..Default::default()
+                },
+                exclude: vec![],
+                options: Options::default(),
+            };
+            assert!(matches!(
+                ConfigLoader::validate_config(&config),
+                Err(CleanError::ConfigParseError(message)) if message.starts_with("Invalid regex pattern")
+            ));
+        }
+    }
+
+    #[test]
+    fn test_disable_and_only_patterns() {
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![
+                    "node_modules".to_string(),
+                    "target".to_string(),
+                    "dist/".to_string(),
+                ],
+                files: vec!["*.log".to_string(), "*.tmp".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+
+        // 配置中带 / 的文件夹模式无论命令行是否带 / 都能匹配
+        let mut disabled = config.clone();
+        ConfigLoader::disable_patterns(&mut disabled, &["dist".to_string()]);
+        assert_eq!(
+            disabled.clean.folders,
+            vec!["node_modules".to_string(), "target".to_string()]
+        );
+        let mut only = config.clone();
+        ConfigLoader::only_patterns(&mut only, &["dist/".to_string()]);
+        assert_eq!(only.clean.folders, vec!["dist/".to_string()]);
+
+        // 禁用的模式被移除，文件夹模式末尾的 / 可省略
+        let mut disabled = config.clone();
+        ConfigLoader::disable_patterns(
+            &mut disabled,
+            &[
+                "node_modules/".to_string(),
+                "dist/".to_string(),
+                "*.log".to_string(),
+            ],
+        );
+        assert_eq!(disabled.clean.folders, vec!["target".to_string()]);
+        assert_eq!(disabled.clean.files, vec!["*.tmp".to_string()]);
+
+        // 只保留指定的模式
+        let mut only = config.clone();
+        ConfigLoader::only_patterns(&mut only, &["node_modules".to_string()]);
+        assert_eq!(only.clean.folders, vec!["node_modules".to_string()]);
+        assert!(only.clean.files.is_empty());
+    }
+
+    #[test]
+    fn test_write_config_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string(), "target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![PathBuf::from("/project/keep")],
+            options: Options {
+                min_size: Some(1024),
+                max_age_days: Some(30),
+                dir_size_action: DirSizeAction::Confirm,
+                ..Options::default()
+            },
+        };
+
+        // 写入 YAML 后重新解析应得到相同的配置
+        let yaml_path = temp_dir.path().join("config.yaml");
+        ConfigLoader::write_config(&config, &yaml_path).unwrap();
+        let parsed = ConfigLoader::parse_config_file(&yaml_path).unwrap();
+        assert_eq!(parsed.clean.folders, config.clean.folders);
+        assert_eq!(parsed.clean.files, config.clean.files);
+        assert_eq!(parsed.options, config.options);
+        assert_eq!(parsed, config);
+
+        // 其他扩展名写入 JSON
+        let json_path = temp_dir.path().join("config.json");
+        ConfigLoader::write_config(&config, &json_path).unwrap();
+        let content = fs::read_to_string(&json_path).unwrap();
+        assert!(content.trim_start().starts_with('{'));
+        assert_eq!(ConfigLoader::parse_config_file(&json_path).unwrap(), config);
+
+        // 不会留下临时文件
+        assert!(!temp_dir.path().join("config.yaml.tmp").exists());
+    }
+
+    #[test]
+    fn test_load_config_without_path() {
+        // 没有项目路径时使用通用默认配置
+        let config = ConfigLoader::load_config_with_env(None, None, &[], None, None).unwrap();
+        assert_eq!(
+            config.clean.folders,
+            ConfigLoader::load_default_config(&ProjectType::Unknown)
+                .clean
+                .folders
+        );
+
+        // 有环境变量中的配置内容时使用其中的配置，命令行模式仍然合并在上面
+        let env_content = r#"{"clean": {"folders": ["env_folder"], "files": ["*.env"]}, "exclude": [], "options": {"min_size": 10}}"#;
+        let config = ConfigLoader::load_config_with_env(
+            None,
+            None,
+            &["*.tmp".to_string()],
+            None,
+            Some(env_content.to_string()),
+        )
+        .unwrap();
+        assert!(config.clean.folders.contains(&"env_folder".to_string()));
+        assert!(config.clean.files.contains(&"*.env".to_string()));
+        assert!(config.clean.files.contains(&"*.tmp".to_string()));
+        assert_eq!(config.options.min_size, Some(10));
+    }
+
+    #[test]
+    fn test_load_config_with_preset() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.yaml");
+
+        // 预设代替项目类型的默认配置，配置文件和命令行的模式合并在上面；
+        // 配置文件没有设置最小年龄时使用预设的默认值
+        fs::write(
+            &config_path,
+            "clean:\n  folders: []\n  files: [\"*.crash\"]\nexclude: []\noptions: {}\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "").unwrap();
+        let config = ConfigLoader::load_config_with_preset(
+            Some(temp_dir.path()),
+            Some(&config_path),
+            &["*.bak".to_string()],
+            Some(Preset::Partials),
+        )
+        .unwrap();
+        assert!(config.clean.folders.is_empty());
+        assert!(config.clean.files.contains(&"*.part".to_string()));
+        assert!(config.clean.files.contains(&"*.download".to_string()));
+        assert!(config.clean.files.contains(&"*.crash".to_string()));
+        assert!(config.clean.files.contains(&"*.bak".to_string()));
+        assert_eq!(config.options.min_age_days, Some(1));
+
+        // 配置文件中的最小年龄优先
+        fs::write(
+            &config_path,
+            "clean:\n  folders: []\n  files: []\nexclude: []\noptions:\n  min_age_days: 7\n",
+        )
+        .unwrap();
+        let config = ConfigLoader::load_config_with_preset(
+            None,
+            Some(&config_path),
+            &[],
+            Some(Preset::Partials),
+        )
+        .unwrap();
+        assert_eq!(config.options.min_age_days, Some(7));
+
+        // 没有预设时照常使用项目类型的默认配置，不设置最小年龄
+        let config =
+            ConfigLoader::load_config_with_preset(None, Some(&config_path), &[], None).unwrap();
+        assert!(config.clean.folders.contains(&"node_modules".to_string()));
+
+        assert_eq!("partials".parse(), Ok(Preset::Partials));
+        assert!("nope".parse::<Preset>().is_err());
+    }
+
+    #[test]
+    fn test_parse_config_str() {
+        let json = ConfigLoader::parse_config_str(
+            r#"  {"clean": {"folders": ["target"], "files": []}, "exclude": [], "options": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(json.clean.folders, vec!["target".to_string()]);
+
+        let yaml = ConfigLoader::parse_config_str(
+            "clean:\n  folders: [dist]\n  files: []\nexclude: []\noptions: {}\n",
+        )
+        .unwrap();
+        assert_eq!(yaml.clean.folders, vec!["dist".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_config_file_encoding() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // 带 UTF-8 BOM 的 JSON 配置可以正常解析
+        let json_path = temp_dir.path().join("config.json");
+        let mut content = b"\xEF\xBB\xBF".to_vec();
+        content.extend_from_slice(
+            br#"{"clean": {"folders": ["dist"], "files": []}, "exclude": [], "options": {}}"#,
+        );
+        fs::write(&json_path, content).unwrap();
+        let config = ConfigLoader::parse_config_file(&json_path).unwrap();
+        assert_eq!(config.clean.folders, vec!["dist"]);
+
+        // 非 UTF-8 编码（如 Latin-1）的文件返回明确的错误
+        let latin1_path = temp_dir.path().join("latin1.json");
+        fs::write(&latin1_path, b"{\"clean\": {\"folders\": [\"caf\xE9\"]}}").unwrap();
+        let err = ConfigLoader::parse_config_file(&latin1_path).unwrap_err();
+        assert!(matches!(err, CleanError::ConfigParseError(_)));
+        let message = err.to_string();
+        assert!(message.contains("is not valid UTF-8"));
+        assert!(message.contains("latin1.json"));
+    }
+
+    #[test]
+    fn test_parse_clean_patterns_file() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // 文件夹和文件分别列出，不论末尾有没有 `/`
+        let yaml_path = temp_dir.path().join("patterns.yaml");
+        fs::write(
+            &yaml_path,
+            "folders:\n  - build/\n  - \"cmake-build-*\"\nfiles:\n  - Makefile.bak\n  - \"*.o\"\n",
+        )
+        .unwrap();
+        let patterns = ConfigLoader::parse_clean_patterns_file(&yaml_path).unwrap();
+        assert_eq!(patterns.folders, vec!["build", "cmake-build-*"]);
+        assert_eq!(patterns.files, vec!["Makefile.bak", "*.o"]);
+
+        // 与现有配置合并时跳过重复的模式
+        let mut config = ConfigLoader::load_default_config(&ProjectType::Unknown);
+        config.clean.folders = vec!["build".to_string()];
+        config.clean.files = vec![];
+        ConfigLoader::merge_clean_patterns(&mut config, &patterns);
+        assert_eq!(config.clean.folders, vec!["build", "cmake-build-*"]);
+        assert_eq!(config.clean.files, vec!["Makefile.bak", "*.o"]);
+
+        // JSON 格式，可以只有一个键
+        let json_path = temp_dir.path().join("patterns.json");
+        fs::write(&json_path, r#"{"files": ["LICENSE.tmp"]}"#).unwrap();
+        let patterns = ConfigLoader::parse_clean_patterns_file(&json_path).unwrap();
+        assert!(patterns.folders.is_empty());
+        assert_eq!(patterns.files, vec!["LICENSE.tmp"]);
+
+        // 拼错的键返回错误，而不是被静默忽略
+        fs::write(&json_path, r#"{"folder": ["dist"]}"#).unwrap();
+        assert!(ConfigLoader::parse_clean_patterns_file(&json_path).is_err());
+    }
+
+    #[test]
+    fn test_parse_config_file_error_location() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // 第 4 行缩进错误
+        let yaml_path = temp_dir.path().join("config.yaml");
+        fs::write(
+            &yaml_path,
+            "clean:\n  folders:\n    - target\n   files: [\"*.log\"]\nexclude: []\n",
+        )
+        .unwrap();
+        let err = ConfigLoader::parse_config_file(&yaml_path).unwrap_err();
+        match &err {
+            CleanError::Deserialize { format, line, .. } => {
+                assert_eq!(*format, "YAML");
+                assert_eq!(*line, 4);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        let message = err.to_string();
+        assert!(message.contains("line 4, column"));
+
+        // JSON 同样记录位置
+        let json_path = temp_dir.path().join("config.json");
+        fs::write(
+            &json_path,
+            "{\n  \"clean\": {\n    \"folders\": [,]\n  }\n}\n",
+        )
+        .unwrap();
+        let err = ConfigLoader::parse_config_file(&json_path).unwrap_err();
+        assert!(matches!(
+            err,
+            CleanError::Deserialize {
+                format: "JSON",
+                line: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_config_file_include() {
+        let temp_dir = TempDir::new().unwrap();
+        let shared = temp_dir.path().join("shared");
+        fs::create_dir(&shared).unwrap();
+
+        // base.yaml <- node.json <- project.yaml，相对路径相对于引用它的文件
+        fs::write(
+            shared.join("base.yaml"),
+            "clean:\n  folders: [target]\n  files: [\"*.log\"]\nexclude: [/srv/keep]\noptions:\n  min_age_days: 7\n  max_walk_errors: 5\n",
+        )
+        .unwrap();
+        fs::write(
+            shared.join("node.json"),
+            r#"{"include": ["base.yaml"], "clean": {"folders": ["node_modules", "target"], "files": []}}"#,
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project.yaml");
+        fs::write(
+            &project,
+            "include:\n  - shared/node.json\nclean:\n  folders: [dist]\n  files: [\"*.tmp\"]\noptions:\n  min_age_days: 30\n",
+        )
+        .unwrap();
+
+        // 被引用的文件先合并，列表追加并去重，当前文件的值覆盖被引用文件的值
+        let config = ConfigLoader::parse_config_file(&project).unwrap();
+        assert_eq!(config.clean.folders, vec!["target", "node_modules", "dist"]);
+        assert_eq!(config.clean.files, vec!["*.log", "*.tmp"]);
+        assert_eq!(config.exclude, vec![PathBuf::from("/srv/keep")]);
+        assert_eq!(config.options.min_age_days, Some(30));
+        assert_eq!(config.options.max_walk_errors, 5);
+
+        // 引用不存在的文件时指出引用它的文件
+        fs::write(&project, "include: [missing.yaml]\n").unwrap();
+        let err = ConfigLoader::parse_config_file(&project)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing.yaml"));
+        assert!(err.contains("project.yaml"));
+    }
+
+    #[test]
+    fn test_parse_config_file_include_cycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.yaml");
+        let b = temp_dir.path().join("b.yaml");
+        fs::write(
+            &a,
+            "include: [b.yaml]\nclean:\n  folders: [dist]\n  files: []\n",
+        )
+        .unwrap();
+        fs::write(&b, "include: [a.yaml]\nexclude: []\n").unwrap();
+
+        let err = ConfigLoader::parse_config_file(&a).unwrap_err();
+        assert!(matches!(err, CleanError::ConfigParseError(_)));
+        let message = err.to_string();
+        assert!(message.contains("Config include cycle"));
+        assert!(message.contains("a.yaml -> "));
+        assert!(message.contains("b.yaml -> "));
+
+        // 文件引用自身同样是循环
+        fs::write(&b, "include: [b.yaml]\n").unwrap();
+        assert!(ConfigLoader::parse_config_file(&b)
+            .unwrap_err()
+            .to_string()
+            .contains("Config include cycle"));
+    }
+
+    #[test]
+    fn test_retention_policy_partition() {
+        use std::time::Duration;
+
+        // 10 个项目，分别在 1、2、...、10 天前修改，输入顺序打乱
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+        let days_ago = [4u64, 9, 1, 7, 2, 10, 5, 3, 8, 6];
+        let items: Vec<(String, SystemTime)> = days_ago
+            .iter()
+            .map(|days| {
+                (
+                    format!("build-{:02}", days),
+                    now - Duration::from_secs(days * 86400 + 60),
+                )
+            })
+            .collect();
+
+        // 7 天内的项目全部保留（1~6 天），最新的 5 个已包含在内，其余删除
+        let policy = RetentionPolicy {
+            keep_days: Some(7),
+            keep_newest: Some(5),
+        };
+        let (kept, deleted) = policy.partition(&items, now);
+        assert_eq!(
+            kept,
+            vec!["build-04", "build-01", "build-02", "build-05", "build-03", "build-06"]
+        );
+        assert_eq!(
+            deleted,
+            vec!["build-09", "build-07", "build-10", "build-08"]
+        );
+
+        // 最近 2 天内只有 2 个项目，但至少保留最新的 5 个
+        let policy = RetentionPolicy {
+            keep_days: Some(2),
+            keep_newest: Some(5),
+        };
+        let (kept, deleted) = policy.partition(&items, now);
+        assert_eq!(
+            kept,
+            vec!["build-04", "build-01", "build-02", "build-05", "build-03"]
+        );
+        assert_eq!(deleted.len(), 5);
+
+        // 只按数量或只按天数保留
+        let (kept, _) = "3"
+            .parse::<RetentionPolicy>()
+            .unwrap()
+            .partition(&items, now);
+        assert_eq!(kept, vec!["build-01", "build-02", "build-03"]);
+        let (kept, _) = "3d"
+            .parse::<RetentionPolicy>()
+            .unwrap()
+            .partition(&items, now);
+        assert_eq!(kept, vec!["build-01", "build-02"]);
+
+        assert_eq!(
+            "7d, 5".parse::<RetentionPolicy>(),
+            Ok(RetentionPolicy {
+                keep_days: Some(7),
+                keep_newest: Some(5),
+            })
+        );
+        assert!("7d,3d".parse::<RetentionPolicy>().is_err());
+        assert!("week".parse::<RetentionPolicy>().is_err());
     }
 }
@@ -1,14 +1,79 @@
+use crate::cancel::CancelFlag;
+use crate::config::{
+    ConfigLoader, DeleteMode, Options, ProjectType, RestoreConflict, TrashSpaceAction,
+};
 use crate::error::CleanError;
-use crate::search::SearchResult;
+use crate::ignores::IgnoreFiles;
+use crate::manifest::DeleteManifest;
+use crate::search::{SearchEngine, SearchResult};
+use crate::space::DiskSpace;
+use filetime::FileTime;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use thiserror::Error;
 use trash;
 
 /// 进度回调函数类型
-type ProgressCallback = Box<dyn FnMut(usize, usize, &Path)>;
+type ProgressCallback<'a> = Box<dyn FnMut(usize, usize, &Path) + 'a>;
+
+/// inode 的不可变属性标志（linux/fs.h 中的 `FS_IMMUTABLE_FL`）
+#[cfg(target_os = "linux")]
+const FS_IMMUTABLE_FL: libc::c_int = 0x0000_0010;
+
+thread_local! {
+    /// 当前线程是否处于预览模式，预览期间拒绝一切修改文件系统的操作
+    static DRY_RUN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 预览模式的作用域：存在期间当前线程不允许修改文件系统，离开作用域时恢复之前的状态
+struct DryRunScope {
+    previous: bool,
+}
+
+impl DryRunScope {
+    fn enter() -> Self {
+        Self {
+            previous: DRY_RUN.with(|dry_run| dry_run.replace(true)),
+        }
+    }
+}
+
+impl Drop for DryRunScope {
+    fn drop(&mut self) {
+        DRY_RUN.with(|dry_run| dry_run.set(self.previous));
+    }
+}
+
+/// 预览模式下尝试修改文件系统时返回的错误
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Refusing to {action} {} during a dry run", path.display())]
+pub struct DryRunViolation {
+    /// 被拒绝的操作
+    pub action: &'static str,
+    /// 操作的目标路径
+    pub path: PathBuf,
+}
+
+impl From<DryRunViolation> for std::io::Error {
+    fn from(violation: DryRunViolation) -> Self {
+        std::io::Error::other(violation)
+    }
+}
+
+impl From<DryRunViolation> for trash::Error {
+    fn from(violation: DryRunViolation) -> Self {
+        trash::Error::Unknown {
+            description: violation.to_string(),
+        }
+    }
+}
 
 /// 删除操作的结果
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct DeleteResult {
     /// 成功删除的文件列表
     pub deleted_files: Vec<PathBuf>,
@@ -20,17 +85,428 @@ pub struct DeleteResult {
     pub failed_dirs: Vec<(PathBuf, String)>,
     /// 删除文件的总大小（字节）
     pub total_size: u64,
+    /// 删除文件实际占用的磁盘空间（字节，仅在启用 `physical_size` 时计算）
+    pub total_physical_size: Option<u64>,
+    /// 释放的 inode 数量，即删除的文件、目录和链接总数（仅在启用 `count_inodes` 时计算）
+    pub inodes_freed: Option<u64>,
+    /// 删除文件按扩展名的总大小（字节，仅在启用 `extension_breakdown` 时计算）
+    pub extension_sizes: Option<HashMap<String, u64>>,
+    /// 被安全检查拒绝删除的项目及触发的规则（这些项目同时记录在 `failed_files`/`failed_dirs` 中）
+    pub safety_refusals: Vec<(PathBuf, SafetyViolation)>,
+}
+
+impl DeleteResult {
+    /// 将另一次删除的结果合并到当前结果中（如按根路径分别删除时）
+    ///
+    /// # 参数
+    /// * `other` - 要合并的删除结果
+    pub fn merge(&mut self, other: DeleteResult) {
+        self.deleted_files.extend(other.deleted_files);
+        self.deleted_dirs.extend(other.deleted_dirs);
+        self.failed_files.extend(other.failed_files);
+        self.failed_dirs.extend(other.failed_dirs);
+        self.safety_refusals.extend(other.safety_refusals);
+        self.total_size += other.total_size;
+        self.total_physical_size = match (self.total_physical_size, other.total_physical_size) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
+        self.inodes_freed = match (self.inodes_freed, other.inodes_freed) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
+        if let Some(other_sizes) = other.extension_sizes {
+            DeleteEngine::merge_extension_sizes(
+                self.extension_sizes.get_or_insert_with(HashMap::new),
+                &other_sizes,
+            );
+        }
+    }
+}
+
+/// 安全检查拒绝删除某个路径的原因
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SafetyViolation {
+    /// 路径是系统目录（或根目录）或位于其中
+    #[error("Cannot delete system directory: {0}")]
+    SystemDirectory(PathBuf),
+    /// 路径中的 `..` 超出了根目录
+    #[error("Invalid path: contains '..'")]
+    ParentTraversal,
+    /// 路径（解析符号链接后）位于其他用户的主目录中
+    #[error("Cannot delete inside another user's home directory: {0}")]
+    OtherUsersHome(PathBuf),
+    /// 路径是特殊文件（FIFO、套接字、设备文件）
+    #[error("Cannot delete special file: {0}")]
+    SpecialFile(PathBuf),
+    /// 路径是当前工作目录或其上级目录
+    #[error("Cannot delete the current working directory or its parent: {0}")]
+    WorkingDirectory(PathBuf),
+    /// 路径不存在
+    #[error("Path not found: {0}")]
+    NotFound(PathBuf),
+}
+
+impl SafetyViolation {
+    /// 规则的简短标识，用于机器可读的输出
+    pub fn code(&self) -> &'static str {
+        match self {
+            SafetyViolation::SystemDirectory(_) => "system-directory",
+            SafetyViolation::ParentTraversal => "parent-traversal",
+            SafetyViolation::OtherUsersHome(_) => "other-users-home",
+            SafetyViolation::SpecialFile(_) => "special-file",
+            SafetyViolation::WorkingDirectory(_) => "working-directory",
+            SafetyViolation::NotFound(_) => "not-found",
+        }
+    }
 }
 
 /// 删除计划，包含要删除的文件和目录（已按删除顺序排序）
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct DeletePlan {
     /// 要删除的文件列表
     pub files: Vec<PathBuf>,
     /// 要删除的目录列表（按深度从深到浅排序）
     pub dirs: Vec<PathBuf>,
+    /// 要永久删除（不经过回收站）的项目，其余项目移到回收站
+    pub permanent: HashSet<PathBuf>,
+}
+
+impl DeletePlan {
+    /// 项目是否要永久删除
+    pub fn is_permanent(&self, path: &Path) -> bool {
+        self.permanent.contains(path)
+    }
+
+    /// 按计划顺序列出所有项目（先文件后目录）
+    ///
+    /// # 返回
+    /// 路径和是否为目录
+    pub fn items(&self) -> Vec<(PathBuf, bool)> {
+        self.files
+            .iter()
+            .map(|file| (file.clone(), false))
+            .chain(self.dirs.iter().map(|dir| (dir.clone(), true)))
+            .collect()
+    }
+
+    /// 按目录聚集列出所有项目：同一子树中的项目连续排列，子树中的项目总是排在其上级目录之前
+    ///
+    /// 路径按组成部分逐级排序，相同前缀的项目因此相邻；上级目录排在其中所有项目之后，
+    /// 删除顺序仍然满足由深到浅的要求。
+    ///
+    /// # 返回
+    /// 路径和是否为目录
+    pub fn items_by_locality(&self) -> Vec<(PathBuf, bool)> {
+        let mut items = self.items();
+        items.sort_by(|(a, _), (b, _)| {
+            if a.starts_with(b) && a != b {
+                return Ordering::Less;
+            }
+            if b.starts_with(a) && a != b {
+                return Ordering::Greater;
+            }
+            a.components().cmp(b.components())
+        });
+        items
+    }
+
+    /// 按可释放空间从大到小列出所有项目，但嵌套的项目总是排在其上级目录之后
+    ///
+    /// 先处理上级目录时，确认删除后其中的项目随之删除，无需再逐个确认。
+    ///
+    /// # 参数
+    /// * `sizes` - 每个项目的大小（如 `SearchResult::item_sizes`），缺失的项目按 0 处理
+    ///
+    /// # 返回
+    /// 路径和是否为目录
+    pub fn items_largest_first(&self, sizes: &[(PathBuf, u64)]) -> Vec<(PathBuf, bool)> {
+        let sizes: HashMap<&Path, u64> = sizes
+            .iter()
+            .map(|(path, size)| (path.as_path(), *size))
+            .collect();
+        let mut sorted = self.items();
+        sorted.sort_by(|a, b| {
+            let size_of = |path: &PathBuf| sizes.get(path.as_path()).copied().unwrap_or(0);
+            size_of(&b.0)
+                .cmp(&size_of(&a.0))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        // 依次输出，输出一个项目前先输出计划中尚未输出的上级目录（由浅到深）
+        let planned_dirs: HashSet<&Path> = self.dirs.iter().map(|dir| dir.as_path()).collect();
+        let mut emitted: HashSet<PathBuf> = HashSet::new();
+        let mut ordered = Vec::with_capacity(sorted.len());
+        for (path, is_dir) in sorted {
+            let ancestors: Vec<&Path> = path
+                .ancestors()
+                .skip(1)
+                .filter(|ancestor| planned_dirs.contains(ancestor))
+                .collect();
+            for ancestor in ancestors.into_iter().rev() {
+                if emitted.insert(ancestor.to_path_buf()) {
+                    ordered.push((ancestor.to_path_buf(), true));
+                }
+            }
+            if emitted.insert(path.clone()) {
+                ordered.push((path, is_dir));
+            }
+        }
+        ordered
+    }
+}
+
+/// 删除选项，控制删除行为
+#[derive(Debug, Clone, Default)]
+pub struct DeleteOptions {
+    /// 删除文件后是否恢复其父目录的修改时间
+    ///
+    /// 某些构建系统依据目录的修改时间判断是否需要重新构建，
+    /// 删除目录中的文件会更新该目录的修改时间，从而触发不必要的重新构建。
+    /// 该选项只对单个文件的删除生效，整个目录的删除不受影响。
+    pub preserve_parent_mtime: bool,
+    /// 是否同时统计实际释放的磁盘空间（物理大小）
+    pub physical_size: bool,
+    /// 是否同时统计释放的 inode 数量
+    pub count_inodes: bool,
+    /// 是否同时按扩展名统计删除的大小，在计算目录大小的同一次遍历中完成
+    pub extension_breakdown: bool,
+    /// 只删除看起来完全由构建生成的目录（为 `None` 时不检查）
+    pub generated_only: Option<SafetyPolicy>,
+    /// 删除速率上限（每秒项目数），避免大量删除占满磁盘 I/O（为 `None` 时不限制）
+    pub throttle: Option<u32>,
+    /// 删除清单路径，每删除成功一个项目就追加一条已完成记录（为 `None` 时不记录）
+    pub manifest: Option<PathBuf>,
+    /// 回收站空间不足时的处理方式（为 `None` 时不检查）
+    pub trash_space_check: Option<TrashSpaceAction>,
+    /// 删除设置了不可变属性的项目前是否先尝试清除该属性（仅 Linux）
+    pub clear_immutable: bool,
+    /// 删除目录前是否先将其重命名为同级的临时名称
+    ///
+    /// 重命名是瞬间完成的原子操作，目录会立即从原位置消失；之后再将重命名后的副本移到回收站。
+    /// 后一步被中断时原位置不会留下删除了一半的目录；失败时副本改回原名称，目录计为删除失败。
+    pub rename_first: bool,
+    /// 是否按目录聚集删除（见 `DeletePlan::items_by_locality`），否则先删除所有文件再删除所有目录
+    pub locality_order: bool,
+    /// 是否逐项永久删除目录（见 `DeleteEngine::remove_directory_piecewise`），启用后 `rename_first` 不再生效
+    pub piecewise_delete: bool,
+    /// 从回收站恢复时原位置已被占用的处理方式
+    pub restore_conflict: RestoreConflict,
+    /// 取消标志，被设置后不再删除剩余的项目（为 `None` 时不检查）
+    pub cancel: Option<CancelFlag>,
+}
+
+/// 删除速率控制器，通过休眠使删除速率不超过目标值
+#[derive(Debug)]
+pub struct Throttle {
+    /// 目标速率（每秒项目数）
+    rate: u32,
+    /// 开始时间
+    start: Instant,
+    /// 已完成的项目数
+    items_done: usize,
+}
+
+impl Throttle {
+    /// 创建速率控制器，速率为 0 时视为不限制
+    pub fn new(rate: u32) -> Self {
+        Throttle {
+            rate,
+            start: Instant::now(),
+            items_done: 0,
+        }
+    }
+
+    /// 计算为保持目标速率需要休眠的时间
+    ///
+    /// # 参数
+    /// * `elapsed` - 从开始到现在经过的时间
+    /// * `items_done` - 已完成的项目数
+    /// * `rate` - 目标速率（每秒项目数）
+    ///
+    /// # 返回
+    /// 需要休眠的时间，已经低于目标速率时返回零
+    pub fn sleep_duration(elapsed: Duration, items_done: usize, rate: u32) -> Duration {
+        if rate == 0 {
+            return Duration::ZERO;
+        }
+        let expected = Duration::from_secs_f64(items_done as f64 / rate as f64);
+        expected.saturating_sub(elapsed)
+    }
+
+    /// 记录完成一个项目，必要时休眠
+    pub fn tick(&mut self) {
+        self.items_done += 1;
+        let sleep = Self::sleep_duration(self.start.elapsed(), self.items_done, self.rate);
+        if !sleep.is_zero() {
+            std::thread::sleep(sleep);
+        }
+    }
+}
+
+/// 判断目录是否完全由构建生成的策略
+#[derive(Debug, Clone, Default)]
+pub struct SafetyPolicy {
+    /// 源文件扩展名列表（不含 `.`，不区分大小写），包含这些文件的目录不会被删除
+    pub source_extensions: Vec<String>,
+}
+
+impl SafetyPolicy {
+    /// 文件是否具有源文件扩展名
+    pub fn is_source_file(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.source_extensions
+                    .iter()
+                    .any(|source| source.eq_ignore_ascii_case(ext))
+            })
+    }
+}
+
+/// `CACHEDIR.TAG` 文件开头的固定签名（见 https://bford.info/cachedir/）
+const CACHEDIR_TAG_SIGNATURE: &str = "Signature: 8a477f597d28d172789f06886806bc55";
+
+/// 分类目录时最多检查的文件数，避免在很大的目录上耗时过长
+const CLASSIFY_MAX_FILES: usize = 10_000;
+
+/// 目录是否为可重新生成的构建产物的启发式判断结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    /// 目录是构建产物的置信度，范围为 0.0 到 1.0，越高越适合建议清理
+    pub safe_score: f32,
+    /// 影响评分的各项依据，可以直接展示给用户
+    pub reasons: Vec<String>,
 }
 
+/// 交互删除时等待确认的单个项目
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteItem {
+    /// 项目路径
+    pub path: PathBuf,
+    /// 是否为目录
+    pub is_dir: bool,
+    /// 大小（字节），目录为其全部内容的大小
+    pub size: u64,
+}
+
+/// 对单个项目的确认结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmation {
+    /// 删除当前项目
+    Yes,
+    /// 跳过当前项目
+    No,
+    /// 删除当前项目和所有剩余项目，不再询问
+    All,
+    /// 取消操作，不再删除任何项目
+    Quit,
+    /// 从回收站恢复上一个删除的项目，然后重新询问当前项目
+    Undo,
+}
+
+/// 交互删除过程中发生的事件，供确认方显示进度
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeleteEvent<'a> {
+    /// 项目已随先删除的上级目录一起删除
+    AlreadyDeleted { path: &'a Path, parent: &'a Path },
+    /// 项目未通过安全检查
+    SafetyCheckFailed { path: &'a Path, error: String },
+    /// 项目被跳过（`reason` 为 `None` 时是用户选择跳过）
+    Skipped {
+        path: &'a Path,
+        reason: Option<String>,
+    },
+    /// 用户选择删除所有剩余项目
+    AllConfirmed,
+    /// 用户取消了操作
+    Cancelled,
+    /// 项目已移到回收站
+    Deleted(&'a Path),
+    /// 项目删除失败
+    Failed { path: &'a Path, error: String },
+    /// 上一个删除的项目已从回收站恢复
+    Restored(&'a Path),
+    /// 原位置已被占用，上一个删除的项目恢复为另一个名称
+    RestoredAs { path: &'a Path, renamed: &'a Path },
+    /// 原位置已被占用，上一个删除的项目没有恢复，仍留在回收站中
+    RestoreConflict(&'a Path),
+    /// 上一个删除的项目无法恢复
+    RestoreFailed { path: &'a Path, error: String },
+    /// 没有可以撤销的删除
+    NothingToUndo,
+    /// 不影响删除结果的警告（如删除清单无法更新）
+    Warning(String),
+}
+
+/// 交互删除时逐项确认的方式，命令行、图形界面等前端各自实现
+pub trait Confirmer {
+    /// 询问是否删除单个项目
+    ///
+    /// # 返回
+    /// 确认结果，无法获取回答（如输入已关闭）时返回错误，删除随之中止
+    fn confirm(&mut self, item: &DeleteItem) -> Result<Confirmation, CleanError>;
+
+    /// 接收删除过程中的事件，默认忽略
+    fn notify(&mut self, _event: &DeleteEvent) {}
+}
+
+/// 从回收站恢复项目的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    /// 已恢复到原位置
+    Restored,
+    /// 原位置已被占用，已恢复为另一个名称
+    Renamed(PathBuf),
+    /// 原位置已被占用，没有恢复，项目仍留在回收站中
+    Conflict,
+}
+
+/// 交互删除时的回收站操作，便于替换（如在测试中只记录操作）
+pub trait TrashOps {
+    /// 将项目移到回收站，返回项目移入回收站时的路径（启用 `rename_first` 时为重命名后的路径）
+    fn trash(&self, path: &Path, is_dir: bool, options: &DeleteOptions) -> Result<PathBuf, String>;
+    /// 将最近移到回收站的项目恢复到原位置 `path`，原位置已被占用时按 `conflict` 处理
+    ///
+    /// `trashed` 是 `trash` 返回的路径，回收站按这个路径记录项目。
+    fn restore(
+        &self,
+        path: &Path,
+        trashed: &Path,
+        conflict: RestoreConflict,
+    ) -> Result<RestoreOutcome, String>;
+}
+
+/// 使用系统回收站
+pub struct SystemTrash;
+
+impl TrashOps for SystemTrash {
+    fn trash(&self, path: &Path, is_dir: bool, options: &DeleteOptions) -> Result<PathBuf, String> {
+        if is_dir {
+            DeleteEngine::trash_directory(path, options).map_err(|e| e.to_string())
+        } else {
+            DeleteEngine::trash_file(path, options)
+                .map(|_| path.to_path_buf())
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    fn restore(
+        &self,
+        path: &Path,
+        trashed: &Path,
+        conflict: RestoreConflict,
+    ) -> Result<RestoreOutcome, String> {
+        DeleteEngine::restore_from_trash(path, trashed, conflict).map_err(|e| e.to_string())
+    }
+}
+
+/// 存放各用户主目录的目录，其中不属于当前用户的主目录不会被删除
+const HOME_PARENTS: [&str; 2] = ["/home", "/Users"];
+
+/// 系统目录，其中的项目不会被删除，也不允许作为扫描的根目录
+const SYSTEM_DIRS: [&str; 7] = ["/usr", "/etc", "/bin", "/sbin", "/var", "/sys", "/proc"];
+
 /// 删除引擎，负责创建删除计划和执行删除操作
 pub struct DeleteEngine;
 
@@ -90,10 +566,30 @@ impl DeleteEngine {
     /// * `dir_path` - 目录路径
     ///
     /// # 返回
-    /// 目录及其所有内容的总大小（字节）
-    fn calculate_dir_size(dir_path: &Path) -> u64 {
+    /// 目录及其所有内容的逻辑大小和物理大小（字节）
+    pub fn calculate_dir_size(dir_path: &Path) -> (u64, u64) {
+        let (total_size, physical_size, _) = Self::calculate_dir_usage(dir_path, None);
+        (total_size, physical_size)
+    }
+
+    /// 计算目录的总大小、物理大小和包含的条目数量
+    ///
+    /// 条目数量包括目录自身及其中所有的文件、子目录和链接，即删除后释放的 inode 数量。
+    ///
+    /// # 参数
+    /// * `dir_path` - 目录路径
+    /// * `extensions` - 指定时在同一次遍历中将每个文件的大小按扩展名计入其中
+    ///
+    /// # 返回
+    /// (逻辑大小, 物理大小, 条目数量)
+    pub fn calculate_dir_usage(
+        dir_path: &Path,
+        mut extensions: Option<&mut HashMap<String, u64>>,
+    ) -> (u64, u64, u64) {
         use walkdir::WalkDir;
         let mut total_size = 0u64;
+        let mut physical_size = 0u64;
+        let mut entries = 0u64;
 
         // 使用 walkdir 遍历目录，比 read_dir 更高效
         for entry in WalkDir::new(dir_path).into_iter() {
@@ -101,19 +597,76 @@ impl DeleteEngine {
                 Ok(e) => e,
                 Err(_) => continue, // 忽略无法访问的条目
             };
+            entries += 1;
 
             // 只统计文件大小，目录本身不占用空间（除了元数据）
             if entry.file_type().is_file() {
                 if let Ok(metadata) = entry.metadata() {
                     total_size += metadata.len();
+                    physical_size += SearchEngine::physical_size(&metadata);
+                    if let Some(extensions) = extensions.as_deref_mut() {
+                        Self::add_extension_size(extensions, entry.path(), metadata.len());
+                    }
                 }
             }
         }
 
-        total_size
+        (total_size, physical_size, entries)
+    }
+
+    /// 将文件的大小按扩展名计入统计
+    ///
+    /// 扩展名为最后一个 `.` 之后的部分（不含 `.`），没有扩展名时为空字符串。
+    pub fn add_extension_size(sizes: &mut HashMap<String, u64>, path: &Path, size: u64) {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        *sizes.entry(extension).or_insert(0) += size;
+    }
+
+    /// 将按扩展名的大小合并到总计中
+    pub fn merge_extension_sizes(total: &mut HashMap<String, u64>, sizes: &HashMap<String, u64>) {
+        for (extension, size) in sizes {
+            *total.entry(extension.clone()).or_insert(0) += size;
+        }
+    }
+
+    /// 计算单个项目的大小、物理大小和条目数量，启用 `extension_breakdown` 时同时按扩展名统计
+    ///
+    /// # 参数
+    /// * `path` - 项目路径
+    /// * `is_dir` - 是否为目录
+    /// * `options` - 删除选项
+    /// * `dir_usage` - 计算目录的（大小, 物理大小, 条目数量）
+    ///
+    /// # 返回
+    /// ((大小, 物理大小, 条目数量), 按扩展名的大小)
+    fn item_usage<F>(
+        path: &Path,
+        is_dir: bool,
+        options: &DeleteOptions,
+        dir_usage: F,
+    ) -> ((u64, u64, u64), HashMap<String, u64>)
+    where
+        F: Fn(&Path, Option<&mut HashMap<String, u64>>) -> (u64, u64, u64),
+    {
+        let mut extensions = HashMap::new();
+        let usage = if is_dir {
+            dir_usage(path, options.extension_breakdown.then_some(&mut extensions))
+        } else {
+            let usage = fs::metadata(path)
+                .map(|m| (m.len(), SearchEngine::physical_size(&m), 1))
+                .unwrap_or((0, 0, 1));
+            if options.extension_breakdown {
+                Self::add_extension_size(&mut extensions, path, usage.0);
+            }
+            usage
+        };
+        (usage, extensions)
     }
 
-    /// 根据搜索结果创建删除计划，目录按深度从深到浅排序
+    /// 根据搜索结果创建删除计划，目录按深度从深到浅排序，深度相同时按路径排序
     ///
     /// # 参数
     /// * `search_result` - 搜索结果
@@ -121,222 +674,1887 @@ impl DeleteEngine {
     /// # 返回
     /// 删除计划，包含要删除的文件和目录
     pub fn create_delete_plan(search_result: &SearchResult) -> DeletePlan {
-        let files = search_result.files.clone();
+        // 位于匹配目录中的文件（同时收集目录和文件时）随目录一起删除
+        let folders: HashSet<PathBuf> = search_result.folders.iter().collect();
+        let files = search_result
+            .files
+            .iter()
+            .filter(|file| !file.ancestors().skip(1).any(|dir| folders.contains(dir)))
+            .collect();
 
         let mut dirs_with_depth: Vec<(PathBuf, usize)> = search_result
             .folders
             .iter()
             .map(|dir| {
                 let depth = dir.components().count();
-                (dir.clone(), depth)
+                (dir, depth)
             })
             .collect();
 
-        dirs_with_depth.sort_by(|a, b| b.1.cmp(&a.1));
+        // 按深度从深到浅排序，深度相同时按路径字典序排序，保证计划顺序稳定可复现
+        dirs_with_depth.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
         let dirs: Vec<PathBuf> = dirs_with_depth.into_iter().map(|(dir, _)| dir).collect();
 
-        DeletePlan { files, dirs }
+        let permanent = search_result
+            .item_origins
+            .iter()
+            .filter(|origin| origin.delete_mode == DeleteMode::Permanent)
+            .map(|origin| origin.path.clone())
+            .collect();
+
+        DeletePlan {
+            files,
+            dirs,
+            permanent,
+        }
     }
 
-    /// 检查路径是否安全，防止删除系统关键目录
+    /// 使用系统的 `du` 命令获取目录的大小，用于核对计算结果
+    ///
+    /// 优先使用 `du -sb`（GNU，逻辑大小），不支持时退化为 `du -sk`（以 KB 为单位）。
+    /// 注意 `du` 会把目录条目本身的大小也计算在内。
     ///
     /// # 参数
-    /// * `path` - 要检查的路径
+    /// * `path` - 目录路径
     ///
     /// # 返回
-    /// 如果路径安全返回 `Ok(())`，否则返回错误
-    pub fn check_safety(path: &Path) -> Result<(), CleanError> {
-        // 规范化路径为绝对路径，移除 `.` 和 `..`，但不解析符号链接
-        let canonical = Self::normalize_path(path)?;
-
-        // 先检查具体的系统目录（按长度从长到短排序，避免误匹配）
-        let system_dirs = ["/usr", "/etc", "/bin", "/sbin", "/var", "/sys", "/proc"];
-        for sys_dir in &system_dirs {
-            if canonical.starts_with(sys_dir) {
-                return Err(CleanError::Other(format!(
-                    "Cannot delete system directory: {}",
-                    canonical.display()
-                )));
+    /// `du` 报告的大小（字节），命令不可用或输出无法解析时返回 `None`
+    #[cfg(unix)]
+    pub fn du_size(path: &Path) -> Option<u64> {
+        use std::process::Command;
+
+        let run = |flag: &str| -> Option<u64> {
+            let output = Command::new("du").arg(flag).arg(path).output().ok()?;
+            if !output.status.success() {
+                return None;
             }
-        }
-
-        // 最后检查根目录，只允许路径正好是 "/"
-        if canonical == Path::new("/") {
-            return Err(CleanError::Other(format!(
-                "Cannot delete system directory: {}",
-                canonical.display()
-            )));
-        }
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        };
 
-        // 检查规范化后的路径是否包含 ".."（规范化后的路径不应该包含，但检查以防万一）
-        // 只检查作为路径分隔符的 ".."，而不是目录名中包含的 ".."
-        let path_str = canonical.to_string_lossy();
-        if path_str.contains("/../") || path_str.ends_with("/..") || path_str.starts_with("../") {
-            return Err(CleanError::Other("Invalid path: contains '..'".to_string()));
-        }
+        run("-sb").or_else(|| run("-sk").map(|kb| kb * 1024))
+    }
 
-        Ok(())
+    /// 使用系统工具获取目录的大小（当前平台不支持）
+    #[cfg(not(unix))]
+    pub fn du_size(_path: &Path) -> Option<u64> {
+        None
     }
 
-    /// 根据搜索结果执行删除（dry-run 模式）
-    /// 这个方法可以直接使用 SearchResult 中的 total_size，避免重复计算
+    /// 判断两个大小的差异是否超出容差
     ///
     /// # 参数
-    /// * `search_result` - 搜索结果（包含已计算的总大小）
-    /// * `dry_run` - 是否为预览模式
+    /// * `computed` - 计算得到的大小
+    /// * `reported` - 系统工具报告的大小
+    /// * `tolerance` - 允许的相对误差（如 0.05 表示 5%）
     ///
     /// # 返回
-    /// 删除结果，包含成功和失败的统计信息
-    pub fn execute_deletion_from_search(
-        search_result: &SearchResult,
-        dry_run: bool,
-    ) -> DeleteResult {
-        let plan = Self::create_delete_plan(search_result);
-
-        if dry_run {
-            // 直接使用 SearchResult 中已经计算好的总大小
-            // 文件大小和目录大小都在搜索阶段计算过了
-            return DeleteResult {
-                deleted_files: plan.files.clone(),
-                deleted_dirs: plan.dirs.clone(),
-                failed_files: Vec::new(),
-                failed_dirs: Vec::new(),
-                total_size: search_result.total_size,
-            };
+    /// 如果差异超出容差返回 `true`
+    pub fn sizes_diverge(computed: u64, reported: u64, tolerance: f64) -> bool {
+        let larger = computed.max(reported);
+        if larger == 0 {
+            return false;
         }
-
-        // 实际删除模式
-        Self::execute_deletion(&plan, false)
-    }
-
-    /// 执行删除操作（不带进度回调）
-    pub fn execute_deletion(plan: &DeletePlan, dry_run: bool) -> DeleteResult {
-        Self::execute_deletion_with_progress(
-            plan,
-            dry_run,
-            None::<Box<dyn FnMut(usize, usize, &Path)>>,
-        )
+        computed.abs_diff(reported) as f64 / larger as f64 > tolerance
     }
 
-    /// 执行删除操作（带进度回调）
+    /// 检查目录中的每个文件是否都像构建产物
+    ///
+    /// 目录中存在源文件扩展名的文件时，认为该目录可能包含真实的工作内容。
     ///
     /// # 参数
-    /// * `plan` - 删除计划
-    /// * `dry_run` - 是否为预览模式（不实际删除）
-    /// * `progress_callback` - 可选的进度回调函数，接收 (current, total, current_path)
+    /// * `dir` - 要检查的目录
+    /// * `policy` - 判断策略
     ///
     /// # 返回
-    /// 删除结果，包含成功和失败的统计信息
-    pub fn execute_deletion_with_progress(
-        plan: &DeletePlan,
-        dry_run: bool,
-        _progress_callback: Option<ProgressCallback>,
-    ) -> DeleteResult {
-        let mut deleted_files = Vec::new();
-        let mut deleted_dirs = Vec::new();
-        let mut failed_files = Vec::new();
-        let mut failed_dirs = Vec::new();
-        let mut total_size = 0u64;
+    /// 如果目录可以安全删除返回 `true`，否则返回 `false`
+    pub fn is_safe_to_remove(dir: &Path, policy: &SafetyPolicy) -> bool {
+        use walkdir::WalkDir;
 
-        if dry_run {
-            // 在 dry-run 模式下，文件大小和目录大小都已经在搜索阶段计算过了
-            // 这里只需要收集结果，total_size 会从 SearchResult 传入
-            // 注意：由于接口限制，我们需要重新计算，但可以通过传入 SearchResult 来优化
-            // 目前为了保持接口一致性，我们仍然需要计算
-            // 但实际上，如果 SearchResult.total_size 已经包含了目录大小，这里就不需要重新计算了
-
-            // 收集文件
-            for file in &plan.files {
-                if let Ok(metadata) = fs::metadata(file) {
-                    total_size += metadata.len();
-                }
-                deleted_files.push(file.clone());
+        for entry in WalkDir::new(dir) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                // 无法完整检查的目录视为不安全
+                Err(_) => return false,
+            };
+            if entry.file_type().is_file() && policy.is_source_file(entry.path()) {
+                return false;
             }
+        }
+        true
+    }
 
-            // 收集目录（大小已经在搜索阶段计算并加到 SearchResult.total_size 中了）
-            // 但这里我们无法访问 SearchResult，所以需要重新计算
-            // 为了优化，我们应该修改接口，让 execute_deletion 接收 SearchResult
-            // 或者修改 DeletePlan 包含总大小信息
+    /// 估计目录是可重新生成的构建产物的置信度，只读取文件系统，不做任何修改
+    ///
+    /// 综合以下依据：目录名是否为常见的构建目录名、父目录中是否有项目清单、
+    /// 是否有 `CACHEDIR.TAG`、是否包含版本控制仓库或被父目录的 `.gitignore` 忽略，
+    /// 以及目录中具有源文件扩展名的文件所占的比例（最多检查 `CLASSIFY_MAX_FILES` 个文件）。
+    ///
+    /// # 参数
+    /// * `dir` - 要分类的目录
+    ///
+    /// # 返回
+    /// 置信度和各项依据
+    pub fn classify(dir: &Path) -> Classification {
+        let mut score = 0.0f32;
+        let mut reasons = Vec::new();
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let parent = dir.parent().filter(|parent| !parent.as_os_str().is_empty());
+
+        // 与某种项目类型默认清理的目录同名
+        let project_types = [
+            ProjectType::NodeJs,
+            ProjectType::Rust,
+            ProjectType::Python,
+            ProjectType::Go,
+            ProjectType::Java,
+            ProjectType::Unknown,
+        ];
+        if project_types.iter().any(|project_type| {
+            ConfigLoader::load_default_config(project_type)
+                .clean
+                .folders
+                .contains(&name)
+        }) {
+            score += 0.3;
+            reasons.push(format!("'{}' is a common build output name", name));
+        }
 
-            // 临时方案：重新计算目录大小（但这样会有重复计算）
-            // 更好的方案是修改接口，传入 SearchResult 或 total_size
-            for dir in &plan.dirs {
-                total_size += Self::calculate_dir_size(dir);
-                deleted_dirs.push(dir.clone());
+        if let Some(parent) = parent {
+            let project_type = ConfigLoader::detect_project_type(parent);
+            if project_type != ProjectType::Unknown {
+                score += 0.15;
+                reasons.push(format!(
+                    "parent directory has a {} project manifest",
+                    project_type.name()
+                ));
             }
+        }
 
-            return DeleteResult {
-                deleted_files,
-                deleted_dirs,
-                failed_files,
-                failed_dirs,
-                total_size,
-            };
+        if fs::read(dir.join("CACHEDIR.TAG"))
+            .is_ok_and(|content| content.starts_with(CACHEDIR_TAG_SIGNATURE.as_bytes()))
+        {
+            score += 0.35;
+            reasons.push("contains a CACHEDIR.TAG".to_string());
         }
 
-        for file in &plan.files {
-            match Self::check_safety(file) {
-                Ok(_) => {
-                    // 在删除前获取文件大小
-                    let file_size = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
-
-                    // 将文件移到回收站而不是直接删除
-                    match trash::delete(file) {
-                        Ok(_) => {
-                            total_size += file_size;
-                            deleted_files.push(file.clone());
-                        }
-                        Err(e) => {
-                            failed_files.push((file.clone(), e.to_string()));
-                        }
-                    }
-                }
-                Err(e) => {
-                    failed_files.push((file.clone(), e.to_string()));
-                }
-            }
+        if [".git", ".hg", ".svn"]
+            .iter()
+            .any(|vcs| fs::symlink_metadata(dir.join(vcs)).is_ok())
+        {
+            score -= 0.5;
+            reasons.push("contains a version control repository".to_string());
+        } else if parent.is_some_and(|parent| IgnoreFiles::new(true).is_ignored(parent, dir, true))
+        {
+            score += 0.15;
+            reasons.push("ignored by the parent directory's .gitignore".to_string());
         }
 
-        for dir in &plan.dirs {
-            match Self::check_safety(dir) {
-                Ok(_) => {
-                    // 在删除前计算目录大小
-                    let dir_size = Self::calculate_dir_size(dir);
-
-                    // 将目录移到回收站而不是直接删除
-                    match trash::delete(dir) {
-                        Ok(_) => {
-                            total_size += dir_size;
-                            deleted_dirs.push(dir.clone());
-                        }
-                        Err(e) => {
-                            failed_dirs.push((dir.clone(), e.to_string()));
-                        }
-                    }
-                }
-                Err(e) => {
-                    failed_dirs.push((dir.clone(), e.to_string()));
-                }
+        // 与 `is_safe_to_remove` 使用相同的源文件判断，但只统计比例，不在第一个源文件处停止
+        let policy = SafetyPolicy {
+            source_extensions: Options::default().source_extensions,
+        };
+        let (mut files, mut sources) = (0usize, 0usize);
+        for entry in walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .take(CLASSIFY_MAX_FILES)
+        {
+            files += 1;
+            if policy.is_source_file(entry.path()) {
+                sources += 1;
             }
         }
+        if files > 0 && sources == 0 {
+            score += 0.05;
+            reasons.push(format!("none of {} files have source extensions", files));
+        } else if sources > 0 {
+            score -= 0.4 * sources as f32 / files as f32;
+            reasons.push(format!(
+                "{} of {} files have source extensions",
+                sources, files
+            ));
+        }
 
-        DeleteResult {
-            deleted_files,
-            deleted_dirs,
-            failed_files,
-            failed_dirs,
-            total_size,
+        Classification {
+            safe_score: score.clamp(0.0, 1.0),
+            reasons,
         }
     }
-}
 
-#[cfg(test)]
+    /// 启用 `generated_only` 时检查目录是否可以安全删除
+    ///
+    /// # 参数
+    /// * `dir` - 要检查的目录
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 如果未启用检查或目录可以安全删除返回 `Ok(())`，否则返回错误
+    pub fn check_generated(dir: &Path, options: &DeleteOptions) -> Result<(), CleanError> {
+        match &options.generated_only {
+            Some(policy) if !Self::is_safe_to_remove(dir, policy) => {
+                Err(CleanError::Other(format!(
+                    "Refusing to delete {}: it contains files that do not look generated",
+                    dir.display()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 当前用户的回收站目录（Linux 为 `$XDG_DATA_HOME/Trash`，macOS 为 `~/.Trash`）
+    ///
+    /// # 返回
+    /// 回收站目录，无法确定（如 Windows）时返回 `None`
+    pub fn trash_dir() -> Option<PathBuf> {
+        if cfg!(target_os = "macos") {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".Trash"))
+        } else if cfg!(unix) {
+            std::env::var_os("XDG_DATA_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    std::env::var_os("HOME")
+                        .map(|home| PathBuf::from(home).join(".local").join("share"))
+                })
+                .map(|data| data.join("Trash"))
+        } else {
+            None
+        }
+    }
+
+    /// 检查指定大小的项目能否放入回收站所在的文件系统
+    ///
+    /// 回收站目录还不存在时使用其最近的已存在的上级目录查询。
+    ///
+    /// # 参数
+    /// * `size` - 项目大小（字节）
+    /// * `dest` - 回收站目录
+    ///
+    /// # 返回
+    /// 空间足够或无法查询可用空间时返回 `true`
+    pub fn trash_would_fit(size: u64, dest: &Path) -> bool {
+        let Some(existing) = dest.ancestors().find(|dir| dir.exists()) else {
+            return true;
+        };
+        match DiskSpace::query(existing) {
+            Ok(space) => Self::fits(size, space.available),
+            Err(_) => true,
+        }
+    }
+
+    /// 判断项目大小是否不超过可用空间
+    pub fn fits(size: u64, available: u64) -> bool {
+        size <= available
+    }
+
+    /// 启用 `trash_space_check` 时检查项目能否放入回收站
+    ///
+    /// 与回收站位于同一文件系统的项目只需重命名，不占用额外空间，总是可以放入。
+    ///
+    /// # 参数
+    /// * `path` - 要删除的路径
+    /// * `size` - 项目大小（字节）
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 空间足够、未启用检查或处理方式为警告时返回 `Ok(())`，需要跳过时返回错误
+    pub fn check_trash_space(
+        path: &Path,
+        size: u64,
+        options: &DeleteOptions,
+    ) -> Result<(), CleanError> {
+        let Some(action) = options.trash_space_check else {
+            return Ok(());
+        };
+        let Some(trash) = Self::trash_dir() else {
+            return Ok(());
+        };
+        if Self::same_filesystem(path, &trash) || Self::trash_would_fit(size, &trash) {
+            return Ok(());
+        }
+
+        let message = format!(
+            "Not enough free space in trash ({}) for {} ({} bytes)",
+            trash.display(),
+            path.display(),
+            size
+        );
+        match action {
+            TrashSpaceAction::Warn => {
+                log::warn!("{}", message);
+                Ok(())
+            }
+            TrashSpaceAction::Skip => Err(CleanError::Other(message)),
+        }
+    }
+
+    /// 判断两个路径是否位于同一文件系统（目标不存在时使用其最近的已存在的上级目录）
+    #[cfg(unix)]
+    fn same_filesystem(path: &Path, dest: &Path) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        let device = |path: &Path| {
+            path.ancestors()
+                .find_map(|dir| fs::symlink_metadata(dir).ok())
+                .map(|m| m.dev())
+        };
+        matches!((device(path), device(dest)), (Some(a), Some(b)) if a == b)
+    }
+
+    /// 判断两个路径是否位于同一文件系统（当前平台无法判断）
+    #[cfg(not(unix))]
+    fn same_filesystem(_path: &Path, _dest: &Path) -> bool {
+        false
+    }
+
+    /// 检查路径是否设置了不可变属性（`chattr +i`）
+    ///
+    /// 设置了不可变属性的文件即使 root 也无法删除或移动。使用非阻塞方式打开，
+    /// 且不跟随符号链接，无法打开或文件系统不支持该属性时返回 `false`。
+    ///
+    /// # 参数
+    /// * `path` - 要检查的路径
+    ///
+    /// # 返回
+    /// 如果设置了不可变属性返回 `true`
+    #[cfg(target_os = "linux")]
+    pub fn is_immutable(path: &Path) -> bool {
+        Self::inode_flags(path).is_ok_and(|flags| flags & FS_IMMUTABLE_FL != 0)
+    }
+
+    /// 检查路径是否设置了不可变属性（当前平台不支持，总是返回 `false`）
+    #[cfg(not(target_os = "linux"))]
+    pub fn is_immutable(_path: &Path) -> bool {
+        false
+    }
+
+    /// 清除路径的不可变属性（相当于 `chattr -i`，需要 `CAP_LINUX_IMMUTABLE` 权限）
+    ///
+    /// # 参数
+    /// * `path` - 要清除属性的路径
+    ///
+    /// # 返回
+    /// 如果清除成功返回 `Ok(())`，否则返回系统错误
+    #[cfg(target_os = "linux")]
+    pub fn clear_immutable(path: &Path) -> std::io::Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let file = Self::open_for_flags(path)?;
+        let flags = Self::inode_flags(path)? & !FS_IMMUTABLE_FL;
+        Self::mutate("clear the immutable attribute of", path, || {
+            // SAFETY: fd 在 file 的生命周期内有效，flags 是内核期望的 int
+            if unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_SETFLAGS, &flags) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        })
+    }
+
+    /// 清除路径的不可变属性（当前平台不支持）
+    #[cfg(not(target_os = "linux"))]
+    pub fn clear_immutable(_path: &Path) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "immutable attribute is not supported on this platform",
+        ))
+    }
+
+    /// 以非阻塞、不跟随符号链接的方式打开路径，用于读写 inode 属性
+    #[cfg(target_os = "linux")]
+    fn open_for_flags(path: &Path) -> std::io::Result<fs::File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_NOFOLLOW)
+            .open(path)
+    }
+
+    /// 读取路径的 inode 属性（`lsattr` 显示的属性）
+    #[cfg(target_os = "linux")]
+    fn inode_flags(path: &Path) -> std::io::Result<libc::c_int> {
+        use std::os::unix::io::AsRawFd;
+
+        let file = Self::open_for_flags(path)?;
+        let mut flags: libc::c_int = 0;
+        // SAFETY: fd 在 file 的生命周期内有效，内核向 flags 写入一个 int
+        if unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_GETFLAGS, &mut flags) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(flags)
+    }
+
+    /// 删除前检查不可变属性，启用 `clear_immutable` 时先尝试清除
+    ///
+    /// # 参数
+    /// * `path` - 要删除的路径
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 可以删除时返回 `Ok(())`，设置了不可变属性且未能清除时返回说明原因的错误
+    pub fn check_immutable(path: &Path, options: &DeleteOptions) -> Result<(), CleanError> {
+        if !Self::is_immutable(path) {
+            return Ok(());
+        }
+        if !options.clear_immutable {
+            return Err(CleanError::Other(format!(
+                "Cannot delete {}: immutable attribute set (use --clear-immutable to clear it first)",
+                path.display()
+            )));
+        }
+        Self::clear_immutable(path).map_err(|e| {
+            CleanError::Other(format!(
+                "Cannot delete {}: immutable attribute set and could not be cleared: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 检查路径是否安全，防止删除系统关键目录
+    ///
+    /// # 参数
+    /// * `path` - 要检查的路径
+    ///
+    /// # 返回
+    /// 如果路径安全返回 `Ok(())`，否则返回触发的安全规则
+    pub fn check_safety(path: &Path) -> Result<(), SafetyViolation> {
+        Self::check_safety_with_cwd(path, Self::working_dir().as_deref())
+    }
+
+    /// 规范化后的当前工作目录，无法获取时返回 `None`
+    ///
+    /// 检查多个路径时只需获取一次，传给 `check_safety_with_cwd`。
+    pub fn working_dir() -> Option<PathBuf> {
+        std::env::current_dir().and_then(fs::canonicalize).ok()
+    }
+
+    /// 检查路径是否安全，使用给定的（已规范化的）当前工作目录
+    ///
+    /// # 参数
+    /// * `path` - 要检查的路径
+    /// * `cwd` - 规范化后的当前工作目录，为 `None` 时不检查工作目录规则
+    ///
+    /// # 返回
+    /// 如果路径安全返回 `Ok(())`，否则返回触发的安全规则
+    pub fn check_safety_with_cwd(path: &Path, cwd: Option<&Path>) -> Result<(), SafetyViolation> {
+        // 规范化路径为绝对路径，移除 `.` 和 `..`，但不解析符号链接
+        let canonical = Self::normalize_path(path).map_err(|e| match e {
+            CleanError::PathNotFound(_) => SafetyViolation::NotFound(path.to_path_buf()),
+            _ => SafetyViolation::ParentTraversal,
+        })?;
+
+        // 先检查具体的系统目录（按长度从长到短排序，避免误匹配）
+        for sys_dir in &SYSTEM_DIRS {
+            if canonical.starts_with(sys_dir) {
+                return Err(SafetyViolation::SystemDirectory(canonical));
+            }
+        }
+
+        // 最后检查根目录，只允许路径正好是 "/"
+        if canonical == Path::new("/") {
+            return Err(SafetyViolation::SystemDirectory(canonical));
+        }
+
+        // 检查规范化后的路径是否包含 ".."（规范化后的路径不应该包含，但检查以防万一）
+        // 只检查作为路径分隔符的 ".."，而不是目录名中包含的 ".."
+        let path_str = canonical.to_string_lossy();
+        if path_str.contains("/../") || path_str.ends_with("/..") || path_str.starts_with("../") {
+            return Err(SafetyViolation::ParentTraversal);
+        }
+
+        // 不存在的路径（如扫描之后已被其他进程删除）没有可删除的内容
+        let metadata = match fs::symlink_metadata(Self::access_path(path)) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(SafetyViolation::NotFound(path.to_path_buf()));
+            }
+            metadata => metadata.ok(),
+        };
+
+        // 通过符号链接解析到其他用户主目录中的路径不能删除（多用户系统上误删他人文件）
+        let resolved = fs::canonicalize(path).unwrap_or_else(|_| canonical.clone());
+        let home = ConfigLoader::home_dir().map(|home| fs::canonicalize(&home).unwrap_or(home));
+        if Self::is_other_users_home(&resolved, home.as_deref()) {
+            return Err(SafetyViolation::OtherUsersHome(resolved));
+        }
+
+        // 特殊文件（FIFO、套接字、设备文件）不是构建产物，不能删除
+        if metadata.is_some_and(|m| SearchEngine::is_special_file(m.file_type())) {
+            return Err(SafetyViolation::SpecialFile(path.to_path_buf()));
+        }
+
+        // 禁止删除当前工作目录或其上级目录，否则 shell 会停留在已删除的目录中
+        if cwd.is_some_and(|cwd| cwd.starts_with(&resolved)) {
+            return Err(SafetyViolation::WorkingDirectory(resolved));
+        }
+
+        Ok(())
+    }
+
+    /// 根据搜索结果执行删除（不带删除选项）
+    /// 预览模式下直接使用 SearchResult 中的 total_size，避免重复计算
+    ///
+    /// # 参数
+    /// * `search_result` - 搜索结果（包含已计算的总大小）
+    /// * `dry_run` - 是否为预览模式
+    ///
+    /// # 返回
+    /// 删除结果，包含成功和失败的统计信息
+    pub fn execute_deletion_from_search(
+        search_result: &SearchResult,
+        dry_run: bool,
+    ) -> DeleteResult {
+        let _dry_run = dry_run.then(DryRunScope::enter);
+        Self::execute_deletion_from_search_with_options(
+            search_result,
+            dry_run,
+            &DeleteOptions::default(),
+        )
+    }
+
+    /// 根据搜索结果使用指定的删除选项执行删除
+    ///
+    /// 文件大小和目录大小都在搜索阶段计算过了，预览模式下直接使用 SearchResult 中的总大小，
+    /// 不再遍历目录（统计 inode 数量时除外）。
+    ///
+    /// # 参数
+    /// * `search_result` - 搜索结果（包含已计算的总大小）
+    /// * `dry_run` - 是否为预览模式
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 删除结果，包含成功和失败的统计信息
+    pub fn execute_deletion_from_search_with_options(
+        search_result: &SearchResult,
+        dry_run: bool,
+        options: &DeleteOptions,
+    ) -> DeleteResult {
+        // 预览期间的任何操作（包括创建计划）都不允许修改文件系统
+        let _dry_run = dry_run.then(DryRunScope::enter);
+        let plan = Self::create_delete_plan(search_result);
+
+        if dry_run {
+            return Self::preview_deletion(
+                &plan,
+                options,
+                Some((search_result.total_size, search_result.total_physical_size)),
+                Self::calculate_dir_usage,
+                None,
+            );
+        }
+
+        Self::execute_deletion_with_options(&plan, false, options)
+    }
+
+    /// 预览删除：列出计划中的所有项目并统计将释放的空间，不实际删除
+    ///
+    /// # 参数
+    /// * `plan` - 删除计划
+    /// * `options` - 删除选项
+    /// * `precomputed` - 搜索阶段已计算的（总大小, 物理大小），为 `None` 时逐项计算
+    /// * `dir_usage` - 计算目录的（大小, 物理大小, 条目数量），只在没有预先计算的大小
+    ///   或需要统计 inode 数量、按扩展名统计时调用
+    /// * `progress_callback` - 逐项计算时在计算每个项目之前调用
+    ///
+    /// # 返回
+    /// 预览的删除结果，所有项目都计为已删除
+    fn preview_deletion<F>(
+        plan: &DeletePlan,
+        options: &DeleteOptions,
+        precomputed: Option<(u64, Option<u64>)>,
+        dir_usage: F,
+        mut progress_callback: Option<ProgressCallback<'_>>,
+    ) -> DeleteResult
+    where
+        F: Fn(&Path, Option<&mut HashMap<String, u64>>) -> (u64, u64, u64),
+    {
+        let mut total_size = 0u64;
+        let mut total_physical_size = 0u64;
+        let mut inodes_freed = 0u64;
+        let mut extension_sizes = HashMap::new();
+        let total = plan.files.len() + plan.dirs.len();
+        let mut report = |index: usize, path: &Path| {
+            if let Some(callback) = progress_callback.as_mut() {
+                callback(index, total, path);
+            }
+        };
+
+        if precomputed.is_none() || options.count_inodes || options.extension_breakdown {
+            let items = plan.files.iter().map(|file| (file, false));
+            let items = items.chain(plan.dirs.iter().map(|dir| (dir, true)));
+            for (index, (path, is_dir)) in items.enumerate() {
+                report(index, path);
+                let ((size, physical_size, entries), extensions) =
+                    Self::item_usage(path, is_dir, options, &dir_usage);
+                total_size += size;
+                total_physical_size += physical_size;
+                inodes_freed += entries;
+                Self::merge_extension_sizes(&mut extension_sizes, &extensions);
+            }
+        }
+
+        let (total_size, total_physical_size) = match precomputed {
+            Some(precomputed) => precomputed,
+            None => (
+                total_size,
+                options.physical_size.then_some(total_physical_size),
+            ),
+        };
+        DeleteResult {
+            deleted_files: plan.files.clone(),
+            deleted_dirs: plan.dirs.clone(),
+            failed_files: Vec::new(),
+            failed_dirs: Vec::new(),
+            total_size,
+            total_physical_size,
+            inodes_freed: options.count_inodes.then_some(inodes_freed),
+            extension_sizes: options.extension_breakdown.then_some(extension_sizes),
+            safety_refusals: Vec::new(),
+        }
+    }
+
+    /// 将单个文件移到回收站
+    ///
+    /// 如果启用了 `preserve_parent_mtime`，会在删除前记录父目录的修改时间，
+    /// 删除成功后再将其恢复。恢复失败不会影响删除结果。
+    ///
+    /// # 参数
+    /// * `file` - 要删除的文件路径
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 如果删除成功返回 `Ok(())`，否则返回回收站错误
+    pub fn trash_file(file: &Path, options: &DeleteOptions) -> Result<(), trash::Error> {
+        Self::preserving_parent_mtime(file, options, |file| {
+            Self::mutate("trash", file, || trash::delete(file))
+        })
+    }
+
+    /// 永久删除项目（不经过回收站），目录连同其全部内容一起删除
+    ///
+    /// 与移到回收站一样，删除文件时按 `preserve_parent_mtime` 恢复其父目录的修改时间。
+    ///
+    /// # 参数
+    /// * `path` - 要删除的项目
+    /// * `is_dir` - 是否为目录
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 如果删除成功返回 `Ok(())`，否则返回错误
+    pub fn remove_permanently(
+        path: &Path,
+        is_dir: bool,
+        options: &DeleteOptions,
+    ) -> Result<(), CleanError> {
+        let removed = if is_dir {
+            Self::mutate("delete", path, || fs::remove_dir_all(path))
+        } else {
+            Self::preserving_parent_mtime(path, options, |file| {
+                Self::mutate("delete", file, || fs::remove_file(file))
+            })
+        };
+        removed.map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to permanently delete {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 执行修改文件系统的操作
+    ///
+    /// `DeleteEngine` 中的删除、重命名、恢复和修改属性都必须经过这里。预览期间调用时，
+    /// 调试构建直接 panic，发布构建拒绝执行并返回错误，保证预览永远不会修改文件系统。
+    ///
+    /// # 参数
+    /// * `action` - 操作的描述（用于错误信息）
+    /// * `path` - 操作的目标路径
+    /// * `op` - 实际修改文件系统的操作
+    ///
+    /// # 返回
+    /// `op` 的结果；预览期间返回 `DryRunViolation` 转换成的错误
+    fn mutate<T, E, F>(action: &'static str, path: &Path, op: F) -> Result<T, E>
+    where
+        E: From<DryRunViolation>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        if DRY_RUN.with(Cell::get) {
+            let violation = DryRunViolation {
+                action,
+                path: path.to_path_buf(),
+            };
+            debug_assert!(false, "{}", violation);
+            return Err(violation.into());
+        }
+        op()
+    }
+
+    /// 删除文件，启用 `preserve_parent_mtime` 时在删除成功后恢复其父目录的修改时间
+    fn preserving_parent_mtime<E, F>(
+        file: &Path,
+        options: &DeleteOptions,
+        remove: F,
+    ) -> Result<(), E>
+    where
+        F: FnOnce(&Path) -> Result<(), E>,
+    {
+        let parent_mtime = if options.preserve_parent_mtime {
+            file.parent().and_then(|parent| {
+                fs::metadata(parent).ok().map(|m| {
+                    (
+                        parent.to_path_buf(),
+                        FileTime::from_last_modification_time(&m),
+                    )
+                })
+            })
+        } else {
+            None
+        };
+
+        remove(file)?;
+
+        if let Some((parent, mtime)) = parent_mtime {
+            if let Err(e) = Self::mutate("restore the mtime of", &parent, || {
+                filetime::set_file_mtime(&parent, mtime)
+            }) {
+                log::warn!("Failed to restore mtime of {}: {}", parent.display(), e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 将最近一次移到回收站的项目恢复到原位置（仅 Linux 和 Windows）
+    ///
+    /// 回收站中有多个原路径相同的项目时恢复最近删除的那一个。
+    ///
+    /// # 参数
+    /// * `path` - 项目被删除前的路径
+    /// * `trashed` - 项目移入回收站时的路径（先重命名再删除时与 `path` 不同）
+    /// * `conflict` - 原位置已被占用时的处理方式
+    ///
+    /// # 返回
+    /// 如果恢复成功返回 `Ok(())`，否则返回错误
+    #[cfg(any(
+        target_os = "windows",
+        all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        )
+    ))]
+    pub fn restore_from_trash(
+        path: &Path,
+        trashed: &Path,
+        conflict: RestoreConflict,
+    ) -> Result<RestoreOutcome, CleanError> {
+        // 回收站记录的是绝对路径，项目本身已不存在，只能规范化其父目录
+        let original = match (trashed.parent(), trashed.file_name()) {
+            (Some(parent), Some(name)) => fs::canonicalize(if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            })
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+            _ => trashed.to_path_buf(),
+        };
+
+        let item = trash::os_limited::list()
+            .map_err(|e| CleanError::Other(format!("Failed to list trash: {}", e)))?
+            .into_iter()
+            .filter(|item| item.original_path() == original)
+            .max_by_key(|item| item.time_deleted)
+            .ok_or_else(|| {
+                CleanError::Other(format!("{} was not found in the trash", path.display()))
+            })?;
+        Self::restore_trashed_with(path, trashed, conflict, |trashed| {
+            Self::mutate("restore", trashed, || {
+                trash::os_limited::restore_all([item])
+            })
+            .map_err(|e| CleanError::Other(format!("Failed to restore {}: {}", path.display(), e)))
+        })
+    }
+
+    /// 将最近一次移到回收站的项目恢复到原位置（当前平台不支持）
+    #[cfg(not(any(
+        target_os = "windows",
+        all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        )
+    )))]
+    pub fn restore_from_trash(
+        path: &Path,
+        _trashed: &Path,
+        _conflict: RestoreConflict,
+    ) -> Result<RestoreOutcome, CleanError> {
+        Err(CleanError::Other(format!(
+            "Cannot restore {}: restoring from the trash is not supported on this platform",
+            path.display()
+        )))
+    }
+
+    /// 将项目恢复到原位置，原位置已被占用时按 `conflict` 处理，不会覆盖现有的内容
+    ///
+    /// 恢复为另一个名称时，现有的项目先临时重命名让出原位置，由 `restore` 恢复后
+    /// 再将恢复的项目改为 `<名称>.restored`（已存在时追加序号），最后把现有的项目移回原位置。
+    ///
+    /// # 参数
+    /// * `path` - 项目被删除前的路径
+    /// * `conflict` - 原位置已被占用时的处理方式
+    /// * `restore` - 将项目恢复到原位置的函数
+    ///
+    /// # 返回
+    /// 恢复的结果
+    pub fn restore_with<F>(
+        path: &Path,
+        conflict: RestoreConflict,
+        restore: F,
+    ) -> Result<RestoreOutcome, CleanError>
+    where
+        F: FnOnce(&Path) -> Result<(), CleanError>,
+    {
+        if fs::symlink_metadata(path).is_err() {
+            return restore(path).map(|_| RestoreOutcome::Restored);
+        }
+        if conflict == RestoreConflict::Skip {
+            return Ok(RestoreOutcome::Conflict);
+        }
+
+        let renamed = Self::restored_name(path);
+        let occupant = Self::rename_aside(path).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to move {} aside for restoring: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let restored = restore(path).and_then(|_| {
+            Self::mutate("rename", path, || fs::rename(path, &renamed)).map_err(|e| {
+                CleanError::Other(format!(
+                    "Restored {} but failed to rename it to {}: {}",
+                    path.display(),
+                    renamed.display(),
+                    e
+                ))
+            })
+        });
+        // 无论恢复是否成功，都把现有的项目移回原位置
+        if let Err(e) = Self::mutate("rename", &occupant, || fs::rename(&occupant, path)) {
+            return Err(CleanError::Other(format!(
+                "Failed to move {} back from {}: {}",
+                path.display(),
+                occupant.display(),
+                e
+            )));
+        }
+        restored.map(|_| RestoreOutcome::Renamed(renamed))
+    }
+
+    /// 将以 `trashed` 路径移入回收站的项目恢复到原位置 `path`
+    ///
+    /// 先重命名再删除的目录在回收站中记录的是重命名后的路径，`restore` 将其恢复到该路径后
+    /// 再改回原名称；其他情况与 `restore_with` 相同。
+    ///
+    /// # 参数
+    /// * `path` - 项目被删除前的路径
+    /// * `trashed` - 项目移入回收站时的路径
+    /// * `conflict` - 原位置已被占用时的处理方式
+    /// * `restore` - 将项目恢复到 `trashed` 的函数
+    ///
+    /// # 返回
+    /// 恢复的结果
+    pub fn restore_trashed_with<F>(
+        path: &Path,
+        trashed: &Path,
+        conflict: RestoreConflict,
+        restore: F,
+    ) -> Result<RestoreOutcome, CleanError>
+    where
+        F: FnOnce(&Path) -> Result<(), CleanError>,
+    {
+        Self::restore_with(path, conflict, |path| {
+            restore(trashed)?;
+            if trashed == path {
+                return Ok(());
+            }
+            Self::mutate("rename", trashed, || fs::rename(trashed, path)).map_err(|e| {
+                CleanError::Other(format!(
+                    "Restored {} but failed to rename it back to {}: {}",
+                    trashed.display(),
+                    path.display(),
+                    e
+                ))
+            })
+        })
+    }
+
+    /// 恢复为另一个名称时使用的同级路径：`<名称>.restored`，已存在时追加序号
+    fn restored_name(path: &Path) -> PathBuf {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut renamed = path.with_file_name(format!("{}.restored", name));
+        let mut attempt = 1;
+        while renamed.symlink_metadata().is_ok() {
+            renamed = path.with_file_name(format!("{}.restored-{}", name, attempt));
+            attempt += 1;
+        }
+        renamed
+    }
+
+    /// 将目录重命名为同级的临时名称（如 `.target.bc-removing-1234`）
+    ///
+    /// # 参数
+    /// * `dir` - 要重命名的目录
+    ///
+    /// # 返回
+    /// 重命名后的路径
+    pub fn rename_aside(dir: &Path) -> std::io::Result<PathBuf> {
+        let parent = dir.parent().unwrap_or_else(|| Path::new("."));
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let base = format!(".{}.bc-removing-{}", name, std::process::id());
+
+        // 临时名称已存在时（如上次中断留下的副本）追加序号
+        let mut renamed = parent.join(&base);
+        let mut attempt = 1;
+        while renamed.symlink_metadata().is_ok() {
+            renamed = parent.join(format!("{}-{}", base, attempt));
+            attempt += 1;
+        }
+        Self::mutate("rename", dir, || fs::rename(dir, &renamed))?;
+        Ok(renamed)
+    }
+
+    /// 名称是否以 `.` 或空格结尾
+    ///
+    /// Windows 的常规文件 API 会去掉名称末尾的 `.` 和空格，无法访问此类项目（通常由跨平台工具创建）。
+    pub fn has_trailing_dot_or_space(path: &Path) -> bool {
+        path.file_name()
+            .map(|name| name.to_string_lossy())
+            .is_some_and(|name| name.ends_with('.') || name.ends_with(' '))
+    }
+
+    /// 访问项目时使用的路径：Windows 上名称以 `.` 或空格结尾时为扩展长度路径（`\\?\` 前缀），
+    /// 这种路径不经过名称规范化；其他情况为路径本身
+    pub fn access_path(path: &Path) -> PathBuf {
+        #[cfg(windows)]
+        if Self::has_trailing_dot_or_space(path) {
+            if let Ok(extended) = Self::extended_length_path(path) {
+                return extended;
+            }
+        }
+        path.to_path_buf()
+    }
+
+    /// 将路径转换为 Windows 扩展长度路径
+    ///
+    /// 只将父目录转换为绝对路径，最后一个组成部分保持原样
+    /// （`std::path::absolute` 会去掉名称末尾的 `.` 和空格）。
+    #[cfg(windows)]
+    fn extended_length_path(path: &Path) -> std::io::Result<PathBuf> {
+        if path.as_os_str().to_string_lossy().starts_with(r"\\?\") {
+            return Ok(path.to_path_buf());
+        }
+        let name = path.file_name().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name")
+        })?;
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let absolute = std::path::absolute(parent)?.join(name);
+        let absolute = absolute.to_string_lossy();
+        Ok(PathBuf::from(match absolute.strip_prefix(r"\\") {
+            Some(unc) => format!(r"\\?\UNC\{}", unc),
+            None => format!(r"\\?\{}", absolute),
+        }))
+    }
+
+    /// Windows 上将名称以 `.` 或空格结尾的项目通过扩展长度路径重命名为常规名称，
+    /// 之后即可用常规 API（包括回收站）删除；其他情况不重命名
+    ///
+    /// # 参数
+    /// * `path` - 要删除的项目
+    ///
+    /// # 返回
+    /// 删除时应使用的路径
+    fn rename_for_removal(path: &Path) -> Result<PathBuf, CleanError> {
+        if !cfg!(windows) || !Self::has_trailing_dot_or_space(path) {
+            return Ok(path.to_path_buf());
+        }
+        let renamed = Self::rename_aside(&Self::access_path(path)).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to rename {} (name ends with a dot or space): {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(path.with_file_name(renamed.file_name().unwrap_or_default()))
+    }
+
+    /// 将目录移到回收站，启用 `rename_first` 时先将其重命名
+    ///
+    /// # 参数
+    /// * `dir` - 要删除的目录
+    /// * `options` - 删除选项
+    ///
+    /// # 返回
+    /// 目录已从原位置移除时返回目录移入回收站时的路径
+    pub fn trash_directory(dir: &Path, options: &DeleteOptions) -> Result<PathBuf, CleanError> {
+        Self::trash_directory_with(dir, options, |path| {
+            Self::mutate("trash", path, || trash::delete(path))
+        })
+    }
+
+    /// 将目录移到回收站，重命名后的副本由 `finish` 处理
+    ///
+    /// `finish` 失败时把副本改回原名称并返回错误，删除失败的目录不计入释放的空间；
+    /// 连改回原名称也失败时，错误中指出留下的副本位置。
+    ///
+    /// # 参数
+    /// * `dir` - 要删除的目录
+    /// * `options` - 删除选项
+    /// * `finish` - 删除（重命名后的）目录的函数
+    ///
+    /// # 返回
+    /// 目录已从原位置移除时返回交给 `finish` 的路径（启用 `rename_first` 时为重命名后的路径）
+    pub fn trash_directory_with<F>(
+        dir: &Path,
+        options: &DeleteOptions,
+        finish: F,
+    ) -> Result<PathBuf, CleanError>
+    where
+        F: FnOnce(&Path) -> Result<(), trash::Error>,
+    {
+        if !options.rename_first {
+            return finish(dir)
+                .map(|_| dir.to_path_buf())
+                .map_err(|e| CleanError::Other(e.to_string()));
+        }
+
+        let renamed = Self::rename_aside(dir).map_err(|e| {
+            CleanError::Other(format!("Failed to rename {} aside: {}", dir.display(), e))
+        })?;
+        if let Err(e) = finish(&renamed) {
+            let message = match Self::mutate("rename", &renamed, || fs::rename(&renamed, dir)) {
+                Ok(()) => format!("Failed to remove {}: {}", dir.display(), e),
+                Err(rename_error) => format!(
+                    "Renamed {} to {} but failed to remove it: {} (renaming it back failed: {})",
+                    dir.display(),
+                    renamed.display(),
+                    e,
+                    rename_error
+                ),
+            };
+            return Err(CleanError::Other(message));
+        }
+        Ok(renamed)
+    }
+
+    /// 由深到浅逐项永久删除目录（不经过回收站）：文件和链接逐个删除，子目录和目录本身在清空后删除
+    ///
+    /// 逐个移到回收站的条目无法作为一个目录恢复，因此这里总是永久删除。比整体删除慢，
+    /// 但每删除一个条目都会调用 `on_entry`，并在条目之间检查取消标志。
+    /// 中途取消或失败时已删除的条目不会恢复，目录中只留下尚未处理的部分。
+    ///
+    /// # 参数
+    /// * `dir` - 要删除的目录
+    /// * `options` - 删除选项（使用其中的取消标志）
+    /// * `on_entry` - 每删除一个条目后调用，参数为已删除的条目数量（包括该条目）和该条目的路径
+    ///
+    /// # 返回
+    /// 整个目录都已删除时返回 `Ok(())`，否则返回说明已删除多少条目的错误
+    pub fn remove_directory_piecewise<F>(
+        dir: &Path,
+        options: &DeleteOptions,
+        mut on_entry: F,
+    ) -> Result<(), CleanError>
+    where
+        F: FnMut(usize, &Path),
+    {
+        use walkdir::WalkDir;
+        let stopped = |removed: usize, reason: String| {
+            CleanError::Other(format!(
+                "Stopped after removing {} entries from {}: {}",
+                removed,
+                dir.display(),
+                reason
+            ))
+        };
+
+        for (removed, entry) in WalkDir::new(dir)
+            .contents_first(true)
+            .into_iter()
+            .enumerate()
+        {
+            if Self::is_cancelled(options) {
+                return Err(stopped(removed, "cancelled".to_string()));
+            }
+            let entry = entry.map_err(|e| stopped(removed, e.to_string()))?;
+            // 目录在其内容之后出现，此时已经为空，直接删除即可
+            let result = if entry.file_type().is_dir() {
+                Self::mutate("delete", entry.path(), || fs::remove_dir(entry.path()))
+            } else {
+                Self::mutate("delete", entry.path(), || fs::remove_file(entry.path()))
+            };
+            result.map_err(|e| stopped(removed, e.to_string()))?;
+            on_entry(removed + 1, entry.path());
+        }
+        Ok(())
+    }
+
+    /// 检查（已解析符号链接的）路径是否位于其他用户的主目录中
+    ///
+    /// `/home` 或 `/Users` 下的每个子目录都视为一个用户的主目录，当前用户的主目录除外。
+    /// 不知道当前用户的主目录时不检查。
+    ///
+    /// # 参数
+    /// * `resolved` - 已解析符号链接的绝对路径
+    /// * `current_home` - 当前用户的主目录（已解析符号链接）
+    ///
+    /// # 返回
+    /// 如果路径位于其他用户的主目录中返回 `true`
+    pub fn is_other_users_home(resolved: &Path, current_home: Option<&Path>) -> bool {
+        let Some(current_home) = current_home else {
+            return false;
+        };
+        if resolved.starts_with(current_home) {
+            return false;
+        }
+        HOME_PARENTS.iter().any(|parent| {
+            resolved
+                .strip_prefix(parent)
+                .is_ok_and(|rest| rest.components().next().is_some())
+        })
+    }
+
+    /// 检查路径是否可以作为扫描的根目录
+    ///
+    /// 在扫描开始前拒绝根目录、用户主目录和系统目录，避免误输入（如 `bc ~ --clean '*'`）
+    /// 在漫长的扫描之后才被逐项的安全检查拦下。符号链接会先被解析。
+    ///
+    /// # 参数
+    /// * `path` - 扫描的根目录
+    /// * `home` - 用户主目录（为 `None` 时不检查）
+    ///
+    /// # 返回
+    /// 可以扫描时返回 `Ok(())`，否则返回说明原因的错误
+    pub fn check_scan_root(path: &Path, home: Option<&Path>) -> Result<(), CleanError> {
+        let normalized = Self::normalize_path(path)?;
+        let resolved = fs::canonicalize(path).unwrap_or_else(|_| normalized.clone());
+        let refuse = |reason: &str| {
+            Err(CleanError::Other(format!(
+                "Refusing to scan {}: it is {} (use --force to scan it anyway)",
+                path.display(),
+                reason
+            )))
+        };
+
+        for candidate in [&normalized, &resolved] {
+            if candidate == Path::new("/") {
+                return refuse("the filesystem root");
+            }
+            if SYSTEM_DIRS.iter().any(|dir| candidate.starts_with(dir)) {
+                return refuse("a system directory");
+            }
+        }
+
+        if let Some(home) = home {
+            let home_resolved = fs::canonicalize(home).unwrap_or_else(|_| home.to_path_buf());
+            if normalized == home || resolved == home_resolved {
+                return refuse("the home directory");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 执行删除操作（不带进度回调）
+    pub fn execute_deletion(plan: &DeletePlan, dry_run: bool) -> DeleteResult {
+        let _dry_run = dry_run.then(DryRunScope::enter);
+        Self::execute_deletion_with_options(plan, dry_run, &DeleteOptions::default())
+    }
+
+    /// 使用指定的删除选项执行删除操作（不带进度回调）
+    pub fn execute_deletion_with_options(
+        plan: &DeletePlan,
+        dry_run: bool,
+        options: &DeleteOptions,
+    ) -> DeleteResult {
+        let _dry_run = dry_run.then(DryRunScope::enter);
+        Self::execute_deletion_with_progress(
+            plan,
+            dry_run,
+            options,
+            None::<Box<dyn FnMut(usize, usize, &Path)>>,
+        )
+    }
+
+    /// 按删除选项打开删除清单
+    ///
+    /// 无法打开时记录警告并继续删除，清单只用于中断后恢复，不影响删除本身。
+    pub fn open_manifest(options: &DeleteOptions) -> Option<DeleteManifest> {
+        let path = options.manifest.as_deref()?;
+        DeleteManifest::open(path)
+            .map_err(|e| log::warn!("{}", e))
+            .ok()
+    }
+
+    /// 检查删除是否已被取消（没有取消标志时总是返回 `false`）
+    pub fn is_cancelled(options: &DeleteOptions) -> bool {
+        options
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.is_cancelled())
+    }
+
+    /// 在删除清单中记录一个已完成删除的项目（没有清单时不做任何事）
+    pub fn commit_to_manifest(manifest: Option<&mut DeleteManifest>, path: &Path, is_dir: bool) {
+        if let Some(manifest) = manifest {
+            if let Err(e) = manifest.commit(path, is_dir) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+
+    /// 交互式执行删除操作，逐个确认每个文件/目录
+    ///
+    /// 用户在询问时选择撤销，会从回收站恢复最近删除的一个项目，然后重新询问当前项目。
+    ///
+    /// # 参数
+    /// * `plan` - 删除计划
+    /// * `options` - 删除选项
+    /// * `confirm_above` - 只询问大小不小于该值的项目，更小的项目直接删除（为 `None` 时每项都询问）
+    /// * `sizes` - 每个项目的大小，指定时按大小从大到小依次询问（为 `None` 时按计划顺序）
+    /// * `trash` - 移到回收站和从回收站恢复的操作
+    /// * `confirmer` - 询问是否删除单个项目，并接收删除过程中的事件
+    ///
+    /// # 返回
+    /// 删除结果；用户取消时返回 `CleanError::Cancelled`
+    pub fn execute_deletion_interactive(
+        plan: &DeletePlan,
+        options: &DeleteOptions,
+        confirm_above: Option<u64>,
+        sizes: Option<&[(PathBuf, u64)]>,
+        trash: &dyn TrashOps,
+        confirmer: &mut dyn Confirmer,
+    ) -> Result<DeleteResult, CleanError> {
+        let mut deleted_files = Vec::new();
+        let mut deleted_dirs: Vec<PathBuf> = Vec::new();
+        let mut failed_files = Vec::new();
+        let mut failed_dirs = Vec::new();
+        let mut total_size = 0u64;
+        let mut total_physical_size = 0u64;
+        let mut inodes_freed = 0u64;
+        // 已删除项目按扩展名的大小，撤销时随项目一起移除
+        let mut deleted_extensions: HashMap<PathBuf, HashMap<String, u64>> = HashMap::new();
+        let mut safety_refusals = Vec::new();
+        let mut confirm_all = false;
+        let mut manifest = Self::open_manifest(options);
+        // 最近移到回收站的项目：(路径, 移入回收站时的路径, 是否为目录, (大小, 物理大小, inode 数量))，
+        // 只能撤销这一个
+        type LastTrashed = (PathBuf, PathBuf, bool, (u64, u64, u64));
+        let mut last_trashed: Option<LastTrashed> = None;
+
+        let items = match sizes {
+            Some(sizes) => plan.items_largest_first(sizes),
+            None => plan.items(),
+        };
+
+        // 逐个删除，超出时间预算后跳过剩余的项目
+        let cwd = Self::working_dir();
+        for (path, is_dir) in &items {
+            if Self::is_cancelled(options) {
+                break;
+            }
+            let is_dir = *is_dir;
+            let failed = if is_dir {
+                &mut failed_dirs
+            } else {
+                &mut failed_files
+            };
+
+            // 先处理上级目录时，其中的项目已经随之删除
+            if let Some(parent) = deleted_dirs.iter().find(|dir| path.starts_with(dir)) {
+                confirmer.notify(&DeleteEvent::AlreadyDeleted { path, parent });
+                continue;
+            }
+
+            let checked = if is_dir {
+                Self::check_safety_with_cwd(path, cwd.as_deref())
+                    .map_err(CleanError::from)
+                    .and_then(|_| Self::check_generated(path, options))
+            } else {
+                Self::check_safety_with_cwd(path, cwd.as_deref()).map_err(CleanError::from)
+            };
+            if let Err(e) = checked {
+                if let CleanError::Unsafe(violation) = &e {
+                    safety_refusals.push((path.clone(), violation.clone()));
+                }
+                failed.push((path.clone(), e.to_string()));
+                confirmer.notify(&DeleteEvent::SafetyCheckFailed {
+                    path,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+
+            // 计算项目大小（目录需要遍历其内容）
+            let ((size, physical_size, entries), extensions) =
+                Self::item_usage(path, is_dir, options, Self::calculate_dir_usage);
+            if let Err(e) = Self::check_trash_space(path, size, options) {
+                confirmer.notify(&DeleteEvent::Skipped {
+                    path,
+                    reason: Some(e.to_string()),
+                });
+                failed.push((path.clone(), e.to_string()));
+                continue;
+            }
+
+            if !confirm_all && confirm_above.is_none_or(|threshold| size >= threshold) {
+                let item = DeleteItem {
+                    path: path.clone(),
+                    is_dir,
+                    size,
+                };
+                let answer = loop {
+                    match confirmer.confirm(&item)? {
+                        Confirmation::Undo => match last_trashed.take() {
+                            Some((
+                                last,
+                                trashed,
+                                last_is_dir,
+                                (last_size, last_physical_size, last_entries),
+                            )) => match trash.restore(&last, &trashed, options.restore_conflict) {
+                                Ok(RestoreOutcome::Conflict) => {
+                                    // 项目仍在回收站中，腾出原位置后可以再次撤销
+                                    confirmer.notify(&DeleteEvent::RestoreConflict(&last));
+                                    last_trashed = Some((
+                                        last,
+                                        trashed,
+                                        last_is_dir,
+                                        (last_size, last_physical_size, last_entries),
+                                    ));
+                                }
+                                Ok(outcome) => {
+                                    if last_is_dir {
+                                        deleted_dirs.retain(|dir| *dir != last);
+                                    } else {
+                                        deleted_files.retain(|file| *file != last);
+                                    }
+                                    total_size -= last_size;
+                                    total_physical_size -= last_physical_size;
+                                    inodes_freed -= last_entries;
+                                    deleted_extensions.remove(&last);
+                                    if let Some(manifest) = manifest.as_mut() {
+                                        if let Err(e) = manifest.revert(&last, last_is_dir) {
+                                            confirmer.notify(&DeleteEvent::Warning(e.to_string()));
+                                        }
+                                    }
+                                    match &outcome {
+                                        RestoreOutcome::Renamed(renamed) => {
+                                            confirmer.notify(&DeleteEvent::RestoredAs {
+                                                path: &last,
+                                                renamed,
+                                            })
+                                        }
+                                        _ => confirmer.notify(&DeleteEvent::Restored(&last)),
+                                    }
+                                }
+                                Err(error) => {
+                                    confirmer
+                                        .notify(&DeleteEvent::RestoreFailed { path: &last, error });
+                                }
+                            },
+                            None => confirmer.notify(&DeleteEvent::NothingToUndo),
+                        },
+                        answer => break answer,
+                    }
+                };
+                match answer {
+                    Confirmation::Yes | Confirmation::Undo => {
+                        // 用户确认删除（撤销已在上面处理，不会到达这里）
+                    }
+                    Confirmation::No => {
+                        confirmer.notify(&DeleteEvent::Skipped { path, reason: None });
+                        continue;
+                    }
+                    Confirmation::All => {
+                        confirm_all = true;
+                        confirmer.notify(&DeleteEvent::AllConfirmed);
+                    }
+                    Confirmation::Quit => {
+                        confirmer.notify(&DeleteEvent::Cancelled);
+                        return Err(CleanError::Cancelled);
+                    }
+                }
+            }
+
+            // 确认之后才检查不可变属性，用户跳过的项目不会被清除属性
+            if let Err(e) = Self::check_immutable(path, options) {
+                confirmer.notify(&DeleteEvent::Skipped {
+                    path,
+                    reason: Some(e.to_string()),
+                });
+                failed.push((path.clone(), e.to_string()));
+                continue;
+            }
+
+            // 永久删除的项目无法撤销
+            let permanent = plan.is_permanent(path);
+            let removed = if permanent {
+                Self::remove_permanently(path, is_dir, options)
+                    .map(|_| None)
+                    .map_err(|e| e.to_string())
+            } else {
+                trash.trash(path, is_dir, options).map(Some)
+            };
+            match removed {
+                Ok(trashed) => {
+                    total_size += size;
+                    total_physical_size += physical_size;
+                    inodes_freed += entries;
+                    deleted_extensions.insert(path.clone(), extensions);
+                    last_trashed = trashed.map(|trashed| {
+                        (
+                            path.clone(),
+                            trashed,
+                            is_dir,
+                            (size, physical_size, entries),
+                        )
+                    });
+                    if is_dir {
+                        deleted_dirs.push(path.clone());
+                    } else {
+                        deleted_files.push(path.clone());
+                    }
+                    Self::commit_to_manifest(manifest.as_mut(), path, is_dir);
+                    confirmer.notify(&DeleteEvent::Deleted(path));
+                }
+                Err(error) => {
+                    failed.push((path.clone(), error.clone()));
+                    confirmer.notify(&DeleteEvent::Failed { path, error });
+                }
+            }
+        }
+
+        let mut extension_sizes = HashMap::new();
+        for extensions in deleted_extensions.values() {
+            Self::merge_extension_sizes(&mut extension_sizes, extensions);
+        }
+        Ok(DeleteResult {
+            deleted_files,
+            deleted_dirs,
+            failed_files,
+            failed_dirs,
+            total_size,
+            total_physical_size: options.physical_size.then_some(total_physical_size),
+            inodes_freed: options.count_inodes.then_some(inodes_freed),
+            extension_sizes: options.extension_breakdown.then_some(extension_sizes),
+            safety_refusals,
+        })
+    }
+
+    /// 执行删除操作（带进度回调）
+    ///
+    /// # 参数
+    /// * `plan` - 删除计划
+    /// * `dry_run` - 是否为预览模式（不实际删除）
+    /// * `options` - 删除选项
+    /// * `progress_callback` - 可选的进度回调函数，在处理每个项目之前调用，
+    ///   接收 (已处理的项目数, 项目总数, 即将处理的路径)；逐项删除目录时每删除其中一个条目
+    ///   也会调用一次，项目数和总数不变，路径为该条目的路径。预览模式下在计算每个项目的大小之前调用
+    ///
+    /// # 返回
+    /// 删除结果，包含成功和失败的统计信息
+    pub fn execute_deletion_with_progress(
+        plan: &DeletePlan,
+        dry_run: bool,
+        options: &DeleteOptions,
+        mut progress_callback: Option<ProgressCallback<'_>>,
+    ) -> DeleteResult {
+        // 在任何分支之前进入预览作用域，预览期间调用的所有函数（包括回调）都不能修改文件系统
+        let _dry_run = dry_run.then(DryRunScope::enter);
+        let mut deleted_files = Vec::new();
+        let mut deleted_dirs = Vec::new();
+        let mut failed_files = Vec::new();
+        let mut failed_dirs = Vec::new();
+        let mut total_size = 0u64;
+        let mut total_physical_size = 0u64;
+        let mut inodes_freed = 0u64;
+        let mut extension_sizes = HashMap::new();
+        let mut safety_refusals = Vec::new();
+
+        if dry_run {
+            // 只有删除计划时没有搜索阶段的大小，需要逐项计算；
+            // 有 SearchResult 时使用 execute_deletion_from_search_with_options 避免重复遍历
+            return Self::preview_deletion(
+                plan,
+                options,
+                None,
+                Self::calculate_dir_usage,
+                progress_callback,
+            );
+        }
+
+        let mut throttle = options.throttle.map(Throttle::new);
+        let mut manifest = Self::open_manifest(options);
+
+        let items = if options.locality_order {
+            plan.items_by_locality()
+        } else {
+            plan.items()
+        };
+        let total = items.len();
+
+        // 取消后跳过剩余的项目，已删除的项目照常计入结果
+        let cwd = Self::working_dir();
+        for (index, (path, is_dir)) in items.into_iter().enumerate() {
+            if Self::is_cancelled(options) {
+                break;
+            }
+            if let Some(callback) = progress_callback.as_mut() {
+                callback(index, total, &path);
+            }
+            let (deleted, failed) = if is_dir {
+                (&mut deleted_dirs, &mut failed_dirs)
+            } else {
+                (&mut deleted_files, &mut failed_files)
+            };
+
+            let checked = if is_dir {
+                Self::check_safety_with_cwd(&path, cwd.as_deref())
+                    .map_err(CleanError::from)
+                    .and_then(|_| Self::check_generated(&path, options))
+            } else {
+                Self::check_safety_with_cwd(&path, cwd.as_deref()).map_err(CleanError::from)
+            };
+            if let Err(e) = checked {
+                if let CleanError::Unsafe(violation) = &e {
+                    safety_refusals.push((path.clone(), violation.clone()));
+                }
+                failed.push((path, e.to_string()));
+                continue;
+            }
+
+            // 在删除前获取文件大小或计算目录大小
+            let access_path = Self::access_path(&path);
+            let ((size, physical_size, entries), extensions) =
+                Self::item_usage(&access_path, is_dir, options, Self::calculate_dir_usage);
+            // 逐项删除的目录不经过回收站
+            let piecewise = is_dir && options.piecewise_delete;
+            let permanent = plan.is_permanent(&path) || piecewise;
+            let checked = if permanent {
+                Self::check_immutable(&path, options)
+            } else {
+                Self::check_trash_space(&path, size, options)
+                    .and_then(|_| Self::check_immutable(&path, options))
+            };
+            if let Err(e) = checked {
+                failed.push((path, e.to_string()));
+                continue;
+            }
+
+            let target = match Self::rename_for_removal(&path) {
+                Ok(target) => target,
+                Err(e) => {
+                    failed.push((path, e.to_string()));
+                    continue;
+                }
+            };
+
+            // 除非模式指定永久删除，否则将文件和目录移到回收站而不是直接删除
+            let trashed = if piecewise {
+                // 逐项删除时每删除一个条目都刷新进度，项目数和总数仍按整个计划计算
+                Self::remove_directory_piecewise(&target, options, |_, entry| {
+                    if let Some(callback) = progress_callback.as_mut() {
+                        callback(index, total, entry);
+                    }
+                })
+                .map_err(|e| e.to_string())
+            } else if permanent {
+                Self::remove_permanently(&target, is_dir, options).map_err(|e| e.to_string())
+            } else if is_dir {
+                Self::trash_directory(&target, options)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            } else {
+                Self::trash_file(&target, options).map_err(|e| e.to_string())
+            };
+            if trashed.is_err() && target != path {
+                // 删除失败时恢复原名称，不留下改名后的副本
+                if let Err(e) =
+                    Self::mutate("rename", &target, || fs::rename(&target, &access_path))
+                {
+                    log::warn!(
+                        "Failed to rename {} back to {}: {}",
+                        target.display(),
+                        path.display(),
+                        e
+                    );
+                }
+            }
+            match trashed {
+                Ok(_) => {
+                    total_size += size;
+                    total_physical_size += physical_size;
+                    inodes_freed += entries;
+                    Self::merge_extension_sizes(&mut extension_sizes, &extensions);
+                    Self::commit_to_manifest(manifest.as_mut(), &path, is_dir);
+                    deleted.push(path);
+                }
+                Err(e) => {
+                    failed.push((path, e));
+                }
+            }
+            if let Some(ref mut throttle) = throttle {
+                throttle.tick();
+            }
+        }
+
+        DeleteResult {
+            deleted_files,
+            deleted_dirs,
+            failed_files,
+            failed_dirs,
+            total_size,
+            total_physical_size: options.physical_size.then_some(total_physical_size),
+            inodes_freed: options.count_inodes.then_some(inodes_freed),
+            extension_sizes: options.extension_breakdown.then_some(extension_sizes),
+            safety_refusals,
+        }
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use std::io::Write;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_execute_deletion_progress_callback() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("target");
+        fs::create_dir(&dir).unwrap();
+        let log = temp_dir.path().join("debug.log");
+        fs::write(&log, "log").unwrap();
+        let plan = DeletePlan {
+            files: vec![log.clone()],
+            dirs: vec![dir.clone()],
+            ..Default::default()
+        };
+
+        // 每个项目处理之前报告一次：(已处理数, 总数, 路径)
+        let mut calls = Vec::new();
+        DeleteEngine::execute_deletion_with_progress(
+            &plan,
+            false,
+            &DeleteOptions::default(),
+            Some(Box::new(|done, total, path: &Path| {
+                calls.push((done, total, path.to_path_buf()))
+            })),
+        );
+        assert_eq!(calls, vec![(0, 2, log), (1, 2, dir)]);
+    }
+
+    #[test]
+    fn test_inodes_freed() {
+        let temp_dir = TempDir::new().unwrap();
+        // node_modules 自身 + 2 个包目录 + 5 个文件 = 8 个 inode
+        let node_modules = temp_dir.path().join("node_modules");
+        fs::create_dir_all(node_modules.join("a")).unwrap();
+        fs::create_dir_all(node_modules.join("b")).unwrap();
+        for (dir, count) in [("a", 3), ("b", 2)] {
+            for i in 0..count {
+                fs::write(node_modules.join(dir).join(format!("{}.js", i)), "x").unwrap();
+            }
+        }
+        let log = temp_dir.path().join("debug.log");
+        fs::write(&log, "log").unwrap();
+
+        let plan = DeletePlan {
+            files: vec![log],
+            dirs: vec![node_modules],
+            ..Default::default()
+        };
+        let options = DeleteOptions {
+            count_inodes: true,
+            ..Default::default()
+        };
+        let result = DeleteEngine::execute_deletion_with_options(&plan, true, &options);
+        // 目录中的 8 个加上 debug.log
+        assert_eq!(result.inodes_freed, Some(9));
+
+        // 未启用时不统计
+        let result = DeleteEngine::execute_deletion(&plan, true);
+        assert_eq!(result.inodes_freed, None);
+    }
+
+    #[test]
+    fn test_extension_sizes() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(target.join("deps")).unwrap();
+        fs::write(target.join("deps").join("libfoo.rlib"), vec![0u8; 300]).unwrap();
+        fs::write(target.join("main.o"), vec![0u8; 150]).unwrap();
+        let log = temp_dir.path().join("debug.log");
+        fs::write(&log, vec![0u8; 10]).unwrap();
+        let plan = DeletePlan {
+            files: vec![log.clone()],
+            dirs: vec![target.clone()],
+            ..Default::default()
+        };
+        let options = DeleteOptions {
+            extension_breakdown: true,
+            ..Default::default()
+        };
+        let expected = HashMap::from([
+            ("rlib".to_string(), 300),
+            ("o".to_string(), 150),
+            ("log".to_string(), 10),
+        ]);
+
+        // 预览和实际删除都在计算大小的同一次遍历中按扩展名统计
+        let result = DeleteEngine::execute_deletion_with_options(&plan, true, &options);
+        assert_eq!(result.extension_sizes.as_ref(), Some(&expected));
+        let result = DeleteEngine::execute_deletion_with_options(&plan, false, &options);
+        assert_eq!(result.deleted_dirs, vec![target.clone()]);
+        assert_eq!(result.extension_sizes.as_ref(), Some(&expected));
+        assert!(!target.exists() && !log.exists());
+
+        // 未启用时不统计
+        let result = DeleteEngine::execute_deletion(&plan, true);
+        assert_eq!(result.extension_sizes, None);
+    }
+
+    #[test]
+    fn test_items_by_locality() {
+        let plan = DeletePlan {
+            files: vec![
+                PathBuf::from("/b/app/debug.log"),
+                PathBuf::from("/a/web/npm-debug.log"),
+                PathBuf::from("/b/app/target/build.log"),
+                PathBuf::from("/a/web/dist/bundle.map"),
+            ],
+            dirs: vec![
+                PathBuf::from("/b/app/target"),
+                PathBuf::from("/a/web/dist"),
+                PathBuf::from("/a/web/node_modules"),
+                PathBuf::from("/b/app/target/debug"),
+            ],
+            ..Default::default()
+        };
+
+        let ordered: Vec<PathBuf> = plan
+            .items_by_locality()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(ordered.len(), 8);
+
+        // 共享前缀的项目连续排列：/a 下的项目全部处理完再处理 /b
+        let under = |prefix: &str| {
+            let positions: Vec<usize> = ordered
+                .iter()
+                .enumerate()
+                .filter(|(_, path)| path.starts_with(prefix))
+                .map(|(i, _)| i)
+                .collect();
+            positions.windows(2).all(|pair| pair[1] == pair[0] + 1)
+        };
+        assert!(under("/a/web"));
+        assert!(under("/b/app"));
+        assert!(under("/b/app/target"));
+        assert!(under("/a/web/dist"));
+
+        // 深度安全：每个目录都排在其中所有项目之后
+        for (i, path) in ordered.iter().enumerate() {
+            assert!(ordered[i + 1..]
+                .iter()
+                .all(|later| !later.starts_with(path) || later == path));
+        }
+        assert_eq!(
+            ordered,
+            vec![
+                PathBuf::from("/a/web/dist/bundle.map"),
+                PathBuf::from("/a/web/dist"),
+                PathBuf::from("/a/web/node_modules"),
+                PathBuf::from("/a/web/npm-debug.log"),
+                PathBuf::from("/b/app/debug.log"),
+                PathBuf::from("/b/app/target/build.log"),
+                PathBuf::from("/b/app/target/debug"),
+                PathBuf::from("/b/app/target"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_items_largest_first() {
+        let plan = DeletePlan {
+            files: vec![
+                PathBuf::from("/p/a.log"),
+                PathBuf::from("/p/target/debug/big.log"),
+            ],
+            dirs: vec![
+                PathBuf::from("/p/target/debug"),
+                PathBuf::from("/p/node_modules"),
+                PathBuf::from("/p/target"),
+            ],
+            ..Default::default()
+        };
+        // target 的大小被低估（如大小为估算值），其中的 debug 和 big.log 看起来更大
+        let sizes = vec![
+            (PathBuf::from("/p/a.log"), 10),
+            (PathBuf::from("/p/target/debug/big.log"), 900),
+            (PathBuf::from("/p/target/debug"), 1000),
+            (PathBuf::from("/p/node_modules"), 500),
+            (PathBuf::from("/p/target"), 100),
+        ];
+
+        let ordered: Vec<PathBuf> = plan
+            .items_largest_first(&sizes)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        // 最大的项目优先，但上级目录总是排在其中的项目之前
+        assert_eq!(
+            ordered,
+            vec![
+                PathBuf::from("/p/target"),
+                PathBuf::from("/p/target/debug"),
+                PathBuf::from("/p/target/debug/big.log"),
+                PathBuf::from("/p/node_modules"),
+                PathBuf::from("/p/a.log"),
+            ]
+        );
+
+        // 默认顺序：先文件后目录，保持计划中的顺序
+        let items = plan.items();
+        assert_eq!(items.len(), 5);
+        assert_eq!(items[0], (PathBuf::from("/p/a.log"), false));
+        assert_eq!(items[2], (PathBuf::from("/p/target/debug"), true));
+    }
+
     #[test]
     fn test_create_delete_plan() {
         let search_result = SearchResult {
@@ -344,11 +2562,13 @@ mod tests {
                 PathBuf::from("/a/b/c/d"),
                 PathBuf::from("/a/b"),
                 PathBuf::from("/a/b/c"),
-            ],
-            files: vec![PathBuf::from("/a/file1.txt"), PathBuf::from("/a/file2.txt")],
+            ]
+            .into(),
+            files: vec![PathBuf::from("/a/file1.txt"), PathBuf::from("/a/file2.txt")].into(),
             total_size: 1000,
             total_dirs_scanned: 5,
             total_files_scanned: 10,
+            ..Default::default()
         };
 
         let plan = DeleteEngine::create_delete_plan(&search_result);
@@ -362,6 +2582,34 @@ mod tests {
         assert_eq!(plan.dirs[0], PathBuf::from("/a/b/c/d"));
     }
 
+    #[test]
+    fn test_create_delete_plan_stable_order() {
+        let search_result = SearchResult {
+            folders: vec![
+                PathBuf::from("/a/c/target"),
+                PathBuf::from("/a/a/target"),
+                PathBuf::from("/b"),
+                PathBuf::from("/a/b/target"),
+                PathBuf::from("/a"),
+            ]
+            .into(),
+            ..Default::default()
+        };
+
+        let plan = DeleteEngine::create_delete_plan(&search_result);
+
+        assert_eq!(
+            plan.dirs,
+            vec![
+                PathBuf::from("/a/a/target"),
+                PathBuf::from("/a/b/target"),
+                PathBuf::from("/a/c/target"),
+                PathBuf::from("/a"),
+                PathBuf::from("/b"),
+            ]
+        );
+    }
+
     #[test]
     fn test_check_safety() {
         let temp_dir = TempDir::new().unwrap();
@@ -388,12 +2636,54 @@ mod tests {
             }
         }
 
-        // 测试不存在的路径（normalize_path 不会失败，因为它不检查路径是否存在）
-        // 但安全检查仍然会通过，因为路径规范化不会失败
+        // 测试不存在的路径（temp 目录位于系统目录下时优先报告系统目录）
         let nonexistent = temp_dir.path().join("nonexistent");
-        // normalize_path 不会因为路径不存在而失败，所以这个测试可能通过
-        // 如果路径规范化后指向系统目录，才会失败
-        let _ = DeleteEngine::check_safety(&nonexistent);
+        if result.is_ok() {
+            assert_eq!(
+                DeleteEngine::check_safety(&nonexistent),
+                Err(SafetyViolation::NotFound(nonexistent.clone()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_safety_violation() {
+        // 系统目录和根目录报告 SystemDirectory，即使路径不存在
+        #[cfg(unix)]
+        {
+            let err = DeleteEngine::check_safety(Path::new("/usr/bin")).unwrap_err();
+            assert_eq!(
+                err,
+                SafetyViolation::SystemDirectory(PathBuf::from("/usr/bin"))
+            );
+            assert_eq!(err.code(), "system-directory");
+            assert_eq!(err.to_string(), "Cannot delete system directory: /usr/bin");
+            assert_eq!(
+                DeleteEngine::check_safety(Path::new("/")),
+                Err(SafetyViolation::SystemDirectory(PathBuf::from("/")))
+            );
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("target");
+        let err = DeleteEngine::check_safety(&missing).unwrap_err();
+        if !matches!(err, SafetyViolation::SystemDirectory(_)) {
+            assert_eq!(err, SafetyViolation::NotFound(missing.clone()));
+            assert_eq!(err.code(), "not-found");
+
+            // 拒绝的原因保留在删除结果中，同时计入删除失败
+            let plan = DeletePlan {
+                files: vec![],
+                dirs: vec![missing.clone()],
+                ..Default::default()
+            };
+            let result = DeleteEngine::execute_deletion(&plan, false);
+            assert_eq!(result.failed_dirs.len(), 1);
+            assert_eq!(
+                result.safety_refusals,
+                vec![(missing.clone(), SafetyViolation::NotFound(missing))]
+            );
+        }
     }
 
     #[test]
@@ -418,6 +2708,7 @@ mod tests {
         let plan = DeletePlan {
             files: vec![test_file.clone()],
             dirs: vec![test_dir.clone()],
+            ..Default::default()
         };
 
         let result = DeleteEngine::execute_deletion(&plan, true);
@@ -443,6 +2734,271 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_execute_deletion_interactive() {
+        /// 按脚本依次回答，并记录被询问的项目和收到的事件
+        struct ScriptedConfirmer {
+            answers: std::vec::IntoIter<Confirmation>,
+            asked: Vec<PathBuf>,
+            events: Vec<String>,
+        }
+
+        impl Confirmer for ScriptedConfirmer {
+            fn confirm(&mut self, item: &DeleteItem) -> Result<Confirmation, CleanError> {
+                self.asked.push(item.path.clone());
+                Ok(self.answers.next().expect("asked more than scripted"))
+            }
+
+            fn notify(&mut self, event: &DeleteEvent) {
+                self.events.push(format!("{:?}", event));
+            }
+        }
+
+        /// 只记录操作、不真正移动文件的回收站
+        #[derive(Default)]
+        struct RecordingTrash {
+            trashed: std::cell::RefCell<Vec<PathBuf>>,
+        }
+
+        impl TrashOps for RecordingTrash {
+            fn trash(
+                &self,
+                path: &Path,
+                _is_dir: bool,
+                _: &DeleteOptions,
+            ) -> Result<PathBuf, String> {
+                self.trashed.borrow_mut().push(path.to_path_buf());
+                Ok(path.to_path_buf())
+            }
+
+            fn restore(
+                &self,
+                _path: &Path,
+                _trashed: &Path,
+                _conflict: RestoreConflict,
+            ) -> Result<RestoreOutcome, String> {
+                Ok(RestoreOutcome::Restored)
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<PathBuf> = ["a.log", "b.log", "c.log", "d.log"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for file in &files {
+            fs::write(file, vec![0u8; 10]).unwrap();
+        }
+        let plan = DeletePlan {
+            files: files.clone(),
+            dirs: vec![],
+            ..Default::default()
+        };
+        let scripted = |answers: Vec<Confirmation>| ScriptedConfirmer {
+            answers: answers.into_iter(),
+            asked: Vec::new(),
+            events: Vec::new(),
+        };
+
+        // a：删除；b：跳过；c：全部删除，d 不再询问
+        let trash = RecordingTrash::default();
+        let mut confirmer = scripted(vec![Confirmation::Yes, Confirmation::No, Confirmation::All]);
+        let result = DeleteEngine::execute_deletion_interactive(
+            &plan,
+            &DeleteOptions::default(),
+            None,
+            None,
+            &trash,
+            &mut confirmer,
+        )
+        .unwrap();
+        assert_eq!(confirmer.asked, files[..3].to_vec());
+        assert_eq!(
+            result.deleted_files,
+            vec![files[0].clone(), files[2].clone(), files[3].clone()]
+        );
+        assert!(result.failed_files.is_empty());
+        assert_eq!(result.total_size, 30);
+        assert_eq!(*trash.trashed.borrow(), result.deleted_files);
+        assert!(confirmer.events.contains(&"AllConfirmed".to_string()));
+
+        // a：删除；b：取消，之后不再删除任何项目
+        let trash = RecordingTrash::default();
+        let mut confirmer = scripted(vec![Confirmation::Yes, Confirmation::Quit]);
+        let result = DeleteEngine::execute_deletion_interactive(
+            &plan,
+            &DeleteOptions::default(),
+            None,
+            None,
+            &trash,
+            &mut confirmer,
+        );
+        assert!(matches!(result, Err(CleanError::Cancelled)));
+        assert_eq!(confirmer.asked, files[..2].to_vec());
+        assert_eq!(*trash.trashed.borrow(), vec![files[0].clone()]);
+        assert_eq!(confirmer.events.last().unwrap(), "Cancelled");
+    }
+
+    #[test]
+    fn test_dry_run_uses_search_total() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("target");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("out.o"), vec![0u8; 100]).unwrap();
+        let file = temp_dir.path().join("app.log");
+        fs::write(&file, vec![0u8; 20]).unwrap();
+
+        let search_result = SearchResult {
+            folders: vec![dir.clone()].into(),
+            files: vec![file.clone()].into(),
+            total_size: 120,
+            ..Default::default()
+        };
+        let plan = DeleteEngine::create_delete_plan(&search_result);
+
+        // 有搜索阶段的大小时不再遍历目录
+        let panicking = |dir: &Path, _: Option<&mut HashMap<String, u64>>| -> (u64, u64, u64) {
+            panic!("{} should not be walked again", dir.display())
+        };
+        let result = DeleteEngine::preview_deletion(
+            &plan,
+            &DeleteOptions::default(),
+            Some((search_result.total_size, None)),
+            panicking,
+            None,
+        );
+        assert_eq!(result.total_size, search_result.total_size);
+        assert_eq!(result.deleted_dirs, vec![dir.clone()]);
+        assert_eq!(result.deleted_files, vec![file.clone()]);
+        assert!(dir.exists() && file.exists());
+
+        let result = DeleteEngine::execute_deletion_from_search_with_options(
+            &search_result,
+            true,
+            &DeleteOptions::default(),
+        );
+        assert_eq!(result.total_size, 120);
+
+        // 只有删除计划时逐项计算，结果相同
+        let result = DeleteEngine::execute_deletion(&plan, true);
+        assert_eq!(result.total_size, 120);
+    }
+
+    #[test]
+    fn test_dry_run_leaves_tree_intact() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for (path, content) in [
+            ("app/target/debug/app", "binary"),
+            ("app/target/debug/deps/libfoo.rlib", "rlib"),
+            ("app/node_modules/pkg/index.js", "js"),
+            ("app/build. /out.o", "trailing space"),
+            ("app/src/main.rs", "fn main() {}"),
+            ("app/debug.log", "log"),
+            ("app/core.dump", "dump"),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        // 记录整个目录树的路径、大小和修改时间
+        let snapshot = || {
+            let mut entries: Vec<_> = walkdir::WalkDir::new(root)
+                .into_iter()
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    let metadata = entry.metadata().unwrap();
+                    (
+                        entry.path().to_path_buf(),
+                        metadata.len(),
+                        FileTime::from_last_modification_time(&metadata),
+                    )
+                })
+                .collect();
+            entries.sort();
+            entries
+        };
+        let before = snapshot();
+
+        let app = root.join("app");
+        let plan = DeletePlan {
+            files: vec![app.join("debug.log"), app.join("core.dump")],
+            dirs: vec![
+                app.join("target"),
+                app.join("node_modules"),
+                app.join("build. "),
+            ],
+            permanent: [app.join("node_modules"), app.join("core.dump")]
+                .into_iter()
+                .collect(),
+        };
+        // 打开所有会修改文件系统的选项
+        let options = DeleteOptions {
+            preserve_parent_mtime: true,
+            count_inodes: true,
+            clear_immutable: true,
+            rename_first: true,
+            locality_order: true,
+            piecewise_delete: true,
+            ..Default::default()
+        };
+        let search_result = SearchResult {
+            folders: plan.dirs.clone().into(),
+            files: plan.files.clone().into(),
+            total_size: 100,
+            ..Default::default()
+        };
+
+        let results = [
+            DeleteEngine::execute_deletion(&plan, true),
+            DeleteEngine::execute_deletion_with_options(&plan, true, &options),
+            DeleteEngine::execute_deletion_with_progress(&plan, true, &options, None),
+            DeleteEngine::execute_deletion_from_search(&search_result, true),
+            DeleteEngine::execute_deletion_from_search_with_options(&search_result, true, &options),
+        ];
+        for result in results {
+            assert_eq!(result.deleted_files.len(), 2);
+            assert_eq!(result.deleted_dirs.len(), 3);
+            assert!(result.failed_files.is_empty() && result.failed_dirs.is_empty());
+        }
+        assert_eq!(snapshot(), before);
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "during a dry run"))]
+    fn test_mutate_rejected_in_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("keep.txt");
+        fs::write(&file, "keep").unwrap();
+
+        // 预览之外正常执行
+        let result: std::io::Result<()> = DeleteEngine::mutate("touch", &file, || Ok(()));
+        assert!(result.is_ok());
+
+        // 预览期间（这里是预览时的进度回调中）调试构建直接 panic，发布构建拒绝执行并返回错误
+        let plan = DeletePlan {
+            files: vec![file.clone()],
+            ..Default::default()
+        };
+        let errors = std::cell::RefCell::new(Vec::new());
+        DeleteEngine::execute_deletion_with_progress(
+            &plan,
+            true,
+            &DeleteOptions::default(),
+            Some(Box::new(|_, _, path: &Path| {
+                let result = DeleteEngine::trash_file(path, &DeleteOptions::default());
+                errors.borrow_mut().push(result.unwrap_err().to_string());
+            })),
+        );
+        assert_eq!(errors.borrow().len(), 1);
+        assert!(errors.borrow()[0].contains("during a dry run"));
+        assert!(file.exists());
+
+        // 离开预览后恢复正常
+        assert!(DeleteEngine::mutate("delete", &file, || fs::remove_file(&file)).is_ok());
+    }
+
     #[test]
     fn test_execute_deletion_actual() {
         let temp_dir = TempDir::new().unwrap();
@@ -458,6 +3014,7 @@ mod tests {
         let plan = DeletePlan {
             files: vec![test_file.clone()],
             dirs: vec![test_dir.clone()],
+            ..Default::default()
         };
 
         let result = DeleteEngine::execute_deletion(&plan, false);
@@ -479,6 +3036,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_has_trailing_dot_or_space() {
+        assert!(DeleteEngine::has_trailing_dot_or_space(Path::new(
+            "out/build "
+        )));
+        assert!(DeleteEngine::has_trailing_dot_or_space(Path::new(
+            "out/build."
+        )));
+        assert!(!DeleteEngine::has_trailing_dot_or_space(Path::new(
+            "out/build"
+        )));
+        assert!(!DeleteEngine::has_trailing_dot_or_space(Path::new(
+            "out/.cache"
+        )));
+        // 其他平台上不需要特殊处理，访问路径就是原路径
+        #[cfg(not(windows))]
+        assert_eq!(
+            DeleteEngine::access_path(Path::new("out/build ")),
+            PathBuf::from("out/build ")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_execute_deletion_trailing_space_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("build ");
+
+        // 常规 API 会去掉名称末尾的空格，只能通过扩展长度路径创建
+        let extended = DeleteEngine::access_path(&dir);
+        assert!(extended.to_string_lossy().starts_with(r"\\?\"));
+        fs::create_dir(&extended).unwrap();
+        fs::write(extended.join("out.o"), b"object").unwrap();
+
+        let plan = DeletePlan {
+            files: vec![],
+            dirs: vec![dir.clone()],
+            ..Default::default()
+        };
+        let result = DeleteEngine::execute_deletion(&plan, false);
+
+        assert!(result.failed_dirs.is_empty(), "{:?}", result.failed_dirs);
+        assert_eq!(result.deleted_dirs, vec![dir]);
+        assert_eq!(result.total_size, 6);
+        assert!(fs::symlink_metadata(&extended).is_err());
+        // 改名后的副本也已被移到回收站
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
     #[test]
     fn test_execute_deletion_with_failures() {
         let temp_dir = TempDir::new().unwrap();
@@ -491,6 +3097,7 @@ mod tests {
         let plan = DeletePlan {
             files: vec![test_file.clone(), nonexistent_file.clone()],
             dirs: vec![],
+            ..Default::default()
         };
 
         let result = DeleteEngine::execute_deletion(&plan, false);
@@ -501,6 +3108,648 @@ mod tests {
         assert_eq!(total_processed, 2);
 
         // 至少应该有一个失败（不存在的文件）
-        assert!(result.failed_files.len() >= 1);
+        assert!(!result.failed_files.is_empty());
+    }
+
+    #[test]
+    fn test_is_other_users_home() {
+        let home = Path::new("/home/alice");
+        // 其他用户主目录中的路径被拒绝
+        assert!(DeleteEngine::is_other_users_home(
+            Path::new("/home/bob/project/target"),
+            Some(home)
+        ));
+        assert!(DeleteEngine::is_other_users_home(
+            Path::new("/Users/carol/app/node_modules"),
+            Some(home)
+        ));
+        // 当前用户自己的主目录中的路径可以删除
+        assert!(!DeleteEngine::is_other_users_home(
+            Path::new("/home/alice/project/target"),
+            Some(home)
+        ));
+        // 名称有相同前缀的其他用户不被当作当前用户
+        assert!(DeleteEngine::is_other_users_home(
+            Path::new("/home/alice2/target"),
+            Some(home)
+        ));
+        // 主目录之外的路径和 /home 本身不受影响
+        assert!(!DeleteEngine::is_other_users_home(
+            Path::new("/tmp/build"),
+            Some(home)
+        ));
+        assert!(!DeleteEngine::is_other_users_home(
+            Path::new("/home"),
+            Some(home)
+        ));
+        // 不知道当前用户的主目录时不检查
+        assert!(!DeleteEngine::is_other_users_home(
+            Path::new("/home/bob/target"),
+            None
+        ));
+    }
+
+    #[test]
+    fn test_check_scan_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let project = home.join("project");
+        fs::create_dir_all(&project).unwrap();
+
+        assert!(DeleteEngine::check_scan_root(Path::new("/"), None).is_err());
+        assert!(DeleteEngine::check_scan_root(Path::new("/usr"), None).is_err());
+        assert!(DeleteEngine::check_scan_root(Path::new("/usr/lib"), None).is_err());
+
+        let err = DeleteEngine::check_scan_root(&home, Some(&home)).unwrap_err();
+        assert!(err.to_string().contains("home directory"));
+        assert!(err.to_string().contains("--force"));
+        // 通过 `..` 指向主目录时同样拒绝
+        assert!(DeleteEngine::check_scan_root(&project.join(".."), Some(&home)).is_err());
+
+        // 主目录下的项目可以扫描
+        assert!(DeleteEngine::check_scan_root(&project, Some(&home)).is_ok());
+        assert!(DeleteEngine::check_scan_root(&home, None).is_ok());
+    }
+
+    #[test]
+    fn test_execute_deletion_piecewise() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(target.join("debug/deps")).unwrap();
+        fs::write(target.join("debug/deps/app.o"), "obj").unwrap();
+        fs::write(target.join("debug/app"), "bin").unwrap();
+        fs::write(target.join(".rustc_info.json"), "{}").unwrap();
+        let plan = DeletePlan {
+            files: vec![],
+            dirs: vec![target.clone()],
+            ..Default::default()
+        };
+        let options = DeleteOptions {
+            piecewise_delete: true,
+            ..Default::default()
+        };
+
+        let mut calls = Vec::new();
+        let result = DeleteEngine::execute_deletion_with_progress(
+            &plan,
+            false,
+            &options,
+            Some(Box::new(|done, total, path: &Path| {
+                calls.push((done, total, path.to_path_buf()))
+            })),
+        );
+        assert_eq!(result.deleted_dirs, vec![target.clone()]);
+        assert!(result.failed_dirs.is_empty());
+        assert!(!target.exists());
+
+        // 项目开始时一次，之后每删除一个条目一次：3 个文件、2 个子目录和目录本身，
+        // 进度仍为计划中的项目数和项目总数
+        assert_eq!(calls[0], (0, 1, target.clone()));
+        let counts: Vec<(usize, usize)> = calls[1..]
+            .iter()
+            .map(|(done, total, _)| (*done, *total))
+            .collect();
+        assert_eq!(counts, vec![(0, 1); 6]);
+        let entries: Vec<&PathBuf> = calls[1..].iter().map(|(_, _, path)| path).collect();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries.last(), Some(&&target));
+        // 由深到浅：子目录总是在其内容之后删除
+        let position = |path: PathBuf| entries.iter().position(|p| **p == path).unwrap();
+        assert!(position(target.join("debug/deps/app.o")) < position(target.join("debug/deps")));
+        assert!(position(target.join("debug/deps")) < position(target.join("debug")));
+
+        // 已取消时不删除任何条目，目录保留
+        fs::create_dir_all(target.join("debug")).unwrap();
+        let cancel = CancelFlag::new();
+        cancel.cancel();
+        let options = DeleteOptions {
+            piecewise_delete: true,
+            cancel: Some(cancel),
+            ..Default::default()
+        };
+        let err =
+            DeleteEngine::remove_directory_piecewise(&target, &options, |_, _| {}).unwrap_err();
+        assert!(err.to_string().contains("Stopped after removing 0 entries"));
+        assert!(target.join("debug").exists());
+    }
+
+    #[test]
+    fn test_execute_deletion_piecewise_progress_total() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut dirs = Vec::new();
+        for name in ["a", "b"] {
+            let dir = temp_dir.path().join(name).join("target");
+            fs::create_dir_all(dir.join("debug")).unwrap();
+            for i in 0..5 {
+                fs::write(dir.join("debug").join(format!("{}.o", i)), "obj").unwrap();
+            }
+            dirs.push(dir);
+        }
+        let log = temp_dir.path().join("build.log");
+        fs::write(&log, "log").unwrap();
+        let plan = DeletePlan {
+            files: vec![log.clone()],
+            dirs: dirs.clone(),
+            ..Default::default()
+        };
+        let options = DeleteOptions {
+            piecewise_delete: true,
+            ..Default::default()
+        };
+
+        let mut calls = Vec::new();
+        let result = DeleteEngine::execute_deletion_with_progress(
+            &plan,
+            false,
+            &options,
+            Some(Box::new(|done, total, _: &Path| calls.push((done, total)))),
+        );
+        assert_eq!(result.deleted_dirs.len(), 2);
+        assert_eq!(result.deleted_files, vec![log]);
+
+        // 逐项删除目录中的条目时项目总数不变，已处理的项目数不会倒退
+        assert!(calls.len() > 3);
+        assert!(calls.iter().all(|(_, total)| *total == 3));
+        assert!(calls.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(calls.iter().all(|(done, _)| *done < 3));
+    }
+
+    #[test]
+    fn test_trash_directory_rename_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("app.o"), "obj").unwrap();
+
+        let options = DeleteOptions {
+            rename_first: true,
+            ..Default::default()
+        };
+        // 后续删除什么都不做：原位置仍应立即消失，内容保留在重命名后的副本中
+        let mut renamed = None;
+        DeleteEngine::trash_directory_with(&target, &options, |path| {
+            renamed = Some(path.to_path_buf());
+            Ok(())
+        })
+        .unwrap();
+
+        let renamed = renamed.unwrap();
+        assert!(!target.exists());
+        assert_eq!(renamed.parent(), Some(temp_dir.path()));
+        assert!(renamed
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(".target.bc-removing-"));
+        assert!(renamed.join("app.o").exists());
+
+        // 临时名称已被占用时使用另一个名称
+        fs::create_dir(&target).unwrap();
+        let second = DeleteEngine::rename_aside(&target).unwrap();
+        assert_ne!(second, renamed);
+        assert!(!target.exists());
+
+        // 后续删除失败时改回原名称并返回错误，不留下隐藏的副本
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("app.o"), "obj").unwrap();
+        let err = DeleteEngine::trash_directory_with(&target, &options, |_| {
+            Err(trash::Error::Unknown {
+                description: "stubbed".to_string(),
+            })
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("Failed to remove"), "{}", err);
+        assert!(target.join("app.o").exists());
+
+        // 未启用时直接交给 finish 处理原路径
+        let mut finished = None;
+        DeleteEngine::trash_directory_with(&target, &DeleteOptions::default(), |path| {
+            finished = Some(path.to_path_buf());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(finished.as_deref(), Some(target.as_path()));
+        assert!(target.exists());
+    }
+
+    #[test]
+    fn test_interactive_undo_rename_first() {
+        /// 用临时目录充当回收站，项目按移入时的名称存放
+        struct FolderTrash {
+            bin: PathBuf,
+        }
+
+        impl TrashOps for FolderTrash {
+            fn trash(
+                &self,
+                path: &Path,
+                _is_dir: bool,
+                options: &DeleteOptions,
+            ) -> Result<PathBuf, String> {
+                DeleteEngine::trash_directory_with(path, options, |path| {
+                    fs::rename(path, self.bin.join(path.file_name().unwrap())).map_err(|e| {
+                        trash::Error::Unknown {
+                            description: e.to_string(),
+                        }
+                    })
+                })
+                .map_err(|e| e.to_string())
+            }
+
+            fn restore(
+                &self,
+                path: &Path,
+                trashed: &Path,
+                conflict: RestoreConflict,
+            ) -> Result<RestoreOutcome, String> {
+                DeleteEngine::restore_trashed_with(path, trashed, conflict, |trashed| {
+                    fs::rename(self.bin.join(trashed.file_name().unwrap()), trashed)
+                        .map_err(|e| CleanError::Other(e.to_string()))
+                })
+                .map_err(|e| e.to_string())
+            }
+        }
+
+        /// 按脚本依次回答
+        struct ScriptedConfirmer(std::vec::IntoIter<Confirmation>);
+
+        impl Confirmer for ScriptedConfirmer {
+            fn confirm(&mut self, _item: &DeleteItem) -> Result<Confirmation, CleanError> {
+                Ok(self.0.next().expect("asked more than scripted"))
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let bin = temp_dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        let project = temp_dir.path().join("project");
+        let dirs: Vec<PathBuf> = ["dist", "target"]
+            .iter()
+            .map(|name| project.join(name))
+            .collect();
+        for dir in &dirs {
+            fs::create_dir_all(dir).unwrap();
+            fs::write(dir.join("out.bin"), vec![0u8; 10]).unwrap();
+        }
+        let plan = DeletePlan {
+            dirs: dirs.clone(),
+            ..Default::default()
+        };
+        let options = DeleteOptions {
+            rename_first: true,
+            ..Default::default()
+        };
+
+        // dist：删除；target：先撤销 dist，再跳过 target
+        let mut confirmer = ScriptedConfirmer(
+            vec![Confirmation::Yes, Confirmation::Undo, Confirmation::No].into_iter(),
+        );
+        let result = DeleteEngine::execute_deletion_interactive(
+            &plan,
+            &options,
+            None,
+            None,
+            &FolderTrash { bin: bin.clone() },
+            &mut confirmer,
+        )
+        .unwrap();
+
+        // 回收站中是重命名后的副本，撤销后恢复为原名称
+        assert!(result.deleted_dirs.is_empty());
+        assert_eq!(result.total_size, 0);
+        assert!(dirs[0].join("out.bin").exists());
+        assert_eq!(fs::read_dir(&bin).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(&project).unwrap().count(), 2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_immutable_file_reported() {
+        use std::os::unix::io::AsRawFd;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("locked.log");
+        fs::write(&file, "log").unwrap();
+
+        // 设置不可变属性需要 CAP_LINUX_IMMUTABLE 且文件系统支持，条件不满足时跳过
+        let set_flags = |flags: libc::c_int| {
+            let handle = DeleteEngine::open_for_flags(&file).unwrap();
+            unsafe { libc::ioctl(handle.as_raw_fd(), libc::FS_IOC_SETFLAGS, &flags) == 0 }
+        };
+        let Ok(flags) = DeleteEngine::inode_flags(&file) else {
+            return;
+        };
+        if !set_flags(flags | FS_IMMUTABLE_FL) {
+            return;
+        }
+        assert!(DeleteEngine::is_immutable(&file));
+
+        let plan = DeletePlan {
+            files: vec![file.clone()],
+            dirs: vec![],
+            ..Default::default()
+        };
+        let result = DeleteEngine::execute_deletion(&plan, false);
+        assert!(result.deleted_files.is_empty());
+        assert_eq!(result.failed_files.len(), 1);
+        assert!(result.failed_files[0].1.contains("immutable attribute set"));
+        assert!(file.exists());
+
+        // 启用 clear_immutable 后先清除属性再删除
+        let options = DeleteOptions {
+            clear_immutable: true,
+            ..Default::default()
+        };
+        let result = DeleteEngine::execute_deletion_with_options(&plan, false, &options);
+        if !result.failed_files.is_empty() {
+            // 回收站不可用时至少属性已被清除，确保临时目录可以被删除
+            set_flags(flags);
+        }
+        assert!(!DeleteEngine::is_immutable(&file));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preserve_parent_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let parent = temp_dir.path().join("logs");
+        fs::create_dir(&parent).unwrap();
+        let log_file = parent.join("app.log");
+        fs::File::create(&log_file)
+            .unwrap()
+            .write_all(b"log content")
+            .unwrap();
+
+        // 将父目录的修改时间设置为一个固定的过去时间
+        let old_mtime = FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&parent, old_mtime).unwrap();
+
+        let plan = DeletePlan {
+            files: vec![log_file.clone()],
+            dirs: vec![],
+            ..Default::default()
+        };
+        let options = DeleteOptions {
+            preserve_parent_mtime: true,
+            ..Default::default()
+        };
+
+        let result = DeleteEngine::execute_deletion_with_options(&plan, false, &options);
+
+        // 删除本身必须成功，否则无法验证修改时间是否被恢复
+        assert_eq!(
+            result.deleted_files,
+            vec![log_file.clone()],
+            "{:?}",
+            result.failed_files
+        );
+        assert!(!log_file.exists());
+        let metadata = fs::metadata(&parent).unwrap();
+        assert_eq!(FileTime::from_last_modification_time(&metadata), old_mtime);
+    }
+
+    #[test]
+    fn test_is_safe_to_remove() {
+        let temp_dir = TempDir::new().unwrap();
+        let policy = SafetyPolicy {
+            source_extensions: vec!["rs".to_string(), "c".to_string()],
+        };
+
+        // 只包含构建产物的目录可以删除
+        let generated = temp_dir.path().join("generated");
+        fs::create_dir_all(generated.join("deps")).unwrap();
+        fs::write(generated.join("main.o"), "obj").unwrap();
+        fs::write(generated.join("deps").join("libfoo.rlib"), "rlib").unwrap();
+        assert!(DeleteEngine::is_safe_to_remove(&generated, &policy));
+
+        // 包含源文件的目录不能删除
+        let misnamed = temp_dir.path().join("misnamed");
+        fs::create_dir_all(misnamed.join("src")).unwrap();
+        fs::write(misnamed.join("main.o"), "obj").unwrap();
+        fs::write(misnamed.join("src").join("lib.RS"), "fn main() {}").unwrap();
+        assert!(!DeleteEngine::is_safe_to_remove(&misnamed, &policy));
+
+        // 启用 generated_only 时拒绝删除并记录失败原因
+        let search_result = SearchResult {
+            folders: vec![misnamed.clone()].into(),
+            ..Default::default()
+        };
+        let plan = DeleteEngine::create_delete_plan(&search_result);
+        let options = DeleteOptions {
+            generated_only: Some(policy),
+            ..Default::default()
+        };
+        let result = DeleteEngine::execute_deletion_with_options(&plan, false, &options);
+        assert!(result.deleted_dirs.is_empty());
+        assert_eq!(result.failed_dirs.len(), 1);
+        assert!(misnamed.exists());
+    }
+
+    #[test]
+    fn test_check_safety_refuses_cwd_and_ancestors() {
+        let temp_dir = TempDir::new().unwrap();
+        let parent = fs::canonicalize(temp_dir.path()).unwrap().join("project");
+        let cwd = parent.join("src");
+        fs::create_dir_all(cwd.join("bin")).unwrap();
+
+        for target in [cwd.as_path(), parent.as_path()] {
+            let err = DeleteEngine::check_safety_with_cwd(target, Some(&cwd)).unwrap_err();
+            assert!(matches!(err, SafetyViolation::WorkingDirectory(_)));
+            assert!(err.to_string().contains("current working directory"));
+            // 无法获取工作目录时不检查这条规则
+            assert!(DeleteEngine::check_safety_with_cwd(target, None).is_ok());
+        }
+
+        // 当前目录下的子目录不受影响
+        assert!(DeleteEngine::check_safety_with_cwd(&cwd.join("bin"), Some(&cwd)).is_ok());
+    }
+
+    #[test]
+    fn test_throttle_sleep_duration() {
+        // 10 个/秒：完成 5 个应耗时 0.5 秒，只过了 0.2 秒时需要休眠 0.3 秒
+        let sleep = Throttle::sleep_duration(Duration::from_millis(200), 5, 10);
+        assert_eq!(sleep, Duration::from_millis(300));
+
+        // 已经慢于目标速率时不休眠
+        let sleep = Throttle::sleep_duration(Duration::from_secs(2), 5, 10);
+        assert_eq!(sleep, Duration::ZERO);
+
+        // 速率为 0 视为不限制
+        assert_eq!(
+            Throttle::sleep_duration(Duration::ZERO, 100, 0),
+            Duration::ZERO
+        );
+
+        // 按计算结果休眠后，整体速率接近目标值
+        let mut throttle = Throttle::new(100);
+        let start = Instant::now();
+        for _ in 0..10 {
+            throttle.tick();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_du_size_agrees_with_calculated_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("target");
+        fs::create_dir_all(dir.join("debug")).unwrap();
+        fs::write(dir.join("a.o"), vec![1u8; 512 * 1024]).unwrap();
+        fs::write(dir.join("debug").join("b.o"), vec![1u8; 512 * 1024]).unwrap();
+
+        let (computed, _) = DeleteEngine::calculate_dir_size(&dir);
+        assert_eq!(computed, 1024 * 1024);
+
+        // du 还会统计目录条目本身，因此只要求在容差范围内一致
+        if let Some(reported) = DeleteEngine::du_size(&dir) {
+            assert!(!DeleteEngine::sizes_diverge(computed, reported, 0.05));
+        }
+
+        assert!(DeleteEngine::sizes_diverge(100, 200, 0.05));
+        assert!(!DeleteEngine::sizes_diverge(0, 0, 0.05));
+    }
+
+    #[test]
+    fn test_classify() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // 明确的构建目录：常见名称、同级 Cargo.toml、CACHEDIR.TAG、被 .gitignore 忽略、没有源文件
+        let project = temp_dir.path().join("app");
+        let target = project.join("target");
+        fs::create_dir_all(target.join("debug")).unwrap();
+        fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(project.join(".gitignore"), "# build output\n/target/\n").unwrap();
+        fs::write(
+            target.join("CACHEDIR.TAG"),
+            "Signature: 8a477f597d28d172789f06886806bc55\n",
+        )
+        .unwrap();
+        fs::write(target.join("debug").join("app.o"), "obj").unwrap();
+        fs::write(target.join("debug").join("libapp.rlib"), "lib").unwrap();
+
+        let build = DeleteEngine::classify(&target);
+        assert_eq!(
+            build.reasons,
+            vec![
+                "'target' is a common build output name",
+                "parent directory has a Rust project manifest",
+                "contains a CACHEDIR.TAG",
+                "ignored by the parent directory's .gitignore",
+                "none of 3 files have source extensions",
+            ]
+        );
+        assert!(build.safe_score > 0.9, "{:?}", build);
+
+        // 含义不明确的目录：名称像构建目录，但没有项目清单，且大部分文件是源文件
+        let ambiguous = temp_dir.path().join("scripts").join("build");
+        fs::create_dir_all(&ambiguous).unwrap();
+        fs::write(ambiguous.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(ambiguous.join("util.py"), "pass").unwrap();
+        fs::write(ambiguous.join("notes.txt"), "todo").unwrap();
+
+        let unclear = DeleteEngine::classify(&ambiguous);
+        assert_eq!(
+            unclear.reasons,
+            vec![
+                "'build' is a common build output name",
+                "2 of 3 files have source extensions",
+            ]
+        );
+        assert!(unclear.safe_score < 0.2, "{:?}", unclear);
+        assert!(build.safe_score > unclear.safe_score);
+
+        // 包含版本控制仓库的目录评分最低
+        fs::create_dir(ambiguous.join(".git")).unwrap();
+        let repo = DeleteEngine::classify(&ambiguous);
+        assert!(repo
+            .reasons
+            .contains(&"contains a version control repository".to_string()));
+        assert_eq!(repo.safe_score, 0.0);
+
+        // 分类只读取文件系统
+        assert!(target.join("debug").join("app.o").exists());
+        assert!(ambiguous.join("main.rs").exists());
+    }
+
+    #[test]
+    fn test_trash_would_fit() {
+        // 项目大小不超过可用空间时可以放入
+        assert!(DeleteEngine::fits(0, 0));
+        assert!(DeleteEngine::fits(1024, 4096));
+        assert!(DeleteEngine::fits(4096, 4096));
+        assert!(!DeleteEngine::fits(4097, 4096));
+        assert!(!DeleteEngine::fits(u64::MAX, 100 * 1024 * 1024 * 1024));
+
+        // 空项目总能放入实际存在的目录，超出整个文件系统的项目不能
+        let temp_dir = TempDir::new().unwrap();
+        assert!(DeleteEngine::trash_would_fit(0, temp_dir.path()));
+        assert!(DeleteEngine::trash_would_fit(
+            0,
+            &temp_dir.path().join("missing").join("Trash")
+        ));
+        if DiskSpace::query(temp_dir.path()).is_ok() {
+            assert!(!DeleteEngine::trash_would_fit(u64::MAX, temp_dir.path()));
+        }
+    }
+
+    #[test]
+    fn test_restore_with_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("node_modules");
+        // 模拟回收站恢复：在原位置重新创建项目，原位置被占用时失败
+        let restore = |target: &Path| -> Result<(), CleanError> {
+            if target.exists() {
+                return Err(CleanError::Other("target exists".to_string()));
+            }
+            fs::create_dir(target)?;
+            fs::write(target.join("marker"), "old")?;
+            Ok(())
+        };
+        let marker = |dir: &Path| fs::read_to_string(dir.join("marker")).unwrap();
+
+        // 原位置空闲时直接恢复
+        assert_eq!(
+            DeleteEngine::restore_with(&path, RestoreConflict::Skip, restore).unwrap(),
+            RestoreOutcome::Restored
+        );
+        assert_eq!(marker(&path), "old");
+
+        // 原位置被占用：skip 不调用恢复，保留现有项目
+        fs::remove_dir_all(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("marker"), "new").unwrap();
+        let outcome = DeleteEngine::restore_with(&path, RestoreConflict::Skip, |_| {
+            panic!("restore must not run when skipping a conflict")
+        })
+        .unwrap();
+        assert_eq!(outcome, RestoreOutcome::Conflict);
+        assert_eq!(marker(&path), "new");
+
+        // rename 恢复为 `node_modules.restored`，现有项目留在原位置
+        let renamed = temp_dir.path().join("node_modules.restored");
+        assert_eq!(
+            DeleteEngine::restore_with(&path, RestoreConflict::Rename, restore).unwrap(),
+            RestoreOutcome::Renamed(renamed.clone())
+        );
+        assert_eq!(marker(&path), "new");
+        assert_eq!(marker(&renamed), "old");
+
+        // 后缀名称也被占用时追加序号
+        assert_eq!(
+            DeleteEngine::restore_with(&path, RestoreConflict::Rename, restore).unwrap(),
+            RestoreOutcome::Renamed(temp_dir.path().join("node_modules.restored-1"))
+        );
+        assert_eq!(marker(&path), "new");
+
+        // 恢复失败时现有项目仍然移回原位置
+        assert!(
+            DeleteEngine::restore_with(&path, RestoreConflict::Rename, |_| Err(CleanError::Other(
+                "trash item missing".to_string()
+            )))
+            .is_err()
+        );
+        assert_eq!(marker(&path), "new");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 3);
     }
 }
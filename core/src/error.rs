@@ -1,3 +1,4 @@
+use crate::delete::{DryRunViolation, SafetyViolation};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -15,6 +16,29 @@ pub enum CleanError {
     #[error("Config parse error: {0}")]
     ConfigParseError(String),
 
+    #[error("Failed to parse {format} at line {line}, column {column}: {message}")]
+    Deserialize {
+        format: &'static str,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("Too many traversal errors: {0} (limit: {1}), aborting scan")]
+    TooManyWalkErrors(usize, usize),
+
+    #[error("No paths provided")]
+    NoPaths,
+
+    #[error("Operation cancelled by user")]
+    Cancelled,
+
+    #[error("{0}")]
+    Unsafe(#[from] SafetyViolation),
+
+    #[error("{0}")]
+    DryRun(#[from] DryRunViolation),
+
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -28,3 +52,40 @@ impl From<std::io::Error> for CleanError {
         }
     }
 }
+
+/// 去掉 serde 错误信息末尾的位置描述（位置已单独记录）
+fn strip_location(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(idx) => message[..idx].to_string(),
+        None => message.to_string(),
+    }
+}
+
+impl From<serde_yaml::Error> for CleanError {
+    fn from(err: serde_yaml::Error) -> Self {
+        match err.location() {
+            Some(location) => CleanError::Deserialize {
+                format: "YAML",
+                line: location.line(),
+                column: location.column(),
+                message: strip_location(&err.to_string()),
+            },
+            None => CleanError::ConfigParseError(format!("Failed to parse YAML: {}", err)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CleanError {
+    fn from(err: serde_json::Error) -> Self {
+        // serde_json 对没有位置信息的错误返回行号 0
+        if err.line() == 0 {
+            return CleanError::ConfigParseError(format!("Failed to parse JSON: {}", err));
+        }
+        CleanError::Deserialize {
+            format: "JSON",
+            line: err.line(),
+            column: err.column(),
+            message: strip_location(&err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 专门用于清理的忽略文件名，语法与 `.gitignore` 相同，总是生效
+pub const BCIGNORE_FILE: &str = ".bcignore";
+
+/// Git 的忽略文件名，启用 `respect_gitignore` 时生效
+pub const GITIGNORE_FILE: &str = ".gitignore";
+
+/// 扫描过程中遇到的忽略文件（`.bcignore`，以及启用时的 `.gitignore`）
+///
+/// 每个目录中的忽略文件只对该目录下的路径生效，第一次需要时读取并缓存。判断路径时从最近的
+/// 目录开始向上查找，第一个给出结论（忽略，或用 `!` 重新包含）的目录决定结果；同一目录中
+/// `.bcignore` 的规则在 `.gitignore` 之后，可以覆盖后者。被忽略的目录中的所有内容也被忽略。
+/// 只读取扫描根路径及其下的忽略文件。
+#[derive(Debug)]
+pub struct IgnoreFiles {
+    /// 是否读取 `.gitignore`
+    respect_gitignore: bool,
+    /// 每个目录的匹配器，目录中没有忽略文件时为 `None`
+    matchers: RefCell<HashMap<PathBuf, Option<Gitignore>>>,
+}
+
+impl IgnoreFiles {
+    /// 创建忽略文件集合
+    ///
+    /// # 参数
+    /// * `respect_gitignore` - 是否同时读取 `.gitignore`
+    pub fn new(respect_gitignore: bool) -> Self {
+        IgnoreFiles {
+            respect_gitignore,
+            matchers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// 判断路径是否被忽略
+    ///
+    /// # 参数
+    /// * `root` - 扫描根路径，只读取它及其下目录中的忽略文件
+    /// * `path` - 要判断的路径（根路径本身从不被忽略）
+    /// * `is_dir` - 路径是否为目录（只匹配目录的规则如 `build/` 需要）
+    ///
+    /// # 返回
+    /// 如果路径或其上级目录被忽略返回 `true`
+    pub fn is_ignored(&self, root: &Path, path: &Path, is_dir: bool) -> bool {
+        let mut matchers = self.matchers.borrow_mut();
+        for dir in path.ancestors().skip(1) {
+            if !dir.starts_with(root) {
+                break;
+            }
+            // 大多数目录已经读取过，先按 &Path 查找，只在第一次遇到目录时分配 PathBuf
+            if !matchers.contains_key(dir) {
+                matchers.insert(dir.to_path_buf(), self.load(dir));
+            }
+            if let Some(matcher) = &matchers[dir] {
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        false
+    }
+
+    /// 读取目录中的忽略文件，无法解析的行记录警告后跳过
+    fn load(&self, dir: &Path) -> Option<Gitignore> {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        let names = [
+            self.respect_gitignore.then_some(GITIGNORE_FILE),
+            Some(BCIGNORE_FILE),
+        ];
+        for name in names.into_iter().flatten() {
+            let file = dir.join(name);
+            if !file.is_file() {
+                continue;
+            }
+            found = true;
+            if let Some(e) = builder.add(&file) {
+                log::warn!("Failed to parse {}: {}", file.display(), e);
+            }
+        }
+        if !found {
+            return None;
+        }
+        builder
+            .build()
+            .map_err(|e| log::warn!("Failed to load ignore files in {}: {}", dir.display(), e))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let app = root.join("app");
+        fs::create_dir_all(app.join("dist")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\ndist/\n").unwrap();
+        fs::write(root.join(".bcignore"), "keep.bin\n").unwrap();
+        // 更近的目录中的规则优先
+        fs::write(app.join(".gitignore"), "!important.log\n").unwrap();
+
+        let gitignore = IgnoreFiles::new(true);
+        assert!(gitignore.is_ignored(root, &root.join("debug.log"), false));
+        assert!(gitignore.is_ignored(root, &app.join("debug.log"), false));
+        assert!(!gitignore.is_ignored(root, &app.join("important.log"), false));
+        // 被忽略的目录及其内容
+        assert!(gitignore.is_ignored(root, &app.join("dist"), true));
+        assert!(gitignore.is_ignored(root, &app.join("dist").join("main.js"), false));
+        // 只匹配目录的规则不匹配同名文件
+        assert!(!gitignore.is_ignored(root, &root.join("dist"), false));
+        assert!(gitignore.is_ignored(root, &app.join("keep.bin"), false));
+        assert!(!gitignore.is_ignored(root, &app.join("main.rs"), false));
+        // 根路径本身和根路径以外的忽略文件不生效
+        assert!(!gitignore.is_ignored(root, root, true));
+        assert!(!gitignore.is_ignored(&app, &app.join("debug.log"), false));
+
+        // 未启用 respect_gitignore 时只读取 .bcignore
+        let bcignore = IgnoreFiles::new(false);
+        assert!(!bcignore.is_ignored(root, &root.join("debug.log"), false));
+        assert!(bcignore.is_ignored(root, &app.join("keep.bin"), false));
+    }
+}
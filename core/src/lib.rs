@@ -3,19 +3,56 @@
 //! 提供清理项目临时文件和目录的核心功能，包括：
 //! - 配置管理：项目类型识别、配置加载和合并
 //! - 文件搜索：路径遍历、模式匹配、过滤规则
+//! - 忽略文件：按扫描中遇到的 `.bcignore` 和 `.gitignore` 排除路径
+//! - 取消控制：提前结束扫描和删除（如超出时间预算）
+//! - 路径压缩：按扫描根路径和相对路径保存大量匹配项
+//! - 扫描缓存：按目录修改时间复用已计算的目录大小
 //! - 文件删除：删除计划生成、安全检查、删除执行
+//! - 删除清单：逐项记录已完成的删除，支持中断后恢复
 //! - 报告生成：统计信息收集、报告格式化
+//! - 运行时配置：所有并行阶段共用的线程数和排队任务上限
+//! - 磁盘空间：查询路径所在文件系统的可用空间
+//! - 工具缓存：定位 Cargo、npm、pip、Go 等工具的全局缓存目录
 //! - 日志记录：多级别日志支持
 
+pub mod cache;
+pub mod cancel;
+pub mod compact;
 pub mod config;
 pub mod delete;
 pub mod error;
+pub mod ignores;
 pub mod log;
+pub mod manifest;
 pub mod report;
+pub mod runtime;
 pub mod search;
+pub mod space;
+pub mod tools;
 
-pub use config::{Config, ConfigLoader, ProjectType};
-pub use delete::{DeleteEngine, DeletePlan, DeleteResult};
+pub use cache::{FileScanCache, ScanCache};
+pub use cancel::CancelFlag;
+pub use compact::CompactPaths;
+pub use config::{
+    Config, ConfigLoader, DeleteMode, DirSizeAction, ExcludedChildAction, OverlapPolicy, Preset,
+    ProjectType, RestoreConflict, RetentionPolicy, TrashSpaceAction, CONFIG_ENV_VAR,
+};
+pub use delete::{
+    Classification, Confirmation, Confirmer, DeleteEngine, DeleteEvent, DeleteItem, DeleteOptions,
+    DeletePlan, DeleteResult, DryRunViolation, RestoreOutcome, SafetyPolicy, SafetyViolation,
+    SystemTrash, Throttle, TrashOps,
+};
 pub use error::CleanError;
-pub use report::{ReportGenerator, Stats};
-pub use search::{SearchEngine, SearchOptions, SearchResult};
+pub use ignores::{IgnoreFiles, BCIGNORE_FILE, GITIGNORE_FILE};
+pub use manifest::{DeleteManifest, ManifestEntry};
+pub use report::{
+    AnonymizeMode, GroupBy, InventoryItem, ItemGroup, PathAnonymizer, ProjectTypeSummary,
+    ReportGenerator, ScanInventory, SizeBucket, Stats,
+};
+pub use runtime::RuntimeConfig;
+pub use search::{
+    Decision, EntryTypeFilter, Explanation, GlobDotPolicy, ItemOrigin, Match, MatchPolicy, Matches,
+    RejectReason, SearchEngine, SearchOptions, SearchResult, Verdict,
+};
+pub use space::{DiskSpace, FreeSpaceThreshold};
+pub use tools::ToolCacheDiscovery;
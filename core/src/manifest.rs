@@ -0,0 +1,217 @@
+use crate::delete::DeletePlan;
+use crate::error::CleanError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 删除清单中的单条记录，每个成功删除的项目写入一行 JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// 已删除的路径
+    pub path: PathBuf,
+    /// 是否为目录
+    pub is_dir: bool,
+    /// 删除是否已完成（只有已完成的记录会在恢复时被跳过）
+    pub committed: bool,
+}
+
+/// 删除清单，记录已经完成删除的项目，用于在中断后恢复
+///
+/// 每删除成功一个项目就追加一行并立即刷新，进程被终止时最多丢失正在处理的那一项。
+pub struct DeleteManifest {
+    file: File,
+}
+
+impl DeleteManifest {
+    /// 以追加模式打开清单文件，文件不存在时创建
+    ///
+    /// # 参数
+    /// * `path` - 清单文件路径
+    ///
+    /// # 返回
+    /// 打开的清单，如果无法打开则返回错误
+    pub fn open(path: &Path) -> Result<Self, CleanError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                CleanError::Other(format!("Failed to open manifest {}: {}", path.display(), e))
+            })?;
+        Ok(DeleteManifest { file })
+    }
+
+    /// 记录一个已完成删除的项目，并立即刷新到磁盘
+    ///
+    /// # 参数
+    /// * `path` - 已删除的路径
+    /// * `is_dir` - 是否为目录
+    ///
+    /// # 返回
+    /// 如果写入成功返回 `Ok(())`，否则返回错误
+    pub fn commit(&mut self, path: &Path, is_dir: bool) -> Result<(), CleanError> {
+        self.append(path, is_dir, true)
+    }
+
+    /// 撤销一个已记录的删除（如项目已从回收站恢复），恢复时不再跳过该项目
+    ///
+    /// # 参数
+    /// * `path` - 已恢复的路径
+    /// * `is_dir` - 是否为目录
+    ///
+    /// # 返回
+    /// 如果写入成功返回 `Ok(())`，否则返回错误
+    pub fn revert(&mut self, path: &Path, is_dir: bool) -> Result<(), CleanError> {
+        self.append(path, is_dir, false)
+    }
+
+    /// 追加一条记录并立即刷新到磁盘
+    fn append(&mut self, path: &Path, is_dir: bool, committed: bool) -> Result<(), CleanError> {
+        let entry = ManifestEntry {
+            path: path.to_path_buf(),
+            is_dir,
+            committed,
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| CleanError::Other(format!("Failed to serialize manifest entry: {}", e)))?;
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.sync_data())
+            .map_err(|e| CleanError::Other(format!("Failed to write manifest: {}", e)))
+    }
+
+    /// 读取清单中已完成删除的路径
+    ///
+    /// 记录按顺序生效，之后被撤销的删除不包括在内。
+    /// 进程在写入过程中被终止时，最后一行可能不完整，这样的行会被忽略。
+    ///
+    /// # 参数
+    /// * `path` - 清单文件路径
+    ///
+    /// # 返回
+    /// 已完成删除的路径集合，文件不存在时返回空集合
+    pub fn load_committed(path: &Path) -> Result<HashSet<PathBuf>, CleanError> {
+        if !path.exists() {
+            return Ok(HashSet::new());
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            CleanError::Other(format!("Failed to read manifest {}: {}", path.display(), e))
+        })?;
+        let mut committed = HashSet::new();
+        for entry in content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<ManifestEntry>(line).ok())
+        {
+            if entry.committed {
+                committed.insert(entry.path);
+            } else {
+                committed.remove(&entry.path);
+            }
+        }
+        Ok(committed)
+    }
+
+    /// 将删除计划与清单对比，移除已经完成删除的项目
+    ///
+    /// # 参数
+    /// * `plan` - 新的删除计划
+    /// * `committed` - 清单中已完成删除的路径
+    ///
+    /// # 返回
+    /// 剩余需要删除的计划，以及被跳过的路径
+    pub fn reconcile(
+        plan: &DeletePlan,
+        committed: &HashSet<PathBuf>,
+    ) -> (DeletePlan, Vec<PathBuf>) {
+        let mut skipped = Vec::new();
+        let mut keep = |path: &PathBuf| {
+            if committed.contains(path) {
+                skipped.push(path.clone());
+                false
+            } else {
+                true
+            }
+        };
+
+        let files = plan.files.iter().filter(|p| keep(p)).cloned().collect();
+        let dirs = plan.dirs.iter().filter(|p| keep(p)).cloned().collect();
+        let permanent = plan.permanent.clone();
+        (
+            DeletePlan {
+                files,
+                dirs,
+                permanent,
+            },
+            skipped,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resume_skips_committed_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest_path = temp_dir.path().join("manifest.jsonl");
+
+        // 模拟中断：提交了一个目录和一个文件，最后一行只写了一半
+        let mut manifest = DeleteManifest::open(&manifest_path).unwrap();
+        manifest
+            .commit(Path::new("/project/a/target"), true)
+            .unwrap();
+        manifest
+            .commit(Path::new("/project/debug.log"), false)
+            .unwrap();
+        drop(manifest);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&manifest_path)
+            .unwrap();
+        write!(file, "{{\"path\":\"/project/b/target\",\"is_d").unwrap();
+
+        let committed = DeleteManifest::load_committed(&manifest_path).unwrap();
+        assert_eq!(committed.len(), 2);
+
+        let plan = DeletePlan {
+            files: vec![
+                PathBuf::from("/project/debug.log"),
+                PathBuf::from("/project/other.log"),
+            ],
+            dirs: vec![
+                PathBuf::from("/project/a/target"),
+                PathBuf::from("/project/b/target"),
+            ],
+            ..Default::default()
+        };
+        let (remaining, skipped) = DeleteManifest::reconcile(&plan, &committed);
+        assert_eq!(remaining.files, vec![PathBuf::from("/project/other.log")]);
+        assert_eq!(remaining.dirs, vec![PathBuf::from("/project/b/target")]);
+        assert_eq!(skipped.len(), 2);
+
+        // 之后被撤销的删除不再视为已完成
+        let reverted_path = temp_dir.path().join("reverted.jsonl");
+        let mut manifest = DeleteManifest::open(&reverted_path).unwrap();
+        manifest
+            .commit(Path::new("/project/a/target"), true)
+            .unwrap();
+        manifest
+            .commit(Path::new("/project/debug.log"), false)
+            .unwrap();
+        manifest
+            .revert(Path::new("/project/debug.log"), false)
+            .unwrap();
+        drop(manifest);
+        let committed = DeleteManifest::load_committed(&reverted_path).unwrap();
+        assert!(!committed.contains(Path::new("/project/debug.log")));
+        assert!(committed.contains(Path::new("/project/a/target")));
+
+        // 清单不存在时没有已完成的项目
+        let missing = temp_dir.path().join("missing.jsonl");
+        assert!(DeleteManifest::load_committed(&missing).unwrap().is_empty());
+    }
+}
@@ -1,9 +1,15 @@
-use crate::delete::DeleteResult;
-use crate::search::SearchResult;
+use crate::config::{Config, ConfigLoader, ProjectType};
+use crate::delete::{DeleteResult, SafetyViolation};
+use crate::search::{GlobDotPolicy, Match, RejectReason, SearchEngine, SearchResult};
+use crate::space::{DiskSpace, FreeSpaceThreshold};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
 use std::time::Duration;
 
 /// 清理统计信息
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Stats {
     /// 扫描的文件数量
     pub files_scanned: usize,
@@ -19,8 +25,379 @@ pub struct Stats {
     pub dirs_failed: usize,
     /// 释放的磁盘空间（字节）
     pub space_freed: u64,
+    /// 实际释放的物理磁盘空间（字节，仅在启用物理大小统计时存在）
+    pub physical_space_freed: Option<u64>,
+    /// 释放的 inode 数量（仅在启用 inode 统计时存在）
+    pub inodes_freed: Option<u64>,
+    /// 被安全检查拒绝删除的项目及触发的规则（同时计入 `files_failed`/`dirs_failed`）
+    pub safety_refusals: Vec<(PathBuf, SafetyViolation)>,
     /// 操作耗时
     pub time_taken: Duration,
+    /// 匹配项按大小区间的分布：(区间, 数量, 总字节数)
+    pub size_histogram: Vec<(SizeBucket, usize, u64)>,
+    /// 本次扫描中没有匹配到任何项目的配置模式（扫描被取消时不统计）
+    pub unmatched_patterns: Vec<String>,
+    /// 按所在项目类型汇总的删除项目，按总大小从大到小排列
+    pub project_types: Vec<ProjectTypeSummary>,
+    /// 位于排除路径中而跳过的条目数量
+    pub skipped_excluded: usize,
+    /// 因大小超出范围而跳过的条目数量
+    pub skipped_size: usize,
+    /// 因年龄超出范围而跳过的条目数量
+    pub skipped_age: usize,
+    /// 直接位于已匹配目录中、没有单独遍历的条目数量
+    pub skipped_in_match: usize,
+    /// 遍历错误数量
+    pub walk_errors: usize,
+    /// 已删除项目（包括已删除目录中的文件）按扩展名汇总的大小，按大小从大到小排列
+    ///
+    /// 扩展名为空表示没有扩展名的文件。只在详细模式下统计，否则为空。
+    pub extension_breakdown: Vec<(String, u64)>,
+}
+
+/// 一种项目类型下已删除项目的汇总
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectTypeSummary {
+    /// 项目类型（无法识别所在项目的项目为 `Unknown`）
+    pub project_type: ProjectType,
+    /// 涉及的项目数量
+    pub projects: usize,
+    /// 已删除的项目数量
+    pub items: usize,
+    /// 已删除项目的总大小（字节）
+    pub total_size: u64,
+}
+
+/// 匹配项的大小区间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeBucket {
+    /// 小于 1 KB
+    UnderKb,
+    /// 1 KB 到 1 MB
+    KbToMb,
+    /// 1 MB 到 100 MB
+    MbTo100Mb,
+    /// 大于等于 100 MB
+    Over100Mb,
+}
+
+impl SizeBucket {
+    /// 所有区间，按从小到大排列
+    pub const ALL: [SizeBucket; 4] = [
+        SizeBucket::UnderKb,
+        SizeBucket::KbToMb,
+        SizeBucket::MbTo100Mb,
+        SizeBucket::Over100Mb,
+    ];
+
+    /// 根据大小确定所属区间
+    pub fn for_size(bytes: u64) -> Self {
+        const KB: u64 = 1024;
+        const MB: u64 = 1024 * 1024;
+        if bytes < KB {
+            SizeBucket::UnderKb
+        } else if bytes < MB {
+            SizeBucket::KbToMb
+        } else if bytes < 100 * MB {
+            SizeBucket::MbTo100Mb
+        } else {
+            SizeBucket::Over100Mb
+        }
+    }
+
+    /// 区间的显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            SizeBucket::UnderKb => "<1KB",
+            SizeBucket::KbToMb => "1KB-1MB",
+            SizeBucket::MbTo100Mb => "1MB-100MB",
+            SizeBucket::Over100Mb => ">100MB",
+        }
+    }
+}
+
+/// 只扫描不删除时输出的清单，供外部工具使用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanInventory {
+    /// 匹配的文件夹和文件，按发现顺序排列
+    pub items: Vec<InventoryItem>,
+    /// 匹配项的总大小（字节）
+    pub total_size: u64,
+    /// 匹配的文件夹数量
+    pub dirs_matched: usize,
+    /// 匹配的文件数量
+    pub files_matched: usize,
+    /// 扫描的文件数量
+    pub files_scanned: usize,
+    /// 扫描的目录数量
+    pub dirs_scanned: usize,
+}
+
+/// 清单中的单个匹配项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryItem {
+    /// 路径（非 UTF-8 路径会有损转换）
+    pub path: String,
+    /// 路径是否包含非 UTF-8 字符（此时 `path` 不能直接用于定位文件）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy_path: bool,
+    /// 匹配项类型：`dir` 或 `file`
+    pub kind: String,
+    /// 大小（字节），文件夹为其全部内容的大小
+    pub size: u64,
+    /// 大小是否为估算值（实际大小 ≥ `size`）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub size_is_estimate: bool,
+    /// 匹配到的清理模式
+    pub pattern: String,
+}
+
+impl InventoryItem {
+    /// 从匹配项创建清单条目
+    pub fn from_match(item: &Match) -> Self {
+        let (path, lossy_path) = Self::path_to_string(&item.path);
+        InventoryItem {
+            path,
+            lossy_path,
+            kind: if item.is_dir { "dir" } else { "file" }.to_string(),
+            size: item.size,
+            size_is_estimate: item.size_is_estimate,
+            pattern: item.pattern.clone(),
+        }
+    }
+
+    fn path_to_string(path: &Path) -> (String, bool) {
+        match path.to_str() {
+            Some(s) => (s.to_string(), false),
+            None => (path.to_string_lossy().into_owned(), true),
+        }
+    }
+}
+
+/// 详细报告中已删除项目的分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// 按发现该项目的扫描根路径分组
+    Root,
+    /// 按匹配到的清理模式分组
+    Pattern,
+    /// 按所在项目的类型分组
+    Type,
+}
+
+impl GroupBy {
+    /// 返回分组方式的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            GroupBy::Root => "root",
+            GroupBy::Pattern => "pattern",
+            GroupBy::Type => "type",
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "root" => Ok(GroupBy::Root),
+            "pattern" => Ok(GroupBy::Pattern),
+            "type" => Ok(GroupBy::Type),
+            _ => Err(format!(
+                "Invalid group: {} (expected root, pattern or type)",
+                s
+            )),
+        }
+    }
+}
+
+/// 报告中路径的匿名化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnonymizeMode {
+    /// 将上级目录的名称替换为 `*`
+    Redact,
+    /// 将上级目录的名称替换为稳定的短哈希，相同的名称总是得到相同的结果
+    Hash,
+}
+
+impl FromStr for AnonymizeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "redact" => Ok(AnonymizeMode::Redact),
+            "hash" => Ok(AnonymizeMode::Hash),
+            _ => Err(format!(
+                "Invalid anonymize mode: {} (expected redact or hash)",
+                s
+            )),
+        }
+    }
+}
+
+/// 匿名化报告中的路径，便于分享报告
+///
+/// 主目录替换为 `~`，其后（不在主目录下时为文件系统根之后）的上级目录名称按模式隐藏或哈希，
+/// 最后一个组成部分（匹配到的文件夹或文件名，如 `node_modules`）保持不变。
+/// 路径的层级和所有大小不变，报告依然可以用来比较。
+#[derive(Debug, Clone)]
+pub struct PathAnonymizer {
+    mode: AnonymizeMode,
+    home: Option<PathBuf>,
+}
+
+impl PathAnonymizer {
+    /// 创建使用当前用户主目录的匿名化器
+    pub fn new(mode: AnonymizeMode) -> Self {
+        Self::with_home(mode, ConfigLoader::home_dir())
+    }
+
+    /// 创建使用指定主目录的匿名化器
+    ///
+    /// # 参数
+    /// * `mode` - 匿名化方式
+    /// * `home` - 替换为 `~` 的主目录，为 `None` 时不替换
+    pub fn with_home(mode: AnonymizeMode, home: Option<PathBuf>) -> Self {
+        PathAnonymizer { mode, home }
+    }
+
+    /// 匿名化单个匹配项的路径，保留最后一个组成部分
+    pub fn anonymize(&self, path: &Path) -> PathBuf {
+        self.anonymize_components(path, true)
+    }
+
+    /// 匿名化删除结果中的所有路径，大小和统计不变
+    pub fn anonymize_delete_result(&self, delete_result: &DeleteResult) -> DeleteResult {
+        let paths = |paths: &[PathBuf]| paths.iter().map(|p| self.anonymize(p)).collect();
+        let failures = |failures: &[(PathBuf, String)]| {
+            failures
+                .iter()
+                .map(|(path, error)| (self.anonymize(path), self.anonymize_message(error, path)))
+                .collect()
+        };
+        DeleteResult {
+            deleted_files: paths(&delete_result.deleted_files),
+            deleted_dirs: paths(&delete_result.deleted_dirs),
+            failed_files: failures(&delete_result.failed_files),
+            failed_dirs: failures(&delete_result.failed_dirs),
+            total_size: delete_result.total_size,
+            total_physical_size: delete_result.total_physical_size,
+            inodes_freed: delete_result.inodes_freed,
+            extension_sizes: delete_result.extension_sizes.clone(),
+            safety_refusals: delete_result
+                .safety_refusals
+                .iter()
+                .map(|(path, violation)| {
+                    (self.anonymize(path), self.anonymize_violation(violation))
+                })
+                .collect(),
+        }
+    }
+
+    /// 匿名化安全规则中记录的路径
+    fn anonymize_violation(&self, violation: &SafetyViolation) -> SafetyViolation {
+        match violation {
+            SafetyViolation::SystemDirectory(path) => {
+                SafetyViolation::SystemDirectory(self.anonymize(path))
+            }
+            SafetyViolation::OtherUsersHome(path) => {
+                SafetyViolation::OtherUsersHome(self.anonymize(path))
+            }
+            SafetyViolation::SpecialFile(path) => {
+                SafetyViolation::SpecialFile(self.anonymize(path))
+            }
+            SafetyViolation::WorkingDirectory(path) => {
+                SafetyViolation::WorkingDirectory(self.anonymize(path))
+            }
+            SafetyViolation::NotFound(path) => SafetyViolation::NotFound(self.anonymize(path)),
+            SafetyViolation::ParentTraversal => SafetyViolation::ParentTraversal,
+        }
+    }
+
+    /// 匿名化分组中的路径；按根路径分组时，组名（扫描根路径）整体匿名化
+    pub fn anonymize_groups(&self, groups: &mut [ItemGroup], group_by: GroupBy) {
+        for group in groups {
+            if group_by == GroupBy::Root {
+                group.name = self
+                    .anonymize_components(Path::new(&group.name), false)
+                    .display()
+                    .to_string();
+            }
+            for (path, _) in &mut group.items {
+                *path = self.anonymize(path);
+            }
+        }
+    }
+
+    /// 匿名化匹配清单中的路径
+    pub fn anonymize_inventory(&self, inventory: &mut ScanInventory) {
+        for item in &mut inventory.items {
+            item.path = self.anonymize(Path::new(&item.path)).display().to_string();
+        }
+    }
+
+    /// 错误信息中可能包含完整路径，将其替换为匿名化后的路径
+    fn anonymize_message(&self, message: &str, path: &Path) -> String {
+        let original = path.display().to_string();
+        if original.is_empty() {
+            return message.to_string();
+        }
+        message.replace(&original, &self.anonymize(path).display().to_string())
+    }
+
+    /// 替换主目录并隐藏其后的目录名称
+    ///
+    /// # 参数
+    /// * `path` - 原始路径
+    /// * `keep_last` - 是否保留最后一个组成部分
+    fn anonymize_components(&self, path: &Path, keep_last: bool) -> PathBuf {
+        let (mut anonymized, rest) = match self
+            .home
+            .as_deref()
+            .and_then(|home| path.strip_prefix(home).ok())
+        {
+            Some(rest) => (PathBuf::from("~"), rest),
+            None => (PathBuf::new(), path),
+        };
+
+        let count = rest.components().count();
+        for (index, component) in rest.components().enumerate() {
+            match component {
+                Component::Normal(name) if !(keep_last && index + 1 == count) => {
+                    anonymized.push(self.anonymize_name(&name.to_string_lossy()));
+                }
+                other => anonymized.push(other.as_os_str()),
+            }
+        }
+        anonymized
+    }
+
+    /// 按模式隐藏单个名称
+    fn anonymize_name(&self, name: &str) -> String {
+        match self.mode {
+            AnonymizeMode::Redact => "*".to_string(),
+            AnonymizeMode::Hash => format!("{:08x}", Self::fnv1a(name.as_bytes())),
+        }
+    }
+
+    /// 32 位 FNV-1a 哈希，结果不随运行或 Rust 版本变化（标准库的哈希器不保证这一点）
+    fn fnv1a(bytes: &[u8]) -> u32 {
+        bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        })
+    }
+}
+
+/// 一组已删除的项目
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemGroup {
+    /// 分组名称（根路径、模式或项目类型）
+    pub name: String,
+    /// 组内的项目及其大小，按路径排序
+    pub items: Vec<(PathBuf, u64)>,
+    /// 组内项目的总大小（字节）
+    pub total_size: u64,
 }
 
 /// 报告生成器，负责收集统计信息和格式化报告
@@ -51,7 +428,30 @@ impl ReportGenerator {
             files_failed: delete_result.failed_files.len(),
             dirs_failed: delete_result.failed_dirs.len(),
             space_freed: delete_result.total_size,
+            physical_space_freed: delete_result.total_physical_size,
+            inodes_freed: delete_result.inodes_freed,
+            safety_refusals: delete_result.safety_refusals.clone(),
             time_taken,
+            size_histogram: Self::size_histogram(
+                search_result.item_sizes.iter().map(|(_, size)| *size),
+            ),
+            project_types: Self::summarize_by_project_type(search_result, delete_result),
+            skipped_excluded: search_result.skipped_excluded,
+            skipped_size: search_result.skipped_size,
+            skipped_age: search_result.skipped_age,
+            skipped_in_match: search_result.skipped_in_match,
+            walk_errors: search_result.walk_errors,
+            extension_breakdown: Self::extension_breakdown(delete_result.extension_sizes.as_ref()),
+            unmatched_patterns: if search_result.cancelled {
+                Vec::new()
+            } else {
+                search_result
+                    .pattern_matches
+                    .iter()
+                    .filter(|(_, count)| *count == 0)
+                    .map(|(pattern, _)| pattern.clone())
+                    .collect()
+            },
         }
     }
 
@@ -65,6 +465,25 @@ impl ReportGenerator {
     /// # 返回
     /// 格式化后的报告字符串
     pub fn format_report(stats: &Stats, delete_result: &DeleteResult, verbose: bool) -> String {
+        Self::format_report_with_groups(stats, delete_result, verbose, None)
+    }
+
+    /// 格式化报告，详细模式下可以将已删除的项目分组显示
+    ///
+    /// # 参数
+    /// * `stats` - 统计信息
+    /// * `delete_result` - 删除结果（用于显示详细信息）
+    /// * `verbose` - 是否使用详细模式
+    /// * `groups` - 已删除项目的分组（为 `None` 时按目录和文件分别列出）
+    ///
+    /// # 返回
+    /// 格式化后的报告字符串
+    pub fn format_report_with_groups(
+        stats: &Stats,
+        delete_result: &DeleteResult,
+        verbose: bool,
+        groups: Option<(GroupBy, &[ItemGroup])>,
+    ) -> String {
         if verbose {
             // 计算匹配的数量（已删除 + 失败）
             let files_matched = stats.files_deleted + stats.files_failed;
@@ -80,7 +499,7 @@ impl ReportGenerator {
                  - Directories deleted: {}\n\
                  - Files failed: {}\n\
                  - Directories failed: {}\n\
-                 - Space freed: {}\n\
+                 - Space freed: {}{}\n\
                  - Time taken: {:.2}s",
                 stats.files_scanned,
                 stats.dirs_scanned,
@@ -90,12 +509,61 @@ impl ReportGenerator {
                 stats.dirs_deleted,
                 stats.files_failed,
                 stats.dirs_failed,
-                Self::format_size(stats.space_freed),
+                Self::format_space_freed(stats),
+                stats
+                    .inodes_freed
+                    .map(|inodes| format!("\n- Inodes freed: {}", inodes))
+                    .unwrap_or_default(),
                 stats.time_taken.as_secs_f64()
             );
 
+            // 添加匹配项的大小分布
+            if stats.size_histogram.iter().any(|(_, count, _)| *count > 0) {
+                report.push_str("\n\n📦 Size Distribution:");
+                for (bucket, count, bytes) in &stats.size_histogram {
+                    report.push_str(&format!(
+                        "\n   - {:>10}: {} items, {}",
+                        bucket.label(),
+                        count,
+                        Self::format_size(*bytes)
+                    ));
+                }
+            }
+
+            // 添加按项目类型的汇总（只有无法识别的项目时不显示）
+            if stats
+                .project_types
+                .iter()
+                .any(|summary| summary.project_type != ProjectType::Unknown)
+            {
+                report.push_str(&Self::format_project_types(&stats.project_types));
+            }
+
+            // 添加按扩展名汇总的释放空间
+            if !stats.extension_breakdown.is_empty() {
+                report.push_str(&Self::format_extension_breakdown(
+                    &stats.extension_breakdown,
+                ));
+            }
+
+            // 添加没有匹配的模式，便于清理共享配置中不再需要的模式
+            if !stats.unmatched_patterns.is_empty() {
+                report.push_str("\n\n🚫 Patterns With No Matches:");
+                for pattern in &stats.unmatched_patterns {
+                    report.push_str(&format!("\n   - {}", pattern));
+                }
+            }
+
+            // 添加按原因统计的跳过数量，便于调整配置
+            report.push_str(&Self::format_skipped(stats));
+
+            // 添加分组的删除项目，代替按目录和文件分别列出
+            if let Some((group_by, groups)) = groups {
+                report.push_str(&Self::format_groups(group_by, groups));
+            }
+
             // 添加删除的目录详细信息
-            if !delete_result.deleted_dirs.is_empty() {
+            if groups.is_none() && !delete_result.deleted_dirs.is_empty() {
                 report.push_str("\n\n📁 Deleted Directories:");
                 for (idx, dir) in delete_result.deleted_dirs.iter().enumerate() {
                     if idx < 50 {
@@ -112,7 +580,7 @@ impl ReportGenerator {
             }
 
             // 添加删除的文件详细信息
-            if !delete_result.deleted_files.is_empty() {
+            if groups.is_none() && !delete_result.deleted_files.is_empty() {
                 report.push_str("\n\n📄 Deleted Files:");
                 for (idx, file) in delete_result.deleted_files.iter().enumerate() {
                     if idx < 50 {
@@ -146,15 +614,760 @@ impl ReportGenerator {
 
             report
         } else {
-            format!(
+            let mut summary = format!(
                 "Cleaned {} directories, {} files, freed {}",
                 stats.dirs_deleted,
                 stats.files_deleted,
-                Self::format_size(stats.space_freed)
+                Self::format_space_freed(stats)
+            );
+            if let Some(inodes) = stats.inodes_freed {
+                summary.push_str(&format!(" ({} inodes)", inodes));
+            }
+            summary
+        }
+    }
+
+    /// 将已删除的项目分组，组按总大小从大到小排列（大小相同时按名称排列）
+    ///
+    /// # 参数
+    /// * `search_result` - 搜索结果（提供每个项目的来源和大小）
+    /// * `delete_result` - 删除结果
+    /// * `group_by` - 分组方式
+    ///
+    /// # 返回
+    /// 分组列表
+    pub fn group_items(
+        search_result: &SearchResult,
+        delete_result: &DeleteResult,
+        group_by: GroupBy,
+    ) -> Vec<ItemGroup> {
+        let origins: HashMap<&Path, _> = search_result
+            .item_origins
+            .iter()
+            .map(|origin| (origin.path.as_path(), origin))
+            .collect();
+        let sizes: HashMap<&Path, u64> = search_result
+            .item_sizes
+            .iter()
+            .map(|(path, size)| (path.as_path(), *size))
+            .collect();
+
+        let mut groups: Vec<ItemGroup> = Vec::new();
+        for path in delete_result
+            .deleted_dirs
+            .iter()
+            .chain(&delete_result.deleted_files)
+        {
+            let origin = origins.get(path.as_path());
+            let name = match (group_by, origin) {
+                (GroupBy::Root, Some(origin)) => origin.root.display().to_string(),
+                (GroupBy::Pattern, Some(origin)) => origin.pattern.clone(),
+                (GroupBy::Type, Some(origin)) => Self::enclosing_project_type(path, &origin.root)
+                    .name()
+                    .to_string(),
+                (_, None) => "(unknown)".to_string(),
+            };
+            let size = sizes.get(path.as_path()).copied().unwrap_or(0);
+
+            match groups.iter_mut().find(|group| group.name == name) {
+                Some(group) => {
+                    group.items.push((path.clone(), size));
+                    group.total_size += size;
+                }
+                None => groups.push(ItemGroup {
+                    name,
+                    items: vec![(path.clone(), size)],
+                    total_size: size,
+                }),
+            }
+        }
+
+        for group in &mut groups {
+            group.items.sort();
+        }
+        groups.sort_by(|a, b| {
+            b.total_size
+                .cmp(&a.total_size)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        groups
+    }
+
+    /// 从项目的上级目录开始向上查找（不超过扫描根路径），返回第一个能识别的项目类型
+    fn enclosing_project_type(path: &Path, root: &Path) -> ProjectType {
+        Self::enclosing_project(path, root)
+            .map(|(_, project_type)| project_type)
+            .unwrap_or(ProjectType::Unknown)
+    }
+
+    /// 查找路径所在的项目：从上级目录开始向上查找（不超过扫描根路径）第一个能识别类型的目录
+    ///
+    /// # 参数
+    /// * `path` - 匹配项的路径
+    /// * `root` - 扫描根路径
+    ///
+    /// # 返回
+    /// 项目目录及其类型，找不到时返回 `None`
+    pub fn enclosing_project(path: &Path, root: &Path) -> Option<(PathBuf, ProjectType)> {
+        path.ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(root))
+            .map(|dir| (dir, ConfigLoader::detect_project_type(dir)))
+            .find(|(_, project_type)| *project_type != ProjectType::Unknown)
+            .map(|(dir, project_type)| (dir.to_path_buf(), project_type))
+    }
+
+    /// 按所在项目的类型汇总已删除的项目
+    ///
+    /// # 参数
+    /// * `search_result` - 搜索结果（提供每个项目的扫描根路径和大小）
+    /// * `delete_result` - 删除结果
+    ///
+    /// # 返回
+    /// 每种项目类型的汇总，按总大小从大到小排列（大小相同时按类型名称排列）
+    pub fn summarize_by_project_type(
+        search_result: &SearchResult,
+        delete_result: &DeleteResult,
+    ) -> Vec<ProjectTypeSummary> {
+        let roots: HashMap<&Path, &Path> = search_result
+            .item_origins
+            .iter()
+            .map(|origin| (origin.path.as_path(), origin.root.as_path()))
+            .collect();
+        let sizes: HashMap<&Path, u64> = search_result
+            .item_sizes
+            .iter()
+            .map(|(path, size)| (path.as_path(), *size))
+            .collect();
+
+        // 同一目录下的项目属于同一个项目，只需查找一次
+        let mut projects_by_parent: HashMap<&Path, Option<(PathBuf, ProjectType)>> = HashMap::new();
+        let mut summaries: Vec<(ProjectTypeSummary, HashSet<PathBuf>)> = Vec::new();
+        for path in delete_result
+            .deleted_dirs
+            .iter()
+            .chain(&delete_result.deleted_files)
+        {
+            let project = match (path.parent(), roots.get(path.as_path())) {
+                (Some(parent), Some(root)) => projects_by_parent
+                    .entry(parent)
+                    .or_insert_with(|| Self::enclosing_project(path, root))
+                    .clone(),
+                _ => None,
+            };
+            let (project_dir, project_type) = match project {
+                Some((dir, project_type)) => (Some(dir), project_type),
+                None => (None, ProjectType::Unknown),
+            };
+            let size = sizes.get(path.as_path()).copied().unwrap_or(0);
+
+            let index = match summaries
+                .iter()
+                .position(|(summary, _)| summary.project_type == project_type)
+            {
+                Some(index) => index,
+                None => {
+                    summaries.push((
+                        ProjectTypeSummary {
+                            project_type,
+                            projects: 0,
+                            items: 0,
+                            total_size: 0,
+                        },
+                        HashSet::new(),
+                    ));
+                    summaries.len() - 1
+                }
+            };
+            let (summary, project_dirs) = &mut summaries[index];
+            summary.items += 1;
+            summary.total_size += size;
+            if let Some(dir) = project_dir {
+                if project_dirs.insert(dir) {
+                    summary.projects += 1;
+                }
+            }
+        }
+
+        let mut summaries: Vec<ProjectTypeSummary> =
+            summaries.into_iter().map(|(summary, _)| summary).collect();
+        summaries.sort_by(|a, b| {
+            b.total_size
+                .cmp(&a.total_size)
+                .then_with(|| a.project_type.name().cmp(b.project_type.name()))
+        });
+        summaries
+    }
+
+    /// 汇总多个项目按扩展名的大小
+    ///
+    /// # 参数
+    /// * `items` - 每组按扩展名的大小（如 `DeleteResult::extension_sizes`）
+    ///
+    /// # 返回
+    /// 扩展名及总字节数，按大小从大到小排列（大小相同时按扩展名排列）
+    pub fn extension_breakdown<'a>(
+        items: impl IntoIterator<Item = &'a HashMap<String, u64>>,
+    ) -> Vec<(String, u64)> {
+        let mut totals: HashMap<String, u64> = HashMap::new();
+        for sizes in items {
+            for (extension, size) in sizes {
+                *totals.entry(extension.clone()).or_insert(0) += size;
+            }
+        }
+        let mut breakdown: Vec<(String, u64)> = totals.into_iter().collect();
+        breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        breakdown
+    }
+
+    /// 格式化按扩展名汇总的释放空间，最多显示 10 种扩展名
+    fn format_extension_breakdown(breakdown: &[(String, u64)]) -> String {
+        let mut section = String::from("\n\n🔤 Space Freed by Extension:");
+        for (extension, size) in breakdown.iter().take(10) {
+            let label = if extension.is_empty() {
+                "no-ext".to_string()
+            } else {
+                format!(".{}", extension)
+            };
+            section.push_str(&format!("\n   - {}: {}", label, Self::format_size(*size)));
+        }
+        if breakdown.len() > 10 {
+            section.push_str(&format!(
+                "\n   ... and {} more extensions",
+                breakdown.len() - 10
+            ));
+        }
+        section
+    }
+
+    /// 格式化按原因统计的跳过数量，全部为 0 时返回空字符串
+    fn format_skipped(stats: &Stats) -> String {
+        let counts = [
+            ("excluded", stats.skipped_excluded),
+            ("outside size range", stats.skipped_size),
+            ("outside age range", stats.skipped_age),
+            ("inside a matched folder", stats.skipped_in_match),
+            ("traversal errors", stats.walk_errors),
+        ];
+        if counts.iter().all(|(_, count)| *count == 0) {
+            return String::new();
+        }
+        let mut section = String::from("\n\n⏭️  Skipped Entries:");
+        for (label, count) in counts.iter().filter(|(_, count)| *count > 0) {
+            section.push_str(&format!("\n   - {}: {}", label, count));
+        }
+        section
+    }
+
+    /// 格式化按项目类型汇总的释放空间
+    fn format_project_types(summaries: &[ProjectTypeSummary]) -> String {
+        let mut output = "\n\n🧩 Space Freed by Project Type:".to_string();
+        for summary in summaries {
+            let line = if summary.project_type == ProjectType::Unknown {
+                format!(
+                    "\n   - Other: {} across {} items",
+                    Self::format_size(summary.total_size),
+                    summary.items
+                )
+            } else {
+                format!(
+                    "\n   - {}: {} across {} project{}",
+                    summary.project_type.name(),
+                    Self::format_size(summary.total_size),
+                    summary.projects,
+                    if summary.projects == 1 { "" } else { "s" }
+                )
+            };
+            output.push_str(&line);
+        }
+        output
+    }
+
+    /// 格式化分组的删除项目，每组显示项目数量和小计
+    fn format_groups(group_by: GroupBy, groups: &[ItemGroup]) -> String {
+        let mut output = format!("\n\n🗂️  Deleted Items by {}:", group_by.label());
+        for group in groups {
+            output.push_str(&format!(
+                "\n   {} ({} items, {})",
+                group.name,
+                group.items.len(),
+                Self::format_size(group.total_size)
+            ));
+            for (path, _) in group.items.iter().take(50) {
+                output.push_str(&format!("\n     - {}", path.display()));
+            }
+            if group.items.len() > 50 {
+                output.push_str(&format!(
+                    "\n     ... and {} more items",
+                    group.items.len() - 50
+                ));
+            }
+        }
+        output
+    }
+
+    /// 统计各大小区间的匹配项数量和总字节数
+    ///
+    /// # 参数
+    /// * `sizes` - 每个匹配项的大小（字节）
+    ///
+    /// # 返回
+    /// 按区间从小到大排列的 (区间, 数量, 总字节数)，包含所有区间
+    pub fn size_histogram(sizes: impl IntoIterator<Item = u64>) -> Vec<(SizeBucket, usize, u64)> {
+        let mut histogram: Vec<(SizeBucket, usize, u64)> = SizeBucket::ALL
+            .iter()
+            .map(|bucket| (*bucket, 0, 0))
+            .collect();
+        for size in sizes {
+            let bucket = SizeBucket::for_size(size);
+            if let Some(entry) = histogram.iter_mut().find(|(b, _, _)| *b == bucket) {
+                entry.1 += 1;
+                entry.2 += size;
+            }
+        }
+        histogram
+    }
+
+    /// 生成扫描清单
+    ///
+    /// # 参数
+    /// * `items` - 匹配项列表
+    /// * `files_scanned` - 扫描的文件数量
+    /// * `dirs_scanned` - 扫描的目录数量
+    ///
+    /// # 返回
+    /// 扫描清单
+    pub fn scan_inventory(
+        items: &[Match],
+        files_scanned: usize,
+        dirs_scanned: usize,
+    ) -> ScanInventory {
+        ScanInventory {
+            items: items.iter().map(InventoryItem::from_match).collect(),
+            total_size: items.iter().map(|item| item.size).sum(),
+            dirs_matched: items.iter().filter(|item| item.is_dir).count(),
+            files_matched: items.iter().filter(|item| !item.is_dir).count(),
+            files_scanned,
+            dirs_scanned,
+        }
+    }
+
+    /// 从搜索结果生成扫描清单，与实际运行一样不包括按保留规则保留和合并的重复项
+    ///
+    /// # 参数
+    /// * `result` - 搜索结果
+    ///
+    /// # 返回
+    /// 扫描清单，匹配项按发现顺序排列
+    pub fn scan_inventory_from_result(result: &SearchResult) -> ScanInventory {
+        let folders: HashSet<PathBuf> = result.folders.iter().collect();
+        let files: HashSet<PathBuf> = result.files.iter().collect();
+        let estimated: HashSet<&PathBuf> = result.estimated_sizes.iter().collect();
+        let confirm: HashSet<&PathBuf> = result
+            .needs_confirmation
+            .iter()
+            .map(|(path, _)| path)
+            .collect();
+        let sizes: HashMap<&PathBuf, u64> = result
+            .item_sizes
+            .iter()
+            .map(|(path, size)| (path, *size))
+            .collect();
+        let items: Vec<Match> = result
+            .item_origins
+            .iter()
+            .filter(|origin| folders.contains(&origin.path) || files.contains(&origin.path))
+            .map(|origin| Match {
+                path: origin.path.clone(),
+                is_dir: folders.contains(&origin.path),
+                size: sizes.get(&origin.path).copied().unwrap_or(0),
+                physical_size: 0,
+                size_is_estimate: estimated.contains(&origin.path),
+                pattern: origin.pattern.clone(),
+                delete_mode: origin.delete_mode,
+                needs_confirmation: confirm.contains(&origin.path),
+            })
+            .collect();
+        ScanInventory {
+            // 匹配目录中的文件（同时收集目录和文件时）不重复计入总大小
+            total_size: result.total_size,
+            ..Self::scan_inventory(
+                &items,
+                result.total_files_scanned,
+                result.total_dirs_scanned,
             )
         }
     }
 
+    /// 格式化单行状态，便于在 shell 提示符或状态栏中显示
+    ///
+    /// 格式固定为 `bc: <目录数>d <文件数>f <释放空间> <耗时>s`，字段以单个空格分隔，
+    /// 例如 `bc: 34d 1.2kf 4.8G 2.1s`：
+    /// - 目录数和文件数为已删除（预览模式下为将要删除）的数量，1000 及以上缩写为 `k`/`M`/`G`，保留一位小数
+    /// - 释放空间按 1024 进制缩写为 `B`/`K`/`M`/`G`/`T`，小于 1 KB 时为整数字节，否则保留一位小数
+    /// - 耗时以秒为单位，保留一位小数
+    ///
+    /// # 参数
+    /// * `stats` - 统计信息
+    ///
+    /// # 返回
+    /// 单行状态字符串（不含换行符）
+    pub fn format_status_line(stats: &Stats) -> String {
+        format!(
+            "bc: {}d {}f {} {:.1}s",
+            Self::compact_count(stats.dirs_deleted),
+            Self::compact_count(stats.files_deleted),
+            Self::compact_size(stats.space_freed),
+            stats.time_taken.as_secs_f64()
+        )
+    }
+
+    /// 格式化 JSON 统计摘要，字段与 `format_summary_csv` 的列相同
+    ///
+    /// 另外在 `safety_refusals` 中列出每个被安全检查拒绝的项目及规则标识（`path`、`rule`），
+    /// 便于与普通的删除失败区分处理。
+    ///
+    /// # 参数
+    /// * `stats` - 统计信息
+    ///
+    /// # 返回
+    /// 单个 JSON 对象，未统计物理大小时 `physical_space_freed` 为 `null`
+    pub fn format_summary_json(stats: &Stats) -> String {
+        let mut summary: serde_json::Map<String, serde_json::Value> = Self::summary_fields(stats)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.into()))
+            .collect();
+        let refusals = stats
+            .safety_refusals
+            .iter()
+            .map(|(path, violation)| {
+                serde_json::json!({
+                    "path": path.display().to_string(),
+                    "rule": violation.code(),
+                })
+            })
+            .collect();
+        summary.insert(
+            "safety_refusals".to_string(),
+            serde_json::Value::Array(refusals),
+        );
+        serde_json::Value::Object(summary).to_string()
+    }
+
+    /// 格式化 CSV 统计摘要：一行表头和一行数值
+    ///
+    /// # 参数
+    /// * `stats` - 统计信息
+    ///
+    /// # 返回
+    /// 两行 CSV（不含结尾换行符），未统计物理大小时 `physical_space_freed` 为空
+    pub fn format_summary_csv(stats: &Stats) -> String {
+        let fields = Self::summary_fields(stats);
+        let header: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+        let values: Vec<String> = fields
+            .iter()
+            .map(|(_, value)| value.map(|v| v.to_string()).unwrap_or_default())
+            .collect();
+        format!("{}\n{}", header.join(","), values.join(","))
+    }
+
+    /// 机器可读统计摘要的字段名和数值（没有数值时为 `None`）
+    fn summary_fields(stats: &Stats) -> Vec<(&'static str, Option<u64>)> {
+        vec![
+            ("files_scanned", Some(stats.files_scanned as u64)),
+            ("dirs_scanned", Some(stats.dirs_scanned as u64)),
+            ("files_deleted", Some(stats.files_deleted as u64)),
+            ("dirs_deleted", Some(stats.dirs_deleted as u64)),
+            ("files_failed", Some(stats.files_failed as u64)),
+            ("dirs_failed", Some(stats.dirs_failed as u64)),
+            ("safety_refused", Some(stats.safety_refusals.len() as u64)),
+            ("space_freed", Some(stats.space_freed)),
+            ("physical_space_freed", stats.physical_space_freed),
+            ("inodes_freed", stats.inodes_freed),
+            ("skipped_excluded", Some(stats.skipped_excluded as u64)),
+            ("skipped_size", Some(stats.skipped_size as u64)),
+            ("skipped_age", Some(stats.skipped_age as u64)),
+            ("skipped_in_match", Some(stats.skipped_in_match as u64)),
+            ("walk_errors", Some(stats.walk_errors as u64)),
+            ("time_taken_ms", Some(stats.time_taken.as_millis() as u64)),
+        ]
+    }
+
+    /// 缩写数量，如 `1234` -> `1.2k`
+    fn compact_count(count: usize) -> String {
+        const UNITS: &[&str] = &["k", "M", "G"];
+        if count < 1000 {
+            return count.to_string();
+        }
+        let mut value = count as f64 / 1000.0;
+        let mut unit_idx = 0;
+        while value >= 1000.0 && unit_idx < UNITS.len() - 1 {
+            value /= 1000.0;
+            unit_idx += 1;
+        }
+        format!("{:.1}{}", value, UNITS[unit_idx])
+    }
+
+    /// 缩写大小，如 `5153960755` -> `4.8G`
+    fn compact_size(bytes: u64) -> String {
+        const UNITS: &[&str] = &["K", "M", "G", "T"];
+        if bytes < 1024 {
+            return format!("{}B", bytes);
+        }
+        let mut size = bytes as f64 / 1024.0;
+        let mut unit_idx = 0;
+        while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit_idx += 1;
+        }
+        format!("{:.1}{}", size, UNITS[unit_idx])
+    }
+
+    /// 格式化每个根路径所在文件系统在清理前后的可用空间变化
+    ///
+    /// # 参数
+    /// * `roots` - 根路径及其清理前、清理后的空间信息
+    ///
+    /// # 返回
+    /// 格式化后的空间变化报告
+    pub fn format_free_space(roots: &[(PathBuf, DiskSpace, DiskSpace)]) -> String {
+        let mut report = String::from("💽 Free Space:");
+        for (root, before, after) in roots {
+            report.push_str(&format!(
+                "\n   - {}: freed {}, {} available, disk now {:.0}% full",
+                root.display(),
+                Self::format_size(after.available.saturating_sub(before.available)),
+                Self::format_size(after.available),
+                after.used_percent()
+            ));
+        }
+        report
+    }
+
+    /// 格式化可用空间高于 `--min-free` 阈值、无需清理时的说明
+    ///
+    /// # 参数
+    /// * `space` - 文件系统的空间信息
+    /// * `threshold` - 可用空间阈值
+    ///
+    /// # 返回
+    /// 如 `23% free, above 10% threshold; nothing to do`
+    pub fn format_free_space_skip(space: &DiskSpace, threshold: &FreeSpaceThreshold) -> String {
+        let (free, threshold) = match *threshold {
+            FreeSpaceThreshold::Bytes(bytes) => {
+                (Self::format_size(space.available), Self::format_size(bytes))
+            }
+            FreeSpaceThreshold::Percent(percent) => (
+                format!("{:.0}%", space.available_percent()),
+                format!("{}%", percent),
+            ),
+        };
+        format!(
+            "{} free, above {} threshold; nothing to do",
+            free, threshold
+        )
+    }
+
+    /// 格式化未被清理的条目列表（反向报告）
+    ///
+    /// 每行一个条目，格式为 `<原因代码>\t<路径>`，便于其他工具解析
+    ///
+    /// # 参数
+    /// * `rejected` - 被扫描但未被选中清理的条目及原因
+    ///
+    /// # 返回
+    /// 格式化后的列表字符串
+    pub fn format_rejected(rejected: &[(PathBuf, RejectReason)]) -> String {
+        rejected
+            .iter()
+            .map(|(path, reason)| format!("{}\t{}", reason.code(), path.display()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 将配置中的模式转换为等价的 `find` 命令，便于在已有的清理脚本中使用
+    ///
+    /// 只做模式的语法转换，不访问文件系统：
+    /// - 文件夹模式转换为 `-type d -name <名称> -prune`，不再进入匹配的目录
+    /// - 文件模式转换为 `-type f -name <模式>`，包含 `/` 的模式转换为 `-path ./<模式>`
+    /// - 花括号展开为多个条件；默认的隐藏文件策略下，通配符模式附加 `! -name '.*'`
+    /// - 带内容条件的模式无法表达，以注释行列出
+    ///
+    /// 注意 `find -path` 中的 `*` 可以跨越 `/`，比 bc 的路径模式更宽松。
+    ///
+    /// # 参数
+    /// * `config` - 配置
+    ///
+    /// # 返回
+    /// `find` 命令（前面可能有说明未转换模式的注释行）
+    pub fn emit_find(config: &Config) -> String {
+        let mut lines = Self::untranslated_comments(config);
+
+        let folder_tests: Vec<String> = Self::folder_names(config)
+            .iter()
+            .map(|name| {
+                let escaped = Self::escape_brackets(name);
+                if name.contains('/') {
+                    format!(
+                        "-path {}",
+                        Self::shell_quote(&format!("./{}", escaped.trim_start_matches('/')))
+                    )
+                } else {
+                    format!("-name {}", Self::shell_quote(&escaped))
+                }
+            })
+            .collect();
+        let file_tests: Vec<String> = Self::translatable_file_patterns(config)
+            .iter()
+            .map(|pattern| {
+                let escaped = Self::escape_brackets(pattern);
+                if pattern.contains('/') {
+                    format!(
+                        "-path {}",
+                        Self::shell_quote(&format!("./{}", escaped.trim_start_matches('/')))
+                    )
+                } else if config.options.glob_dot_policy == GlobDotPolicy::Explicit
+                    && !pattern.starts_with('.')
+                    && pattern.contains(['*', '?'])
+                {
+                    format!("\\( -name {} ! -name '.*' \\)", Self::shell_quote(&escaped))
+                } else {
+                    format!("-name {}", Self::shell_quote(&escaped))
+                }
+            })
+            .collect();
+
+        let mut command = String::from("find .");
+        if !config.options.recursive {
+            command.push_str(" -maxdepth 1");
+        }
+        let mut branches = Vec::new();
+        if !folder_tests.is_empty() {
+            branches.push(format!(
+                "\\( -type d \\( {} \\) -prune -print \\)",
+                folder_tests.join(" -o ")
+            ));
+        }
+        if !file_tests.is_empty() {
+            branches.push(format!(
+                "\\( -type f \\( {} \\) -print \\)",
+                file_tests.join(" -o ")
+            ));
+        }
+        if branches.is_empty() {
+            command.push_str(" -false");
+        } else {
+            command.push(' ');
+            command.push_str(&branches.join(" -o "));
+        }
+        lines.push(command);
+        lines.join("\n")
+    }
+
+    /// 将配置中的模式转换为等价的 `rsync` 排除规则（用于 `--filter='merge FILE'`）
+    ///
+    /// 文件夹模式转换为 `- <名称>/`（只匹配目录），文件模式转换为 `- <模式>`，
+    /// 包含 `/` 的模式锚定到传输的根目录。rsync 的通配符可以匹配开头的 `.`，
+    /// 带内容条件的模式无法表达，以注释行列出。
+    ///
+    /// # 参数
+    /// * `config` - 配置
+    ///
+    /// # 返回
+    /// 每行一条规则
+    pub fn emit_rsync(config: &Config) -> String {
+        let mut lines = Self::untranslated_comments(config);
+        for name in Self::folder_names(config) {
+            let escaped = Self::escape_brackets(&name);
+            if name.contains('/') {
+                lines.push(format!("- /{}/", escaped.trim_start_matches('/')));
+            } else {
+                lines.push(format!("- {}/", escaped));
+            }
+        }
+        for pattern in Self::translatable_file_patterns(config) {
+            let escaped = Self::escape_brackets(&pattern);
+            if pattern.contains('/') {
+                lines.push(format!("- /{}", escaped.trim_start_matches('/')));
+            } else {
+                lines.push(format!("- {}", escaped));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// 展开花括号后的文件夹名称或路径（去掉末尾的 `/`），跳过无法转换的模式
+    fn folder_names(config: &Config) -> Vec<String> {
+        config
+            .clean
+            .folders
+            .iter()
+            .filter(|pattern| Self::untranslatable_reason(pattern).is_none())
+            .flat_map(|pattern| SearchEngine::expand_braces(pattern.trim_end_matches('/')))
+            .collect()
+    }
+
+    /// 展开花括号后的文件模式，跳过无法转换的模式
+    fn translatable_file_patterns(config: &Config) -> Vec<String> {
+        config
+            .clean
+            .files
+            .iter()
+            .filter(|pattern| Self::untranslatable_reason(pattern).is_none())
+            .flat_map(|pattern| SearchEngine::expand_braces(pattern))
+            .collect()
+    }
+
+    /// 列出无法转换的模式的注释行
+    fn untranslated_comments(config: &Config) -> Vec<String> {
+        let clean = &config.clean;
+        clean
+            .folders
+            .iter()
+            .chain(&clean.files)
+            .filter_map(|pattern| {
+                Self::untranslatable_reason(pattern)
+                    .map(|reason| format!("# not translated ({}): {}", reason, pattern))
+            })
+            .collect()
+    }
+
+    /// 模式无法转换为 `find`/`rsync` 规则的原因：正则表达式、内容条件，
+    /// 或可以跨越多级目录的 `**`（两者的通配符语义与 bc 不同）
+    fn untranslatable_reason(pattern: &str) -> Option<&'static str> {
+        if SearchEngine::is_regex_pattern(pattern) {
+            Some("regular expression")
+        } else if SearchEngine::split_content_pattern(pattern).1.is_some() {
+            Some("content condition")
+        } else if pattern.split('/').any(|segment| segment == "**") {
+            Some("recursive wildcard")
+        } else {
+            None
+        }
+    }
+
+    /// bc 的模式不支持字符集，`[` 和 `]` 按字面匹配，转换时需要转义
+    fn escape_brackets(pattern: &str) -> String {
+        pattern.replace('[', "\\[").replace(']', "\\]")
+    }
+
+    /// 用单引号包裹字符串，使其可以安全地作为 shell 参数
+    fn shell_quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+
+    /// 格式化释放的空间，启用物理大小统计时同时显示逻辑大小和物理大小
+    fn format_space_freed(stats: &Stats) -> String {
+        match stats.physical_space_freed {
+            Some(physical) => format!(
+                "logical {} / physical {}",
+                Self::format_size(stats.space_freed),
+                Self::format_size(physical)
+            ),
+            None => Self::format_size(stats.space_freed),
+        }
+    }
+
     fn format_size(bytes: u64) -> String {
         const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
         let mut size = bytes as f64;
@@ -172,20 +1385,22 @@ impl ReportGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use crate::delete::DeleteEngine;
     use std::time::Instant;
 
     #[test]
     fn test_collect_stats() {
         let search_result = SearchResult {
-            folders: vec![PathBuf::from("/test/dir1"), PathBuf::from("/test/dir2")],
+            folders: vec![PathBuf::from("/test/dir1"), PathBuf::from("/test/dir2")].into(),
             files: vec![
                 PathBuf::from("/test/file1.txt"),
                 PathBuf::from("/test/file2.txt"),
-            ],
+            ]
+            .into(),
             total_size: 2048,
             total_dirs_scanned: 10,
             total_files_scanned: 20,
+            ..Default::default()
         };
 
         let delete_result = DeleteResult {
@@ -197,6 +1412,7 @@ mod tests {
             )],
             failed_dirs: vec![],
             total_size: 1024,
+            ..Default::default()
         };
 
         let start_time = Instant::now();
@@ -213,6 +1429,73 @@ mod tests {
         assert!(stats.time_taken.as_millis() >= 10);
     }
 
+    #[test]
+    fn test_format_skipped() {
+        let stats = Stats {
+            skipped_excluded: 2,
+            skipped_age: 5,
+            walk_errors: 1,
+            ..Default::default()
+        };
+        let report = ReportGenerator::format_report(&stats, &DeleteResult::default(), true);
+        assert!(report.contains(
+            "⏭️  Skipped Entries:\n   - excluded: 2\n   - outside age range: 5\n   - traversal errors: 1"
+        ));
+        // 为 0 的原因不显示，非详细模式不显示
+        assert!(!report.contains("outside size range"));
+        let report = ReportGenerator::format_report(&stats, &DeleteResult::default(), false);
+        assert!(!report.contains("Skipped Entries"));
+
+        let report =
+            ReportGenerator::format_report(&Stats::default(), &DeleteResult::default(), true);
+        assert!(!report.contains("Skipped Entries"));
+    }
+
+    #[test]
+    fn test_unmatched_patterns() {
+        use crate::config::{CleanConfig, Config, Options};
+        use crate::search::SearchEngine;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        std::fs::create_dir_all(project.join("node_modules")).unwrap();
+        std::fs::write(project.join("debug.log"), "log").unwrap();
+
+        // bower_components 和 *.pyc 在这个项目中已经不存在
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string(), "bower_components".to_string()],
+                files: vec!["*.log".to_string(), "*.pyc".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project.to_path_buf()];
+        let search_result = SearchEngine::search(&paths, &config).unwrap();
+        assert_eq!(
+            search_result.pattern_matches,
+            vec![
+                ("node_modules".to_string(), 1),
+                ("bower_components".to_string(), 0),
+                ("*.log".to_string(), 1),
+                ("*.pyc".to_string(), 0),
+            ]
+        );
+
+        let stats = ReportGenerator::collect_stats(
+            &search_result,
+            &DeleteResult::default(),
+            Instant::now(),
+        );
+        assert_eq!(stats.unmatched_patterns, vec!["bower_components", "*.pyc"]);
+
+        let report = ReportGenerator::format_report(&stats, &DeleteResult::default(), true);
+        assert!(report.contains("🚫 Patterns With No Matches:\n   - bower_components\n   - *.pyc"));
+        let report = ReportGenerator::format_report(&stats, &DeleteResult::default(), false);
+        assert!(!report.contains("bower_components"));
+    }
+
     #[test]
     fn test_format_report() {
         let stats = Stats {
@@ -224,6 +1507,7 @@ mod tests {
             dirs_failed: 1,
             space_freed: 1024 * 1024, // 1MB
             time_taken: std::time::Duration::from_secs(1),
+            ..Default::default()
         };
 
         let delete_result = DeleteResult {
@@ -232,6 +1516,7 @@ mod tests {
             failed_files: vec![],
             failed_dirs: vec![],
             total_size: 0,
+            ..Default::default()
         };
 
         // 测试详细模式
@@ -256,6 +1541,7 @@ mod tests {
             failed_files: vec![],
             failed_dirs: vec![],
             total_size: 0,
+            ..Default::default()
         };
 
         // 测试字节
@@ -268,6 +1554,7 @@ mod tests {
             dirs_failed: 0,
             space_freed: 512,
             time_taken: std::time::Duration::from_secs(0),
+            ..Default::default()
         };
         let report = ReportGenerator::format_report(&stats, &empty_delete_result, false);
         assert!(report.contains("B"));
@@ -282,6 +1569,7 @@ mod tests {
             dirs_failed: 0,
             space_freed: 2048,
             time_taken: std::time::Duration::from_secs(0),
+            ..Default::default()
         };
         let report = ReportGenerator::format_report(&stats, &empty_delete_result, false);
         assert!(report.contains("KB"));
@@ -296,8 +1584,620 @@ mod tests {
             dirs_failed: 0,
             space_freed: 2 * 1024 * 1024,
             time_taken: std::time::Duration::from_secs(0),
+            ..Default::default()
         };
         let report = ReportGenerator::format_report(&stats, &empty_delete_result, false);
         assert!(report.contains("MB"));
     }
+
+    #[test]
+    fn test_format_rejected() {
+        let rejected = vec![
+            (PathBuf::from("/test/small.log"), RejectReason::TooSmall),
+            (PathBuf::from("/test/src"), RejectReason::NoPatternMatch),
+        ];
+
+        let output = ReportGenerator::format_rejected(&rejected);
+        assert_eq!(
+            output,
+            "too-small\t/test/small.log\nno-pattern-match\t/test/src"
+        );
+    }
+
+    #[test]
+    fn test_format_report_inodes_freed() {
+        let stats = Stats {
+            files_deleted: 1,
+            inodes_freed: Some(1234),
+            ..Default::default()
+        };
+        let delete_result = DeleteResult::default();
+        let report = ReportGenerator::format_report(&stats, &delete_result, false);
+        assert!(report.ends_with("(1234 inodes)"));
+        let report = ReportGenerator::format_report(&stats, &delete_result, true);
+        assert!(report.contains("\n- Inodes freed: 1234\n"));
+
+        // 未启用时不显示
+        let stats = Stats::default();
+        let report = ReportGenerator::format_report(&stats, &delete_result, true);
+        assert!(!report.contains("Inodes"));
+    }
+
+    #[test]
+    fn test_format_report_physical_size() {
+        let stats = Stats {
+            dirs_deleted: 1,
+            space_freed: 2 * 1024 * 1024,
+            physical_space_freed: Some(1024 * 1024),
+            ..Default::default()
+        };
+        let delete_result = DeleteResult::default();
+
+        let report = ReportGenerator::format_report(&stats, &delete_result, false);
+        assert!(report.contains("logical 2.00 MB / physical 1.00 MB"));
+
+        let report = ReportGenerator::format_report(&stats, &delete_result, true);
+        assert!(report.contains("Space freed: logical 2.00 MB / physical 1.00 MB"));
+
+        // 未启用物理大小统计时只显示逻辑大小
+        let stats = Stats {
+            physical_space_freed: None,
+            ..stats
+        };
+        let report = ReportGenerator::format_report(&stats, &delete_result, false);
+        assert!(report.contains("freed 2.00 MB"));
+        assert!(!report.contains("physical"));
+    }
+
+    #[test]
+    fn test_size_histogram() {
+        const MB: u64 = 1024 * 1024;
+        let sizes = vec![10, 1023, 1024, 512 * 1024, MB, 99 * MB, 100 * MB, 200 * MB];
+        let histogram = ReportGenerator::size_histogram(sizes);
+
+        assert_eq!(
+            histogram,
+            vec![
+                (SizeBucket::UnderKb, 2, 1033),
+                (SizeBucket::KbToMb, 2, 1024 + 512 * 1024),
+                (SizeBucket::MbTo100Mb, 2, 100 * MB),
+                (SizeBucket::Over100Mb, 2, 300 * MB),
+            ]
+        );
+
+        // 详细报告中显示大小分布
+        let stats = Stats {
+            size_histogram: histogram,
+            ..Default::default()
+        };
+        let report = ReportGenerator::format_report(&stats, &DeleteResult::default(), true);
+        assert!(report.contains("Size Distribution"));
+        assert!(report.contains(">100MB: 2 items, 300.00 MB"));
+    }
+
+    #[test]
+    fn test_scan_inventory_json() {
+        let items = vec![
+            Match {
+                path: PathBuf::from("/project/node_modules"),
+                is_dir: true,
+                size: 4096,
+                physical_size: 4096,
+                size_is_estimate: false,
+                pattern: "node_modules".to_string(),
+                delete_mode: Default::default(),
+                needs_confirmation: false,
+            },
+            Match {
+                path: PathBuf::from("/project/debug.log"),
+                is_dir: false,
+                size: 100,
+                physical_size: 4096,
+                size_is_estimate: false,
+                pattern: "*.log".to_string(),
+                delete_mode: Default::default(),
+                needs_confirmation: false,
+            },
+        ];
+        let inventory = ReportGenerator::scan_inventory(&items, 10, 3);
+        let json = serde_json::to_string_pretty(&inventory).unwrap();
+
+        let parsed: ScanInventory = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.items.len(), 2);
+        assert_eq!(parsed.total_size, 4196);
+        assert_eq!(parsed.dirs_matched, 1);
+        assert_eq!(parsed.files_matched, 1);
+        assert_eq!(parsed.items[0].kind, "dir");
+        assert_eq!(parsed.items[1].pattern, "*.log");
+        // UTF-8 路径不输出 lossy_path 字段
+        assert!(!json.contains("lossy_path"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_inventory_item_lossy_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let item = Match {
+            path: PathBuf::from(OsStr::from_bytes(b"/project/\xff.log")),
+            is_dir: false,
+            size: 1,
+            physical_size: 1,
+            size_is_estimate: false,
+            pattern: "*.log".to_string(),
+            delete_mode: Default::default(),
+            needs_confirmation: false,
+        };
+        let inventory_item = InventoryItem::from_match(&item);
+        assert!(inventory_item.lossy_path);
+        assert_eq!(inventory_item.path, "/project/\u{fffd}.log");
+    }
+
+    #[test]
+    fn test_format_free_space() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let before = DiskSpace {
+            total: 100 * GB,
+            available: 43 * GB,
+        };
+        let after = DiskSpace {
+            total: 100 * GB,
+            available: 55 * GB,
+        };
+        let report =
+            ReportGenerator::format_free_space(&[(PathBuf::from("/project"), before, after)]);
+        assert!(report.contains("/project: freed 12.00 GB"));
+        assert!(report.contains("disk now 45% full"));
+
+        // 可用空间高于 --min-free 阈值时的说明
+        let space = DiskSpace {
+            total: 100 * GB,
+            available: 23 * GB,
+        };
+        assert_eq!(
+            ReportGenerator::format_free_space_skip(&space, &FreeSpaceThreshold::Percent(10.0)),
+            "23% free, above 10% threshold; nothing to do"
+        );
+        assert_eq!(
+            ReportGenerator::format_free_space_skip(&space, &FreeSpaceThreshold::Bytes(5 * GB)),
+            "23.00 GB free, above 5.00 GB threshold; nothing to do"
+        );
+    }
+
+    #[test]
+    fn test_extension_breakdown() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        std::fs::create_dir_all(target.join("deps")).unwrap();
+        std::fs::write(target.join("deps").join("libfoo.rlib"), vec![0u8; 300]).unwrap();
+        std::fs::write(target.join("deps").join("libbar.rlib"), vec![0u8; 200]).unwrap();
+        std::fs::write(target.join("main.o"), vec![0u8; 150]).unwrap();
+        std::fs::write(target.join("build-stamp"), vec![0u8; 40]).unwrap();
+        let log = temp_dir.path().join("debug.log");
+        std::fs::write(&log, vec![0u8; 10]).unwrap();
+        let archive = temp_dir.path().join("dist.tar.gz");
+        std::fs::write(&archive, vec![0u8; 150]).unwrap();
+
+        // 目录在计算大小的同一次遍历中按扩展名统计
+        let mut target_sizes = HashMap::new();
+        let (size, _, _) = DeleteEngine::calculate_dir_usage(&target, Some(&mut target_sizes));
+        assert_eq!(size, 690);
+        assert_eq!(target_sizes.get("rlib"), Some(&500));
+        assert_eq!(target_sizes.get("o"), Some(&150));
+        assert_eq!(target_sizes.get(""), Some(&40));
+
+        let mut file_sizes = HashMap::new();
+        DeleteEngine::add_extension_size(&mut file_sizes, &log, 10);
+        DeleteEngine::add_extension_size(&mut file_sizes, &archive, 150);
+        let items = [target_sizes, file_sizes];
+        let breakdown = ReportGenerator::extension_breakdown(&items);
+        assert_eq!(
+            breakdown,
+            vec![
+                ("rlib".to_string(), 500),
+                ("gz".to_string(), 150),
+                ("o".to_string(), 150),
+                ("".to_string(), 40),
+                ("log".to_string(), 10),
+            ]
+        );
+
+        let stats = Stats {
+            extension_breakdown: breakdown,
+            ..Default::default()
+        };
+        let report = ReportGenerator::format_report(&stats, &DeleteResult::default(), true);
+        assert!(report.contains("🔤 Space Freed by Extension:"));
+        assert!(report.contains("   - .rlib: 500.00 B"));
+        assert!(report.contains("   - no-ext: 40.00 B"));
+    }
+
+    #[test]
+    fn test_emit_find_and_rsync() {
+        use crate::config::{CleanConfig, Options};
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules/".to_string()],
+                files: vec![
+                    "*.log".to_string(),
+                    "logs/*.{tmp,bak}".to_string(),
+                    "*.txt content:^BUILD".to_string(),
+                ],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+
+        let find = ReportGenerator::emit_find(&config);
+        let mut lines = find.lines();
+        assert_eq!(
+            lines.next(),
+            Some("# not translated (content condition): *.txt content:^BUILD")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "find . \\( -type d \\( -name 'node_modules' \\) -prune -print \\) -o \
+                 \\( -type f \\( \\( -name '*.log' ! -name '.*' \\) -o -path './logs/*.tmp' \
+                 -o -path './logs/*.bak' \\) -print \\)"
+            )
+        );
+        assert_eq!(lines.next(), None);
+
+        let rsync = ReportGenerator::emit_rsync(&config);
+        assert_eq!(
+            rsync,
+            "# not translated (content condition): *.txt content:^BUILD\n\
+             - node_modules/\n- *.log\n- /logs/*.tmp\n- /logs/*.bak"
+        );
+
+        // 正则表达式和 `**` 模式无法转换，以注释行列出
+        let mut with_regex = config.clone();
+        with_regex.clean.folders.push(r"re:^out-\d+$".to_string());
+        with_regex.clean.files = vec!["re:^a/".to_string(), "**/*.tmp".to_string()];
+        assert_eq!(
+            ReportGenerator::emit_rsync(&with_regex),
+            "# not translated (regular expression): re:^out-\\d+$\n\
+             # not translated (regular expression): re:^a/\n\
+             # not translated (recursive wildcard): **/*.tmp\n\
+             - node_modules/"
+        );
+
+        // 包含 `/` 的目录模式按路径转换
+        let mut nested = config.clone();
+        nested.clean.folders = vec!["packages/*/dist/".to_string()];
+        nested.clean.files.clear();
+        assert_eq!(
+            ReportGenerator::emit_find(&nested),
+            "find . \\( -type d \\( -path './packages/*/dist' \\) -prune -print \\)"
+        );
+        assert_eq!(ReportGenerator::emit_rsync(&nested), "- /packages/*/dist/");
+
+        // 通配符可以匹配隐藏文件时不排除 `.` 开头的名称；单引号和方括号需要转义
+        config.options.glob_dot_policy = GlobDotPolicy::MatchDot;
+        config.options.recursive = false;
+        config.clean.folders.clear();
+        config.clean.files = vec!["it's[1].log".to_string(), "*.o".to_string()];
+        assert_eq!(
+            ReportGenerator::emit_find(&config),
+            "find . -maxdepth 1 \\( -type f \\( -name 'it'\\''s\\[1\\].log' -o -name '*.o' \\) -print \\)"
+        );
+
+        config.clean.files.clear();
+        assert_eq!(
+            ReportGenerator::emit_find(&config),
+            "find . -maxdepth 1 -false"
+        );
+    }
+
+    #[test]
+    fn test_format_summary_json_and_csv() {
+        let stats = Stats {
+            dirs_deleted: 3,
+            files_deleted: 12,
+            space_freed: 4096,
+            time_taken: std::time::Duration::from_millis(1500),
+            ..Default::default()
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&ReportGenerator::format_summary_json(&stats)).unwrap();
+        assert_eq!(json["dirs_deleted"], 3);
+        assert_eq!(json["files_deleted"], 12);
+        assert_eq!(json["space_freed"], 4096);
+        assert_eq!(json["time_taken_ms"], 1500);
+        assert!(json["physical_space_freed"].is_null());
+        assert_eq!(json["safety_refused"], 0);
+        assert_eq!(json["safety_refusals"], serde_json::json!([]));
+
+        // 安全检查拒绝的项目带有规则标识，可以与普通的删除失败区分
+        let refused = Stats {
+            dirs_failed: 2,
+            safety_refusals: vec![(
+                PathBuf::from("/usr/lib/build"),
+                SafetyViolation::SystemDirectory(PathBuf::from("/usr/lib/build")),
+            )],
+            ..Default::default()
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&ReportGenerator::format_summary_json(&refused)).unwrap();
+        assert_eq!(json["dirs_failed"], 2);
+        assert_eq!(json["safety_refused"], 1);
+        assert_eq!(
+            json["safety_refusals"],
+            serde_json::json!([{"path": "/usr/lib/build", "rule": "system-directory"}])
+        );
+
+        let csv = ReportGenerator::format_summary_csv(&stats);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let columns: Vec<&str> = lines[0].split(',').collect();
+        let values: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(columns.len(), values.len());
+        let value_of = |name: &str| values[columns.iter().position(|c| *c == name).unwrap()];
+        assert_eq!(value_of("dirs_deleted"), "3");
+        assert_eq!(value_of("space_freed"), "4096");
+        assert_eq!(value_of("physical_space_freed"), "");
+    }
+
+    #[test]
+    fn test_format_status_line() {
+        let stats = Stats {
+            dirs_deleted: 34,
+            files_deleted: 1234,
+            space_freed: 5_153_960_755, // 4.8 GB
+            time_taken: std::time::Duration::from_millis(2100),
+            ..Default::default()
+        };
+        assert_eq!(
+            ReportGenerator::format_status_line(&stats),
+            "bc: 34d 1.2kf 4.8G 2.1s"
+        );
+
+        // 边界：小于 1000 的数量和小于 1 KB 的大小不缩写
+        let stats = Stats {
+            dirs_deleted: 0,
+            files_deleted: 999,
+            space_freed: 512,
+            time_taken: std::time::Duration::from_secs(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            ReportGenerator::format_status_line(&stats),
+            "bc: 0d 999f 512B 0.0s"
+        );
+
+        let stats = Stats {
+            dirs_deleted: 2_500_000,
+            files_deleted: 1000,
+            space_freed: 1536,
+            ..Default::default()
+        };
+        assert_eq!(
+            ReportGenerator::format_status_line(&stats),
+            "bc: 2.5Md 1.0kf 1.5K 0.0s"
+        );
+    }
+
+    #[test]
+    fn test_summarize_by_project_type() {
+        use crate::config::{CleanConfig, Config, Options};
+        use crate::search::SearchEngine;
+
+        // 一个扫描根路径下有两个 Node.js 项目和一个 Rust 项目
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for (name, size) in [("web", 3000), ("admin", 1000)] {
+            let project = root.join(name);
+            std::fs::create_dir_all(project.join("node_modules").join("react")).unwrap();
+            std::fs::write(project.join("package.json"), "{}").unwrap();
+            std::fs::write(
+                project.join("node_modules").join("react").join("index.js"),
+                vec![0u8; size],
+            )
+            .unwrap();
+        }
+        let api = root.join("api");
+        std::fs::create_dir_all(api.join("target")).unwrap();
+        std::fs::write(api.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(api.join("target").join("app"), vec![0u8; 500]).unwrap();
+        std::fs::write(api.join("debug.log"), vec![0u8; 20]).unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string(), "target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![root.to_path_buf()];
+        let search_result = SearchEngine::search(&paths, &config).unwrap();
+        let delete_result = DeleteResult {
+            deleted_dirs: search_result.folders.to_paths(),
+            deleted_files: search_result.files.to_paths(),
+            ..Default::default()
+        };
+
+        let summaries = ReportGenerator::summarize_by_project_type(&search_result, &delete_result);
+        assert_eq!(
+            summaries,
+            vec![
+                ProjectTypeSummary {
+                    project_type: ProjectType::NodeJs,
+                    projects: 2,
+                    items: 2,
+                    total_size: 4000,
+                },
+                ProjectTypeSummary {
+                    project_type: ProjectType::Rust,
+                    projects: 1,
+                    items: 2,
+                    total_size: 520,
+                },
+            ]
+        );
+
+        let stats = ReportGenerator::collect_stats(&search_result, &delete_result, Instant::now());
+        let report = ReportGenerator::format_report(&stats, &delete_result, true);
+        assert!(report.contains("🧩 Space Freed by Project Type:"));
+        assert!(report.contains("Node.js: 3.91 KB across 2 projects"));
+        assert!(report.contains("Rust: 520.00 B across 1 project"));
+    }
+
+    #[test]
+    fn test_group_items() {
+        use crate::search::ItemOrigin;
+
+        // 一个 Node.js 项目和一个 Rust 项目，作为两个扫描根路径
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let web = temp_dir.path().join("web");
+        let api = temp_dir.path().join("api");
+        std::fs::create_dir_all(web.join("node_modules")).unwrap();
+        std::fs::write(web.join("package.json"), "{}").unwrap();
+        std::fs::create_dir_all(api.join("target")).unwrap();
+        std::fs::write(api.join("Cargo.toml"), "[package]").unwrap();
+
+        let items = [
+            (web.join("node_modules"), &web, "node_modules", 3000),
+            (web.join("npm.log"), &web, "*.log", 200),
+            (api.join("target"), &api, "target", 1000),
+            (api.join("debug.log"), &api, "*.log", 100),
+        ];
+        let search_result = SearchResult {
+            item_sizes: items
+                .iter()
+                .map(|(path, _, _, size)| (path.clone(), *size))
+                .collect(),
+            item_origins: items
+                .iter()
+                .map(|(path, root, pattern, _)| ItemOrigin {
+                    path: path.clone(),
+                    root: root.to_path_buf(),
+                    pattern: pattern.to_string(),
+                    delete_mode: Default::default(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let delete_result = DeleteResult {
+            deleted_dirs: vec![web.join("node_modules"), api.join("target")],
+            deleted_files: vec![web.join("npm.log"), api.join("debug.log")],
+            ..Default::default()
+        };
+        let summary = |groups: &[ItemGroup]| -> Vec<(String, usize, u64)> {
+            groups
+                .iter()
+                .map(|g| (g.name.clone(), g.items.len(), g.total_size))
+                .collect()
+        };
+
+        // 按扫描根路径分组
+        let groups = ReportGenerator::group_items(&search_result, &delete_result, GroupBy::Root);
+        assert_eq!(
+            summary(&groups),
+            vec![
+                (web.display().to_string(), 2, 3200),
+                (api.display().to_string(), 2, 1100),
+            ]
+        );
+
+        // 按匹配的模式分组
+        let groups = ReportGenerator::group_items(&search_result, &delete_result, GroupBy::Pattern);
+        assert_eq!(
+            summary(&groups),
+            vec![
+                ("node_modules".to_string(), 1, 3000),
+                ("target".to_string(), 1, 1000),
+                ("*.log".to_string(), 2, 300),
+            ]
+        );
+
+        // 按所在项目的类型分组，并在详细报告中显示分组标题和小计
+        let groups = ReportGenerator::group_items(&search_result, &delete_result, GroupBy::Type);
+        assert_eq!(
+            summary(&groups),
+            vec![
+                ("Node.js".to_string(), 2, 3200),
+                ("Rust".to_string(), 2, 1100),
+            ]
+        );
+        let report = ReportGenerator::format_report_with_groups(
+            &Stats::default(),
+            &delete_result,
+            true,
+            Some((GroupBy::Type, &groups)),
+        );
+        assert!(report.contains("Deleted Items by type:"));
+        assert!(report.contains("Node.js (2 items, 3.12 KB)"));
+        assert!(report.contains("Rust (2 items, 1.07 KB)"));
+        assert!(!report.contains("Deleted Directories:"));
+
+        assert_eq!("Pattern".parse::<GroupBy>(), Ok(GroupBy::Pattern));
+        assert!("size".parse::<GroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_path_anonymizer() {
+        let home = PathBuf::from("/home/alice");
+        let redact = PathAnonymizer::with_home(AnonymizeMode::Redact, Some(home.clone()));
+        let hash = PathAnonymizer::with_home(AnonymizeMode::Hash, Some(home));
+
+        // 主目录替换为 ~，上级目录名称被隐藏，匹配到的名称保留
+        let path = Path::new("/home/alice/work/secret-app/node_modules");
+        assert_eq!(redact.anonymize(path), PathBuf::from("~/*/*/node_modules"));
+        // 不在主目录下的路径保留文件系统根
+        assert_eq!(
+            redact.anonymize(Path::new("/srv/build/target")),
+            PathBuf::from("/*/*/target")
+        );
+
+        // 哈希模式对相同的输入总是得到相同的结果，不同的名称得到不同的结果
+        let hashed = hash.anonymize(path);
+        assert_eq!(hashed, hash.anonymize(path));
+        assert_eq!(hashed, PathBuf::from("~/5b98d260/3bf14827/node_modules"));
+        let components: Vec<_> = hashed.components().collect();
+        assert_eq!(components.len(), 4);
+        assert_ne!(components[1], components[2]);
+        // 同一个目录在不同路径中得到相同的哈希，报告中的层级关系因此保留
+        assert_eq!(
+            hash.anonymize(Path::new("/home/alice/work/other/target"))
+                .components()
+                .nth(1),
+            Some(components[1])
+        );
+
+        // 删除结果和分组中的路径都被匿名化，大小不变
+        let delete_result = DeleteResult {
+            deleted_dirs: vec![path.to_path_buf()],
+            failed_files: vec![(
+                PathBuf::from("/home/alice/work/app.log"),
+                "Permission denied: /home/alice/work/app.log".to_string(),
+            )],
+            total_size: 4096,
+            ..Default::default()
+        };
+        let anonymized = redact.anonymize_delete_result(&delete_result);
+        assert_eq!(anonymized.total_size, 4096);
+        let report = ReportGenerator::format_report(&Stats::default(), &anonymized, true);
+        assert!(report.contains("~/*/*/node_modules"));
+        assert!(report.contains("Permission denied: ~/*/app.log"));
+        assert!(!report.contains("alice"));
+
+        let mut groups = vec![ItemGroup {
+            name: "/home/alice/work".to_string(),
+            items: vec![(path.to_path_buf(), 4096)],
+            total_size: 4096,
+        }];
+        redact.anonymize_groups(&mut groups, GroupBy::Root);
+        assert_eq!(groups[0].name, "~/*");
+        assert_eq!(groups[0].items[0].0, PathBuf::from("~/*/*/node_modules"));
+
+        assert_eq!("hash".parse::<AnonymizeMode>(), Ok(AnonymizeMode::Hash));
+        assert!("scramble".parse::<AnonymizeMode>().is_err());
+    }
 }
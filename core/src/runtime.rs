@@ -0,0 +1,88 @@
+use crate::error::CleanError;
+
+/// 每个工作线程最多排队的后台任务数量
+const PENDING_PER_JOB: usize = 4;
+
+/// 运行时配置，所有并行阶段共用同一个并发度设置
+///
+/// 并行阶段（如 `parallel_size_compute`）都从这里获取线程数，而不是各自创建线程。
+/// `jobs` 为 1 时所有阶段串行执行。
+///
+/// 内存上限：同时等待完成的后台任务最多 `max_pending` 个，达到上限后主遍历先等待
+/// 已有任务完成，因此排队中的结果不会随匹配数量无限增长。匹配路径本身仍会累积在
+/// `SearchResult` 中；需要限制这部分内存时使用流式接口 `SearchEngine::matches`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// 并行阶段使用的线程数（1 表示串行执行）
+    pub jobs: usize,
+    /// 同时等待完成的后台任务上限
+    pub max_pending: usize,
+}
+
+impl RuntimeConfig {
+    /// 按指定的线程数创建运行时配置
+    ///
+    /// # 参数
+    /// * `jobs` - 线程数，为 `None` 或 0 时使用 CPU 数量
+    pub fn new(jobs: Option<usize>) -> Self {
+        let jobs = jobs
+            .filter(|jobs| *jobs > 0)
+            .unwrap_or_else(Self::default_jobs);
+        RuntimeConfig {
+            jobs,
+            max_pending: jobs * PENDING_PER_JOB,
+        }
+    }
+
+    /// 默认线程数：可用的 CPU 数量（无法获取时为 1）
+    pub fn default_jobs() -> usize {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    }
+
+    /// 是否串行执行所有阶段
+    pub fn is_serial(&self) -> bool {
+        self.jobs <= 1
+    }
+
+    /// 创建包含 `jobs` 个线程的线程池
+    ///
+    /// # 返回
+    /// 线程池，创建失败时返回错误
+    pub fn thread_pool(&self) -> Result<rayon::ThreadPool, CleanError> {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(self.jobs)
+            .thread_name(|index| format!("bc-worker-{}", index))
+            .build()
+            .map_err(|e| CleanError::Other(format!("Failed to create thread pool: {}", e)))
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_config() {
+        let runtime = RuntimeConfig::new(Some(3));
+        assert_eq!(runtime.jobs, 3);
+        assert_eq!(runtime.max_pending, 12);
+        assert!(!runtime.is_serial());
+        assert_eq!(runtime.thread_pool().unwrap().current_num_threads(), 3);
+
+        assert!(RuntimeConfig::new(Some(1)).is_serial());
+        // 未指定或为 0 时使用 CPU 数量
+        assert_eq!(RuntimeConfig::new(None).jobs, RuntimeConfig::default_jobs());
+        assert_eq!(
+            RuntimeConfig::new(Some(0)).jobs,
+            RuntimeConfig::default_jobs()
+        );
+    }
+}
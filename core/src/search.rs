@@ -1,23 +1,568 @@
-use crate::config::Config;
+use crate::cache::{CacheEntry, ScanCache};
+use crate::cancel::CancelFlag;
+use crate::compact::CompactPaths;
+use crate::config::{
+    Config, DeleteMode, DirSizeAction, ExcludedChildAction, OverlapPolicy, RetentionPolicy,
+};
 use crate::error::CleanError;
-use std::fs;
+use crate::ignores::IgnoreFiles;
+use crate::runtime::RuntimeConfig;
+use filetime::FileTime;
+use rayon::prelude::*;
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
-/// 搜索结果，包含匹配的文件夹、文件和总大小
+/// 正则表达式模式的前缀，如 `re:^bundle-[0-9a-f]+\.js$`，其余部分是匹配名称的正则表达式
+pub const REGEX_PATTERN_PREFIX: &str = "re:";
+
+/// 条目未被选中清理的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RejectReason {
+    /// 位于排除路径中
+    Excluded,
+    /// 文件小于最小大小
+    TooSmall,
+    /// 文件大于最大大小
+    TooLarge,
+    /// 文件年龄小于最小年龄
+    TooNew,
+    /// 文件年龄大于最大年龄
+    TooOld,
+    /// 没有匹配任何清理模式
+    NoPatternMatch,
+    /// 所有者不是指定的用户
+    NotOwned,
+    /// 目录包含的条目少于最小数量
+    TooFewEntries,
+    /// 目录中包含排除路径，不能整体删除
+    ContainsExcluded,
+    /// 既不是普通文件也不是目录（FIFO、套接字、设备文件）
+    SpecialFile,
+    /// 所在目录中最新的匹配文件，按 `keep_newest_per_dir` 保留
+    KeptNewest,
+    /// 按 `retention` 保留策略保留
+    Retained,
+    /// 名称匹配目录模式的目录符号链接，未启用 `include_symlinked_dirs`
+    SymlinkedDir,
+    /// 重试后仍无法读取文件元数据
+    StatFailed,
+    /// 被 `.bcignore` 或（启用时的）`.gitignore` 忽略
+    Ignored,
+}
+
+impl RejectReason {
+    /// 返回原因的机器可读代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            RejectReason::Excluded => "excluded",
+            RejectReason::TooSmall => "too-small",
+            RejectReason::TooLarge => "too-large",
+            RejectReason::TooNew => "too-new",
+            RejectReason::TooOld => "too-old",
+            RejectReason::NoPatternMatch => "no-pattern-match",
+            RejectReason::NotOwned => "not-owned",
+            RejectReason::TooFewEntries => "too-few-entries",
+            RejectReason::ContainsExcluded => "contains-excluded",
+            RejectReason::SpecialFile => "special-file",
+            RejectReason::KeptNewest => "kept-newest",
+            RejectReason::Retained => "retained",
+            RejectReason::SymlinkedDir => "symlinked-dir",
+            RejectReason::StatFailed => "stat-failed",
+            RejectReason::Ignored => "ignored",
+        }
+    }
+}
+
+/// 单个路径的清理判定（见 `SearchEngine::explain`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// 会被清理：路径自身或其所在的目录匹配了模式
+    WouldClean {
+        /// 匹配的模式
+        pattern: String,
+        /// 实际匹配的路径（路径自身或其上级目录）
+        matched_path: PathBuf,
+    },
+    /// 被过滤条件排除
+    Rejected(RejectReason),
+    /// 匹配了模式但受安全检查保护，删除时会被拒绝
+    Protected(String),
+    /// 超出遍历范围（未启用递归），扫描不会访问到该路径
+    NotReached,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::WouldClean {
+                pattern,
+                matched_path,
+            } => write!(
+                f,
+                "would be cleaned (pattern `{}` matched {})",
+                pattern,
+                matched_path.display()
+            ),
+            Verdict::Rejected(reason) => write!(f, "not cleaned: {}", reason.code()),
+            Verdict::Protected(reason) => write!(f, "protected: {}", reason),
+            Verdict::NotReached => write!(f, "not cleaned: outside the scanned depth"),
+        }
+    }
+}
+
+/// 单个路径的逐步判定过程
 #[derive(Debug, Clone)]
+pub struct Explanation {
+    /// 被查询的路径
+    pub path: PathBuf,
+    /// 用于匹配相对路径模式的扫描根路径
+    pub root: PathBuf,
+    /// 名称（或相对路径）匹配的所有模式，不考虑其他过滤条件
+    pub matched_patterns: Vec<String>,
+    /// 在路径之前处理、被过滤条件排除的上级目录及原因（只包括名称匹配目录模式的目录）
+    pub ancestor_rejections: Vec<(PathBuf, RejectReason)>,
+    /// 最终判定
+    pub verdict: Verdict,
+}
+
+/// 遍历时按条目类型过滤
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryTypeFilter {
+    /// 返回所有条目
+    #[default]
+    All,
+    /// 只返回普通文件
+    FilesOnly,
+    /// 只返回目录
+    DirsOnly,
+}
+
+impl EntryTypeFilter {
+    /// 判断条目类型是否满足过滤条件
+    pub fn accepts(&self, file_type: fs::FileType) -> bool {
+        match self {
+            EntryTypeFilter::All => true,
+            EntryTypeFilter::FilesOnly => file_type.is_file(),
+            EntryTypeFilter::DirsOnly => file_type.is_dir(),
+        }
+    }
+}
+
+/// 通配符对开头的 `.`（隐藏文件）的匹配策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GlobDotPolicy {
+    /// 通配符不匹配开头的 `.`，隐藏文件只能被以 `.` 开头的模式匹配（与 shell 一致）
+    #[default]
+    Explicit,
+    /// 通配符可以匹配开头的 `.`
+    MatchDot,
+}
+
+/// 搜索结果，包含匹配的文件夹、文件和总大小
+///
+/// 可以保存为 JSON 文件，检查或编辑后再从文件加载并执行删除，无需重新扫描。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SearchResult {
-    /// 匹配的文件夹路径列表
-    pub folders: Vec<PathBuf>,
-    /// 匹配的文件路径列表
-    pub files: Vec<PathBuf>,
+    /// 匹配的文件夹路径列表（按扫描根路径和相对路径保存）
+    pub folders: CompactPaths,
+    /// 匹配的文件路径列表（按扫描根路径和相对路径保存）
+    pub files: CompactPaths,
     /// 匹配文件的总大小（字节）
     pub total_size: u64,
+    /// 匹配文件实际占用的磁盘空间（字节，仅在启用 `physical_size` 时计算）
+    pub total_physical_size: Option<u64>,
     /// 扫描过程中遇到的所有目录总数（包括匹配和不匹配的）
     pub total_dirs_scanned: usize,
     /// 扫描过程中遇到的所有文件总数（包括匹配和不匹配的）
     pub total_files_scanned: usize,
+    /// 位于排除路径中而跳过的条目数量
+    pub skipped_excluded: usize,
+    /// 因大小超出范围（`min_size`/`max_size` 及目录大小阈值）而跳过的条目数量
+    pub skipped_size: usize,
+    /// 因年龄超出范围（`min_age_days`/`max_age_days`）而跳过的条目数量
+    pub skipped_age: usize,
+    /// 直接位于已匹配目录中、因此没有单独遍历的条目数量（更深层的内容不会被访问，不计入）
+    pub skipped_in_match: usize,
+    /// 遍历时遇到的错误数量（如权限不足、符号链接循环）
+    pub walk_errors: usize,
+    /// 被扫描但未被选中清理的条目及原因（仅在启用 `explain` 时记录）
+    pub rejected: Vec<(PathBuf, RejectReason)>,
+    /// 扫描中遇到的特殊文件（FIFO、套接字、设备文件），这些文件总是被跳过
+    pub special_files: Vec<PathBuf>,
+    /// 名称匹配目录模式但未清理的目录符号链接（未启用 `include_symlinked_dirs`）
+    pub symlinked_dirs: Vec<PathBuf>,
+    /// 大小超出目录阈值、删除前必须经过交互式确认的目录及其大小
+    pub needs_confirmation: Vec<(PathBuf, u64)>,
+    /// 每个匹配项（文件夹和文件）的大小，按发现顺序排列
+    pub item_sizes: Vec<(PathBuf, u64)>,
+    /// 是否有目录大小为估算值（`total_size` 此时只是下限）
+    pub size_is_estimate: bool,
+    /// 大小为估算值的匹配目录
+    pub estimated_sizes: Vec<PathBuf>,
+    /// 每个匹配项的来源（扫描根路径和匹配的模式），按发现顺序排列
+    pub item_origins: Vec<ItemOrigin>,
+    /// 每个配置的模式匹配到的项目数量，按配置顺序排列（目录模式在前）
+    pub pattern_matches: Vec<(String, usize)>,
+    /// 扫描是否因取消而提前结束（此时结果不完整）
+    pub cancelled: bool,
+    /// 在大小写不敏感的文件系统上与其他匹配项只有大小写不同、实际是同一位置而被合并掉的路径
+    pub case_duplicates: Vec<PathBuf>,
+}
+
+/// 匹配项的来源，用于按根路径或模式分组报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemOrigin {
+    /// 匹配项路径
+    pub path: PathBuf,
+    /// 发现该匹配项的扫描根路径
+    pub root: PathBuf,
+    /// 匹配到的清理模式
+    pub pattern: String,
+    /// 该模式的删除方式
+    #[serde(default)]
+    pub delete_mode: DeleteMode,
+}
+
+impl SearchResult {
+    /// 追加并行扫描中另一部分的结果，统计数量相加（模式匹配数量和大小写重复项在合并后统一计算）
+    fn absorb(&mut self, part: SearchResult) {
+        self.folders.extend(&part.folders);
+        self.files.extend(&part.files);
+        self.total_size += part.total_size;
+        self.total_physical_size = match (self.total_physical_size, part.total_physical_size) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.total_dirs_scanned += part.total_dirs_scanned;
+        self.total_files_scanned += part.total_files_scanned;
+        self.skipped_excluded += part.skipped_excluded;
+        self.skipped_size += part.skipped_size;
+        self.skipped_age += part.skipped_age;
+        self.skipped_in_match += part.skipped_in_match;
+        self.walk_errors += part.walk_errors;
+        self.rejected.extend(part.rejected);
+        self.special_files.extend(part.special_files);
+        self.symlinked_dirs.extend(part.symlinked_dirs);
+        self.needs_confirmation.extend(part.needs_confirmation);
+        self.item_sizes.extend(part.item_sizes);
+        self.size_is_estimate |= part.size_is_estimate;
+        self.estimated_sizes.extend(part.estimated_sizes);
+        self.item_origins.extend(part.item_origins);
+        self.cancelled |= part.cancelled;
+    }
+
+    /// 将搜索结果保存为 JSON 文件
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    ///
+    /// # 返回
+    /// 如果保存成功返回 `Ok(())`，否则返回错误
+    pub fn save(&self, path: &Path) -> Result<(), CleanError> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| CleanError::Other(format!("Failed to serialize scan result: {}", e)))?;
+        fs::write(path, content).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to write scan result {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 从 JSON 文件加载之前保存的搜索结果
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    ///
+    /// # 返回
+    /// 加载的搜索结果，如果文件不存在或无法解析则返回错误
+    pub fn load(path: &Path) -> Result<Self, CleanError> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            CleanError::Other(format!(
+                "Failed to read scan result {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let mut result: SearchResult = serde_json::from_str(&content)?;
+        result.compact_by_roots();
+        Ok(result)
+    }
+
+    /// 按匹配项来源中记录的扫描根路径重新保存 `folders` 和 `files`
+    fn compact_by_roots(&mut self) {
+        let mut roots: Vec<PathBuf> = self
+            .item_origins
+            .iter()
+            .map(|origin| origin.root.clone())
+            .collect();
+        roots.sort();
+        roots.dedup();
+        self.folders.set_roots(&roots);
+        self.files.set_roots(&roots);
+    }
+
+    /// 从匹配的文件夹和文件中随机抽取最多 `n` 个路径，用于抽查大量匹配结果
+    ///
+    /// 相同的匹配结果和 `seed` 总是得到相同的样本，与扫描时的发现顺序无关。
+    ///
+    /// # 参数
+    /// * `n` - 样本大小，匹配项不足 `n` 个时返回全部匹配项
+    /// * `seed` - 随机数种子
+    ///
+    /// # 返回
+    /// 抽取的路径，按路径排序
+    pub fn sample(&self, n: usize, seed: u64) -> Vec<PathBuf> {
+        let mut items: Vec<PathBuf> = self.folders.iter().chain(self.files.iter()).collect();
+        items.sort();
+        let n = n.min(items.len());
+
+        // 部分 Fisher-Yates 洗牌，只确定前 n 个位置
+        let mut state = seed;
+        for i in 0..n {
+            let remaining = (items.len() - i) as u64;
+            let j = i + (splitmix64(&mut state) % remaining) as usize;
+            items.swap(i, j);
+        }
+
+        items.truncate(n);
+        let mut sample = items;
+        sample.sort();
+        sample
+    }
+
+    /// 将扫描范围之外的目录（如工具的全局缓存）作为匹配的文件夹加入结果
+    ///
+    /// 已经在结果中或位于已匹配文件夹内的目录会被忽略；已匹配的、位于该目录内的项目随其一起删除，
+    /// 从结果中移除以免重复计算大小。
+    ///
+    /// # 参数
+    /// * `dir` - 要加入的目录
+    /// * `pattern` - 记录为该目录匹配到的模式
+    ///
+    /// # 返回
+    /// 如果目录被加入返回 `true`
+    pub fn add_extra_folder(&mut self, dir: &Path, pattern: &str) -> bool {
+        if self.folders.iter().any(|folder| dir.starts_with(folder)) {
+            return false;
+        }
+
+        let nested: Vec<PathBuf> = self
+            .folders
+            .iter()
+            .chain(self.files.iter())
+            .filter(|path| path.starts_with(dir))
+            .collect();
+        if !nested.is_empty() {
+            if let Some(total) = self.total_physical_size.as_mut() {
+                let nested_physical_size: u64 = nested
+                    .iter()
+                    .map(|path| match fs::symlink_metadata(path) {
+                        Ok(metadata) if metadata.is_dir() => {
+                            SearchEngine::calculate_dir_size_limited(path, None).1
+                        }
+                        Ok(metadata) => SearchEngine::physical_size(&metadata),
+                        Err(_) => 0,
+                    })
+                    .sum();
+                *total = total.saturating_sub(nested_physical_size);
+            }
+            self.remove_items(&nested);
+        }
+
+        let (size, physical_size, estimated) = SearchEngine::calculate_dir_size_limited(dir, None);
+        self.folders.push(dir);
+        self.item_sizes.push((dir.to_path_buf(), size));
+        self.item_origins.push(ItemOrigin {
+            path: dir.to_path_buf(),
+            root: dir.to_path_buf(),
+            pattern: pattern.to_string(),
+            delete_mode: DeleteMode::default(),
+        });
+        self.total_size += size;
+        if let Some(total) = self.total_physical_size.as_mut() {
+            *total += physical_size;
+        }
+        self.size_is_estimate |= estimated;
+        if estimated {
+            self.estimated_sizes.push(dir.to_path_buf());
+        }
+        true
+    }
+
+    /// 移除已经不存在的匹配项，并相应地减少总大小
+    ///
+    /// 保存的结果可能在扫描之后被修改过，删除前需要确认每个路径仍然存在。
+    ///
+    /// # 返回
+    /// 被移除的路径列表
+    pub fn retain_existing(&mut self) -> Vec<PathBuf> {
+        let vanished: Vec<PathBuf> = self
+            .folders
+            .iter()
+            .chain(self.files.iter())
+            .filter(|path| fs::symlink_metadata(path).is_err())
+            .collect();
+        if vanished.is_empty() {
+            return vanished;
+        }
+
+        self.remove_items(&vanished);
+        vanished
+    }
+
+    /// 在每个目录中保留修改时间最新的一个匹配文件，将其从结果中移除
+    ///
+    /// # 返回
+    /// 被保留（从结果中移除）的文件列表，按路径排序
+    pub fn keep_newest_per_dir(&mut self) -> Vec<PathBuf> {
+        let files = self.files.to_paths();
+        let mut newest: HashMap<&Path, (SystemTime, &PathBuf)> = HashMap::new();
+        for file in &files {
+            let (Some(parent), Ok(modified)) = (
+                file.parent(),
+                fs::symlink_metadata(file).and_then(|m| m.modified()),
+            ) else {
+                continue;
+            };
+            let current = newest.entry(parent).or_insert((modified, file));
+            if modified > current.0 {
+                *current = (modified, file);
+            }
+        }
+        let mut kept: Vec<PathBuf> = newest.into_values().map(|(_, file)| file.clone()).collect();
+        kept.sort();
+
+        if let Some(total) = self.total_physical_size.as_mut() {
+            let kept_physical_size: u64 = kept
+                .iter()
+                .filter_map(|file| fs::symlink_metadata(file).ok())
+                .map(|metadata| SearchEngine::physical_size(&metadata))
+                .sum();
+            *total = total.saturating_sub(kept_physical_size);
+        }
+        self.remove_items(&kept);
+        kept
+    }
+
+    /// 合并只有大小写不同、实际指向同一位置的匹配项（大小写不敏感的文件系统上可能出现）
+    ///
+    /// 每组只保留最先发现的路径，避免同一目录被删除两次（第二次会因为找不到而失败）。
+    ///
+    /// # 参数
+    /// * `same_file` - 判断两个路径是否指向同一位置，通常为 `SearchEngine::same_file`
+    ///
+    /// # 返回
+    /// 被合并掉（从结果中移除）的路径列表，按发现顺序排列
+    pub fn collapse_case_duplicates(
+        &mut self,
+        same_file: impl Fn(&Path, &Path) -> bool,
+    ) -> Vec<PathBuf> {
+        // 按忽略大小写后的路径分组，只有同组的路径才需要检查是否指向同一位置
+        let mut first_seen: HashMap<String, PathBuf> = HashMap::new();
+        let mut duplicates = Vec::new();
+        for path in self.folders.iter().chain(self.files.iter()) {
+            let key = path.to_string_lossy().to_lowercase();
+            match first_seen.get(&key) {
+                Some(first) if same_file(first, &path) => duplicates.push(path),
+                Some(_) => {}
+                None => {
+                    first_seen.insert(key, path);
+                }
+            }
+        }
+        if !duplicates.is_empty() {
+            self.remove_items(&duplicates);
+        }
+        duplicates
+    }
+
+    /// 按保留策略在每个目录中保留部分匹配项（文件夹和文件一起计算），将其从结果中移除
+    ///
+    /// # 参数
+    /// * `policy` - 保留策略
+    /// * `now` - 计算年龄时使用的当前时间
+    ///
+    /// # 返回
+    /// 被保留（从结果中移除）的项目列表，按路径排序
+    pub fn apply_retention(&mut self, policy: &RetentionPolicy, now: SystemTime) -> Vec<PathBuf> {
+        let mut siblings: HashMap<&Path, Vec<(&PathBuf, SystemTime)>> = HashMap::new();
+        let items: Vec<PathBuf> = self.folders.iter().chain(self.files.iter()).collect();
+        for item in &items {
+            let (Some(parent), Ok(modified)) = (
+                item.parent(),
+                fs::symlink_metadata(item).and_then(|m| m.modified()),
+            ) else {
+                continue;
+            };
+            siblings.entry(parent).or_default().push((item, modified));
+        }
+        let mut kept: Vec<PathBuf> = siblings
+            .values()
+            .flat_map(|items| policy.partition(items, now).0)
+            .cloned()
+            .collect();
+        kept.sort();
+
+        if let Some(total) = self.total_physical_size.as_mut() {
+            let kept_physical_size: u64 = kept
+                .iter()
+                .map(|item| {
+                    if item.is_dir() {
+                        SearchEngine::calculate_dir_size_limited(item, None).1
+                    } else {
+                        fs::symlink_metadata(item)
+                            .map(|metadata| SearchEngine::physical_size(&metadata))
+                            .unwrap_or(0)
+                    }
+                })
+                .sum();
+            *total = total.saturating_sub(kept_physical_size);
+        }
+        self.remove_items(&kept);
+        kept
+    }
+
+    /// 从结果中移除指定的项目，并从总大小中减去它们的大小
+    fn remove_items(&mut self, removed: &[PathBuf]) {
+        let removed: HashSet<&Path> = removed.iter().map(PathBuf::as_path).collect();
+        let removed_size: u64 = self
+            .item_sizes
+            .iter()
+            .filter(|(path, _)| removed.contains(path.as_path()))
+            .map(|(_, size)| size)
+            .sum();
+        self.total_size = self.total_size.saturating_sub(removed_size);
+        self.folders.retain(|path| !removed.contains(path));
+        self.files.retain(|path| !removed.contains(path));
+        self.folders.shrink_to_fit();
+        self.files.shrink_to_fit();
+        self.item_sizes
+            .retain(|(path, _)| !removed.contains(path.as_path()));
+        self.estimated_sizes
+            .retain(|path| !removed.contains(path.as_path()));
+        self.item_origins
+            .retain(|origin| !removed.contains(origin.path.as_path()));
+        self.needs_confirmation
+            .retain(|(path, _)| !removed.contains(path.as_path()));
+    }
 }
 
 /// 搜索选项，控制搜索行为
@@ -37,6 +582,79 @@ pub struct SearchOptions {
     pub min_age_days: Option<u32>,
     /// 最大文件年龄（天数）
     pub max_age_days: Option<u32>,
+    /// 计算年龄时使用的当前时间（`None` 表示使用系统时间）
+    pub now: Option<SystemTime>,
+    /// 是否记录被扫描但未被选中清理的条目及原因
+    pub explain: bool,
+    /// 遍历错误数量上限，超过后中止扫描（0 表示不限制）
+    pub max_walk_errors: usize,
+    /// 读取文件元数据遇到可重试的错误时的重试次数
+    pub stat_retries: u32,
+    /// 是否同时统计实际占用的磁盘空间（物理大小）
+    pub physical_size: bool,
+    /// `walk_path` 是否先返回目录内容再返回目录本身（后序遍历）
+    ///
+    /// 适合删除类的遍历（子项先于父目录处理）。搜索内部依赖前序遍历来跳过
+    /// 已匹配文件夹的子项，因此该选项只影响 `walk_path`。
+    pub contents_first: bool,
+    /// 是否同时使用符号链接目标的名称匹配文件模式
+    pub match_symlink_targets: bool,
+    /// 是否清理名称匹配目录模式的目录符号链接（只删除链接本身）
+    pub include_symlinked_dirs: bool,
+    /// 扫描根路径本身是符号链接时是否进入链接指向的目录（`follow_symlinks` 只控制根路径以下的链接）
+    pub dereference_root: bool,
+    /// 通配符是否可以匹配隐藏文件开头的 `.`
+    pub glob_dot_policy: GlobDotPolicy,
+    /// 只清理属于该用户（uid）的文件和目录（仅 Unix）
+    pub only_owned_by: Option<u32>,
+    /// 计算目录大小时的最大遍历深度（None 表示不限制），超出部分不计入大小
+    pub size_max_depth: Option<usize>,
+    /// 是否在线程池中并行计算匹配目录的大小
+    pub parallel_size_compute: bool,
+    /// 是否将每个根路径下的直接子条目分配到线程池中并行遍历
+    pub parallel_scan: bool,
+    /// 是否排除扫描中遇到的 `.gitignore` 忽略的路径（`.bcignore` 总是生效）
+    pub respect_gitignore: bool,
+    /// 并行阶段共用的线程数和排队任务上限
+    pub runtime: RuntimeConfig,
+    /// 匹配目录中的文件同时匹配文件模式时的处理方式
+    pub on_overlap: OverlapPolicy,
+    /// 是否按目录中最新文件的修改时间检查匹配目录的年龄
+    pub dir_age_from_contents: bool,
+    /// 匹配目录至少包含的直接子条目数量
+    pub min_dir_entries: Option<usize>,
+    /// 匹配目录的最小大小（字节）
+    pub dir_min_size: Option<u64>,
+    /// 匹配目录的最大大小（字节）
+    pub dir_max_size: Option<u64>,
+    /// 匹配目录大小超出范围时的处理方式
+    pub dir_size_action: DirSizeAction,
+    /// 匹配目录中包含排除路径时的处理方式
+    pub excluded_child_action: ExcludedChildAction,
+    /// 检查文件内容条件时读取的文件开头字节数
+    pub content_sniff_bytes: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        (&crate::config::Options::default()).into()
+    }
+}
+
+impl SearchOptions {
+    /// 计算年龄时使用的当前时间，未指定时为系统时间
+    pub fn now(&self) -> SystemTime {
+        self.now.unwrap_or_else(SystemTime::now)
+    }
+}
+
+/// SplitMix64 伪随机数生成器，用于可复现的抽样
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
 /// 搜索引擎，负责文件系统遍历和模式匹配
@@ -55,21 +673,47 @@ impl SearchEngine {
         Self::search_with_progress(paths, config, None::<fn(usize, usize, usize, usize, u64)>)
     }
 
-    /// 递归计算目录的总大小
+    /// 递归计算目录的总大小，最多遍历 `max_depth` 层
     ///
     /// 注意：文件系统不直接存储目录大小，必须遍历所有文件才能计算。
-    /// 这里使用 walkdir 库来优化遍历性能。
+    /// 这里使用 walkdir 库来优化遍历性能。超过深度的内容不计入大小，
+    /// 此时结果只是下限（实际大小 ≥ 返回值）。
+    ///
+    /// # 参数
+    /// * `dir_path` - 目录路径
+    /// * `max_depth` - 最大遍历深度（`None` 表示不限制，1 表示只统计直接子文件）
+    ///
+    /// # 返回
+    /// 逻辑大小、物理大小（字节），以及结果是否为估算值（有内容超出深度未统计）
+    pub fn calculate_dir_size_limited(
+        dir_path: &Path,
+        max_depth: Option<usize>,
+    ) -> (u64, u64, bool) {
+        Self::walk_dir_stats(dir_path, max_depth, false).0
+    }
+
+    /// 遍历目录，计算大小并（可选）查找其中最新文件的修改时间
     ///
     /// # 参数
     /// * `dir_path` - 目录路径
+    /// * `max_depth` - 最大遍历深度（`None` 表示不限制）
+    /// * `track_newest` - 是否记录最新文件的修改时间
     ///
     /// # 返回
-    /// 目录及其所有内容的总大小（字节）
-    fn calculate_dir_size(dir_path: &Path) -> u64 {
+    /// 大小（同 `calculate_dir_size_limited`），以及最新文件的修改时间（没有文件或未记录时为 `None`）
+    fn walk_dir_stats(
+        dir_path: &Path,
+        max_depth: Option<usize>,
+        track_newest: bool,
+    ) -> ((u64, u64, bool), Option<SystemTime>) {
         let mut total_size = 0u64;
+        let mut physical_size = 0u64;
+        let mut estimated = false;
+        let mut newest: Option<SystemTime> = None;
 
         // 使用 walkdir 遍历目录，比 read_dir 更高效
-        for entry in WalkDir::new(dir_path).into_iter() {
+        let walker = WalkDir::new(dir_path).max_depth(max_depth.unwrap_or(usize::MAX));
+        for entry in walker.into_iter() {
             let entry = match entry {
                 Ok(e) => e,
                 Err(_) => continue, // 忽略无法访问的条目
@@ -79,11 +723,163 @@ impl SearchEngine {
             if entry.file_type().is_file() {
                 if let Ok(metadata) = entry.metadata() {
                     total_size += metadata.len();
+                    physical_size += Self::physical_size(&metadata);
+                    if track_newest {
+                        if let Ok(modified) = metadata.modified() {
+                            newest = newest.max(Some(modified));
+                        }
+                    }
+                }
+            } else if !estimated && entry.file_type().is_dir() && Some(entry.depth()) == max_depth {
+                // 位于深度上限的非空目录，其内容没有被统计
+                estimated = fs::read_dir(entry.path())
+                    .map(|mut entries| entries.next().is_some())
+                    .unwrap_or(false);
+            }
+        }
+
+        ((total_size, physical_size, estimated), newest)
+    }
+
+    /// 计算目录大小，优先复用扫描缓存中修改时间一致的结果
+    ///
+    /// # 参数
+    /// * `dir_path` - 目录路径
+    /// * `cache` - 可选的扫描缓存，未命中时计算并写入（估算值不写入缓存）
+    /// * `max_depth` - 最大遍历深度（`None` 表示不限制）
+    /// * `track_newest` - 是否查找最新文件的修改时间（缓存命中时仍需遍历目录）
+    ///
+    /// # 返回
+    /// 目录的逻辑大小、物理大小（字节）和结果是否为估算值，以及最新文件的修改时间
+    fn calculate_dir_size_cached<'a>(
+        dir_path: &Path,
+        cache: Option<&mut (dyn ScanCache + 'a)>,
+        max_depth: Option<usize>,
+        track_newest: bool,
+    ) -> ((u64, u64, bool), Option<SystemTime>) {
+        let Some(cache) = cache else {
+            return Self::walk_dir_stats(dir_path, max_depth, track_newest);
+        };
+        let mtime = match fs::metadata(dir_path) {
+            Ok(m) => FileTime::from_last_modification_time(&m),
+            Err(_) => return Self::walk_dir_stats(dir_path, max_depth, track_newest),
+        };
+
+        if let Some(entry) = cache.get(dir_path, mtime) {
+            let newest = if track_newest {
+                Self::walk_dir_stats(dir_path, max_depth, true).1
+            } else {
+                None
+            };
+            return ((entry.size, entry.physical_size, false), newest);
+        }
+
+        let (sizes, newest) = Self::walk_dir_stats(dir_path, max_depth, track_newest);
+        Self::store_dir_size(cache, dir_path, mtime, sizes);
+        (sizes, newest)
+    }
+
+    /// 将计算出的目录大小写入扫描缓存（估算值不写入）
+    fn store_dir_size(
+        cache: &mut dyn ScanCache,
+        dir_path: &Path,
+        mtime: FileTime,
+        (size, physical_size, estimated): (u64, u64, bool),
+    ) {
+        if !estimated {
+            cache.insert(
+                dir_path,
+                CacheEntry {
+                    mtime_secs: mtime.unix_seconds(),
+                    mtime_nanos: mtime.nanoseconds(),
+                    size,
+                    physical_size,
+                },
+            );
+        }
+    }
+
+    /// 读取文件元数据，跟随符号链接时返回链接目标的元数据
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    /// * `follow_symlinks` - 是否跟随符号链接
+    fn stat_path(path: &Path, follow_symlinks: bool) -> io::Result<fs::Metadata> {
+        if follow_symlinks {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        }
+    }
+
+    /// 读取文件元数据，遇到可重试的错误时短暂等待后重试
+    ///
+    /// 网络文件系统上有效的文件也可能暂时无法读取元数据（超时、句柄过期等），
+    /// 直接跳过会导致这次扫描漏掉该文件。文件不存在、权限不足等错误不会重试。
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    /// * `retries` - 最多重试的次数
+    /// * `backoff` - 第一次重试前的等待时间，之后每次翻倍
+    /// * `stat` - 实际读取元数据的函数
+    ///
+    /// # 返回
+    /// 文件元数据，重试次数用完或遇到不可重试的错误时返回最后一次的错误
+    pub fn stat_with_retry<S>(
+        path: &Path,
+        retries: u32,
+        backoff: Duration,
+        mut stat: S,
+    ) -> io::Result<fs::Metadata>
+    where
+        S: FnMut(&Path) -> io::Result<fs::Metadata>,
+    {
+        let mut attempt = 0;
+        loop {
+            match stat(path) {
+                Err(e) if attempt < retries && Self::is_retryable(&e) => {
+                    log::debug!("Retrying stat of {} after error: {}", path.display(), e);
+                    thread::sleep(backoff * 2u32.saturating_pow(attempt));
+                    attempt += 1;
                 }
+                result => return result,
             }
         }
+    }
 
-        total_size
+    /// 判断读取元数据的错误是否可能是暂时性的
+    fn is_retryable(error: &io::Error) -> bool {
+        matches!(
+            error.kind(),
+            io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::ResourceBusy
+                | io::ErrorKind::StaleNetworkFileHandle
+        )
+    }
+
+    /// 计算文件实际占用的磁盘空间（物理大小）
+    ///
+    /// 在启用透明压缩的文件系统（btrfs/ZFS/NTFS）或稀疏文件上，`metadata.len()`
+    /// 返回的逻辑大小会高于实际释放的空间。Unix 上使用 `st_blocks * 512` 计算，
+    /// 其他平台退化为逻辑大小。
+    ///
+    /// # 参数
+    /// * `metadata` - 文件元数据
+    ///
+    /// # 返回
+    /// 文件实际占用的磁盘空间（字节）
+    pub fn physical_size(metadata: &fs::Metadata) -> u64 {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            metadata.blocks() * 512
+        }
+        #[cfg(not(unix))]
+        {
+            metadata.len()
+        }
     }
 
     /// 在指定路径中搜索匹配的文件和文件夹（带进度回调）
@@ -101,158 +897,515 @@ impl SearchEngine {
     pub fn search_with_progress<F>(
         paths: &[PathBuf],
         config: &Config,
-        mut progress_callback: Option<F>,
+        progress_callback: Option<F>,
     ) -> Result<SearchResult, CleanError>
     where
         F: FnMut(usize, usize, usize, usize, u64),
     {
-        let mut folders = Vec::new();
-        let mut files = Vec::new();
-        let mut total_size = 0u64;
-        let mut total_dirs_scanned = 0usize;
-        let mut total_files_scanned = 0usize;
-        // 记录已匹配的文件夹路径，用于跳过其子文件夹
-        // 使用 Arc<Mutex<>> 以便在闭包中共享和修改
-        let matched_folders = Arc::new(Mutex::new(std::collections::HashSet::new()));
-
-        let search_options: SearchOptions = (&config.options).into();
-
-        for path in paths {
-            let matched_folders_clone = Arc::clone(&matched_folders);
-            let config_exclude = &config.exclude;
-
-            for entry in Self::walk_path_with_filter(path, &search_options, move |entry_path| {
-                let matched = matched_folders_clone.lock().unwrap();
-                !Self::is_in_matched_folder(entry_path, &matched)
-            }) {
-                let entry_path = match entry {
-                    Ok(path) => path,
-                    Err(_) => {
-                        // 忽略遍历错误（如权限问题、符号链接循环等），继续处理其他文件
-                        continue;
-                    }
-                };
-
-                if Self::should_exclude(&entry_path, config_exclude) {
-                    continue;
-                }
+        Self::search_with_cache(paths, config, None, progress_callback)
+    }
 
-                let metadata = match fs::metadata(&entry_path) {
-                    Ok(m) => m,
-                    Err(_) => continue,
-                };
+    /// 在指定路径中搜索匹配的文件和文件夹，使用扫描缓存复用未变化目录的大小
+    ///
+    /// # 参数
+    /// * `paths` - 要搜索的路径列表（应该已经展开和验证）
+    /// * `config` - 清理配置，包含匹配模式和过滤选项
+    /// * `cache` - 可选的扫描缓存，修改时间未变化的已匹配目录直接使用缓存的大小
+    /// * `progress_callback` - 可选的进度回调函数，参数同 `search_with_progress`
+    ///
+    /// # 返回
+    /// 搜索结果，包含匹配的文件夹、文件和总大小
+    pub fn search_with_cache<'a, F>(
+        paths: &'a [PathBuf],
+        config: &'a Config,
+        cache: Option<&'a mut dyn ScanCache>,
+        progress_callback: Option<F>,
+    ) -> Result<SearchResult, CleanError>
+    where
+        F: FnMut(usize, usize, usize, usize, u64),
+    {
+        Self::search_with_cancel(paths, config, cache, progress_callback, None)
+    }
 
-                if metadata.is_file() {
-                    total_files_scanned += 1;
-                    let size = metadata.len();
-
-                    if !Self::check_size(size, search_options.min_size, search_options.max_size) {
-                        // 每扫描 1000 个文件输出一次进度
-                        if total_files_scanned.is_multiple_of(1000) {
-                            if let Some(ref mut cb) = progress_callback {
-                                cb(
-                                    total_files_scanned,
-                                    total_dirs_scanned,
-                                    files.len(),
-                                    folders.len(),
-                                    total_size,
-                                );
-                            }
-                        }
-                        continue;
-                    }
+    /// 在指定路径中搜索匹配的文件和文件夹，取消标志被设置后提前结束并返回已找到的部分结果
+    ///
+    /// # 参数
+    /// * `paths` - 要搜索的路径列表（应该已经展开和验证）
+    /// * `config` - 清理配置，包含匹配模式和过滤选项
+    /// * `cache` - 可选的扫描缓存，参数同 `search_with_cache`
+    /// * `progress_callback` - 可选的进度回调函数，参数同 `search_with_progress`
+    /// * `cancel` - 可选的取消标志
+    ///
+    /// # 返回
+    /// 搜索结果，提前结束时 `cancelled` 为 `true`
+    pub fn search_with_cancel<'a, F>(
+        paths: &'a [PathBuf],
+        config: &'a Config,
+        cache: Option<&'a mut dyn ScanCache>,
+        progress_callback: Option<F>,
+        cancel: Option<CancelFlag>,
+    ) -> Result<SearchResult, CleanError>
+    where
+        F: FnMut(usize, usize, usize, usize, u64),
+    {
+        // 并行扫描需要在多个线程中共享缓存，使用缓存时串行扫描
+        if cache.is_none() {
+            if let Some(pool) = Self::parallel_scan_pool(config) {
+                return Self::search_parallel(paths, config, &pool, progress_callback, cancel);
+            }
+        }
+        let mut matches = Matches::new(paths, config, cache, progress_callback);
+        if let Some(cancel) = cancel {
+            matches = matches.with_cancel(cancel);
+        }
+        Self::collect_matches(matches, paths, config)
+    }
 
-                    if !Self::check_age(
-                        &metadata,
-                        search_options.min_age_days,
-                        search_options.max_age_days,
-                    ) {
-                        // 每扫描 1000 个文件输出一次进度
-                        if total_files_scanned.is_multiple_of(1000) {
-                            if let Some(ref mut cb) = progress_callback {
-                                cb(
-                                    total_files_scanned,
-                                    total_dirs_scanned,
-                                    files.len(),
-                                    folders.len(),
-                                    total_size,
-                                );
-                            }
-                        }
-                        continue;
-                    }
+    /// 启用 `parallel_scan` 且不止一个线程时创建并行扫描使用的线程池
+    ///
+    /// 非递归扫描（或最大深度为 0）只有一层，不值得并行；线程池创建失败时退回串行扫描。
+    fn parallel_scan_pool(config: &Config) -> Option<rayon::ThreadPool> {
+        let options: SearchOptions = (&config.options).into();
+        if !options.parallel_scan
+            || !options.recursive
+            || options.max_depth == Some(0)
+            || options.runtime.is_serial()
+        {
+            return None;
+        }
+        options
+            .runtime
+            .thread_pool()
+            .map_err(|e| log::warn!("{}, scanning serially", e))
+            .ok()
+    }
 
-                    let name = entry_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("");
+    /// 并行扫描：每个根路径下的直接子条目作为单独的任务在线程池中遍历，结果按根路径
+    /// 和子条目名称的顺序合并为一个搜索结果
+    ///
+    /// 根路径本身先在调用线程中处理，被整体匹配时不再遍历其内容。每个直接子条目的整个子树
+    /// 由同一个任务遍历，已匹配目录的内容总在匹配它的任务中被跳过，不会在多个任务中重复统计。
+    /// 进度回调只在调用线程中调用，参数为所有任务的累计值。
+    fn search_parallel<F>(
+        paths: &[PathBuf],
+        config: &Config,
+        pool: &rayon::ThreadPool,
+        mut progress_callback: Option<F>,
+        cancel: Option<CancelFlag>,
+    ) -> Result<SearchResult, CleanError>
+    where
+        F: FnMut(usize, usize, usize, usize, u64),
+    {
+        let options: SearchOptions = (&config.options).into();
+        let progress = ScanProgress::default();
+        let collect_both = options.on_overlap == OverlapPolicy::CollectBoth;
+        let mut result = SearchResult {
+            folders: CompactPaths::new(paths),
+            files: CompactPaths::new(paths),
+            ..Default::default()
+        };
 
-                    for file_pattern in &config.clean.files {
-                        if Self::match_pattern(file_pattern, name) {
-                            files.push(entry_path.clone());
-                            total_size += size;
-                            break;
-                        }
-                    }
+        for root in paths {
+            // 无法列出内容的根路径（如文件）按原来的方式完整遍历
+            let Ok(entries) = fs::read_dir(root) else {
+                let (part, _) =
+                    Self::collect_part(paths, root, config, None, &[], &progress, &cancel)?;
+                result.absorb(part);
+                continue;
+            };
+            let mut names: Vec<OsString> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name())
+                .collect();
+            names.sort();
 
-                    // 每扫描 1000 个文件输出一次进度
-                    if total_files_scanned.is_multiple_of(1000) {
-                        if let Some(ref mut cb) = progress_callback {
-                            cb(
-                                total_files_scanned,
-                                total_dirs_scanned,
-                                files.len(),
-                                folders.len(),
-                                total_size,
-                            );
-                        }
-                    }
-                } else if metadata.is_dir() {
-                    total_dirs_scanned += 1;
-                    let name = entry_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("");
-
-                    for folder_pattern in &config.clean.folders {
-                        if Self::match_pattern(folder_pattern, name) {
-                            // 记录匹配的文件夹，后续跳过其子文件夹
-                            matched_folders.lock().unwrap().insert(entry_path.clone());
-                            folders.push(entry_path.clone());
-                            // 立即计算目录大小，避免扫描完成后的额外等待
-                            total_size += Self::calculate_dir_size(&entry_path);
-                            break;
-                        }
-                    }
+            let (root_part, matched) = Self::collect_part(
+                paths,
+                root,
+                config,
+                Some(ScanPart::Root),
+                &[],
+                &progress,
+                &cancel,
+            )?;
+            result.absorb(root_part);
+            if result.cancelled {
+                continue;
+            }
+            if !collect_both && matched.contains(root) {
+                // 与串行扫描一样，被跳过的直接子条目计入已匹配目录中跳过的条目
+                result.skipped_in_match += names.len();
+                continue;
+            }
 
-                    // 每扫描 100 个目录输出一次进度，或者每当匹配到目录时也输出
-                    if total_dirs_scanned.is_multiple_of(100)
-                        || !folders.is_empty() && folders.len().is_multiple_of(10)
-                    {
-                        if let Some(ref mut cb) = progress_callback {
-                            cb(
-                                total_files_scanned,
-                                total_dirs_scanned,
-                                files.len(),
-                                folders.len(),
-                                total_size,
-                            );
-                        }
+            let parts = thread::scope(|scope| {
+                let worker = scope.spawn(|| {
+                    pool.install(|| {
+                        names
+                            .par_iter()
+                            .map(|name| {
+                                Self::collect_part(
+                                    paths,
+                                    root,
+                                    config,
+                                    Some(ScanPart::Child(name.clone())),
+                                    &matched,
+                                    &progress,
+                                    &cancel,
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                });
+                while !worker.is_finished() {
+                    thread::sleep(PARALLEL_PROGRESS_INTERVAL);
+                    if let Some(callback) = progress_callback.as_mut() {
+                        progress.report(callback);
                     }
                 }
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            });
+            for part in parts {
+                result.absorb(part?.0);
             }
         }
 
-        Ok(SearchResult {
-            folders,
-            files,
-            total_size,
-            total_dirs_scanned,
-            total_files_scanned,
-        })
+        // 每个任务单独计数，合并后再检查遍历错误的总数
+        if options.max_walk_errors > 0 && result.walk_errors > options.max_walk_errors {
+            return Err(CleanError::TooManyWalkErrors(
+                result.walk_errors,
+                options.max_walk_errors,
+            ));
+        }
+        if let Some(callback) = progress_callback.as_mut() {
+            progress.report(callback);
+        }
+        Ok(Self::finish_result(result, config))
     }
 
-    /// 遍历指定路径，返回所有文件和目录的迭代器
+    /// 遍历并行扫描中的一部分
+    ///
+    /// # 参数
+    /// * `paths` - 所有根路径，用于确定匹配项的来源
+    /// * `root` - 要遍历的根路径
+    /// * `config` - 清理配置
+    /// * `part` - 要遍历的部分，为 `None` 时遍历整个根路径
+    /// * `matched` - 已经匹配的目录，其内容不再匹配
+    /// * `progress` - 累计进度
+    /// * `cancel` - 可选的取消标志
+    ///
+    /// # 返回
+    /// 未经后续处理的部分结果，以及遍历结束时已匹配的目录
+    fn collect_part(
+        paths: &[PathBuf],
+        root: &PathBuf,
+        config: &Config,
+        part: Option<ScanPart>,
+        matched: &[PathBuf],
+        progress: &ScanProgress,
+        cancel: &Option<CancelFlag>,
+    ) -> Result<(SearchResult, Vec<PathBuf>), CleanError> {
+        let mut matches = Matches::new(
+            std::slice::from_ref(root),
+            config,
+            None,
+            Some(progress.part_callback()),
+        );
+        matches.part = part;
+        // 任务本身已经在线程池中运行，目录大小直接在任务中计算，不再嵌套线程池
+        matches.pending_sizes = None;
+        matches
+            .matched_folders
+            .lock()
+            .unwrap()
+            .extend(matched.iter().cloned());
+        if let Some(cancel) = cancel {
+            matches = matches.with_cancel(cancel.clone());
+        }
+        let result = Self::gather_matches(&mut matches, paths)?;
+        // 回调只按固定间隔调用，结束时补上最后一次的增量
+        matches.report_progress();
+        let matched = matches
+            .matched_folders
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect();
+        Ok((result, matched))
+    }
+
+    /// 在指定路径中搜索，使用自定义匹配策略决定清理哪些条目
+    ///
+    /// 配置中的模式和排除路径不再参与匹配，其余选项（递归、大小、年龄等过滤条件）仍然生效。
+    /// 以配置本身作为策略时与 `search` 的结果相同。
+    ///
+    /// # 参数
+    /// * `paths` - 要搜索的路径列表（应该已经展开和验证）
+    /// * `config` - 清理配置，只使用其中的选项
+    /// * `policy` - 匹配策略
+    ///
+    /// # 返回
+    /// 搜索结果，结构与 `search` 相同
+    pub fn search_with_policy(
+        paths: &[PathBuf],
+        config: &Config,
+        policy: &dyn MatchPolicy,
+    ) -> Result<SearchResult, CleanError> {
+        let matches = Matches::<NoProgress>::new(paths, config, None, None).with_policy(policy);
+        Self::collect_matches(matches, paths, config)
+    }
+
+    /// 收集匹配迭代器返回的全部匹配项，生成搜索结果
+    fn collect_matches<F>(
+        mut matches: Matches<'_, F>,
+        paths: &[PathBuf],
+        config: &Config,
+    ) -> Result<SearchResult, CleanError>
+    where
+        F: FnMut(usize, usize, usize, usize, u64),
+    {
+        let result = Self::gather_matches(&mut matches, paths)?;
+        Ok(Self::finish_result(result, config))
+    }
+
+    /// 收集匹配迭代器返回的全部匹配项和统计数量，不做合并后的处理
+    fn gather_matches<F>(
+        matches: &mut Matches<'_, F>,
+        paths: &[PathBuf],
+    ) -> Result<SearchResult, CleanError>
+    where
+        F: FnMut(usize, usize, usize, usize, u64),
+    {
+        let mut folders = CompactPaths::new(paths);
+        let mut files = CompactPaths::new(paths);
+        let mut needs_confirmation = Vec::new();
+        let mut item_sizes = Vec::new();
+        let mut item_origins = Vec::new();
+        let mut size_is_estimate = false;
+        let mut estimated_sizes = Vec::new();
+
+        for item in matches.by_ref() {
+            let item = item?;
+            size_is_estimate |= item.size_is_estimate;
+            if item.size_is_estimate {
+                estimated_sizes.push(item.path.clone());
+            }
+            item_sizes.push((item.path.clone(), item.size));
+            item_origins.push(ItemOrigin {
+                path: item.path.clone(),
+                root: Self::root_of(&item.path, paths),
+                pattern: item.pattern.clone(),
+                delete_mode: item.delete_mode,
+            });
+            if item.is_dir {
+                if item.needs_confirmation {
+                    needs_confirmation.push((item.path.clone(), item.size));
+                }
+                folders.push(item.path);
+            } else {
+                files.push(item.path);
+            }
+        }
+        folders.shrink_to_fit();
+        files.shrink_to_fit();
+
+        Ok(SearchResult {
+            folders,
+            files,
+            total_size: matches.total_size,
+            total_physical_size: matches
+                .options
+                .physical_size
+                .then_some(matches.total_physical_size),
+            total_dirs_scanned: matches.dirs_scanned,
+            total_files_scanned: matches.files_scanned,
+            skipped_excluded: matches.skipped_excluded,
+            skipped_size: matches.skipped_size,
+            skipped_age: matches.skipped_age,
+            skipped_in_match: matches.skipped_in_match.load(Ordering::Relaxed),
+            walk_errors: matches.walk_errors,
+            rejected: std::mem::take(&mut matches.rejected),
+            special_files: std::mem::take(&mut matches.special_files),
+            symlinked_dirs: std::mem::take(&mut matches.symlinked_dirs),
+            needs_confirmation,
+            item_sizes,
+            size_is_estimate,
+            estimated_sizes,
+            item_origins,
+            cancelled: matches.cancelled,
+            ..Default::default()
+        })
+    }
+
+    /// 统计模式的匹配数量、合并大小写重复项并应用保留策略，生成最终的搜索结果
+    fn finish_result(mut result: SearchResult, config: &Config) -> SearchResult {
+        result.pattern_matches = Self::count_pattern_matches(config, &result.item_origins);
+        result.case_duplicates = result.collapse_case_duplicates(SearchEngine::same_file);
+
+        if config.options.keep_newest_per_dir {
+            for kept in result.keep_newest_per_dir() {
+                if config.options.explain {
+                    result.rejected.push((kept, RejectReason::KeptNewest));
+                }
+            }
+        }
+        if let Some(policy) = &config.options.retention {
+            let now = config.options.now.unwrap_or_else(SystemTime::now);
+            for kept in result.apply_retention(policy, now) {
+                if config.options.explain {
+                    result.rejected.push((kept, RejectReason::Retained));
+                }
+            }
+        }
+        result
+    }
+
+    /// 统计每个配置的模式匹配到的项目数量
+    fn count_pattern_matches(config: &Config, origins: &[ItemOrigin]) -> Vec<(String, usize)> {
+        config
+            .clean
+            .folders
+            .iter()
+            .chain(&config.clean.files)
+            .map(|pattern| {
+                let count = origins
+                    .iter()
+                    .filter(|origin| origin.pattern == *pattern)
+                    .count();
+                (pattern.clone(), count)
+            })
+            .collect()
+    }
+
+    /// 查找包含路径的扫描根路径（多个根路径嵌套时取最深的一个）
+    fn root_of(path: &Path, roots: &[PathBuf]) -> PathBuf {
+        roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .cloned()
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// 惰性搜索匹配的文件和文件夹，边遍历边返回匹配项，不累积结果
+    ///
+    /// 适合需要流式处理匹配项的库用户（如发送到 channel）。`search` 等价于
+    /// 收集该迭代器的所有匹配项。
+    ///
+    /// # 参数
+    /// * `paths` - 要搜索的路径列表（应该已经展开和验证）
+    /// * `config` - 清理配置，包含匹配模式和过滤选项
+    ///
+    /// # 返回
+    /// 匹配项迭代器，遇到不可恢复的错误时返回错误并结束迭代
+    pub fn matches<'a>(paths: &'a [PathBuf], config: &'a Config) -> Matches<'a> {
+        Matches::new(paths, config, None, None)
+    }
+
+    /// 解释单个路径会不会被清理，以及原因
+    ///
+    /// 按扫描时的顺序从根路径向下逐级处理路径的上级目录和路径自身，使用与扫描相同的
+    /// 判断逻辑（模式匹配、排除、大小、年龄、所有者等）；匹配后再进行删除前的安全检查。
+    ///
+    /// # 参数
+    /// * `path` - 要解释的路径
+    /// * `roots` - 扫描根路径，使用包含该路径的第一个（都不包含时使用路径的上级目录）
+    /// * `config` - 清理配置
+    ///
+    /// # 返回
+    /// 逐步判定过程，路径不存在时返回错误
+    pub fn explain(
+        path: &Path,
+        roots: &[PathBuf],
+        config: &Config,
+    ) -> Result<Explanation, CleanError> {
+        let metadata =
+            fs::symlink_metadata(path).map_err(|_| CleanError::PathNotFound(path.to_path_buf()))?;
+        let root = roots
+            .iter()
+            .find(|root| path.starts_with(root))
+            .cloned()
+            .unwrap_or_else(|| match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            });
+
+        // 记录所有拒绝原因，并同步计算目录大小以便立即得到结果
+        let mut config = config.clone();
+        config.options.explain = true;
+        config.options.parallel_size_compute = false;
+        let scan_roots = [root.clone()];
+        let mut matches = Matches::<NoProgress>::new(&scan_roots, &config, None, None);
+        matches.current_root = Some(&root);
+
+        let matched_patterns = matches.matching_patterns(path, metadata.is_dir());
+        let explanation = |ancestor_rejections, verdict| Explanation {
+            path: path.to_path_buf(),
+            root: root.clone(),
+            matched_patterns: matched_patterns.clone(),
+            ancestor_rejections,
+            verdict,
+        };
+
+        let depth = path
+            .strip_prefix(&root)
+            .map(|relative| relative.components().count())
+            .unwrap_or(0);
+        if !matches.options.recursive && depth > 1 {
+            return Ok(explanation(Vec::new(), Verdict::NotReached));
+        }
+
+        // 从根路径向下处理：上级目录匹配后，其中的内容不会再被单独处理
+        let mut chain: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|ancestor| ancestor.starts_with(&root))
+            .collect();
+        chain.reverse();
+        chain.push(path);
+
+        let mut ancestor_rejections = Vec::new();
+        for entry_path in chain {
+            let file_type = if matches.options.follow_symlinks {
+                fs::metadata(entry_path).or_else(|_| fs::symlink_metadata(entry_path))
+            } else {
+                fs::symlink_metadata(entry_path)
+            }
+            .map_err(|_| CleanError::PathNotFound(entry_path.to_path_buf()))?
+            .file_type();
+
+            matches.rejected.clear();
+            let found = matches.process_entry(Ok((entry_path.to_path_buf(), file_type)));
+            let mut items: Vec<Match> = found.transpose()?.into_iter().collect();
+            items.extend(matches.queued.drain(..));
+            if let Some(item) = items.into_iter().find(|item| path.starts_with(&item.path)) {
+                let verdict = match crate::delete::DeleteEngine::check_safety(&item.path) {
+                    Ok(()) => Verdict::WouldClean {
+                        pattern: item.pattern,
+                        matched_path: item.path,
+                    },
+                    Err(e) => Verdict::Protected(e.to_string()),
+                };
+                return Ok(explanation(ancestor_rejections, verdict));
+            }
+
+            let reason = matches.rejected.last().map(|(_, reason)| *reason);
+            if entry_path == path {
+                let reason = reason.unwrap_or(RejectReason::NoPatternMatch);
+                return Ok(explanation(ancestor_rejections, Verdict::Rejected(reason)));
+            }
+            // 名称不匹配的上级目录不值得报告
+            if let Some(reason) = reason.filter(|reason| *reason != RejectReason::NoPatternMatch) {
+                ancestor_rejections.push((entry_path.to_path_buf(), reason));
+            }
+        }
+        unreachable!("the explained path is always the last entry of the chain")
+    }
+
+    /// 遍历指定路径，返回所有文件和目录的迭代器
     ///
     /// # 参数
     /// * `path` - 要遍历的根路径
@@ -264,6 +1417,27 @@ impl SearchEngine {
         path: &Path,
         options: &SearchOptions,
     ) -> impl Iterator<Item = Result<PathBuf, CleanError>> {
+        Self::walk_path_typed(path, options, EntryTypeFilter::All)
+            .map(|entry| entry.map(|(path, _)| path))
+    }
+
+    /// 遍历指定路径，返回条目及其类型，并按类型过滤
+    ///
+    /// 条目类型来自遍历本身（大多数平台上由目录项直接提供），不需要再对每个条目调用
+    /// `fs::metadata`。跟随符号链接时返回链接目标的类型。
+    ///
+    /// # 参数
+    /// * `path` - 要遍历的根路径
+    /// * `options` - 搜索选项，控制遍历行为
+    /// * `type_filter` - 只返回该类型的条目（不影响遍历本身，目录的内容仍会被遍历）
+    ///
+    /// # 返回
+    /// 迭代器，每个元素是路径和类型，或错误
+    pub fn walk_path_typed(
+        path: &Path,
+        options: &SearchOptions,
+        type_filter: EntryTypeFilter,
+    ) -> impl Iterator<Item = Result<(PathBuf, fs::FileType), CleanError>> {
         WalkDir::new(path)
             .max_depth(if options.recursive {
                 options.max_depth.unwrap_or(usize::MAX)
@@ -271,10 +1445,17 @@ impl SearchEngine {
                 1
             })
             .follow_links(options.follow_symlinks)
+            .follow_root_links(options.dereference_root)
+            .contents_first(options.contents_first)
             .into_iter()
+            .filter(move |entry| {
+                entry
+                    .as_ref()
+                    .map_or(true, |e| type_filter.accepts(e.file_type()))
+            })
             .map(|entry| {
                 entry
-                    .map(|e| e.path().to_path_buf())
+                    .map(|e| (e.path().to_path_buf(), e.file_type()))
                     .map_err(|e| CleanError::Other(e.to_string()))
             })
     }
@@ -284,47 +1465,380 @@ impl SearchEngine {
     /// # 参数
     /// * `path` - 要遍历的根路径
     /// * `options` - 搜索选项，控制遍历行为
+    /// * `part` - 并行扫描时只遍历的部分，为 `None` 时遍历整个根路径
     /// * `filter` - 过滤函数，返回 true 表示保留该条目
     ///
     /// # 返回
-    /// 路径迭代器，每个元素是路径或错误
+    /// 迭代器，每个元素是路径和类型，或错误
     fn walk_path_with_filter<F>(
         path: &Path,
         options: &SearchOptions,
+        part: Option<&ScanPart>,
         filter: F,
-    ) -> impl Iterator<Item = Result<PathBuf, CleanError>>
+    ) -> impl Iterator<Item = Result<(PathBuf, fs::FileType), CleanError>>
     where
         F: Fn(&Path) -> bool + Send + Sync,
     {
-        WalkDir::new(path)
-            .max_depth(if options.recursive {
-                options.max_depth.unwrap_or(usize::MAX)
-            } else {
-                1
-            })
+        let max_depth = if options.recursive {
+            options.max_depth.unwrap_or(usize::MAX)
+        } else {
+            1
+        };
+        // 子条目直接从该条目开始遍历，深度比从根路径开始少一层，不再读取根路径的内容；
+        // 子条目是遍历中的普通条目，按 follow_symlinks 决定是否跟随
+        let (start, max_depth, follow_root_links) = match part {
+            None => (path.to_path_buf(), max_depth, options.dereference_root),
+            Some(ScanPart::Root) => (path.to_path_buf(), 0, options.dereference_root),
+            Some(ScanPart::Child(name)) => (
+                path.join(name),
+                max_depth.saturating_sub(1),
+                options.follow_symlinks,
+            ),
+        };
+        WalkDir::new(start)
+            .max_depth(max_depth)
             .follow_links(options.follow_symlinks)
+            .follow_root_links(follow_root_links)
             .into_iter()
             .filter_entry(move |e| filter(e.path()))
             .map(|entry| {
                 entry
-                    .map(|e| e.path().to_path_buf())
+                    .map(|e| (e.path().to_path_buf(), e.file_type()))
                     .map_err(|e| CleanError::Other(e.to_string()))
             })
     }
 
+    /// 拆分文件模式中的名称模式和内容条件
+    ///
+    /// 文件模式可以在名称模式后附加 ` content:<正则表达式>`，如 `*.log content:^BUILD LOG`，
+    /// 此时只有开头内容匹配该正则表达式的文件才会被选中。
+    ///
+    /// # 参数
+    /// * `pattern` - 文件模式
+    ///
+    /// # 返回
+    /// 名称模式和内容条件（没有内容条件时为 `None`）
+    pub fn split_content_pattern(pattern: &str) -> (&str, Option<&str>) {
+        match pattern.split_once(" content:") {
+            Some((name, content)) => (name.trim_end(), Some(content)),
+            None => (pattern, None),
+        }
+    }
+
+    /// 检查文件开头的内容是否匹配正则表达式
+    ///
+    /// # 参数
+    /// * `path` - 文件路径
+    /// * `regex` - 内容条件
+    /// * `limit` - 最多读取的字节数
+    ///
+    /// # 返回
+    /// 如果匹配返回 `true`，文件无法读取时返回 `false`
+    pub fn content_matches(path: &Path, regex: &Regex, limit: usize) -> bool {
+        let mut head = Vec::new();
+        File::open(path)
+            .and_then(|file| file.take(limit as u64).read_to_end(&mut head))
+            .is_ok_and(|_| regex.is_match(&head))
+    }
+
+    /// 判断条目是否为特殊文件（FIFO、套接字、块设备或字符设备）
+    ///
+    /// # 参数
+    /// * `file_type` - 条目类型
+    ///
+    /// # 返回
+    /// 如果既不是普通文件、目录也不是符号链接返回 `true`
+    pub fn is_special_file(file_type: fs::FileType) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            file_type.is_fifo()
+                || file_type.is_socket()
+                || file_type.is_block_device()
+                || file_type.is_char_device()
+        }
+        #[cfg(not(unix))]
+        {
+            !(file_type.is_file() || file_type.is_dir() || file_type.is_symlink())
+        }
+    }
+
+    /// 获取符号链接最终指向的目标名称
+    ///
+    /// # 参数
+    /// * `path` - 路径
+    ///
+    /// # 返回
+    /// 如果路径是符号链接返回目标的文件名（目标不存在时使用链接中记录的路径），否则返回 `None`
+    fn symlink_target_name(path: &Path) -> Option<String> {
+        let metadata = fs::symlink_metadata(path).ok()?;
+        if !metadata.file_type().is_symlink() {
+            return None;
+        }
+        let target = fs::canonicalize(path)
+            .or_else(|_| fs::read_link(path))
+            .ok()?;
+        target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+
     /// 匹配文件名或文件夹名是否与模式匹配
     ///
+    /// 使用默认的隐藏文件策略：通配符不匹配开头的 `.`（与 shell 一致）。
+    ///
     /// # 参数
-    /// * `pattern` - 匹配模式（文件夹以 `/` 结尾，文件支持通配符 `*` 和 `?`）
+    /// * `pattern` - 匹配模式（文件夹以 `/` 结尾，文件支持通配符 `*` 和 `?`，
+    ///   以 `re:` 开头时为匹配名称的正则表达式）
     /// * `name` - 要匹配的文件名或文件夹名
     ///
     /// # 返回
     /// 如果匹配返回 `true`，否则返回 `false`
     pub fn match_pattern(pattern: &str, name: &str) -> bool {
+        Self::match_pattern_with_policy(pattern, name, GlobDotPolicy::default())
+    }
+
+    /// 按指定的隐藏文件策略匹配文件名或文件夹名
+    ///
+    /// # 参数
+    /// * `pattern` - 匹配模式（文件夹以 `/` 结尾，文件支持通配符 `*` 和 `?`）
+    /// * `name` - 要匹配的文件名或文件夹名
+    /// * `policy` - 通配符是否可以匹配开头的 `.`
+    ///
+    /// # 返回
+    /// 如果匹配返回 `true`，否则返回 `false`
+    pub fn match_pattern_with_policy(pattern: &str, name: &str, policy: GlobDotPolicy) -> bool {
+        // 正则表达式自行决定是否匹配隐藏文件，不展开花括号
+        if let Some(source) = pattern.strip_prefix(REGEX_PATTERN_PREFIX) {
+            return Self::match_regex(source, name);
+        }
+        // 包含花括号或转义的模式先展开成多个模式，任意一个匹配即可
+        if pattern.contains(['{', '\\']) {
+            return Self::expand_braces(pattern)
+                .iter()
+                .any(|expanded| Self::match_expanded_pattern(expanded, name, policy));
+        }
+        Self::match_expanded_pattern(pattern, name, policy)
+    }
+
+    /// 模式是否为 `re:` 开头的正则表达式模式
+    pub fn is_regex_pattern(pattern: &str) -> bool {
+        pattern.starts_with(REGEX_PATTERN_PREFIX)
+    }
+
+    /// 模式是否按相对于扫描根路径的路径匹配（除末尾的 `/` 以外包含 `/` 的通配符模式）
+    ///
+    /// 正则表达式模式总是只匹配名称，其中的 `/` 不改变匹配方式。
+    pub fn is_path_pattern(pattern: &str) -> bool {
+        pattern.trim_end_matches('/').contains('/') && !Self::is_regex_pattern(pattern)
+    }
+
+    /// 编译正则表达式模式
+    ///
+    /// # 参数
+    /// * `pattern` - 匹配模式
+    ///
+    /// # 返回
+    /// 不是正则表达式模式时返回 `None`，否则返回编译结果
+    pub fn compile_regex_pattern(pattern: &str) -> Option<Result<regex::Regex, regex::Error>> {
+        pattern
+            .strip_prefix(REGEX_PATTERN_PREFIX)
+            .map(regex::Regex::new)
+    }
+
+    /// 用正则表达式匹配名称，编译结果按线程缓存，扫描大量条目时不会重复编译
+    ///
+    /// 无效的正则表达式在加载配置时已经报错，这里视为不匹配。
+    fn match_regex(source: &str, name: &str) -> bool {
+        thread_local! {
+            static COMPILED: RefCell<HashMap<String, Option<regex::Regex>>> =
+                RefCell::new(HashMap::new());
+        }
+        COMPILED.with(|compiled| {
+            let mut compiled = compiled.borrow_mut();
+            if !compiled.contains_key(source) {
+                compiled.insert(source.to_string(), regex::Regex::new(source).ok());
+            }
+            compiled[source]
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(name))
+        })
+    }
+
+    /// 展开模式中的花括号，如 `*.{log,tmp}` 展开为 `*.log` 和 `*.tmp`
+    ///
+    /// 与 shell 的规则一致：
+    /// - 支持嵌套，如 `{a,b{c,d}}` 展开为 `a`、`bc`、`bd`
+    /// - 允许空的候选项，如 `a{,b}` 展开为 `a` 和 `ab`
+    /// - 不包含逗号（如 `{a}`）或不配对的花括号按字面处理
+    /// - `\{`、`\}`、`\,` 和 `\\` 表示字面字符
+    ///
+    /// # 参数
+    /// * `pattern` - 匹配模式
+    ///
+    /// # 返回
+    /// 展开后的模式列表（已去除转义），不包含花括号时只有模式本身
+    pub fn expand_braces(pattern: &str) -> Vec<String> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 2,
+                '{' => {
+                    if let Some((close, commas)) = Self::find_brace_group(&chars, i) {
+                        if !commas.is_empty() {
+                            let prefix: String = chars[..i].iter().collect();
+                            let suffix: String = chars[close + 1..].iter().collect();
+                            let mut bounds = vec![i];
+                            bounds.extend(&commas);
+                            bounds.push(close);
+                            return bounds
+                                .windows(2)
+                                .flat_map(|w| {
+                                    let alternative: String =
+                                        chars[w[0] + 1..w[1]].iter().collect();
+                                    Self::expand_braces(&format!(
+                                        "{}{}{}",
+                                        prefix, alternative, suffix
+                                    ))
+                                })
+                                .collect();
+                        }
+                    }
+                    // 没有逗号或不配对时按字面处理，继续查找后面（或内部）的花括号
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+        vec![Self::unescape_pattern(&chars)]
+    }
+
+    /// 查找与 `open` 位置的 `{` 配对的 `}`，同时记录顶层逗号的位置
+    fn find_brace_group(chars: &[char], open: usize) -> Option<(usize, Vec<usize>)> {
+        let mut depth = 0;
+        let mut commas = Vec::new();
+        let mut i = open;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 1,
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((i, commas));
+                    }
+                }
+                ',' if depth == 1 => commas.push(i),
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// 去除模式中的转义：`\{`、`\}`、`\,` 和 `\\` 变为对应的字面字符
+    fn unescape_pattern(chars: &[char]) -> String {
+        let mut result = String::with_capacity(chars.len());
+        let mut iter = chars.iter().peekable();
+        while let Some(&c) = iter.next() {
+            if c == '\\' {
+                if let Some(&&next) = iter.peek() {
+                    if matches!(next, '{' | '}' | ',' | '\\') {
+                        result.push(next);
+                        iter.next();
+                        continue;
+                    }
+                }
+            }
+            result.push(c);
+        }
+        result
+    }
+
+    /// 按路径匹配包含 `/` 的模式，如 `logs/*.log`、`packages/*/dist`、`**/*.tmp`
+    ///
+    /// 模式从扫描根路径开始逐级匹配，每一级的规则与文件名匹配相同，
+    /// 通配符不会跨越 `/`（`logs/*.log` 不匹配 `logs/sub/a.log`）。
+    /// 单独成为一级的 `**` 匹配零个或多个完整的目录（`**/*.tmp` 匹配任意深度的 `.tmp` 文件）；
+    /// 默认的隐藏文件策略下 `**` 不进入以 `.` 开头的目录。
+    ///
+    /// # 参数
+    /// * `pattern` - 匹配模式
+    /// * `relative` - 相对于扫描根路径的路径
+    /// * `policy` - 通配符是否可以匹配开头的 `.`
+    ///
+    /// # 返回
+    /// 如果匹配返回 `true`，否则返回 `false`
+    pub fn match_relative_path(pattern: &str, relative: &Path, policy: GlobDotPolicy) -> bool {
+        let Some(components) = relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<&str>>>()
+        else {
+            return false;
+        };
+
+        Self::expand_braces(pattern).iter().any(|expanded| {
+            let segments: Vec<&str> = expanded.trim_start_matches('/').split('/').collect();
+            Self::match_segments(&segments, &components, policy)
+        })
+    }
+
+    /// 匹配目录模式：包含 `/` 的模式匹配相对于扫描根路径的路径，其他模式只匹配目录名称
+    ///
+    /// # 参数
+    /// * `pattern` - 目录模式（末尾的 `/` 可有可无）
+    /// * `name` - 目录名称
+    /// * `relative` - 相对于扫描根路径的路径，为 `None` 时包含 `/` 的模式不匹配
+    /// * `policy` - 通配符是否可以匹配开头的 `.`
+    ///
+    /// # 返回
+    /// 如果匹配返回 `true`，否则返回 `false`
+    pub fn match_folder_pattern(
+        pattern: &str,
+        name: &str,
+        relative: Option<&Path>,
+        policy: GlobDotPolicy,
+    ) -> bool {
+        if Self::is_path_pattern(pattern) {
+            relative.is_some_and(|relative| {
+                Self::match_relative_path(pattern.trim_end_matches('/'), relative, policy)
+            })
+        } else {
+            Self::match_pattern_with_policy(pattern, name, policy)
+        }
+    }
+
+    /// 逐级匹配模式的各级和路径的各个组件，`**` 可以匹配零个或多个组件
+    fn match_segments(segments: &[&str], components: &[&str], policy: GlobDotPolicy) -> bool {
+        match segments.split_first() {
+            None => components.is_empty(),
+            Some((&"**", rest)) => (0..=components.len()).any(|skipped| {
+                components[..skipped]
+                    .iter()
+                    .all(|name| policy == GlobDotPolicy::MatchDot || !name.starts_with('.'))
+                    && Self::match_segments(rest, &components[skipped..], policy)
+            }),
+            Some((segment, rest)) => components.split_first().is_some_and(|(name, remaining)| {
+                Self::match_expanded_pattern(segment, name, policy)
+                    && Self::match_segments(rest, remaining, policy)
+            }),
+        }
+    }
+
+    fn match_expanded_pattern(pattern: &str, name: &str, policy: GlobDotPolicy) -> bool {
         if pattern.ends_with('/') {
             let folder_pattern = pattern.trim_end_matches('/');
             folder_pattern == name
         } else {
+            // 隐藏文件只能被以 `.` 开头的模式显式匹配
+            if policy == GlobDotPolicy::Explicit
+                && name.starts_with('.')
+                && !pattern.starts_with('.')
+            {
+                return false;
+            }
             Self::glob_match(pattern, name)
         }
     }
@@ -384,285 +1898,3877 @@ impl SearchEngine {
         false
     }
 
-    /// 检查路径是否在已匹配的文件夹内
+    /// 判断两个路径是否指向文件系统中的同一位置
+    ///
+    /// Unix 上比较设备号和 inode（不跟随符号链接），其他平台比较规范化后的路径。
+    /// 任一路径不存在时返回 `false`。
     ///
     /// # 参数
-    /// * `path` - 要检查的路径
-    /// * `matched_folders` - 已匹配的文件夹集合
+    /// * `a` - 第一个路径
+    /// * `b` - 第二个路径
     ///
     /// # 返回
-    /// 如果路径在已匹配的文件夹内，返回 `true`
-    fn is_in_matched_folder(
-        path: &Path,
-        matched_folders: &std::collections::HashSet<PathBuf>,
-    ) -> bool {
-        for matched_folder in matched_folders {
-            // 检查 path 是否是 matched_folder 的子路径
-            // 注意：path 不能等于 matched_folder 本身（因为 matched_folder 本身需要被处理）
-            if path != matched_folder.as_path() && path.starts_with(matched_folder) {
-                return true;
-            }
+    /// 指向同一位置返回 `true`
+    #[cfg(unix)]
+    pub fn same_file(a: &Path, b: &Path) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
         }
-        false
     }
 
-    fn check_size(size: u64, min_size: Option<u64>, max_size: Option<u64>) -> bool {
-        if let Some(min) = min_size {
-            if size < min {
-                return false;
-            }
-        }
-        if let Some(max) = max_size {
-            if size > max {
-                return false;
-            }
+    /// 判断两个路径是否指向文件系统中的同一位置（比较规范化后的路径）
+    #[cfg(not(unix))]
+    pub fn same_file(a: &Path, b: &Path) -> bool {
+        match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
         }
+    }
+
+    /// 检查目录下是否有被排除的子路径（不包括目录本身）
+    ///
+    /// # 参数
+    /// * `dir` - 要检查的目录
+    /// * `excludes` - 排除路径列表
+    ///
+    /// # 返回
+    /// 如果某个排除路径位于目录之下，返回 `true`
+    pub fn contains_excluded(dir: &Path, excludes: &[PathBuf]) -> bool {
+        excludes
+            .iter()
+            .any(|exclude| exclude.as_path() != dir && exclude.starts_with(dir))
+    }
+
+    /// 列出目录中可以删除的内容：跳过排除路径，包含排除路径的子目录继续向下展开
+    ///
+    /// # 参数
+    /// * `dir` - 包含排除路径的匹配目录
+    /// * `excludes` - 排除路径列表
+    ///
+    /// # 返回
+    /// 可以整体删除的文件和目录列表（按路径排序）
+    pub fn non_excluded_children(dir: &Path, excludes: &[PathBuf]) -> Vec<PathBuf> {
+        let mut children: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+            Err(_) => return Vec::new(),
+        };
+        children.sort();
+
+        let mut result = Vec::new();
+        for child in children {
+            if Self::should_exclude(&child, excludes) {
+                continue;
+            }
+            let is_dir = fs::symlink_metadata(&child)
+                .map(|m| m.is_dir())
+                .unwrap_or(false);
+            if is_dir && Self::contains_excluded(&child, excludes) {
+                result.extend(Self::non_excluded_children(&child, excludes));
+            } else {
+                result.push(child);
+            }
+        }
+        result
+    }
+
+    /// 检查路径是否在已匹配的文件夹内
+    ///
+    /// # 参数
+    /// * `path` - 要检查的路径
+    /// * `matched_folders` - 已匹配的文件夹集合
+    ///
+    /// # 返回
+    /// 如果路径在已匹配的文件夹内，返回 `true`
+    fn is_in_matched_folder(path: &Path, matched_folders: &HashSet<PathBuf>) -> bool {
+        for matched_folder in matched_folders {
+            // 检查 path 是否是 matched_folder 的子路径
+            // 注意：path 不能等于 matched_folder 本身（因为 matched_folder 本身需要被处理）
+            if path != matched_folder.as_path() && path.starts_with(matched_folder) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 检查文件大小是否在范围内，不在范围内时返回对应的拒绝原因
+    fn size_reject_reason(
+        size: u64,
+        min_size: Option<u64>,
+        max_size: Option<u64>,
+    ) -> Option<RejectReason> {
+        if let Some(min) = min_size {
+            if size < min {
+                return Some(RejectReason::TooSmall);
+            }
+        }
+        if let Some(max) = max_size {
+            if size > max {
+                return Some(RejectReason::TooLarge);
+            }
+        }
+        None
+    }
+
+    /// 检查文件年龄是否在范围内，不在范围内时返回对应的拒绝原因
+    ///
+    /// # 参数
+    /// * `metadata` - 文件元数据
+    /// * `min_age_days` - 最小年龄（天数）
+    /// * `max_age_days` - 最大年龄（天数）
+    /// * `now` - 计算年龄时使用的当前时间
+    fn age_reject_reason(
+        metadata: &fs::Metadata,
+        min_age_days: Option<u32>,
+        max_age_days: Option<u32>,
+        now: SystemTime,
+    ) -> Option<RejectReason> {
+        if min_age_days.is_none() && max_age_days.is_none() {
+            return None;
+        }
+
+        metadata.modified().ok().and_then(|modified| {
+            Self::modified_age_reject_reason(modified, min_age_days, max_age_days, now)
+        })
+    }
+
+    /// 检查修改时间对应的年龄是否在范围内，不在范围内时返回对应的拒绝原因
+    ///
+    /// 修改时间晚于 `now` 时（如时钟偏差）不做检查。
+    fn modified_age_reject_reason(
+        modified: SystemTime,
+        min_age_days: Option<u32>,
+        max_age_days: Option<u32>,
+        now: SystemTime,
+    ) -> Option<RejectReason> {
+        if let Ok(elapsed) = now.duration_since(modified) {
+            let age_days = elapsed.as_secs() / 86400;
+
+            if let Some(min_age) = min_age_days {
+                if age_days < min_age as u64 {
+                    return Some(RejectReason::TooNew);
+                }
+            }
+            if let Some(max_age) = max_age_days {
+                if age_days > max_age as u64 {
+                    return Some(RejectReason::TooOld);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// 单个匹配项，由 `SearchEngine::matches` 按发现顺序逐个返回
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    /// 匹配的路径
+    pub path: PathBuf,
+    /// 是否为文件夹
+    pub is_dir: bool,
+    /// 逻辑大小（字节），文件夹为其全部内容的大小
+    pub size: u64,
+    /// 物理大小（字节）
+    pub physical_size: u64,
+    /// 大小是否为估算值（受 `size_max_depth` 限制，实际大小 ≥ `size`）
+    pub size_is_estimate: bool,
+    /// 匹配到的清理模式
+    pub pattern: String,
+    /// 匹配到的模式的删除方式
+    pub delete_mode: DeleteMode,
+    /// 大小超出目录阈值、删除前必须经过交互式确认
+    pub needs_confirmation: bool,
+}
+
+/// 遍历得到的条目：路径和类型，或遍历错误
+type WalkEntry = Result<(PathBuf, fs::FileType), CleanError>;
+
+/// 读取文件元数据的函数：参数为路径和是否跟随符号链接
+type StatFn = fn(&Path, bool) -> io::Result<fs::Metadata>;
+
+/// 读取文件元数据失败后第一次重试前的等待时间
+const STAT_RETRY_BACKOFF: Duration = Duration::from_millis(20);
+
+/// 并行扫描时调用线程汇总进度的间隔
+const PARALLEL_PROGRESS_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 并行扫描时单个任务负责遍历的部分
+#[derive(Debug, Clone)]
+enum ScanPart {
+    /// 只处理根路径本身，不遍历其内容
+    Root,
+    /// 只遍历根路径下该名称的直接子条目及其内容（不再处理根路径本身）
+    Child(OsString),
+}
+
+/// 并行扫描中所有任务累计的进度，由调用线程读取后传给进度回调
+#[derive(Default)]
+struct ScanProgress {
+    files_scanned: AtomicUsize,
+    dirs_scanned: AtomicUsize,
+    files_matched: AtomicUsize,
+    dirs_matched: AtomicUsize,
+    total_size: AtomicU64,
+}
+
+impl ScanProgress {
+    /// 单个任务的进度回调，把该任务两次回调之间的增量累加到总进度中
+    fn part_callback(&self) -> impl FnMut(usize, usize, usize, usize, u64) + '_ {
+        let mut last = (0, 0, 0, 0, 0);
+        move |files_scanned, dirs_scanned, files_matched, dirs_matched, total_size| {
+            self.files_scanned
+                .fetch_add(files_scanned - last.0, Ordering::Relaxed);
+            self.dirs_scanned
+                .fetch_add(dirs_scanned - last.1, Ordering::Relaxed);
+            self.files_matched
+                .fetch_add(files_matched - last.2, Ordering::Relaxed);
+            self.dirs_matched
+                .fetch_add(dirs_matched - last.3, Ordering::Relaxed);
+            self.total_size
+                .fetch_add(total_size - last.4, Ordering::Relaxed);
+            last = (
+                files_scanned,
+                dirs_scanned,
+                files_matched,
+                dirs_matched,
+                total_size,
+            );
+        }
+    }
+
+    /// 以当前的累计进度调用进度回调
+    fn report<F>(&self, callback: &mut F)
+    where
+        F: FnMut(usize, usize, usize, usize, u64),
+    {
+        callback(
+            self.files_scanned.load(Ordering::Relaxed),
+            self.dirs_scanned.load(Ordering::Relaxed),
+            self.files_matched.load(Ordering::Relaxed),
+            self.dirs_matched.load(Ordering::Relaxed),
+            self.total_size.load(Ordering::Relaxed),
+        );
+    }
+}
+
+/// 不接收进度的回调类型
+type NoProgress = fn(usize, usize, usize, usize, u64);
+
+/// 在线程池中计算完成的目录大小：目录路径、匹配的模式、计算前的修改时间和大小
+type SizeResult = (
+    PathBuf,
+    String,
+    Option<FileTime>,
+    ((u64, u64, bool), Option<SystemTime>),
+);
+
+/// 正在线程池中计算大小的目录
+struct PendingSizes {
+    pool: rayon::ThreadPool,
+    sender: Sender<SizeResult>,
+    receiver: Receiver<SizeResult>,
+    in_flight: usize,
+}
+
+/// 匹配策略对单个条目的判定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// 清理该条目，目录会被整体清理；参数为报告中显示的匹配原因（通常是模式）
+    Clean(String),
+    /// 不清理该条目并记录原因，目录会继续遍历其内容
+    Skip(RejectReason),
+}
+
+/// 匹配策略：决定扫描到的条目是否清理
+///
+/// 扫描总是通过匹配策略判定条目，默认的策略按配置中的模式判定（`Config` 也实现了该 trait）。
+/// 库的使用者可以实现该 trait 完全自定义匹配逻辑（如清理带有 `CACHEDIR.TAG` 的目录），
+/// 见 `SearchEngine::search_with_policy`。大小、年龄、所有者等过滤条件仍按配置选项检查。
+pub trait MatchPolicy {
+    /// 判定条目是否清理
+    ///
+    /// # 参数
+    /// * `entry` - 条目路径
+    /// * `relative` - 相对于扫描根路径的路径，没有扫描根路径时为 `None`
+    /// * `metadata` - 条目的元数据（目录跟随符号链接，文件按 `follow_symlinks` 决定）
+    ///
+    /// # 返回
+    /// 清理或跳过的判定
+    fn decide(&self, entry: &Path, relative: Option<&Path>, metadata: &fs::Metadata) -> Decision;
+
+    /// 按配置中的模式判定的策略返回该配置，扫描时据此只构建一次模式匹配器
+    fn pattern_config(&self) -> Option<&Config> {
+        None
+    }
+}
+
+impl MatchPolicy for Config {
+    /// 按排除路径、目录模式和文件模式判定
+    ///
+    /// 每次调用都重新构建模式匹配器，只适合单独判定少量条目；扫描时通过 `pattern_config`
+    /// 使用与 `SearchEngine::search` 相同的匹配器。包含 `/` 的模式只在有 `relative` 时匹配。
+    fn decide(&self, entry: &Path, relative: Option<&Path>, metadata: &fs::Metadata) -> Decision {
+        if SearchEngine::should_exclude(entry, &self.exclude) {
+            return Decision::Skip(RejectReason::Excluded);
+        }
+        PatternMatcher::new(self, &(&self.options).into()).decide(entry, relative, metadata)
+    }
+
+    fn pattern_config(&self) -> Option<&Config> {
+        Some(self)
+    }
+}
+
+/// 按配置中的目录模式和文件模式匹配条目
+struct PatternMatcher<'a> {
+    config: &'a Config,
+    glob_dot_policy: GlobDotPolicy,
+    match_symlink_targets: bool,
+    content_sniff_bytes: usize,
+    // 带有内容条件的文件模式及其编译后的正则表达式
+    content_regexes: HashMap<&'a str, Regex>,
+}
+
+impl<'a> PatternMatcher<'a> {
+    fn new(config: &'a Config, options: &SearchOptions) -> Self {
+        let content_regexes = config
+            .clean
+            .files
+            .iter()
+            .filter_map(|pattern| {
+                let content = SearchEngine::split_content_pattern(pattern).1?;
+                match Regex::new(content) {
+                    Ok(regex) => Some((pattern.as_str(), regex)),
+                    Err(e) => {
+                        log::warn!("Invalid content pattern in '{}': {}", pattern, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        PatternMatcher {
+            config,
+            glob_dot_policy: options.glob_dot_policy,
+            match_symlink_targets: options.match_symlink_targets,
+            content_sniff_bytes: options.content_sniff_bytes,
+            content_regexes,
+        }
+    }
+
+    /// 查找与目录匹配的第一个目录模式
+    ///
+    /// # 参数
+    /// * `entry_path` - 目录路径
+    /// * `relative` - 相对于扫描根路径的路径，包含 `/` 的目录模式（如 `packages/*/dist/`）只匹配该路径
+    fn folder_pattern(&self, entry_path: &Path, relative: Option<&Path>) -> Option<String> {
+        let name = entry_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+        self.config
+            .clean
+            .folders
+            .iter()
+            .find(|folder_pattern| {
+                SearchEngine::match_folder_pattern(
+                    folder_pattern,
+                    name,
+                    relative,
+                    self.glob_dot_policy,
+                )
+            })
+            .cloned()
+    }
+
+    /// 查找与文件匹配的第一个文件模式
+    ///
+    /// # 参数
+    /// * `entry_path` - 文件路径
+    /// * `relative` - 相对于扫描根路径的路径，包含 `/` 的文件模式只匹配该路径
+    fn file_pattern(&self, entry_path: &Path, relative: Option<&Path>) -> Option<String> {
+        let name = entry_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+        let find_pattern = |name: &str| {
+            self.config
+                .clean
+                .files
+                .iter()
+                .find(|pattern| {
+                    let (file_pattern, content) = SearchEngine::split_content_pattern(pattern);
+                    let name_matches = if SearchEngine::is_path_pattern(file_pattern) {
+                        relative.is_some_and(|relative| {
+                            SearchEngine::match_relative_path(
+                                file_pattern,
+                                relative,
+                                self.glob_dot_policy,
+                            )
+                        })
+                    } else {
+                        SearchEngine::match_pattern_with_policy(
+                            file_pattern,
+                            name,
+                            self.glob_dot_policy,
+                        )
+                    };
+                    // 内容条件只在名称匹配后检查，需要读取文件开头
+                    name_matches
+                        && (content.is_none()
+                            || self
+                                .content_regexes
+                                .get(pattern.as_str())
+                                .is_some_and(|regex| {
+                                    SearchEngine::content_matches(
+                                        entry_path,
+                                        regex,
+                                        self.content_sniff_bytes,
+                                    )
+                                }))
+                })
+                .cloned()
+        };
+
+        // 默认只匹配链接自身的名称，启用 match_symlink_targets 时也匹配目标的名称
+        find_pattern(name).or_else(|| {
+            if self.match_symlink_targets {
+                SearchEngine::symlink_target_name(entry_path)
+                    .and_then(|target| find_pattern(&target))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl MatchPolicy for PatternMatcher<'_> {
+    /// 目录按目录模式、其他条目按文件模式判定，不检查排除路径（扫描时已单独检查）
+    fn decide(&self, entry: &Path, relative: Option<&Path>, metadata: &fs::Metadata) -> Decision {
+        let pattern = if metadata.is_dir() {
+            self.folder_pattern(entry, relative)
+        } else {
+            self.file_pattern(entry, relative)
+        };
+        pattern.map_or(
+            Decision::Skip(RejectReason::NoPatternMatch),
+            Decision::Clean,
+        )
+    }
+}
+
+/// 惰性匹配迭代器，边遍历边返回匹配项
+///
+/// 已匹配的文件夹会被记录，其子项在后续遍历中被跳过。扫描统计
+/// （扫描数量、被拒绝的条目等）保存在迭代器中，可在迭代过程中或结束后读取。
+pub struct Matches<'a, F = NoProgress> {
+    config: &'a Config,
+    options: SearchOptions,
+    paths: std::slice::Iter<'a, PathBuf>,
+    // 正在遍历的根路径，用于计算包含 `/` 的文件模式所需的相对路径
+    current_root: Option<&'a Path>,
+    walker: Option<Box<dyn Iterator<Item = WalkEntry>>>,
+    // 记录已匹配的文件夹路径，用于跳过其子文件夹
+    // 使用 Arc<Mutex<>> 以便在遍历过滤闭包中共享
+    matched_folders: Arc<Mutex<HashSet<PathBuf>>>,
+    cache: Option<&'a mut dyn ScanCache>,
+    progress_callback: Option<F>,
+    finished: bool,
+    files_scanned: usize,
+    dirs_scanned: usize,
+    files_matched: usize,
+    dirs_matched: usize,
+    total_size: u64,
+    total_physical_size: u64,
+    walk_errors: usize,
+    skipped_excluded: usize,
+    skipped_size: usize,
+    skipped_age: usize,
+    // 遍历过滤闭包中累计，因此与 matched_folders 一样需要共享
+    skipped_in_match: Arc<AtomicUsize>,
+    rejected: Vec<(PathBuf, RejectReason)>,
+    special_files: Vec<PathBuf>,
+    symlinked_dirs: Vec<PathBuf>,
+    // 启用 parallel_size_compute 时，匹配目录的大小在线程池中计算，完成后再返回匹配项
+    pending_sizes: Option<PendingSizes>,
+    // 一次产生多个匹配项时（如部分清理包含排除路径的目录）尚未返回的匹配项
+    queued: VecDeque<Match>,
+    // 按配置中的模式匹配条目名称，是未设置自定义匹配策略时的默认策略
+    patterns: PatternMatcher<'a>,
+    // 自定义匹配策略，设置后代替配置中的模式和排除路径决定是否清理
+    policy: Option<&'a dyn MatchPolicy>,
+    // 取消标志，被设置后停止遍历
+    cancel: Option<CancelFlag>,
+    cancelled: bool,
+    // 并行扫描时该迭代器只遍历根路径的一部分
+    part: Option<ScanPart>,
+    // 扫描中遇到的忽略文件
+    ignore_files: IgnoreFiles,
+    // 被忽略的目录，与 matched_folders 一样在遍历过滤闭包中跳过其内容
+    ignored_dirs: Arc<Mutex<HashSet<PathBuf>>>,
+    // 读取文件元数据的函数，测试中可替换为模拟暂时故障的实现
+    stat: StatFn,
+}
+
+impl<'a, F> Matches<'a, F>
+where
+    F: FnMut(usize, usize, usize, usize, u64),
+{
+    fn new(
+        paths: &'a [PathBuf],
+        config: &'a Config,
+        cache: Option<&'a mut dyn ScanCache>,
+        progress_callback: Option<F>,
+    ) -> Self {
+        let options: SearchOptions = (&config.options).into();
+        let patterns = PatternMatcher::new(config, &options);
+        // 只有一个线程时串行计算，线程池创建失败时同样退回串行计算
+        let pending_sizes = (options.parallel_size_compute && !options.runtime.is_serial())
+            .then(|| options.runtime.thread_pool())
+            .and_then(|pool| {
+                pool.map_err(|e| log::warn!("{}, computing sizes serially", e))
+                    .ok()
+            })
+            .map(|pool| {
+                let (sender, receiver) = mpsc::channel();
+                PendingSizes {
+                    pool,
+                    sender,
+                    receiver,
+                    in_flight: 0,
+                }
+            });
+        let ignore_files = IgnoreFiles::new(options.respect_gitignore);
+        Matches {
+            config,
+            options,
+            paths: paths.iter(),
+            current_root: None,
+            walker: None,
+            matched_folders: Arc::new(Mutex::new(HashSet::new())),
+            cache,
+            progress_callback,
+            finished: false,
+            files_scanned: 0,
+            dirs_scanned: 0,
+            files_matched: 0,
+            dirs_matched: 0,
+            total_size: 0,
+            total_physical_size: 0,
+            walk_errors: 0,
+            skipped_excluded: 0,
+            skipped_size: 0,
+            skipped_age: 0,
+            skipped_in_match: Arc::new(AtomicUsize::new(0)),
+            rejected: Vec::new(),
+            special_files: Vec::new(),
+            symlinked_dirs: Vec::new(),
+            pending_sizes,
+            queued: VecDeque::new(),
+            patterns,
+            policy: None,
+            cancel: None,
+            cancelled: false,
+            part: None,
+            ignore_files,
+            ignored_dirs: Arc::new(Mutex::new(HashSet::new())),
+            stat: SearchEngine::stat_path,
+        }
+    }
+
+    /// 使用自定义匹配策略代替配置中的模式和排除路径
+    ///
+    /// 按配置中的模式判定的策略（如 `Config`）使用其模式构建一次匹配器，与默认策略的匹配方式相同。
+    ///
+    /// # 参数
+    /// * `policy` - 匹配策略
+    pub fn with_policy(mut self, policy: &'a dyn MatchPolicy) -> Self {
+        match policy.pattern_config() {
+            Some(config) => {
+                self.patterns = PatternMatcher::new(config, &self.options);
+                self.policy = None;
+            }
+            None => self.policy = Some(policy),
+        }
+        self
+    }
+
+    /// 当前使用的匹配策略：自定义策略，或按配置中的模式匹配的默认策略
+    fn policy(&self) -> &dyn MatchPolicy {
+        self.policy.unwrap_or(&self.patterns)
+    }
+
+    /// 使用取消标志，标志被设置后迭代提前结束
+    ///
+    /// 已返回的匹配项不受影响，仍在线程池中计算大小的目录会被丢弃。
+    ///
+    /// # 参数
+    /// * `cancel` - 取消标志
+    pub fn with_cancel(mut self, cancel: CancelFlag) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// 使用指定的函数读取文件元数据（测试用）
+    #[cfg(test)]
+    fn with_stat(mut self, stat: StatFn) -> Self {
+        self.stat = stat;
+        self
+    }
+
+    /// 迭代是否因取消而提前结束
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// 到目前为止扫描的文件数量
+    pub fn files_scanned(&self) -> usize {
+        self.files_scanned
+    }
+
+    /// 到目前为止扫描的目录数量
+    pub fn dirs_scanned(&self) -> usize {
+        self.dirs_scanned
+    }
+
+    /// 到目前为止匹配项的总大小（字节）
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// 被扫描但未被选中清理的条目及原因（仅在启用 `explain` 时记录）
+    pub fn rejected(&self) -> &[(PathBuf, RejectReason)] {
+        &self.rejected
+    }
+
+    /// 到目前为止遇到的特殊文件（FIFO、套接字、设备文件）
+    pub fn special_files(&self) -> &[PathBuf] {
+        &self.special_files
+    }
+
+    /// 到目前为止遇到的、名称匹配目录模式但未清理的目录符号链接
+    pub fn symlinked_dirs(&self) -> &[PathBuf] {
+        &self.symlinked_dirs
+    }
+
+    fn report_progress(&mut self) {
+        if let Some(ref mut cb) = self.progress_callback {
+            cb(
+                self.files_scanned,
+                self.dirs_scanned,
+                self.files_matched,
+                self.dirs_matched,
+                self.total_size,
+            );
+        }
+    }
+
+    /// 模式的删除方式：模式单独指定的方式，否则为全局的删除方式
+    fn delete_mode(&self, pattern: &str) -> DeleteMode {
+        self.config
+            .clean
+            .mode_for(pattern, self.config.options.delete_mode)
+    }
+
+    fn reject(&mut self, path: &Path, reason: RejectReason) {
+        match reason {
+            RejectReason::Excluded | RejectReason::Ignored => self.skipped_excluded += 1,
+            RejectReason::TooSmall | RejectReason::TooLarge => self.skipped_size += 1,
+            RejectReason::TooNew | RejectReason::TooOld => self.skipped_age += 1,
+            _ => {}
+        }
+        if self.options.explain {
+            self.rejected.push((path.to_path_buf(), reason));
+        }
+    }
+
+    /// 列出名称（或相对路径）与条目匹配的所有模式，不考虑其他过滤条件和内容条件
+    fn matching_patterns(&self, entry_path: &Path, is_dir: bool) -> Vec<String> {
+        let name = entry_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+        let policy = self.options.glob_dot_policy;
+        let relative = self
+            .current_root
+            .and_then(|root| entry_path.strip_prefix(root).ok());
+        if is_dir {
+            return self
+                .config
+                .clean
+                .folders
+                .iter()
+                .filter(|pattern| {
+                    SearchEngine::match_folder_pattern(pattern, name, relative, policy)
+                })
+                .cloned()
+                .collect();
+        }
+        self.config
+            .clean
+            .files
+            .iter()
+            .filter(|pattern| {
+                let file_pattern = SearchEngine::split_content_pattern(pattern).0;
+                if SearchEngine::is_path_pattern(file_pattern) {
+                    relative.is_some_and(|relative| {
+                        SearchEngine::match_relative_path(file_pattern, relative, policy)
+                    })
+                } else {
+                    SearchEngine::match_pattern_with_policy(file_pattern, name, policy)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 检查条目是否属于 `only_owned_by` 指定的用户（未指定或非 Unix 平台时总是返回 `true`）
+    #[cfg(unix)]
+    fn is_owned(&self, metadata: &fs::Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        self.options
+            .only_owned_by
+            .is_none_or(|uid| metadata.uid() == uid)
+    }
+
+    #[cfg(not(unix))]
+    fn is_owned(&self, _metadata: &fs::Metadata) -> bool {
         true
     }
 
-    fn check_age(
-        metadata: &fs::Metadata,
-        min_age_days: Option<u32>,
-        max_age_days: Option<u32>,
-    ) -> bool {
-        if min_age_days.is_none() && max_age_days.is_none() {
-            return true;
+    /// 处理遍历到的单个条目，匹配时返回匹配项
+    fn process_entry(&mut self, entry: WalkEntry) -> Option<Result<Match, CleanError>> {
+        let (entry_path, file_type) = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                // 忽略遍历错误（如权限问题、符号链接循环等），继续处理其他文件
+                // 但错误过多时通常说明根路径有误或挂载点异常，此时中止扫描
+                self.walk_errors += 1;
+                log::debug!("Traversal error: {}", e);
+                if self.options.max_walk_errors > 0
+                    && self.walk_errors > self.options.max_walk_errors
+                {
+                    return Some(Err(CleanError::TooManyWalkErrors(
+                        self.walk_errors,
+                        self.options.max_walk_errors,
+                    )));
+                }
+                return None;
+            }
+        };
+
+        // 使用自定义匹配策略时由策略决定是否排除
+        if self.policy.is_none() && SearchEngine::should_exclude(&entry_path, &self.config.exclude)
+        {
+            self.reject(&entry_path, RejectReason::Excluded);
+            return None;
+        }
+
+        // 忽略文件由用户放在目录树中，使用自定义匹配策略时同样生效
+        if self.current_root.is_some_and(|root| {
+            self.ignore_files
+                .is_ignored(root, &entry_path, file_type.is_dir())
+        }) {
+            // 被忽略的目录中的内容同样被忽略，遍历时直接跳过
+            if file_type.is_dir() {
+                self.ignored_dirs.lock().unwrap().insert(entry_path.clone());
+            }
+            self.reject(&entry_path, RejectReason::Ignored);
+            return None;
+        }
+
+        // 未跟随的、指向目录的符号链接按目录模式单独处理
+        if file_type.is_symlink() {
+            if let Some(found) = self.match_symlinked_dir(&entry_path) {
+                return found.map(Ok);
+            }
+        }
+
+        // 未跟随的符号链接按文件处理（删除时只移除链接本身）
+        if file_type.is_file() || file_type.is_symlink() {
+            // 不跟随符号链接时使用链接自身的元数据，大小和年龄都以链接本身为准
+            let metadata = match SearchEngine::stat_with_retry(
+                &entry_path,
+                self.options.stat_retries,
+                STAT_RETRY_BACKOFF,
+                |path| (self.stat)(path, self.options.follow_symlinks),
+            ) {
+                Ok(m) => m,
+                Err(e) => {
+                    log::debug!("Failed to stat {}: {}", entry_path.display(), e);
+                    self.reject(&entry_path, RejectReason::StatFailed);
+                    return None;
+                }
+            };
+            self.files_scanned += 1;
+            let found = self.match_file(&entry_path, &metadata);
+
+            // 每扫描 1000 个文件输出一次进度
+            if self.files_scanned.is_multiple_of(1000) {
+                self.report_progress();
+            }
+            found.map(Ok)
+        } else if file_type.is_dir() {
+            self.dirs_scanned += 1;
+            let found = self.match_folder(&entry_path);
+
+            // 每扫描 100 个目录输出一次进度，或者每当匹配到目录时也输出
+            if self.dirs_scanned.is_multiple_of(100)
+                || self.dirs_matched > 0 && self.dirs_matched.is_multiple_of(10)
+            {
+                self.report_progress();
+            }
+            found.map(Ok)
+        } else {
+            // 特殊文件不能按普通文件删除，也不计入扫描数量，只记录下来供诊断
+            if SearchEngine::is_special_file(file_type) {
+                log::debug!("Skipping special file: {}", entry_path.display());
+                self.special_files.push(entry_path.clone());
+                self.reject(&entry_path, RejectReason::SpecialFile);
+            }
+            None
+        }
+    }
+
+    fn match_file(&mut self, entry_path: &Path, metadata: &fs::Metadata) -> Option<Match> {
+        if !self.is_owned(metadata) {
+            self.reject(entry_path, RejectReason::NotOwned);
+            return None;
+        }
+
+        let size = metadata.len();
+
+        if let Some(reason) =
+            SearchEngine::size_reject_reason(size, self.options.min_size, self.options.max_size)
+        {
+            self.reject(entry_path, reason);
+            return None;
+        }
+
+        if let Some(reason) = SearchEngine::age_reject_reason(
+            metadata,
+            self.options.min_age_days,
+            self.options.max_age_days,
+            self.options.now(),
+        ) {
+            self.reject(entry_path, reason);
+            return None;
+        }
+
+        let pattern = match self.decide_file(entry_path, metadata) {
+            Ok(pattern) => pattern,
+            Err(reason) => {
+                self.reject(entry_path, reason);
+                return None;
+            }
+        };
+
+        let physical_size = SearchEngine::physical_size(metadata);
+        self.files_matched += 1;
+        // 同时收集目录和其中的文件时，已匹配目录中的文件大小已经计入该目录
+        let inside_match = self.options.on_overlap == OverlapPolicy::CollectBoth
+            && SearchEngine::is_in_matched_folder(
+                entry_path,
+                &self.matched_folders.lock().unwrap(),
+            );
+        if !inside_match {
+            self.total_size += size;
+            self.total_physical_size += physical_size;
+        }
+        Some(Match {
+            path: entry_path.to_path_buf(),
+            is_dir: false,
+            size,
+            physical_size,
+            size_is_estimate: false,
+            delete_mode: self.delete_mode(&pattern),
+            pattern,
+            needs_confirmation: false,
+        })
+    }
+
+    fn match_folder(&mut self, entry_path: &Path) -> Option<Match> {
+        // 继续遍历已匹配目录的内容时只匹配其中的文件，子目录已包含在外层目录中
+        if self.options.on_overlap == OverlapPolicy::CollectBoth
+            && SearchEngine::is_in_matched_folder(entry_path, &self.matched_folders.lock().unwrap())
+        {
+            return None;
+        }
+
+        // 目录按其自身的所有者判断，只在需要时读取元数据
+        if self.options.only_owned_by.is_some() {
+            let metadata = if self.options.follow_symlinks {
+                fs::metadata(entry_path)
+            } else {
+                fs::symlink_metadata(entry_path)
+            };
+            if !metadata.is_ok_and(|metadata| self.is_owned(&metadata)) {
+                self.reject(entry_path, RejectReason::NotOwned);
+                return None;
+            }
+        }
+
+        let pattern = match self.decide_folder(entry_path) {
+            Ok(pattern) => pattern,
+            Err(reason) => {
+                self.reject(entry_path, reason);
+                return None;
+            }
+        };
+
+        // 跟随符号链接时遍历器报告的是目标类型，需要单独检查路径本身是否为链接
+        if self.options.follow_symlinks
+            && fs::symlink_metadata(entry_path).is_ok_and(|m| m.file_type().is_symlink())
+        {
+            return self.symlinked_dir_match(entry_path, pattern);
+        }
+
+        // 子条目太少的目录（如只有一个占位文件的 build/）很可能不是真正的构建输出
+        if let Some(min_entries) = self.options.min_dir_entries {
+            let entries = fs::read_dir(entry_path)
+                .map(|entries| entries.count())
+                .unwrap_or(0);
+            if entries < min_entries {
+                self.reject(entry_path, RejectReason::TooFewEntries);
+                return None;
+            }
+        }
+
+        // 排除规则优先：目录中有被排除的路径时不能整体删除
+        let partial = SearchEngine::contains_excluded(entry_path, &self.config.exclude);
+        if partial && self.options.excluded_child_action == ExcludedChildAction::Skip {
+            // 不记录为已匹配，继续遍历其内容
+            self.reject(entry_path, RejectReason::ContainsExcluded);
+            return None;
+        }
+
+        // 记录匹配的文件夹，后续跳过其子文件夹
+        self.matched_folders
+            .lock()
+            .unwrap()
+            .insert(entry_path.to_path_buf());
+
+        if partial {
+            return self.match_partial_folder(entry_path, pattern);
+        }
+        self.measure_folder(entry_path, pattern)
+    }
+
+    /// 按匹配策略判定目录是否清理
+    ///
+    /// # 返回
+    /// 匹配的模式（或策略给出的匹配原因），不清理时返回原因
+    fn decide_folder(&self, entry_path: &Path) -> Result<String, RejectReason> {
+        let metadata = fs::metadata(entry_path).map_err(|_| RejectReason::StatFailed)?;
+        self.decide(entry_path, &metadata)
+    }
+
+    /// 按匹配策略判定文件是否清理
+    ///
+    /// # 返回
+    /// 匹配的模式（或策略给出的匹配原因），不清理时返回原因
+    fn decide_file(
+        &self,
+        entry_path: &Path,
+        metadata: &fs::Metadata,
+    ) -> Result<String, RejectReason> {
+        self.decide(entry_path, metadata)
+    }
+
+    /// 按匹配策略判定条目，包含 `/` 的模式匹配相对于扫描根路径的路径
+    fn decide(&self, entry_path: &Path, metadata: &fs::Metadata) -> Result<String, RejectReason> {
+        let relative = self
+            .current_root
+            .and_then(|root| entry_path.strip_prefix(root).ok());
+        match self.policy().decide(entry_path, relative, metadata) {
+            Decision::Clean(pattern) => Ok(pattern),
+            Decision::Skip(reason) => Err(reason),
+        }
+    }
+
+    /// 检查未跟随的符号链接是否为名称匹配目录模式的目录链接
+    ///
+    /// # 返回
+    /// 不是这类链接时返回 `None`（按普通文件继续处理），否则返回匹配结果
+    fn match_symlinked_dir(&mut self, entry_path: &Path) -> Option<Option<Match>> {
+        let pattern = self.decide_folder(entry_path).ok()?;
+        if !fs::metadata(entry_path).is_ok_and(|m| m.is_dir()) {
+            return None;
+        }
+        self.files_scanned += 1;
+        Some(self.symlinked_dir_match(entry_path, pattern))
+    }
+
+    /// 处理名称匹配目录模式的目录符号链接
+    ///
+    /// 默认只记录并跳过；启用 `include_symlinked_dirs` 时作为文件匹配，删除时只移除链接本身
+    fn symlinked_dir_match(&mut self, entry_path: &Path, pattern: String) -> Option<Match> {
+        if !self.options.include_symlinked_dirs {
+            log::debug!("Skipping symlinked directory: {}", entry_path.display());
+            self.symlinked_dirs.push(entry_path.to_path_buf());
+            self.reject(entry_path, RejectReason::SymlinkedDir);
+            return None;
+        }
+        let metadata = fs::symlink_metadata(entry_path).ok()?;
+        if !self.is_owned(&metadata) {
+            self.reject(entry_path, RejectReason::NotOwned);
+            return None;
+        }
+
+        // 跟随符号链接时也不再进入已匹配的链接
+        self.matched_folders
+            .lock()
+            .unwrap()
+            .insert(entry_path.to_path_buf());
+
+        let size = metadata.len();
+        let physical_size = SearchEngine::physical_size(&metadata);
+        self.files_matched += 1;
+        self.total_size += size;
+        self.total_physical_size += physical_size;
+        Some(Match {
+            path: entry_path.to_path_buf(),
+            is_dir: false,
+            size,
+            physical_size,
+            size_is_estimate: false,
+            delete_mode: self.delete_mode(&pattern),
+            pattern,
+            needs_confirmation: false,
+        })
+    }
+
+    /// 部分清理包含排除路径的目录：将其中可以删除的内容逐个作为匹配项
+    fn match_partial_folder(&mut self, entry_path: &Path, pattern: String) -> Option<Match> {
+        for child in SearchEngine::non_excluded_children(entry_path, &self.config.exclude) {
+            let Ok(metadata) = fs::symlink_metadata(&child) else {
+                continue;
+            };
+            let found = if metadata.is_dir() {
+                self.measure_folder(&child, pattern.clone())
+            } else {
+                let size = metadata.len();
+                let physical_size = SearchEngine::physical_size(&metadata);
+                self.files_matched += 1;
+                self.total_size += size;
+                self.total_physical_size += physical_size;
+                Some(Match {
+                    path: child,
+                    is_dir: false,
+                    size,
+                    physical_size,
+                    size_is_estimate: false,
+                    delete_mode: self.delete_mode(&pattern),
+                    pattern: pattern.clone(),
+                    needs_confirmation: false,
+                })
+            };
+            self.queued.extend(found);
+        }
+        self.queued.pop_front()
+    }
+
+    /// 计算匹配目录的大小并完成匹配（启用并行计算时交给线程池）
+    fn measure_folder(&mut self, entry_path: &Path, pattern: String) -> Option<Match> {
+        if self.pending_sizes.is_some() {
+            return self.spawn_size_compute(entry_path, pattern);
+        }
+
+        // 立即计算目录大小，避免扫描完成后的额外等待
+        let track_newest = self.checks_dir_age();
+        let (sizes, newest) = SearchEngine::calculate_dir_size_cached(
+            entry_path,
+            self.cache.as_deref_mut(),
+            self.options.size_max_depth,
+            track_newest,
+        );
+        self.finish_folder(entry_path, pattern, sizes, newest)
+    }
+
+    /// 是否需要检查匹配目录的年龄
+    fn checks_dir_age(&self) -> bool {
+        self.options.dir_age_from_contents
+            && (self.options.min_age_days.is_some() || self.options.max_age_days.is_some())
+    }
+
+    /// 在线程池中计算目录大小，主遍历继续进行；缓存命中时直接返回匹配项
+    fn spawn_size_compute(&mut self, entry_path: &Path, pattern: String) -> Option<Match> {
+        let mtime = fs::metadata(entry_path)
+            .ok()
+            .map(|m| FileTime::from_last_modification_time(&m));
+        if let (Some(cache), Some(mtime)) = (self.cache.as_deref_mut(), mtime) {
+            if let Some(entry) = cache.get(entry_path, mtime) {
+                let sizes = (entry.size, entry.physical_size, false);
+                let newest = if self.checks_dir_age() {
+                    SearchEngine::walk_dir_stats(entry_path, self.options.size_max_depth, true).1
+                } else {
+                    None
+                };
+                return self.finish_folder(entry_path, pattern, sizes, newest);
+            }
+        }
+
+        // 排队的任务达到上限时先等待一个完成，限制排队结果占用的内存
+        let at_limit = self
+            .pending_sizes
+            .as_ref()
+            .is_some_and(|pending| pending.in_flight >= self.options.runtime.max_pending);
+        if at_limit {
+            if let Some(Some(item)) = self.next_computed_folder(true) {
+                self.queued.push_back(item);
+            }
+        }
+
+        let track_newest = self.checks_dir_age();
+        let pending = self.pending_sizes.as_mut()?;
+        let sender = pending.sender.clone();
+        let path = entry_path.to_path_buf();
+        let max_depth = self.options.size_max_depth;
+        pending.pool.spawn(move || {
+            let sizes = SearchEngine::walk_dir_stats(&path, max_depth, track_newest);
+            // 接收端只会在迭代器被丢弃后关闭，此时结果已不再需要
+            let _ = sender.send((path, pattern, mtime, sizes));
+        });
+        pending.in_flight += 1;
+        None
+    }
+
+    /// 取出一个在线程池中计算完成的目录，`wait` 为 `true` 时等待尚未完成的计算
+    fn next_computed_folder(&mut self, wait: bool) -> Option<Option<Match>> {
+        let pending = self.pending_sizes.as_mut()?;
+        if pending.in_flight == 0 {
+            return None;
+        }
+        let (path, pattern, mtime, (sizes, newest)) = if wait {
+            pending.receiver.recv().ok()?
+        } else {
+            pending.receiver.try_recv().ok()?
+        };
+        pending.in_flight -= 1;
+
+        if let (Some(cache), Some(mtime)) = (self.cache.as_deref_mut(), mtime) {
+            SearchEngine::store_dir_size(cache, &path, mtime, sizes);
+        }
+        Some(self.finish_folder(&path, pattern, sizes, newest))
+    }
+
+    /// 根据目录大小完成匹配：检查年龄和大小阈值并累计统计
+    fn finish_folder(
+        &mut self,
+        entry_path: &Path,
+        pattern: String,
+        (size, physical_size, size_is_estimate): (u64, u64, bool),
+        newest: Option<SystemTime>,
+    ) -> Option<Match> {
+        // 目录年龄按其中最新文件计算，没有文件时使用目录自身的修改时间
+        if self.checks_dir_age() {
+            let modified = newest.or_else(|| {
+                fs::metadata(entry_path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            });
+            if let Some(reason) = modified.and_then(|modified| {
+                SearchEngine::modified_age_reject_reason(
+                    modified,
+                    self.options.min_age_days,
+                    self.options.max_age_days,
+                    self.options.now(),
+                )
+            }) {
+                self.reject(entry_path, reason);
+                return None;
+            }
+        }
+
+        // 目录大小超出阈值时跳过，或要求删除前确认
+        let mut needs_confirmation = false;
+        if let Some(reason) = SearchEngine::size_reject_reason(
+            size,
+            self.options.dir_min_size,
+            self.options.dir_max_size,
+        ) {
+            match self.options.dir_size_action {
+                DirSizeAction::Skip => {
+                    self.reject(entry_path, reason);
+                    return None;
+                }
+                DirSizeAction::Confirm => needs_confirmation = true,
+            }
+        }
+
+        self.dirs_matched += 1;
+        self.total_size += size;
+        self.total_physical_size += physical_size;
+        Some(Match {
+            path: entry_path.to_path_buf(),
+            is_dir: true,
+            size,
+            physical_size,
+            size_is_estimate,
+            delete_mode: self.delete_mode(&pattern),
+            pattern,
+            needs_confirmation,
+        })
+    }
+}
+
+impl<F> Iterator for Matches<'_, F>
+where
+    F: FnMut(usize, usize, usize, usize, u64),
+{
+    type Item = Result<Match, CleanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            if let Some(item) = self.queued.pop_front() {
+                return Some(Ok(item));
+            }
+
+            if self
+                .cancel
+                .as_ref()
+                .is_some_and(|cancel| cancel.is_cancelled())
+            {
+                self.cancelled = true;
+                self.finished = true;
+                self.walker = None;
+                return None;
+            }
+
+            // 优先返回已经在线程池中计算完成的目录
+            while let Some(folder) = self.next_computed_folder(false) {
+                if let Some(item) = folder {
+                    return Some(Ok(item));
+                }
+            }
+
+            if self.walker.is_none() {
+                let Some(path) = self.paths.next() else {
+                    // 所有根路径遍历完毕，等待剩余的目录大小计算完成
+                    while let Some(folder) = self.next_computed_folder(true) {
+                        if let Some(item) = folder {
+                            return Some(Ok(item));
+                        }
+                    }
+                    return None;
+                };
+                self.current_root = Some(path);
+                let matched_folders = Arc::clone(&self.matched_folders);
+                let ignored_dirs = Arc::clone(&self.ignored_dirs);
+                let skipped_in_match = Arc::clone(&self.skipped_in_match);
+                let collect_both = self.options.on_overlap == OverlapPolicy::CollectBoth;
+                self.walker = Some(Box::new(SearchEngine::walk_path_with_filter(
+                    path,
+                    &self.options,
+                    self.part.as_ref(),
+                    move |entry_path| {
+                        if !collect_both {
+                            let matched = matched_folders.lock().unwrap();
+                            if SearchEngine::is_in_matched_folder(entry_path, &matched) {
+                                skipped_in_match.fetch_add(1, Ordering::Relaxed);
+                                return false;
+                            }
+                        }
+                        !SearchEngine::is_in_matched_folder(
+                            entry_path,
+                            &ignored_dirs.lock().unwrap(),
+                        )
+                    },
+                )));
+            }
+
+            let entry = match self.walker.as_mut().and_then(|walker| walker.next()) {
+                Some(entry) => entry,
+                None => {
+                    // 当前根路径遍历完毕，继续下一个
+                    self.walker = None;
+                    continue;
+                }
+            };
+
+            if let Some(item) = self.process_entry(entry) {
+                if item.is_err() {
+                    self.finished = true;
+                }
+                return Some(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CleanConfig, Config, ConfigLoader, Options};
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_match_pattern() {
+        // 测试文件夹匹配
+        assert!(SearchEngine::match_pattern("node_modules/", "node_modules"));
+        assert!(!SearchEngine::match_pattern("node_modules/", "dist"));
+
+        // 测试文件通配符匹配
+        assert!(SearchEngine::match_pattern("*.log", "test.log"));
+        assert!(SearchEngine::match_pattern("*.log", "app.log"));
+        assert!(!SearchEngine::match_pattern("*.log", "test.txt"));
+
+        // 测试 ? 通配符
+        assert!(SearchEngine::match_pattern("test?.txt", "test1.txt"));
+        assert!(SearchEngine::match_pattern("test?.txt", "testa.txt"));
+        assert!(!SearchEngine::match_pattern("test?.txt", "test.txt"));
+
+        // 测试精确匹配
+        assert!(SearchEngine::match_pattern("test.txt", "test.txt"));
+        assert!(!SearchEngine::match_pattern("test.txt", "test.log"));
+
+        // 默认情况下通配符不匹配隐藏文件开头的 .
+        assert!(!SearchEngine::match_pattern("*", ".foo"));
+        assert!(!SearchEngine::match_pattern("?foo", ".foo"));
+        assert!(SearchEngine::match_pattern(".*", ".foo"));
+        assert!(!SearchEngine::match_pattern("*.log", ".x.log"));
+        assert!(SearchEngine::match_pattern(".*.log", ".x.log"));
+        assert!(SearchEngine::match_pattern(".env", ".env"));
+
+        // 允许通配符匹配开头的 . 时
+        assert!(SearchEngine::match_pattern_with_policy(
+            "*.log",
+            ".x.log",
+            GlobDotPolicy::MatchDot
+        ));
+        assert!(SearchEngine::match_pattern_with_policy(
+            "*",
+            ".foo",
+            GlobDotPolicy::MatchDot
+        ));
+    }
+
+    #[test]
+    fn test_match_regex_pattern() {
+        let pattern = r"re:^bundle-[0-9a-f]{8}\.js$";
+        assert!(SearchEngine::match_pattern(pattern, "bundle-0123abcd.js"));
+        assert!(!SearchEngine::match_pattern(pattern, "bundle-xyz.js"));
+        assert!(!SearchEngine::match_pattern(
+            pattern,
+            "bundle-0123abcd.js.map"
+        ));
+        // 重复调用使用缓存的编译结果，结果不变
+        for _ in 0..3 {
+            assert!(SearchEngine::match_pattern(pattern, "bundle-89abcdef.js"));
+        }
+
+        // 没有 `re:` 前缀时仍然按通配符匹配，花括号不会在正则表达式中展开
+        assert!(SearchEngine::match_pattern("bundle-*.js", "bundle-xyz.js"));
+        assert!(SearchEngine::match_pattern("re:^a{2}$", "aa"));
+        assert!(!SearchEngine::match_pattern("re:^a{2}$", "a2"));
+        // 正则表达式自行决定是否匹配隐藏文件
+        assert!(SearchEngine::match_pattern(
+            r"re:^\.cache-\d+$",
+            ".cache-42"
+        ));
+        // 无效的正则表达式不匹配任何名称（加载配置时已经报错）
+        assert!(!SearchEngine::match_pattern("re:(", "("));
+
+        assert!(SearchEngine::is_regex_pattern("re:.*"));
+        assert!(SearchEngine::compile_regex_pattern("*.log").is_none());
+        assert!(matches!(
+            SearchEngine::compile_regex_pattern("re:["),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_regex_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("out-1")).unwrap();
+        fs::create_dir_all(root.join("out-latest")).unwrap();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(root.join("web/bundle-0123abcd.js"), "x").unwrap();
+        fs::write(root.join("web/bundle-main.js"), "x").unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![r"re:^out-\d+$".to_string()],
+                // 正则表达式只匹配名称，其中的 `/` 不会使其按路径匹配
+                files: vec![r"re:^bundle-[0-9a-f]{8}\.js$|^web/".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        ConfigLoader::validate_config(&config).unwrap();
+        let result = SearchEngine::search(&[root.to_path_buf()], &config).unwrap();
+        assert_eq!(result.folders, vec![root.join("out-1")]);
+        assert_eq!(result.files, vec![root.join("web/bundle-0123abcd.js")]);
+    }
+
+    #[test]
+    fn test_match_multi_dot_extension() {
+        // 多段扩展名按完整后缀匹配，而不只是最后一个 . 之后的部分
+        assert!(SearchEngine::match_pattern("*.tar.gz", "x.tar.gz"));
+        assert!(SearchEngine::match_pattern("*.tar.gz", "archive.v1.tar.gz"));
+        assert!(!SearchEngine::match_pattern("*.tar.gz", "x.gz"));
+        assert!(!SearchEngine::match_pattern("*.tar.gz", "x.tar"));
+        assert!(!SearchEngine::match_pattern("*.tar.gz", "x.tar.gz.bak"));
+        assert!(SearchEngine::match_pattern("*.{tar.gz,tgz}", "x.tgz"));
+        assert!(!SearchEngine::match_pattern("*.tar.gz", ".tar.gz"));
+    }
+
+    #[test]
+    fn test_brace_expansion() {
+        // 展开为多个模式，任意一个匹配即可
+        assert_eq!(
+            SearchEngine::expand_braces("*.{log,tmp}"),
+            vec!["*.log", "*.tmp"]
+        );
+        assert!(SearchEngine::match_pattern("*.{log,tmp}", "app.log"));
+        assert!(SearchEngine::match_pattern("*.{log,tmp}", "cache.tmp"));
+        assert!(!SearchEngine::match_pattern("*.{log,tmp}", "main.rs"));
+        assert!(SearchEngine::match_pattern("{dist,build}/", "build"));
+
+        // 嵌套和空候选项
+        assert_eq!(
+            SearchEngine::expand_braces("a{b,c{d,e}}f"),
+            vec!["abf", "acdf", "acef"]
+        );
+        assert_eq!(
+            SearchEngine::expand_braces("*.log{,.1}"),
+            vec!["*.log", "*.log.1"]
+        );
+
+        // 转义的花括号和逗号按字面处理
+        assert_eq!(SearchEngine::expand_braces("\\{a,b\\}"), vec!["{a,b}"]);
+        assert_eq!(SearchEngine::expand_braces("{a\\,b,c}"), vec!["a,b", "c"]);
+        assert!(SearchEngine::match_pattern("\\{a,b\\}", "{a,b}"));
+        assert!(!SearchEngine::match_pattern("\\{a,b\\}", "a"));
+
+        // 不配对或没有逗号的花括号按字面处理
+        assert_eq!(SearchEngine::expand_braces("*.{log"), vec!["*.{log"]);
+        assert_eq!(SearchEngine::expand_braces("log}"), vec!["log}"]);
+        assert_eq!(SearchEngine::expand_braces("{a}"), vec!["{a}"]);
+        assert_eq!(SearchEngine::expand_braces("{{a,b}"), vec!["{a", "{b"]);
+        assert!(SearchEngine::match_pattern("*.{log", "app.{log"));
+        assert!(!SearchEngine::match_pattern("*.{log", "app.log"));
+    }
+
+    #[test]
+    fn test_should_exclude() {
+        let excludes = vec![
+            PathBuf::from("/exclude/path1"),
+            PathBuf::from("/exclude/path2"),
+        ];
+
+        // 测试应该排除的路径
+        assert!(SearchEngine::should_exclude(
+            &PathBuf::from("/exclude/path1/sub"),
+            &excludes
+        ));
+        assert!(SearchEngine::should_exclude(
+            &PathBuf::from("/exclude/path2"),
+            &excludes
+        ));
+
+        // 测试不应该排除的路径
+        assert!(!SearchEngine::should_exclude(
+            &PathBuf::from("/other/path"),
+            &excludes
+        ));
+    }
+
+    #[test]
+    fn test_check_size() {
+        // 测试无限制
+        assert!(SearchEngine::size_reject_reason(1000, None, None).is_none());
+
+        // 测试最小大小
+        assert!(SearchEngine::size_reject_reason(1000, Some(500), None).is_none());
+        assert!(SearchEngine::size_reject_reason(1000, Some(1500), None).is_some());
+
+        // 测试最大大小
+        assert!(SearchEngine::size_reject_reason(1000, None, Some(2000)).is_none());
+        assert!(SearchEngine::size_reject_reason(1000, None, Some(500)).is_some());
+
+        // 测试范围
+        assert!(SearchEngine::size_reject_reason(1000, Some(500), Some(2000)).is_none());
+        assert!(SearchEngine::size_reject_reason(1000, Some(1500), Some(2000)).is_some());
+        assert!(SearchEngine::size_reject_reason(1000, Some(500), Some(800)).is_some());
+    }
+
+    #[test]
+    fn test_check_age() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        fs::File::create(&test_file).unwrap();
+
+        // 修改时间固定为 2024-01-01，当前时间固定为其后 10 天，结果不受系统时间影响
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+        filetime::set_file_mtime(&test_file, FileTime::from_system_time(modified)).unwrap();
+        let metadata = fs::metadata(&test_file).unwrap();
+        let now = modified + Duration::from_secs(10 * 86400);
+
+        // 测试无限制
+        assert!(SearchEngine::age_reject_reason(&metadata, None, None, now).is_none());
+
+        // 测试最小年龄：10 天满足 7 天，不满足 30 天
+        assert!(SearchEngine::age_reject_reason(&metadata, Some(7), None, now).is_none());
+        assert_eq!(
+            SearchEngine::age_reject_reason(&metadata, Some(30), None, now),
+            Some(RejectReason::TooNew)
+        );
+        // 恰好 10 天也满足最小年龄
+        assert!(SearchEngine::age_reject_reason(&metadata, Some(10), None, now).is_none());
+
+        // 测试最大年龄
+        assert_eq!(
+            SearchEngine::age_reject_reason(&metadata, None, Some(7), now),
+            Some(RejectReason::TooOld)
+        );
+        assert!(SearchEngine::age_reject_reason(&metadata, None, Some(30), now).is_none());
+
+        // 修改时间晚于当前时间时不检查
+        let before = modified - Duration::from_secs(86400);
+        assert!(SearchEngine::age_reject_reason(&metadata, Some(7), None, before).is_none());
+
+        // 通过选项固定当前时间，搜索结果同样确定
+        let search = |now: SystemTime| {
+            let config = Config {
+                clean: CleanConfig {
+                    folders: vec![],
+                    files: vec!["*.txt".to_string()],
+                    ..Default::default()
+                },
+                exclude: vec![],
+                options: Options {
+                    min_age_days: Some(30),
+                    now: Some(now),
+                    ..Options::default()
+                },
+            };
+            SearchEngine::search(&[temp_dir.path().to_path_buf()], &config)
+                .unwrap()
+                .files
+        };
+        assert!(search(now).is_empty());
+        assert_eq!(
+            search(modified + Duration::from_secs(40 * 86400)),
+            vec![test_file.clone()]
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 创建测试目录结构
+        let node_modules = project_path.join("node_modules");
+        fs::create_dir(&node_modules).unwrap();
+        let dist = project_path.join("dist");
+        fs::create_dir(&dist).unwrap();
+
+        let test_file = project_path.join("test.log");
+        fs::File::create(&test_file)
+            .unwrap()
+            .write_all(b"test")
+            .unwrap();
+
+        // 创建配置
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string(), "dist".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                recursive: true,
+                follow_symlinks: false,
+                min_size: None,
+                max_size: None,
+                min_age_days: None,
+                max_age_days: None,
+                ..Options::default()
+            },
+        };
+
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+
+        // 验证搜索结果
+        assert!(result.folders.len() >= 2);
+        assert!(!result.files.is_empty());
+        assert!(result.total_size > 0);
+    }
+
+    #[test]
+    fn test_walk_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+
+        // 创建测试文件
+        let file1 = test_path.join("file1.txt");
+        fs::File::create(&file1).unwrap();
+        let subdir = test_path.join("subdir");
+        fs::create_dir(&subdir).unwrap();
+        let file2 = subdir.join("file2.txt");
+        fs::File::create(&file2).unwrap();
+
+        let options = SearchOptions {
+            recursive: true,
+            follow_symlinks: false,
+            max_depth: None,
+            min_size: None,
+            max_size: None,
+            min_age_days: None,
+            max_age_days: None,
+            explain: false,
+            max_walk_errors: 0,
+            physical_size: false,
+            contents_first: false,
+            ..SearchOptions::default()
+        };
+
+        let paths: Vec<PathBuf> = SearchEngine::walk_path(test_path, &options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert!(paths.len() >= 3); // 至少包含根目录、子目录和两个文件
+    }
+
+    #[test]
+    fn test_walk_path_typed() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        let subdir = test_path.join("subdir");
+        fs::create_dir(&subdir).unwrap();
+        fs::write(test_path.join("file1.txt"), "1").unwrap();
+        fs::write(subdir.join("file2.txt"), "2").unwrap();
+        let options = SearchOptions::default();
+
+        // 遍历返回的类型与实际类型一致
+        let entries = SearchEngine::walk_path_typed(test_path, &options, EntryTypeFilter::All)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries.len(), 4);
+        for (path, file_type) in &entries {
+            let metadata = fs::symlink_metadata(path).unwrap();
+            assert_eq!(file_type.is_dir(), metadata.is_dir());
+            assert_eq!(file_type.is_file(), metadata.is_file());
+        }
+
+        // 只返回文件时不包含目录，但仍然遍历子目录中的文件
+        let mut files: Vec<PathBuf> =
+            SearchEngine::walk_path_typed(test_path, &options, EntryTypeFilter::FilesOnly)
+                .map(|entry| entry.map(|(path, _)| path))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![test_path.join("file1.txt"), subdir.join("file2.txt")]
+        );
+
+        let dirs: Vec<PathBuf> =
+            SearchEngine::walk_path_typed(test_path, &options, EntryTypeFilter::DirsOnly)
+                .map(|entry| entry.map(|(path, _)| path))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        assert_eq!(dirs, vec![test_path.to_path_buf(), subdir]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_skips_special_files() {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::write(project_path.join("app.log"), "log").unwrap();
+
+        // 名称匹配 *.log 的 FIFO
+        let fifo = project_path.join("events.log");
+        let fifo_path = CString::new(fifo.as_os_str().as_bytes()).unwrap();
+        // SAFETY: 路径是有效的以 NUL 结尾的字符串
+        assert_eq!(unsafe { libc::mkfifo(fifo_path.as_ptr(), 0o644) }, 0);
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                explain: true,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let result = SearchEngine::search(&paths, &config).unwrap();
+
+        // FIFO 不被匹配，但会被记录下来
+        assert_eq!(result.files, vec![project_path.join("app.log")]);
+        assert_eq!(result.special_files, vec![fifo.clone()]);
+        assert!(result
+            .rejected
+            .contains(&(fifo.clone(), RejectReason::SpecialFile)));
+
+        // 即使出现在删除计划中也不会被删除
+        assert!(crate::delete::DeleteEngine::check_safety(&fifo).is_err());
+    }
+
+    #[test]
+    fn test_search_keep_newest_per_dir() {
+        use filetime::set_file_mtime;
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let logs = project_path.join("logs");
+        fs::create_dir_all(&logs).unwrap();
+        let now = SystemTime::now();
+        for (name, days_ago) in [("old.log", 3), ("older.log", 5), ("new.log", 1)] {
+            let path = logs.join(name);
+            fs::write(&path, "log").unwrap();
+            let mtime = FileTime::from_system_time(now - Duration::from_secs(days_ago * 86400));
+            set_file_mtime(&path, mtime).unwrap();
+        }
+        // 另一个目录中唯一的日志也会被保留
+        fs::write(project_path.join("build.log"), "log").unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                keep_newest_per_dir: true,
+                explain: true,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let mut result = SearchEngine::search(&paths, &config).unwrap();
+        result.files.sort();
+
+        // 最新的日志保留，两份较旧的日志计划删除
+        assert_eq!(
+            result.files,
+            vec![logs.join("old.log"), logs.join("older.log")]
+        );
+        assert_eq!(result.total_size, 6);
+        assert_eq!(result.item_sizes.len(), 2);
+        assert!(result
+            .rejected
+            .contains(&(logs.join("new.log"), RejectReason::KeptNewest)));
+        assert!(result
+            .rejected
+            .contains(&(project_path.join("build.log"), RejectReason::KeptNewest)));
+    }
+
+    #[test]
+    fn test_walk_path_contents_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+
+        let subdir = test_path.join("subdir");
+        fs::create_dir(&subdir).unwrap();
+        let child = subdir.join("child.txt");
+        fs::File::create(&child).unwrap();
+
+        let mut options = SearchOptions::default();
+
+        let position =
+            |paths: &[PathBuf], target: &Path| paths.iter().position(|p| p == target).unwrap();
+
+        // 默认前序遍历：父目录先于子项
+        let paths: Vec<PathBuf> = SearchEngine::walk_path(test_path, &options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(position(&paths, &subdir) < position(&paths, &child));
+
+        // 后序遍历：子项先于父目录
+        options.contents_first = true;
+        let paths: Vec<PathBuf> = SearchEngine::walk_path(test_path, &options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(position(&paths, &child) < position(&paths, &subdir));
+        assert_eq!(paths.last().unwrap(), test_path);
+    }
+
+    #[test]
+    fn test_search_dir_size_thresholds() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 小目录：只有 10 字节
+        let small = project_path.join("a").join("build");
+        fs::create_dir_all(&small).unwrap();
+        fs::File::create(small.join("stub.txt"))
+            .unwrap()
+            .write_all(&[0u8; 10])
+            .unwrap();
+
+        // 大目录：4096 字节
+        let large = project_path.join("b").join("build");
+        fs::create_dir_all(&large).unwrap();
+        fs::File::create(large.join("output.bin"))
+            .unwrap()
+            .write_all(&[0u8; 4096])
+            .unwrap();
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["build".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                dir_min_size: Some(1024),
+                ..Options::default()
+            },
+        };
+
+        // 默认跳过小于阈值的目录
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        assert_eq!(result.folders, vec![large.clone()]);
+        assert_eq!(result.total_size, 4096);
+        assert!(result.needs_confirmation.is_empty());
+
+        // 确认模式下仍然选中，但要求删除前确认
+        config.options.dir_size_action = DirSizeAction::Confirm;
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        assert_eq!(result.folders.len(), 2);
+        assert_eq!(result.needs_confirmation, vec![(small, 10)]);
+    }
+
+    #[test]
+    fn test_search_with_cache_reuses_unchanged_dirs() {
+        use std::collections::HashMap;
+
+        /// 记录命中和未命中次数的缓存
+        #[derive(Default)]
+        struct RecordingCache {
+            entries: HashMap<PathBuf, CacheEntry>,
+            hits: usize,
+            misses: usize,
+        }
+
+        impl ScanCache for RecordingCache {
+            fn get(&mut self, dir: &Path, mtime: FileTime) -> Option<CacheEntry> {
+                match self.entries.get(dir) {
+                    Some(entry) if entry.is_fresh(mtime) => {
+                        self.hits += 1;
+                        Some(entry.clone())
+                    }
+                    _ => {
+                        self.misses += 1;
+                        None
+                    }
+                }
+            }
+
+            fn insert(&mut self, dir: &Path, entry: CacheEntry) {
+                self.entries.insert(dir.to_path_buf(), entry);
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let target = project_path.join("target");
+        fs::create_dir(&target).unwrap();
+        fs::File::create(target.join("app"))
+            .unwrap()
+            .write_all(&[0u8; 100])
+            .unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = [project_path.to_path_buf()];
+        let mut cache = RecordingCache::default();
+
+        // 第一次扫描：缓存未命中，计算并写入
+        let first = SearchEngine::search_with_cache(
+            &paths,
+            &config,
+            Some(&mut cache),
+            None::<fn(usize, usize, usize, usize, u64)>,
+        )
+        .unwrap();
+        assert_eq!((cache.hits, cache.misses), (0, 1));
+        assert_eq!(first.total_size, 100);
+
+        // 将缓存中的大小改为特殊值，以证明第二次扫描直接使用缓存而不是重新遍历
+        cache.entries.get_mut(&target).unwrap().size = 12345;
+
+        let second = SearchEngine::search_with_cache(
+            &paths,
+            &config,
+            Some(&mut cache),
+            None::<fn(usize, usize, usize, usize, u64)>,
+        )
+        .unwrap();
+        assert_eq!((cache.hits, cache.misses), (1, 1));
+        assert_eq!(second.total_size, 12345);
+        assert_eq!(second.folders, first.folders);
+    }
+
+    #[test]
+    fn test_search_skip_matched_folder_children() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 创建测试目录结构：
+        // project/
+        //   ├── node_modules/        (匹配的文件夹)
+        //   │   ├── subdir/          (应该被跳过)
+        //   │   │   └── file.txt     (应该被跳过)
+        //   │   └── file.js          (应该被跳过)
+        //   └── other/                (不匹配，应该被遍历)
+        //       └── file.txt
+
+        let node_modules = project_path.join("node_modules");
+        fs::create_dir_all(&node_modules).unwrap();
+
+        let node_modules_subdir = node_modules.join("subdir");
+        fs::create_dir_all(&node_modules_subdir).unwrap();
+        let file_in_node_modules = node_modules_subdir.join("file.txt");
+        fs::File::create(&file_in_node_modules).unwrap();
+
+        let file_in_node_modules_root = node_modules.join("file.js");
+        fs::File::create(&file_in_node_modules_root).unwrap();
+
+        let other_dir = project_path.join("other");
+        fs::create_dir_all(&other_dir).unwrap();
+        let file_in_other = other_dir.join("file.txt");
+        fs::File::create(&file_in_other).unwrap();
+
+        // 创建配置，只搜索 node_modules 文件夹
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                recursive: true,
+                follow_symlinks: false,
+                min_size: None,
+                max_size: None,
+                min_age_days: None,
+                max_age_days: None,
+                ..Options::default()
+            },
+        };
+
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+
+        // 验证：只应该找到 node_modules 文件夹本身
+        // 不应该找到 node_modules 内的子文件夹和文件
+        assert_eq!(result.folders.len(), 1);
+        assert_eq!(result.folders.get(0), Some(node_modules));
+        assert_eq!(result.files.len(), 0);
+    }
+
+    #[test]
+    fn test_search_explain_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        let small_log = project_path.join("small.log");
+        fs::File::create(&small_log)
+            .unwrap()
+            .write_all(b"x")
+            .unwrap();
+        let large_log = project_path.join("large.log");
+        fs::File::create(&large_log)
+            .unwrap()
+            .write_all(&[0u8; 128])
+            .unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                min_size: Some(64),
+                explain: true,
+                ..Options::default()
+            },
+        };
+
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+
+        // 大文件被选中，小文件因大小不足被拒绝
+        assert_eq!(result.files, vec![large_log]);
+        assert!(result
+            .rejected
+            .contains(&(small_log.clone(), RejectReason::TooSmall)));
+
+        // 未启用 explain 时不记录
+        let config = Config {
+            options: Options {
+                min_size: Some(64),
+                ..Options::default()
+            },
+            ..config
+        };
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        assert!(result.rejected.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_aborts_on_too_many_walk_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 跟随符号链接时，每个悬空链接都会产生一个遍历错误
+        for i in 0..5 {
+            std::os::unix::fs::symlink(
+                project_path.join(format!("missing_{}", i)),
+                project_path.join(format!("dangling_{}", i)),
+            )
+            .unwrap();
+        }
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                follow_symlinks: true,
+                max_walk_errors: 3,
+                ..Options::default()
+            },
+        };
+
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config);
+        assert!(matches!(result, Err(CleanError::TooManyWalkErrors(4, 3))));
+
+        // 未超过阈值时继续扫描
+        config.options.max_walk_errors = 5;
+        assert!(SearchEngine::search(&[project_path.to_path_buf()], &config).is_ok());
+
+        // 0 表示不限制
+        config.options.max_walk_errors = 0;
+        assert!(SearchEngine::search(&[project_path.to_path_buf()], &config).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_physical_size() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 稀疏文件：逻辑大小 1 MB，但几乎不占用磁盘块
+        let sparse_file = project_path.join("sparse.log");
+        fs::File::create(&sparse_file)
+            .unwrap()
+            .set_len(1024 * 1024)
+            .unwrap();
+        let metadata = fs::metadata(&sparse_file).unwrap();
+        assert_eq!(
+            SearchEngine::physical_size(&metadata),
+            metadata.blocks() * 512
+        );
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                physical_size: true,
+                ..Options::default()
+            },
+        };
+
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        assert_eq!(result.total_size, metadata.len());
+        assert_eq!(result.total_physical_size, Some(metadata.blocks() * 512));
+        assert!(result.total_physical_size.unwrap() < result.total_size);
+
+        // 未启用时不统计物理大小
+        config.options.physical_size = false;
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        assert_eq!(result.total_physical_size, None);
+    }
+
+    #[test]
+    fn test_matches_lazy_iteration() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        for i in 0..50 {
+            fs::write(project_path.join(format!("{}.log", i)), "log").unwrap();
+        }
+        let target = project_path.join("sub").join("target");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("inner.log"), "log").unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 只取前两个匹配项时应提前停止，不遍历全部文件
+        let mut matches = SearchEngine::matches(&paths, &config);
+        let first_two: Vec<Match> = matches.by_ref().take(2).map(|m| m.unwrap()).collect();
+        assert_eq!(first_two.len(), 2);
+        assert!(matches.files_scanned() < 50);
+
+        // 收集全部匹配项应与 search 的结果一致（已匹配文件夹的子项仍被跳过）
+        let collected: Vec<Match> = SearchEngine::matches(&paths, &config)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let result = SearchEngine::search(&paths, &config).unwrap();
+
+        let folders: Vec<PathBuf> = collected
+            .iter()
+            .filter(|m| m.is_dir)
+            .map(|m| m.path.clone())
+            .collect();
+        let files: Vec<PathBuf> = collected
+            .iter()
+            .filter(|m| !m.is_dir)
+            .map(|m| m.path.clone())
+            .collect();
+        assert_eq!(result.folders, folders);
+        assert_eq!(result.files, files);
+        assert_eq!(files.len(), 50);
+        assert_eq!(
+            collected.iter().map(|m| m.size).sum::<u64>(),
+            result.total_size
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let small_log = project_path.join("small.log");
+        fs::write(&small_log, "log").unwrap();
+        let large_log = project_path.join("large.log");
+        fs::write(&large_log, vec![0u8; 1000]).unwrap();
+        let node_modules = project_path.join("node_modules");
+        fs::create_dir_all(node_modules.join("pkg")).unwrap();
+        let index = node_modules.join("pkg/index.js");
+        fs::write(&index, "module.exports = 1;").unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                min_size: Some(100),
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 被 min_size 过滤的文件报告对应的原因
+        let explanation = SearchEngine::explain(&small_log, &paths, &config).unwrap();
+        assert_eq!(explanation.root, project_path);
+        assert_eq!(explanation.matched_patterns, vec!["*.log".to_string()]);
+        assert_eq!(
+            explanation.verdict,
+            Verdict::Rejected(RejectReason::TooSmall)
+        );
+        assert_eq!(explanation.verdict.to_string(), "not cleaned: too-small");
+
+        // 匹配的文件会被清理
+        let explanation = SearchEngine::explain(&large_log, &paths, &config).unwrap();
+        assert!(explanation
+            .verdict
+            .to_string()
+            .starts_with("would be cleaned"));
+
+        // 匹配目录中的文件随目录一起清理，自身不需要匹配任何模式
+        let explanation = SearchEngine::explain(&index, &paths, &config).unwrap();
+        assert!(explanation.matched_patterns.is_empty());
+        assert_eq!(
+            explanation.verdict,
+            Verdict::WouldClean {
+                pattern: "node_modules".to_string(),
+                matched_path: node_modules.clone(),
+            }
+        );
+
+        // 没有匹配任何模式
+        let explanation = SearchEngine::explain(
+            &node_modules.join("pkg"),
+            &[node_modules.join("pkg")],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            explanation.verdict,
+            Verdict::Rejected(RejectReason::NoPatternMatch)
+        );
+
+        // 不存在的路径返回错误
+        assert!(SearchEngine::explain(&project_path.join("missing"), &paths, &config).is_err());
+    }
+
+    #[test]
+    fn test_search_delete_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path();
+        fs::create_dir_all(project.join("node_modules/pkg")).unwrap();
+        fs::write(project.join("build.tmp"), "tmp").unwrap();
+        fs::write(project.join("debug.log"), "log").unwrap();
+
+        let mut config = ConfigLoader::parse_config_str(
+            r#"{
+                "clean": {
+                    "folders": ["node_modules"],
+                    "files": [{"pattern": "*.tmp", "mode": "permanent"}, "*.log"]
+                },
+                "exclude": [],
+                "options": {}
+            }"#,
+        )
+        .unwrap();
+        let mode_of = |result: &SearchResult, name: &str| {
+            result
+                .item_origins
+                .iter()
+                .find(|origin| origin.path == project.join(name))
+                .map(|origin| origin.delete_mode)
+                .unwrap()
+        };
+
+        let result = SearchEngine::search(&[project.to_path_buf()], &config).unwrap();
+        assert_eq!(mode_of(&result, "node_modules"), DeleteMode::Trash);
+        assert_eq!(mode_of(&result, "build.tmp"), DeleteMode::Permanent);
+        assert_eq!(mode_of(&result, "debug.log"), DeleteMode::Trash);
+
+        // 删除计划中只有指定永久删除的项目不经过回收站
+        let plan = crate::delete::DeleteEngine::create_delete_plan(&result);
+        assert!(plan.is_permanent(&project.join("build.tmp")));
+        assert!(!plan.is_permanent(&project.join("debug.log")));
+        assert!(!plan.is_permanent(&project.join("node_modules")));
+
+        // 没有单独指定的模式使用全局的删除方式
+        config.options.delete_mode = DeleteMode::Permanent;
+        let result = SearchEngine::search(&[project.to_path_buf()], &config).unwrap();
+        assert_eq!(mode_of(&result, "node_modules"), DeleteMode::Permanent);
+        assert_eq!(mode_of(&result, "debug.log"), DeleteMode::Permanent);
+
+        // 永久删除的项目直接删除
+        let plan = crate::delete::DeleteEngine::create_delete_plan(&result);
+        let deleted = crate::delete::DeleteEngine::execute_deletion(&plan, false);
+        assert_eq!(deleted.deleted_files.len(), 2);
+        assert_eq!(deleted.deleted_dirs, vec![project.join("node_modules")]);
+        assert!(!project.join("node_modules").exists());
+        assert!(!project.join("build.tmp").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_symlinked_root() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("project-v2");
+        fs::create_dir_all(project_path.join("node_modules/pkg")).unwrap();
+        fs::write(project_path.join("build.log"), "log").unwrap();
+        // 扫描根路径是指向项目目录的符号链接
+        let current = temp_dir.path().join("current-project");
+        symlink(&project_path, &current).unwrap();
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![current.clone()];
+
+        // 默认进入根路径链接指向的目录，匹配项的路径保留在链接之下
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert_eq!(result.folders, vec![current.join("node_modules")]);
+        assert_eq!(result.files, vec![current.join("build.log")]);
+
+        // 关闭后根路径只作为链接本身出现，不会进入
+        config.options.dereference_root = false;
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert!(result.folders.is_empty());
+        assert!(result.files.is_empty());
+
+        // 跟随符号链接时总会进入
+        config.options.follow_symlinks = true;
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert_eq!(result.folders, vec![current.join("node_modules")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_symlinked_dirs() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("app");
+        fs::create_dir_all(&project_path).unwrap();
+        // 链接目标位于扫描范围之外
+        let shared = temp_dir.path().join("shared_modules");
+        fs::create_dir_all(shared.join("pkg")).unwrap();
+        fs::write(shared.join("pkg/index.js"), "module.exports = 1;").unwrap();
+        let link = project_path.join("node_modules");
+        symlink(&shared, &link).unwrap();
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                explain: true,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.clone()];
+
+        // 默认只报告链接，不论是否跟随符号链接
+        for follow_symlinks in [false, true] {
+            config.options.follow_symlinks = follow_symlinks;
+            let result = SearchEngine::search(&paths, &config).unwrap();
+            assert!(result.folders.is_empty());
+            assert!(result.files.is_empty());
+            assert_eq!(result.symlinked_dirs, vec![link.clone()]);
+            assert!(result
+                .rejected
+                .contains(&(link.clone(), RejectReason::SymlinkedDir)));
+        }
+
+        // 启用 include_symlinked_dirs 后作为链接清理，目标保持不变
+        for follow_symlinks in [false, true] {
+            config.options.follow_symlinks = follow_symlinks;
+            config.options.include_symlinked_dirs = true;
+            let result = SearchEngine::search(&paths, &config).unwrap();
+            assert!(result.folders.is_empty());
+            assert_eq!(result.files, vec![link.clone()]);
+            assert!(result.symlinked_dirs.is_empty());
+            assert_eq!(
+                result.total_size,
+                fs::symlink_metadata(&link).unwrap().len()
+            );
+        }
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        let delete_result =
+            crate::delete::DeleteEngine::execute_deletion_from_search(&result, false);
+        assert_eq!(delete_result.deleted_files, vec![link.clone()]);
+        assert!(fs::symlink_metadata(&link).is_err());
+        assert!(shared.join("pkg/index.js").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_symlink_matching() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 链接名称匹配、目标名称不匹配
+        fs::write(project_path.join("real.dat"), vec![0u8; 10_000]).unwrap();
+        symlink("real.dat", project_path.join("link.log")).unwrap();
+        // 目标名称匹配、链接名称不匹配
+        fs::write(project_path.join("target.log"), "log").unwrap();
+        symlink("target.log", project_path.join("alias.dat")).unwrap();
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 默认只匹配链接自身的名称，大小使用链接自身的元数据
+        let mut result = SearchEngine::search(&paths, &config).unwrap();
+        result.files.sort();
+        assert_eq!(
+            result.files,
+            vec![
+                project_path.join("link.log"),
+                project_path.join("target.log")
+            ]
+        );
+        let link_size = fs::symlink_metadata(project_path.join("link.log"))
+            .unwrap()
+            .len();
+        assert_eq!(result.total_size, link_size + 3);
+
+        // 启用 match_symlink_targets 后也匹配目标名称
+        config.options.match_symlink_targets = true;
+        let mut result = SearchEngine::search(&paths, &config).unwrap();
+        result.files.sort();
+        assert_eq!(
+            result.files,
+            vec![
+                project_path.join("alias.dat"),
+                project_path.join("link.log"),
+                project_path.join("target.log")
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_only_owned_by() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::write(project_path.join("a.log"), "log").unwrap();
+        fs::create_dir(project_path.join("target")).unwrap();
+        let owner = fs::metadata(project_path.join("a.log")).unwrap().uid();
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                only_owned_by: Some(owner),
+                explain: true,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 属于指定用户的条目被匹配
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.folders.len(), 1);
+
+        // 所有者不一致的文件和目录被排除
+        config.options.only_owned_by = Some(owner.wrapping_add(1));
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert!(result.files.is_empty());
+        assert!(result.folders.is_empty());
+        assert!(result
+            .rejected
+            .iter()
+            .any(|(path, reason)| path == &project_path.join("a.log")
+                && *reason == RejectReason::NotOwned));
+        assert!(result
+            .rejected
+            .iter()
+            .any(|(path, reason)| path == &project_path.join("target")
+                && *reason == RejectReason::NotOwned));
+    }
+
+    #[test]
+    fn test_search_min_dir_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 只有一个占位文件的 build 目录
+        let stub = project_path.join("stub").join("build");
+        fs::create_dir_all(&stub).unwrap();
+        fs::write(stub.join(".gitkeep"), "").unwrap();
+        // 包含 10 个条目的 build 目录
+        let real = project_path.join("real").join("build");
+        fs::create_dir_all(&real).unwrap();
+        for i in 0..10 {
+            fs::write(real.join(format!("out{}.o", i)), "obj").unwrap();
+        }
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["build".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                min_dir_entries: Some(5),
+                explain: true,
+                ..Options::default()
+            },
+        };
+
+        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        assert_eq!(result.folders, vec![real]);
+        assert!(result
+            .rejected
+            .contains(&(stub, RejectReason::TooFewEntries)));
+    }
+
+    #[test]
+    fn test_search_size_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // target/a.o (100 字节) + target/deep/nested/b.o (1000 字节)
+        let target = project_path.join("target");
+        fs::create_dir_all(target.join("deep").join("nested")).unwrap();
+        fs::write(target.join("a.o"), vec![0u8; 100]).unwrap();
+        fs::write(
+            target.join("deep").join("nested").join("b.o"),
+            vec![0u8; 1000],
+        )
+        .unwrap();
+
+        let mut config = Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                size_max_depth: Some(1),
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 只统计第一层，并标记为估算值
+        let matched: Vec<Match> = SearchEngine::matches(&paths, &config)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].size, 100);
+        assert!(matched[0].size_is_estimate);
+        assert!(
+            SearchEngine::search(&paths, &config)
+                .unwrap()
+                .size_is_estimate
+        );
+
+        // 不限制深度时统计全部内容
+        config.options.size_max_depth = None;
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert_eq!(result.total_size, 1100);
+        assert!(!result.size_is_estimate);
+    }
+
+    #[test]
+    fn test_search_result_save_and_load() {
+        use crate::delete::DeleteEngine;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("project");
+        fs::create_dir_all(project_path.join("a").join("dist")).unwrap();
+        fs::create_dir_all(project_path.join("b").join("dist")).unwrap();
+        fs::write(project_path.join("a").join("dist").join("x.js"), "x").unwrap();
+        fs::write(project_path.join("debug.log"), "log").unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["dist".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.clone()];
+        let result = SearchEngine::search(&paths, &config).unwrap();
+
+        // 经过 JSON 往返后生成的删除计划与原结果完全一致
+        let saved = temp_dir.path().join("scan.json");
+        result.save(&saved).unwrap();
+        let mut loaded = SearchResult::load(&saved).unwrap();
+        let plan = DeleteEngine::create_delete_plan(&result);
+        let loaded_plan = DeleteEngine::create_delete_plan(&loaded);
+        assert_eq!(loaded_plan.files, plan.files);
+        assert_eq!(loaded_plan.dirs, plan.dirs);
+        assert_eq!(loaded.total_size, result.total_size);
+
+        // 扫描之后消失的路径在删除前被移除
+        fs::remove_dir_all(project_path.join("a").join("dist")).unwrap();
+        let vanished = loaded.retain_existing();
+        assert_eq!(vanished, vec![project_path.join("a").join("dist")]);
+        assert_eq!(loaded.folders, vec![project_path.join("b").join("dist")]);
+        assert_eq!(loaded.total_size, result.total_size - 1);
+
+        // 手动编辑的文件可以省略字段
+        fs::write(&saved, r#"{"files": ["/tmp/x.log"]}"#).unwrap();
+        let edited = SearchResult::load(&saved).unwrap();
+        assert_eq!(edited.files, vec![PathBuf::from("/tmp/x.log")]);
+        assert!(edited.folders.is_empty());
+    }
+
+    #[test]
+    fn test_add_extra_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = temp_dir.path().join("cache");
+        fs::create_dir_all(cache.join("build")).unwrap();
+        fs::write(cache.join("build").join("out.o"), "obj").unwrap();
+        fs::write(cache.join("debug.log"), "log").unwrap();
+        fs::write(cache.join("index"), "index").unwrap();
+        let other = temp_dir.path().join("other.log");
+        fs::write(&other, "other").unwrap();
+
+        let mut result = SearchResult {
+            folders: vec![cache.join("build")].into(),
+            files: vec![cache.join("debug.log"), other.clone()].into(),
+            item_sizes: vec![
+                (cache.join("build"), 3),
+                (cache.join("debug.log"), 3),
+                (other.clone(), 5),
+            ],
+            total_size: 11,
+            ..Default::default()
+        };
+
+        // 已匹配的、位于加入目录内的项目被合并进该目录，大小只计算一次
+        assert!(result.add_extra_folder(&cache, "tool-cache"));
+        assert_eq!(result.folders, vec![cache.clone()]);
+        assert_eq!(result.files, vec![other.clone()]);
+        assert_eq!(result.total_size, 5 + 11);
+
+        // 已经在结果中或位于已匹配文件夹内的目录被忽略
+        assert!(!result.add_extra_folder(&cache, "tool-cache"));
+        assert!(!result.add_extra_folder(&cache.join("build"), "tool-cache"));
+        assert_eq!(result.total_size, 5 + 11);
+    }
+
+    #[test]
+    fn test_large_search_result_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir
+            .path()
+            .join("workspace")
+            .join("monorepo")
+            .join("packages");
+        fs::create_dir_all(root.join("pkg0")).unwrap();
+
+        // 合成的大型扫描结果：每个包一个匹配目录，前十个包各有两个只有大小写不同的日志文件
+        let mut result = SearchResult {
+            folders: CompactPaths::new(std::slice::from_ref(&root)),
+            files: CompactPaths::new(std::slice::from_ref(&root)),
+            ..Default::default()
+        };
+        let mut folders = Vec::new();
+        let mut files = Vec::new();
+        for i in 0..20_000 {
+            let package = root.join(format!("pkg{}", i));
+            let mut items = vec![(package.join("node_modules"), true)];
+            if i < 10 {
+                items.push((package.join("Build.log"), false));
+                items.push((package.join("build.log"), false));
+            }
+            for (path, is_dir) in items {
+                result.item_sizes.push((path.clone(), 1));
+                result.item_origins.push(ItemOrigin {
+                    path: path.clone(),
+                    root: root.clone(),
+                    pattern: String::new(),
+                    delete_mode: DeleteMode::default(),
+                });
+                result.total_size += 1;
+                if is_dir {
+                    result.folders.push(&path);
+                    folders.push(path);
+                } else {
+                    result.files.push(&path);
+                    files.push(path);
+                }
+            }
+        }
+        assert_eq!(result.folders, folders);
+        assert_eq!(result.files, files);
+
+        // 合并大小写重复项和加入额外目录都通过访问器移除匹配项
+        let duplicates = result.collapse_case_duplicates(|a, b| {
+            a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+        });
+        assert_eq!(duplicates.len(), 10);
+        files.retain(|file| !duplicates.contains(file));
+        assert!(result.add_extra_folder(&root.join("pkg0"), "extra"));
+        folders.retain(|folder| !folder.starts_with(root.join("pkg0")));
+        folders.push(root.join("pkg0"));
+        files.retain(|file| !file.starts_with(root.join("pkg0")));
+        assert_eq!(result.folders, folders);
+        assert_eq!(result.files, files);
+        assert_eq!(result.total_size, (folders.len() - 1 + files.len()) as u64);
+
+        // 保存后加载得到相同的路径，并按扫描根路径重新压缩
+        let saved = temp_dir.path().join("result.json");
+        result.save(&saved).unwrap();
+        let loaded = SearchResult::load(&saved).unwrap();
+        assert_eq!(loaded.folders, folders);
+        assert_eq!(loaded.files, files);
+        let expanded: usize = folders
+            .iter()
+            .map(|path| std::mem::size_of::<PathBuf>() + path.as_os_str().len())
+            .sum();
+        assert!(loaded.folders.heap_size() * 10 < expanded * 7);
+    }
+
+    #[test]
+    fn test_search_parallel_size_compute() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 多个大小不同的匹配目录，分布在不同的子项目中
+        for (i, name) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            let target = project_path.join(name).join("target");
+            fs::create_dir_all(target.join("debug").join("deps")).unwrap();
+            fs::write(target.join("out.bin"), vec![0u8; 1000 * (i + 1)]).unwrap();
+            fs::write(
+                target.join("debug").join("deps").join("lib.rlib"),
+                vec![0u8; 100 * (i + 1)],
+            )
+            .unwrap();
+        }
+        fs::write(project_path.join("build.log"), vec![0u8; 7]).unwrap();
+
+        let config_for = |parallel_size_compute: bool| Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                parallel_size_compute,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let serial = SearchEngine::search(&paths, &config_for(false)).unwrap();
+        let parallel = SearchEngine::search(&paths, &config_for(true)).unwrap();
+
+        // 总大小一致，且每个目录的大小归属正确
+        assert_eq!(serial.total_size, 16507);
+        assert_eq!(parallel.total_size, serial.total_size);
+        assert_eq!(parallel.folders.len(), 5);
+        let mut serial_sizes = serial.item_sizes.clone();
+        let mut parallel_sizes = parallel.item_sizes.clone();
+        serial_sizes.sort();
+        parallel_sizes.sort();
+        assert_eq!(parallel_sizes, serial_sizes);
+    }
+
+    #[test]
+    fn test_search_parallel_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("repo");
+
+        // 匹配目录中还有同名的目录，不能在多个任务中重复统计
+        let nested = project_path.join("a").join("node_modules").join("x");
+        fs::create_dir_all(nested.join("node_modules").join("y")).unwrap();
+        fs::write(nested.join("index.js"), vec![0u8; 100]).unwrap();
+        fs::write(
+            nested.join("node_modules").join("y").join("index.js"),
+            vec![0u8; 10],
+        )
+        .unwrap();
+        fs::write(nested.join("node_modules").join("debug.log"), "log").unwrap();
+        for name in ["b", "c", "d", "e", "f"] {
+            let dir = project_path.join(name);
+            fs::create_dir_all(dir.join("target").join("debug")).unwrap();
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::write(
+                dir.join("target").join("debug").join("app"),
+                vec![0u8; 1000],
+            )
+            .unwrap();
+            fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+            fs::write(dir.join("run.log"), "log").unwrap();
+        }
+        fs::write(project_path.join("build.log"), vec![0u8; 7]).unwrap();
+        // 本身被匹配的根路径不再遍历其内容
+        let matched_root = temp_dir.path().join("other").join("node_modules");
+        fs::create_dir_all(matched_root.join("pkg").join("node_modules")).unwrap();
+        fs::write(matched_root.join("pkg").join("index.js"), vec![0u8; 50]).unwrap();
+
+        let config_for = |parallel_scan: bool, descend_into_matches: bool| Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string(), "target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                parallel_scan,
+                descend_into_matches,
+                jobs: Some(4),
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.clone(), matched_root.clone()];
+        let sorted = |mut paths: Vec<PathBuf>| {
+            paths.sort();
+            paths
+        };
+
+        for descend_into_matches in [false, true] {
+            let serial =
+                SearchEngine::search(&paths, &config_for(false, descend_into_matches)).unwrap();
+            let mut progress = None;
+            let parallel = SearchEngine::search_with_progress(
+                &paths,
+                &config_for(true, descend_into_matches),
+                Some(|files, dirs, files_matched, dirs_matched, size| {
+                    progress = Some((files, dirs, files_matched, dirs_matched, size))
+                }),
+            )
+            .unwrap();
+
+            assert_eq!(
+                sorted(parallel.folders.to_paths()),
+                sorted(serial.folders.to_paths())
+            );
+            assert_eq!(
+                sorted(parallel.files.to_paths()),
+                sorted(serial.files.to_paths())
+            );
+            assert_eq!(parallel.total_size, serial.total_size);
+            assert_eq!(parallel.total_dirs_scanned, serial.total_dirs_scanned);
+            assert_eq!(parallel.total_files_scanned, serial.total_files_scanned);
+            assert_eq!(parallel.skipped_in_match, serial.skipped_in_match);
+            assert_eq!(parallel.pattern_matches, serial.pattern_matches);
+            let mut origins: Vec<_> = parallel
+                .item_origins
+                .iter()
+                .map(|origin| (origin.path.clone(), origin.root.clone()))
+                .collect();
+            origins.sort();
+            assert_eq!(origins.len(), parallel.folders.len() + parallel.files.len());
+            assert!(origins
+                .iter()
+                .all(|(path, root)| path.starts_with(root) && paths.contains(root)));
+            // 进度回调最后收到的是所有任务的累计值
+            assert_eq!(
+                progress,
+                Some((
+                    serial.total_files_scanned,
+                    serial.total_dirs_scanned,
+                    serial.files.len(),
+                    serial.folders.len(),
+                    serial.total_size,
+                ))
+            );
+        }
+
+        // 默认只清理外层的匹配目录
+        let parallel = SearchEngine::search(&paths, &config_for(true, false)).unwrap();
+        assert_eq!(parallel.folders.len(), 7);
+        assert!(parallel.folders.contains(&matched_root));
+        assert!(parallel
+            .folders
+            .contains(&project_path.join("a").join("node_modules")));
+        assert_eq!(parallel.files.len(), 6);
+    }
+
+    #[test]
+    fn test_search_respects_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let web = project_path.join("web");
+        fs::create_dir_all(web.join("node_modules").join("pkg")).unwrap();
+        fs::create_dir_all(project_path.join("target")).unwrap();
+        fs::write(web.join("node_modules").join("pkg").join("index.js"), "js").unwrap();
+        fs::write(project_path.join("target").join("app"), "bin").unwrap();
+        fs::write(project_path.join("build.log"), "log").unwrap();
+        fs::write(web.join("keep.log"), "log").unwrap();
+        fs::write(web.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(project_path.join(".bcignore"), "keep.log\n").unwrap();
+
+        let config_for = |respect_gitignore: bool, parallel_scan: bool| Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string(), "target".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                respect_gitignore,
+                parallel_scan,
+                explain: true,
+                jobs: Some(2),
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // .bcignore 总是生效，.gitignore 只在启用时生效
+        let result = SearchEngine::search(&paths, &config_for(false, false)).unwrap();
+        assert_eq!(result.files, vec![project_path.join("build.log")]);
+        assert!(result.folders.contains(&web.join("node_modules")));
+        assert!(result
+            .rejected
+            .contains(&(web.join("keep.log"), RejectReason::Ignored)));
+
+        for parallel_scan in [false, true] {
+            let result = SearchEngine::search(&paths, &config_for(true, parallel_scan)).unwrap();
+            assert_eq!(result.folders, vec![project_path.join("target")]);
+            assert_eq!(result.files, vec![project_path.join("build.log")]);
+            assert!(result
+                .rejected
+                .contains(&(web.join("node_modules"), RejectReason::Ignored)));
+            // 被忽略目录的内容不再遍历
+            assert!(!result
+                .rejected
+                .iter()
+                .any(|(path, _)| path.starts_with(web.join("node_modules").join("pkg"))));
+        }
+    }
+
+    #[test]
+    fn test_search_preset_partials() {
+        use crate::config::Preset;
+        use filetime::set_file_mtime;
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let old = FileTime::from_system_time(SystemTime::now() - Duration::from_secs(3 * 86400));
+        for (name, content) in [
+            ("foo.part", "partial"),
+            ("bar.download", "partial"),
+            ("build.tmp-1234", "partial"),
+            ("tool.lock", "4242\n"),
+            ("empty.lock", ""),
+            // 需要提交到版本库的依赖锁定文件不匹配
+            (
+                "Cargo.lock",
+                "# This file is automatically @generated by Cargo.\n",
+            ),
+            ("notes.txt", "keep"),
+        ] {
+            fs::write(root.join(name), content).unwrap();
+            set_file_mtime(root.join(name), old).unwrap();
+        }
+        // 正在进行的下载还没有达到预设的最小年龄
+        fs::write(root.join("fresh.part"), "downloading").unwrap();
+
+        let preset = Preset::Partials;
+        let mut config = ConfigLoader::load_preset_config(preset);
+        config.options.min_age_days = preset.min_age_days();
+        let result = SearchEngine::search(&[root.to_path_buf()], &config).unwrap();
+
+        let mut files = result.files.clone();
+        files.sort();
+        let expected: Vec<PathBuf> = [
+            "bar.download",
+            "build.tmp-1234",
+            "empty.lock",
+            "foo.part",
+            "tool.lock",
+        ]
+        .iter()
+        .map(|name| root.join(name))
+        .collect();
+        assert_eq!(files, expected);
+        assert!(result.folders.is_empty());
+        assert_eq!(result.skipped_age, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_skip_counters() {
+        use filetime::set_file_mtime;
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let old = FileTime::from_system_time(SystemTime::now() - Duration::from_secs(10 * 86400));
+
+        // 排除目录及其中的文件
+        let excluded = project_path.join("vendor");
+        fs::create_dir_all(&excluded).unwrap();
+        fs::write(excluded.join("vendored.log"), vec![0u8; 100]).unwrap();
+        // 太小、太新、符合条件的日志
+        fs::write(project_path.join("small.log"), "log").unwrap();
+        fs::write(project_path.join("new.log"), vec![0u8; 100]).unwrap();
+        fs::write(project_path.join("old.log"), vec![0u8; 100]).unwrap();
+        set_file_mtime(project_path.join("old.log"), old).unwrap();
+        // 已匹配目录中的三个直接子条目
+        let node_modules = project_path.join("node_modules");
+        fs::create_dir_all(node_modules.join("pkg")).unwrap();
+        fs::write(node_modules.join("pkg/index.js"), "x").unwrap();
+        fs::write(node_modules.join("a.js"), "x").unwrap();
+        fs::write(node_modules.join("b.js"), "x").unwrap();
+        // 跟随符号链接时的循环会产生遍历错误
+        let nested = project_path.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        std::os::unix::fs::symlink(&nested, nested.join("loop")).unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![excluded.clone()],
+            options: Options {
+                follow_symlinks: true,
+                min_size: Some(10),
+                min_age_days: Some(1),
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let result = SearchEngine::search(&paths, &config).unwrap();
+
+        assert_eq!(result.files, vec![project_path.join("old.log")]);
+        assert_eq!(result.folders, vec![node_modules]);
+        assert_eq!(result.skipped_excluded, 2);
+        assert_eq!(result.skipped_size, 1);
+        assert_eq!(result.skipped_age, 1);
+        assert_eq!(result.skipped_in_match, 3);
+        assert_eq!(result.walk_errors, 1);
+    }
+
+    #[test]
+    fn test_search_result_sample() {
+        let result = SearchResult {
+            folders: (0..20)
+                .map(|i| PathBuf::from(format!("/p/dir{:02}", i)))
+                .collect(),
+            files: (0..30)
+                .map(|i| PathBuf::from(format!("/p/file{:02}.log", i)))
+                .collect(),
+            ..Default::default()
+        };
+
+        // 固定种子得到确定的样本，大小为请求的数量且没有重复
+        let sample = result.sample(5, 42);
+        assert_eq!(sample.len(), 5);
+        assert_eq!(sample, result.sample(5, 42));
+        let mut unique = sample.clone();
+        unique.dedup();
+        assert_eq!(unique, sample);
+        assert!(sample
+            .iter()
+            .all(|path| result.folders.contains(path) || result.files.contains(path)));
+
+        // 与匹配项的发现顺序无关
+        let mut reordered = result.clone();
+        reordered.folders = result.folders.iter().rev().collect();
+        reordered.files = result.files.iter().rev().collect();
+        assert_eq!(reordered.sample(5, 42), sample);
+
+        // 不同的种子通常得到不同的样本
+        assert_ne!(result.sample(5, 7), sample);
+
+        // 匹配项不足时返回全部
+        let small = SearchResult {
+            files: vec![PathBuf::from("/p/b.log"), PathBuf::from("/p/a.log")].into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            small.sample(10, 42),
+            vec![PathBuf::from("/p/a.log"), PathBuf::from("/p/b.log")]
+        );
+        assert!(SearchResult::default().sample(3, 42).is_empty());
+    }
+
+    #[test]
+    fn test_collapse_case_duplicates() {
+        use crate::delete::DeleteEngine;
+
+        let mut result = SearchResult {
+            folders: vec![
+                PathBuf::from("/project/Build"),
+                PathBuf::from("/project/build"),
+                PathBuf::from("/project/dist"),
+            ]
+            .into(),
+            files: vec![PathBuf::from("/project/App.log")].into(),
+            total_size: 350,
+            item_sizes: vec![
+                (PathBuf::from("/project/Build"), 100),
+                (PathBuf::from("/project/build"), 100),
+                (PathBuf::from("/project/dist"), 100),
+                (PathBuf::from("/project/App.log"), 50),
+            ],
+            ..Default::default()
+        };
+
+        // 模拟大小写敏感的文件系统：只有大小写不同的路径是不同的目录
+        let mut case_sensitive = result.clone();
+        assert!(case_sensitive
+            .collapse_case_duplicates(|a, b| a == b)
+            .is_empty());
+        assert_eq!(case_sensitive.folders.len(), 3);
+
+        // 模拟大小写不敏感的文件系统：Build 和 build 合并为一个计划项
+        let duplicates = result.collapse_case_duplicates(|a, b| {
+            a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+        });
+        assert_eq!(duplicates, vec![PathBuf::from("/project/build")]);
+        assert_eq!(result.total_size, 250);
+        let plan = DeleteEngine::create_delete_plan(&result);
+        assert_eq!(
+            plan.dirs,
+            vec![
+                PathBuf::from("/project/Build"),
+                PathBuf::from("/project/dist")
+            ]
+        );
+        assert_eq!(plan.files, vec![PathBuf::from("/project/App.log")]);
+
+        // 真实文件系统上的同一性检查
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.log");
+        let b = temp_dir.path().join("b.log");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+        assert!(SearchEngine::same_file(&a, &a));
+        assert!(!SearchEngine::same_file(&a, &b));
+        assert!(!SearchEngine::same_file(
+            &a,
+            &temp_dir.path().join("missing")
+        ));
+    }
+
+    #[test]
+    fn test_search_honors_jobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        for (i, name) in ["a", "b", "c", "d", "e", "f"].iter().enumerate() {
+            let target = project_path.join(name).join("target");
+            fs::create_dir_all(&target).unwrap();
+            fs::write(target.join("out.bin"), vec![0u8; 100 * (i + 1)]).unwrap();
+        }
+
+        let config_for = |jobs: usize| Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                parallel_size_compute: true,
+                jobs: Some(jobs),
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // jobs = 1 时即使启用并行计算也串行执行，不创建线程池
+        let serial_config = config_for(1);
+        assert!(SearchEngine::matches(&paths, &serial_config)
+            .pending_sizes
+            .is_none());
+        let parallel_config = config_for(4);
+        let matches = SearchEngine::matches(&paths, &parallel_config);
+        assert_eq!(
+            matches
+                .pending_sizes
+                .as_ref()
+                .unwrap()
+                .pool
+                .current_num_threads(),
+            4
+        );
+        drop(matches);
+
+        let sorted = |mut sizes: Vec<(PathBuf, u64)>| {
+            sizes.sort();
+            sizes
+        };
+        let serial = SearchEngine::search(&paths, &serial_config).unwrap();
+        let parallel = SearchEngine::search(&paths, &parallel_config).unwrap();
+        assert_eq!(serial.total_size, 2100);
+        assert_eq!(parallel.total_size, serial.total_size);
+        assert_eq!(
+            sorted(parallel.item_sizes),
+            sorted(serial.item_sizes.clone())
+        );
+
+        // 排队任务达到上限时先等待已有任务完成，结果不变
+        let mut matches = SearchEngine::matches(&paths, &parallel_config);
+        matches.options.runtime.max_pending = 1;
+        let bounded: Vec<(PathBuf, u64)> = matches
+            .map(|item| item.map(|item| (item.path, item.size)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(sorted(bounded), sorted(serial.item_sizes));
+    }
+
+    #[test]
+    fn test_search_file_content_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+
+        // 两个 .log 文件，只有构建日志以签名开头
+        fs::write(
+            project_path.join("build.log"),
+            "BUILD LOG\ncompiling main.rs\n",
+        )
+        .unwrap();
+        fs::write(
+            project_path.join("app.log"),
+            "2024-01-01 INFO started\nBUILD LOG\n",
+        )
+        .unwrap();
+
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log content:^BUILD LOG".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert_eq!(result.files, vec![project_path.join("build.log")]);
+
+        // 只读取开头的字节，签名超出读取范围时不匹配
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log content:compiling".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                content_sniff_bytes: 4,
+                ..Options::default()
+            },
+        };
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert!(result.files.is_empty());
+    }
+
+    #[test]
+    fn test_search_cancelled_after_deadline() {
+        use std::time::{Duration, Instant};
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        for i in 0..20 {
+            let target = project_path.join(format!("crate{:02}", i)).join("target");
+            fs::create_dir_all(&target).unwrap();
+            fs::write(target.join("out.bin"), "bin").unwrap();
         }
 
-        if let Ok(modified) = metadata.modified() {
-            if let Ok(elapsed) = modified.elapsed() {
-                let age_days = elapsed.as_secs() / 86400;
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
 
-                if let Some(min_age) = min_age_days {
-                    if age_days < min_age as u64 {
-                        return false;
-                    }
-                }
-                if let Some(max_age) = max_age_days {
-                    if age_days > max_age as u64 {
-                        return false;
-                    }
-                }
-                return true;
-            }
+        // 模拟缓慢的文件系统：每处理一个匹配项都等待一段时间，预算很快用完
+        let cancel = CancelFlag::with_deadline(Instant::now() + Duration::from_millis(50));
+        let mut matches = SearchEngine::matches(&paths, &config).with_cancel(cancel);
+        let mut found = 0;
+        for item in matches.by_ref() {
+            item.unwrap();
+            found += 1;
+            std::thread::sleep(Duration::from_millis(20));
         }
-        true
-    }
-}
+        assert!(matches.cancelled());
+        assert!(found > 0 && found < 20);
+        assert_eq!(matches.total_size(), 3 * found as u64);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::{CleanConfig, Config, Options};
-    use std::fs;
-    use std::io::Write;
-    use tempfile::TempDir;
+        // 已经取消时不返回任何结果
+        let cancel = CancelFlag::new();
+        cancel.cancel();
+        let result = SearchEngine::search_with_cancel(
+            &paths,
+            &config,
+            None,
+            None::<fn(usize, usize, usize, usize, u64)>,
+            Some(cancel),
+        )
+        .unwrap();
+        assert!(result.cancelled);
+        assert!(result.folders.is_empty());
+
+        // 没有取消时扫描完整
+        let result = SearchEngine::search(&paths, &config).unwrap();
+        assert!(!result.cancelled);
+        assert_eq!(result.folders.len(), 20);
+    }
 
     #[test]
-    fn test_match_pattern() {
-        // 测试文件夹匹配
-        assert!(SearchEngine::match_pattern("node_modules/", "node_modules"));
-        assert!(!SearchEngine::match_pattern("node_modules/", "dist"));
+    fn test_search_dir_age_from_contents() {
+        use filetime::set_file_mtime;
+        use std::time::Duration;
 
-        // 测试文件通配符匹配
-        assert!(SearchEngine::match_pattern("*.log", "test.log"));
-        assert!(SearchEngine::match_pattern("*.log", "app.log"));
-        assert!(!SearchEngine::match_pattern("*.log", "test.txt"));
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let month_ago =
+            FileTime::from_system_time(SystemTime::now() - Duration::from_secs(30 * 86400));
 
-        // 测试 ? 通配符
-        assert!(SearchEngine::match_pattern("test?.txt", "test1.txt"));
-        assert!(SearchEngine::match_pattern("test?.txt", "testa.txt"));
-        assert!(!SearchEngine::match_pattern("test?.txt", "test.txt"));
+        // build/ 目录本身很久没有增删条目，但其中的文件刚刚被写入
+        let fresh = project_path.join("fresh").join("build");
+        fs::create_dir_all(fresh.join("obj")).unwrap();
+        fs::write(fresh.join("obj").join("main.o"), "obj").unwrap();
+        set_file_mtime(fresh.join("obj"), month_ago).unwrap();
+        set_file_mtime(&fresh, month_ago).unwrap();
 
-        // 测试精确匹配
-        assert!(SearchEngine::match_pattern("test.txt", "test.txt"));
-        assert!(!SearchEngine::match_pattern("test.txt", "test.log"));
+        // 所有内容都很旧的 build/ 目录
+        let stale = project_path.join("stale").join("build");
+        fs::create_dir_all(&stale).unwrap();
+        fs::write(stale.join("main.o"), "obj").unwrap();
+        set_file_mtime(stale.join("main.o"), month_ago).unwrap();
+        set_file_mtime(&stale, month_ago).unwrap();
+
+        let config_for = |dir_age_from_contents| Config {
+            clean: CleanConfig {
+                folders: vec!["build".to_string()],
+                files: vec![],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                min_age_days: Some(7),
+                dir_age_from_contents,
+                explain: true,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        let result = SearchEngine::search(&paths, &config_for(true)).unwrap();
+        assert_eq!(result.folders, vec![stale.clone()]);
+        assert!(result
+            .rejected
+            .contains(&(fresh.clone(), RejectReason::TooNew)));
+
+        // 未启用时目录不检查年龄
+        let mut result = SearchEngine::search(&paths, &config_for(false)).unwrap();
+        result.folders.sort();
+        assert_eq!(result.folders, vec![fresh, stale]);
     }
 
     #[test]
-    fn test_should_exclude() {
-        let excludes = vec![
-            PathBuf::from("/exclude/path1"),
-            PathBuf::from("/exclude/path2"),
-        ];
+    fn test_search_matched_folder_with_excluded_child() {
+        use crate::delete::DeleteEngine;
 
-        // 测试应该排除的路径
-        assert!(SearchEngine::should_exclude(
-            &PathBuf::from("/exclude/path1/sub"),
-            &excludes
-        ));
-        assert!(SearchEngine::should_exclude(
-            &PathBuf::from("/exclude/path2"),
-            &excludes
-        ));
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let node_modules = project_path.join("node_modules");
+        let cache = node_modules.join(".cache");
+        fs::create_dir_all(cache.join("babel")).unwrap();
+        fs::write(cache.join("babel").join("keep.json"), "{}").unwrap();
+        fs::write(cache.join("other.bin"), "bin").unwrap();
+        fs::create_dir_all(node_modules.join("react")).unwrap();
+        fs::write(node_modules.join("react").join("index.js"), "js").unwrap();
+        fs::write(node_modules.join("install.log"), "log").unwrap();
 
-        // 测试不应该排除的路径
-        assert!(!SearchEngine::should_exclude(
-            &PathBuf::from("/other/path"),
-            &excludes
-        ));
+        let config_for = |excluded_child_action| Config {
+            clean: CleanConfig {
+                folders: vec!["node_modules".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![cache.join("babel")],
+            options: Options {
+                explain: true,
+                excluded_child_action,
+                ..Options::default()
+            },
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 默认不整体删除，只清理其中单独匹配的条目
+        let result = SearchEngine::search(&paths, &config_for(ExcludedChildAction::Skip)).unwrap();
+        assert!(result.folders.is_empty());
+        assert_eq!(result.files, vec![node_modules.join("install.log")]);
+        assert!(result
+            .rejected
+            .contains(&(node_modules.clone(), RejectReason::ContainsExcluded)));
+
+        // 部分清理时删除排除路径以外的所有内容
+        let result =
+            SearchEngine::search(&paths, &config_for(ExcludedChildAction::Partial)).unwrap();
+        let mut folders = result.folders.clone();
+        folders.sort();
+        assert_eq!(folders, vec![node_modules.join("react")]);
+        let mut files = result.files.clone();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![cache.join("other.bin"), node_modules.join("install.log")]
+        );
+        assert_eq!(result.total_size, 8);
+
+        let plan = DeleteEngine::create_delete_plan(&result);
+        let deleted = DeleteEngine::execute_deletion(&plan, false);
+        assert!(deleted.failed_dirs.is_empty() && deleted.failed_files.is_empty());
+        assert!(cache.join("babel").join("keep.json").exists());
+        assert!(!node_modules.join("react").exists());
+        assert!(!cache.join("other.bin").exists());
     }
 
     #[test]
-    fn test_check_size() {
-        // 测试无限制
-        assert!(SearchEngine::check_size(1000, None, None));
+    fn test_search_file_pattern_with_slash() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        for file in [
+            "logs/a.log",
+            "logs/sub/b.log",
+            "other/a.log",
+            "other/logs/c.log",
+            "build/app.js.map",
+            "src/app.js.map",
+            "deep/x.tmp",
+        ] {
+            let path = project_path.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "x").unwrap();
+        }
 
-        // 测试最小大小
-        assert!(SearchEngine::check_size(1000, Some(500), None));
-        assert!(!SearchEngine::check_size(1000, Some(1500), None));
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec![
+                    "logs/*.log".to_string(),
+                    "build/*.map".to_string(),
+                    "*.tmp".to_string(),
+                ],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let result = SearchEngine::search(&paths, &config).unwrap();
 
-        // 测试最大大小
-        assert!(SearchEngine::check_size(1000, None, Some(2000)));
-        assert!(!SearchEngine::check_size(1000, None, Some(500)));
+        // 包含 `/` 的模式只匹配扫描根路径下对应位置的文件，不含 `/` 的模式仍然匹配任意位置
+        let mut files = result.files.clone();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                project_path.join("build/app.js.map"),
+                project_path.join("deep/x.tmp"),
+                project_path.join("logs/a.log"),
+            ]
+        );
 
-        // 测试范围
-        assert!(SearchEngine::check_size(1000, Some(500), Some(2000)));
-        assert!(!SearchEngine::check_size(1000, Some(1500), Some(2000)));
-        assert!(!SearchEngine::check_size(1000, Some(500), Some(800)));
+        assert!(SearchEngine::match_relative_path(
+            "{logs,tmp}/*.log",
+            Path::new("tmp/a.log"),
+            GlobDotPolicy::Explicit
+        ));
+        assert!(!SearchEngine::match_relative_path(
+            "logs/*",
+            Path::new("logs/.hidden"),
+            GlobDotPolicy::Explicit
+        ));
     }
 
     #[test]
-    fn test_check_age() {
+    fn test_match_relative_path_globstar() {
+        let matches = |pattern: &str, path: &str, policy: GlobDotPolicy| {
+            SearchEngine::match_relative_path(pattern, Path::new(path), policy)
+        };
+        let explicit = GlobDotPolicy::Explicit;
+
+        // `**` 匹配零个或多个完整的目录
+        assert!(matches("**/*.tmp", "a.tmp", explicit));
+        assert!(matches("**/*.tmp", "x/y/a.tmp", explicit));
+        assert!(!matches("**/*.tmp", "x/y/a.log", explicit));
+        assert!(matches("a/**/b", "a/b", explicit));
+        assert!(matches("a/**/b", "a/x/y/b", explicit));
+        assert!(!matches("a/**/b", "c/x/b", explicit));
+        assert!(matches("a/**", "a/x/y", explicit));
+        // 默认不进入隐藏目录，除非通配符可以匹配开头的 `.`
+        assert!(!matches("**/*.tmp", ".cache/a.tmp", explicit));
+        assert!(matches("**/*.tmp", ".cache/a.tmp", GlobDotPolicy::MatchDot));
+        // 名称中的 `**` 仍然只在一级之内匹配
+        assert!(!matches("a**b", "a/x/b", explicit));
+    }
+
+    #[test]
+    fn test_search_folder_path_patterns() {
         let temp_dir = TempDir::new().unwrap();
-        let test_file = temp_dir.path().join("test.txt");
-        fs::File::create(&test_file).unwrap();
+        let root = temp_dir.path();
+        for file in [
+            "packages/a/dist/index.js",
+            "packages/b/dist/index.js",
+            "packages/c/src/dist/keep.js",
+            "dist/app.js",
+            "scratch.tmp",
+            "packages/a/src/deep/cache.tmp",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "x").unwrap();
+        }
 
-        let metadata = fs::metadata(&test_file).unwrap();
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["packages/*/dist/".to_string()],
+                files: vec!["**/*.tmp".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let result = SearchEngine::search(&[root.to_path_buf()], &config).unwrap();
 
-        // 测试无限制
-        assert!(SearchEngine::check_age(&metadata, None, None));
+        // 只清理各个包中的 dist，不清理顶层和更深层的 dist
+        let mut folders = result.folders.clone();
+        folders.sort();
+        assert_eq!(
+            folders,
+            vec![root.join("packages/a/dist"), root.join("packages/b/dist")]
+        );
+        let mut files = result.files.clone();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                root.join("packages/a/src/deep/cache.tmp"),
+                root.join("scratch.tmp"),
+            ]
+        );
 
-        // 测试最小年龄（新文件应该不满足最小年龄要求）
-        // 注意：这个测试可能不稳定，因为文件是刚创建的
-        // 实际使用中，文件年龄应该大于0天
+        // 没有扫描根路径时包含 `/` 的目录模式不匹配
+        assert!(!SearchEngine::match_folder_pattern(
+            "packages/*/dist",
+            "dist",
+            None,
+            GlobDotPolicy::Explicit
+        ));
+        assert!(SearchEngine::match_folder_pattern(
+            "dist/",
+            "dist",
+            None,
+            GlobDotPolicy::Explicit
+        ));
     }
 
     #[test]
-    fn test_search() {
+    fn test_search_overlap_policy() {
         let temp_dir = TempDir::new().unwrap();
         let project_path = temp_dir.path();
-
-        // 创建测试目录结构
         let node_modules = project_path.join("node_modules");
-        fs::create_dir(&node_modules).unwrap();
-        let dist = project_path.join("dist");
-        fs::create_dir(&dist).unwrap();
-
-        let test_file = project_path.join("test.log");
-        fs::File::create(&test_file)
-            .unwrap()
-            .write_all(b"test")
-            .unwrap();
+        fs::create_dir_all(node_modules.join("pkg")).unwrap();
+        fs::write(node_modules.join("pkg").join("debug.log"), "log").unwrap();
 
-        // 创建配置
-        let config = Config {
+        let config_for = |on_overlap: OverlapPolicy| Config {
             clean: CleanConfig {
-                folders: vec!["node_modules".to_string(), "dist".to_string()],
+                folders: vec!["node_modules".to_string()],
                 files: vec!["*.log".to_string()],
+                ..Default::default()
             },
             exclude: vec![],
             options: Options {
-                recursive: true,
-                follow_symlinks: false,
-                min_size: None,
-                max_size: None,
-                min_age_days: None,
-                max_age_days: None,
+                on_overlap,
+                ..Options::default()
             },
         };
+        let paths = vec![project_path.to_path_buf()];
 
-        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        // 目录匹配优先：不再遍历目录内容，其中的文件不会单独出现
+        let result = SearchEngine::search(&paths, &config_for(OverlapPolicy::FolderWins)).unwrap();
+        assert_eq!(result.folders, vec![node_modules.clone()]);
+        assert!(result.files.is_empty());
+        assert_eq!(result.skipped_in_match, 1);
 
-        // 验证搜索结果
-        assert!(result.folders.len() >= 2);
-        assert!(result.files.len() >= 1);
-        assert!(result.total_size > 0);
+        // 同时收集：目录和其中匹配文件模式的文件都被收集
+        let result = SearchEngine::search(&paths, &config_for(OverlapPolicy::CollectBoth)).unwrap();
+        assert_eq!(result.folders, vec![node_modules.clone()]);
+        assert_eq!(
+            result.files,
+            vec![node_modules.join("pkg").join("debug.log")]
+        );
+
+        // 旧的 descend_into_matches 选项等同于同时收集
+        let mut config = config_for(OverlapPolicy::FolderWins);
+        config.options.descend_into_matches = true;
+        let options: SearchOptions = (&config.options).into();
+        assert_eq!(options.on_overlap, OverlapPolicy::CollectBoth);
+    }
+
+    // 模拟网络文件系统的暂时故障：前 N 次读取元数据返回超时错误
+    static FLAKY_STAT_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+    fn flaky_stat(path: &Path, follow_symlinks: bool) -> io::Result<fs::Metadata> {
+        if FLAKY_STAT_FAILURES
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return Err(io::Error::from(io::ErrorKind::TimedOut));
+        }
+        SearchEngine::stat_path(path, follow_symlinks)
     }
 
     #[test]
-    fn test_walk_path() {
+    fn test_stat_retry_counts_flaky_file() {
         let temp_dir = TempDir::new().unwrap();
-        let test_path = temp_dir.path();
+        let log = temp_dir.path().join("build.log");
+        fs::write(&log, "log").unwrap();
 
-        // 创建测试文件
-        let file1 = test_path.join("file1.txt");
-        fs::File::create(&file1).unwrap();
-        let subdir = test_path.join("subdir");
-        fs::create_dir(&subdir).unwrap();
-        let file2 = subdir.join("file2.txt");
-        fs::File::create(&file2).unwrap();
-
-        let options = SearchOptions {
-            recursive: true,
-            follow_symlinks: false,
-            max_depth: None,
-            min_size: None,
-            max_size: None,
-            min_age_days: None,
-            max_age_days: None,
+        let config_for = |stat_retries: u32| Config {
+            clean: CleanConfig {
+                folders: vec![],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options {
+                stat_retries,
+                explain: true,
+                ..Options::default()
+            },
         };
+        let paths = vec![temp_dir.path().to_path_buf()];
 
-        let paths: Vec<PathBuf> = SearchEngine::walk_path(test_path, &options)
-            .collect::<Result<Vec<_>, _>>()
-            .unwrap();
+        // 第一次失败、重试后成功：文件仍被统计和匹配
+        let config = config_for(2);
+        FLAKY_STAT_FAILURES.store(1, Ordering::SeqCst);
+        let mut matches = SearchEngine::matches(&paths, &config).with_stat(flaky_stat);
+        let found: Vec<Match> = matches.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, log);
+        assert_eq!(matches.files_scanned(), 1);
 
-        assert!(paths.len() >= 3); // 至少包含根目录、子目录和两个文件
+        // 不重试时文件被跳过，并记录原因
+        let config = config_for(0);
+        FLAKY_STAT_FAILURES.store(1, Ordering::SeqCst);
+        let mut matches = SearchEngine::matches(&paths, &config).with_stat(flaky_stat);
+        assert_eq!(matches.by_ref().count(), 0);
+        assert_eq!(matches.files_scanned(), 0);
+        assert!(matches
+            .rejected()
+            .contains(&(log.clone(), RejectReason::StatFailed)));
+
+        // 文件不存在等不可重试的错误只尝试一次
+        let mut calls = 0;
+        let result = SearchEngine::stat_with_retry(&log, 3, Duration::ZERO, |_| {
+            calls += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    /// 清理带有 `CACHEDIR.TAG` 的目录和名称以 `~` 结尾的备份文件
+    struct CacheDirPolicy;
+
+    impl MatchPolicy for CacheDirPolicy {
+        fn decide(
+            &self,
+            entry: &Path,
+            _relative: Option<&Path>,
+            metadata: &fs::Metadata,
+        ) -> Decision {
+            let matched = if metadata.is_dir() {
+                entry.join("CACHEDIR.TAG").is_file()
+            } else {
+                entry.to_string_lossy().ends_with('~')
+            };
+            if matched {
+                Decision::Clean("custom".to_string())
+            } else {
+                Decision::Skip(RejectReason::NoPatternMatch)
+            }
+        }
     }
 
     #[test]
-    fn test_search_skip_matched_folder_children() {
+    fn test_search_with_custom_policy() {
         let temp_dir = TempDir::new().unwrap();
         let project_path = temp_dir.path();
+        let cache = project_path.join("some-cache");
+        fs::create_dir_all(cache.join("nested")).unwrap();
+        fs::write(
+            cache.join("CACHEDIR.TAG"),
+            "Signature: 8a477f597d28d172789f06886806bc55",
+        )
+        .unwrap();
+        fs::write(cache.join("nested").join("notes.txt~"), "old").unwrap();
+        fs::create_dir_all(project_path.join("target")).unwrap();
+        fs::write(project_path.join("main.rs~"), "old").unwrap();
+        fs::write(project_path.join("main.rs"), "fn main() {}").unwrap();
 
-        // 创建测试目录结构：
-        // project/
-        //   ├── node_modules/        (匹配的文件夹)
-        //   │   ├── subdir/          (应该被跳过)
-        //   │   │   └── file.txt     (应该被跳过)
-        //   │   └── file.js          (应该被跳过)
-        //   └── other/                (不匹配，应该被遍历)
-        //       └── file.txt
-
-        let node_modules = project_path.join("node_modules");
-        fs::create_dir_all(&node_modules).unwrap();
+        // 配置中的模式不参与匹配，只使用其中的选项
+        let config = Config {
+            clean: CleanConfig {
+                folders: vec!["target".to_string()],
+                files: vec!["*.rs".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
+        let result = SearchEngine::search_with_policy(&paths, &config, &CacheDirPolicy).unwrap();
 
-        let node_modules_subdir = node_modules.join("subdir");
-        fs::create_dir_all(&node_modules_subdir).unwrap();
-        let file_in_node_modules = node_modules_subdir.join("file.txt");
-        fs::File::create(&file_in_node_modules).unwrap();
+        // 带标记的目录被整体清理，其中的备份文件不再单独出现
+        assert_eq!(result.folders, vec![cache.clone()]);
+        assert_eq!(result.files, vec![project_path.join("main.rs~")]);
+        assert!(result
+            .item_origins
+            .iter()
+            .all(|origin| origin.pattern == "custom"));
 
-        let file_in_node_modules_root = node_modules.join("file.js");
-        fs::File::create(&file_in_node_modules_root).unwrap();
+        // 默认策略（Config）按模式和排除路径判定
+        let metadata = fs::metadata(project_path.join("target")).unwrap();
+        assert_eq!(
+            config.decide(&project_path.join("target"), None, &metadata),
+            Decision::Clean("target".to_string())
+        );
+        let metadata = fs::metadata(project_path.join("main.rs~")).unwrap();
+        assert_eq!(
+            config.decide(&project_path.join("main.rs~"), None, &metadata),
+            Decision::Skip(RejectReason::NoPatternMatch)
+        );
+        let excluded = Config {
+            exclude: vec![project_path.join("target")],
+            ..config.clone()
+        };
+        let metadata = fs::metadata(project_path.join("target")).unwrap();
+        assert_eq!(
+            excluded.decide(&project_path.join("target"), None, &metadata),
+            Decision::Skip(RejectReason::Excluded)
+        );
+    }
 
-        let other_dir = project_path.join("other");
-        fs::create_dir_all(&other_dir).unwrap();
-        let file_in_other = other_dir.join("file.txt");
-        fs::File::create(&file_in_other).unwrap();
+    #[test]
+    fn test_search_with_config_policy_matches_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join("packages").join("app").join("dist")).unwrap();
+        fs::create_dir_all(project_path.join("logs")).unwrap();
+        fs::create_dir_all(project_path.join("target")).unwrap();
+        fs::write(project_path.join("logs").join("run.log"), "log").unwrap();
+        fs::write(project_path.join("debug.log"), "log").unwrap();
 
-        // 创建配置，只搜索 node_modules 文件夹
         let config = Config {
             clean: CleanConfig {
-                folders: vec!["node_modules".to_string()],
-                files: vec![],
+                folders: vec!["packages/*/dist/".to_string(), "target".to_string()],
+                files: vec!["logs/*.log".to_string()],
+                ..Default::default()
+            },
+            exclude: vec![],
+            options: Options::default(),
+        };
+        let paths = vec![project_path.to_path_buf()];
+
+        // 以配置本身作为策略时与默认搜索的结果相同，包括包含 `/` 的模式
+        let expected = SearchEngine::search(&paths, &config).unwrap();
+        let result = SearchEngine::search_with_policy(&paths, &config, &config).unwrap();
+        assert_eq!(result.folders, expected.folders);
+        assert_eq!(result.files, expected.files);
+        assert_eq!(result.total_size, expected.total_size);
+        assert_eq!(result.folders.len(), 2);
+        assert_eq!(
+            result.files,
+            vec![project_path.join("logs").join("run.log")]
+        );
+
+        // 单独调用时包含 `/` 的模式按给出的相对路径匹配
+        let dist = project_path.join("packages").join("app").join("dist");
+        let metadata = fs::metadata(&dist).unwrap();
+        assert_eq!(
+            config.decide(&dist, Some(Path::new("packages/app/dist")), &metadata),
+            Decision::Clean("packages/*/dist/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_search_descend_into_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let tmp = project_path.join("tmp");
+        fs::create_dir_all(tmp.join("tmp")).unwrap();
+        fs::write(tmp.join("run.log"), "log").unwrap();
+        fs::write(tmp.join("tmp").join("nested.log"), "log").unwrap();
+        fs::write(project_path.join("top.log"), "log").unwrap();
+
+        let config_for = |descend_into_matches: bool| Config {
+            clean: CleanConfig {
+                folders: vec!["tmp".to_string()],
+                files: vec!["*.log".to_string()],
+                ..Default::default()
             },
             exclude: vec![],
             options: Options {
-                recursive: true,
-                follow_symlinks: false,
-                min_size: None,
-                max_size: None,
-                min_age_days: None,
-                max_age_days: None,
+                descend_into_matches,
+                ..Options::default()
             },
         };
+        let paths = vec![project_path.to_path_buf()];
 
-        let result = SearchEngine::search(&[project_path.to_path_buf()], &config).unwrap();
+        // 默认跳过已匹配目录的内容
+        let result = SearchEngine::search(&paths, &config_for(false)).unwrap();
+        assert_eq!(result.folders, vec![tmp.clone()]);
+        assert_eq!(result.files, vec![project_path.join("top.log")]);
 
-        // 验证：只应该找到 node_modules 文件夹本身
-        // 不应该找到 node_modules 内的子文件夹和文件
-        assert_eq!(result.folders.len(), 1);
-        assert_eq!(result.folders[0], node_modules);
-        assert_eq!(result.files.len(), 0);
+        // 开启后仍然匹配目录，同时找到其中的文件，但不重复匹配嵌套的同名目录
+        let result = SearchEngine::search(&paths, &config_for(true)).unwrap();
+        assert_eq!(result.folders, vec![tmp.clone()]);
+        // 目录中的文件不重复计入总大小
+        assert_eq!(result.total_size, 9);
+        let mut files = result.files.clone();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                tmp.join("run.log"),
+                tmp.join("tmp").join("nested.log"),
+                project_path.join("top.log"),
+            ]
+        );
+
+        // 目录中的文件随目录一起删除，不单独出现在删除计划中
+        let plan = crate::delete::DeleteEngine::create_delete_plan(&result);
+        assert_eq!(plan.dirs, vec![tmp.clone()]);
+        assert_eq!(plan.files, vec![project_path.join("top.log")]);
     }
 }
@@ -0,0 +1,148 @@
+use crate::error::CleanError;
+use std::path::Path;
+
+/// 文件系统的空间信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskSpace {
+    /// 文件系统总大小（字节）
+    pub total: u64,
+    /// 当前用户可用的空间（字节）
+    pub available: u64,
+}
+
+impl DiskSpace {
+    /// 查询路径所在文件系统的空间信息
+    ///
+    /// # 参数
+    /// * `path` - 文件系统中的任意路径
+    ///
+    /// # 返回
+    /// 空间信息，如果查询失败或平台不支持则返回错误
+    #[cfg(unix)]
+    pub fn query(path: &Path) -> Result<Self, CleanError> {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| CleanError::Other(format!("Invalid path {}: {}", path.display(), e)))?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: c_path 是有效的 C 字符串，stat 是可写的 statvfs 结构
+        let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+        if ret != 0 {
+            return Err(CleanError::Other(format!(
+                "Failed to query free space for {}: {}",
+                path.display(),
+                std::io::Error::last_os_error()
+            )));
+        }
+
+        let fragment_size = stat.f_frsize as u64;
+        Ok(DiskSpace {
+            total: stat.f_blocks as u64 * fragment_size,
+            available: stat.f_bavail as u64 * fragment_size,
+        })
+    }
+
+    /// 查询路径所在文件系统的空间信息（当前平台不支持）
+    #[cfg(not(unix))]
+    pub fn query(path: &Path) -> Result<Self, CleanError> {
+        Err(CleanError::Other(format!(
+            "Free space query is not supported on this platform: {}",
+            path.display()
+        )))
+    }
+
+    /// 可用空间的百分比（0-100）
+    pub fn available_percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.available as f64 / self.total as f64 * 100.0
+    }
+
+    /// 已使用空间的百分比（0-100）
+    pub fn used_percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.total.saturating_sub(self.available)) as f64 / self.total as f64 * 100.0
+    }
+}
+
+/// 可用空间阈值：绝对大小，或占文件系统总大小的百分比
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FreeSpaceThreshold {
+    /// 可用空间的字节数
+    Bytes(u64),
+    /// 可用空间占总大小的百分比（0-100）
+    Percent(f64),
+}
+
+impl FreeSpaceThreshold {
+    /// 判断文件系统的可用空间是否低于阈值
+    ///
+    /// # 参数
+    /// * `space` - 文件系统的空间信息
+    ///
+    /// # 返回
+    /// 可用空间低于阈值时返回 `true`
+    pub fn is_low(&self, space: &DiskSpace) -> bool {
+        match *self {
+            FreeSpaceThreshold::Bytes(bytes) => space.available < bytes,
+            FreeSpaceThreshold::Percent(percent) => space.available_percent() < percent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_query_disk_space() {
+        let temp_dir = TempDir::new().unwrap();
+        let space = DiskSpace::query(temp_dir.path()).unwrap();
+
+        assert!(space.total > 0);
+        assert!(space.available <= space.total);
+        assert!((0.0..=100.0).contains(&space.used_percent()));
+
+        // 不存在的路径返回错误
+        assert!(DiskSpace::query(&temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_used_percent() {
+        let space = DiskSpace {
+            total: 1000,
+            available: 250,
+        };
+        assert_eq!(space.used_percent(), 75.0);
+        assert_eq!(space.available_percent(), 25.0);
+    }
+
+    #[test]
+    fn test_free_space_threshold() {
+        let space = DiskSpace {
+            total: 1000,
+            available: 230,
+        };
+
+        // 23% 可用：高于 10% 阈值时不算空间不足，低于 30% 阈值时算
+        assert!(!FreeSpaceThreshold::Percent(10.0).is_low(&space));
+        assert!(FreeSpaceThreshold::Percent(30.0).is_low(&space));
+
+        // 绝对大小阈值，恰好等于阈值时不算空间不足
+        assert!(!FreeSpaceThreshold::Bytes(230).is_low(&space));
+        assert!(FreeSpaceThreshold::Bytes(500).is_low(&space));
+
+        // 无法得到总大小的文件系统按 0% 可用处理
+        let empty = DiskSpace {
+            total: 0,
+            available: 0,
+        };
+        assert!(FreeSpaceThreshold::Percent(10.0).is_low(&empty));
+    }
+}
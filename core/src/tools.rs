@@ -0,0 +1,153 @@
+use crate::config::ProjectType;
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// 工具缓存发现器，从常见构建工具的配置中定位其全局缓存目录
+///
+/// 优先读取环境变量，未设置时再调用对应的命令查询（如 `npm config get cache`）。
+pub struct ToolCacheDiscovery;
+
+impl ToolCacheDiscovery {
+    /// 查找与项目类型相关的工具缓存目录
+    ///
+    /// # 参数
+    /// * `project_type` - 项目类型，`Unknown` 时查找所有支持的工具
+    ///
+    /// # 返回
+    /// 存在的缓存目录列表
+    pub fn discover_tool_caches(project_type: &ProjectType) -> Vec<PathBuf> {
+        Self::discover_with(
+            project_type,
+            |name| env::var(name).ok().filter(|value| !value.is_empty()),
+            |program, args| {
+                let output = Command::new(program).args(args).output().ok()?;
+                output
+                    .status
+                    .success()
+                    .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                    .filter(|value| !value.is_empty())
+            },
+        )
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .collect()
+    }
+
+    /// 使用指定的环境变量读取和命令执行方式查找工具缓存目录（不检查目录是否存在）
+    ///
+    /// # 参数
+    /// * `project_type` - 项目类型，`Unknown` 时查找所有支持的工具
+    /// * `env_var` - 读取环境变量，未设置时返回 `None`
+    /// * `run` - 执行命令并返回其输出，失败时返回 `None`
+    ///
+    /// # 返回
+    /// 候选缓存目录列表
+    pub fn discover_with<E, R>(project_type: &ProjectType, env_var: E, run: R) -> Vec<PathBuf>
+    where
+        E: Fn(&str) -> Option<String>,
+        R: Fn(&str, &[&str]) -> Option<String>,
+    {
+        let wants =
+            |tool: ProjectType| *project_type == ProjectType::Unknown || *project_type == tool;
+        let mut caches = Vec::new();
+
+        if wants(ProjectType::Rust) {
+            let cargo_home = env_var("CARGO_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env_var("HOME").map(|home| PathBuf::from(home).join(".cargo")));
+            if let Some(cargo_home) = cargo_home {
+                caches.push(cargo_home.join("registry"));
+                caches.push(cargo_home.join("git"));
+            }
+        }
+
+        if wants(ProjectType::NodeJs) {
+            caches.extend(
+                env_var("npm_config_cache")
+                    .or_else(|| run("npm", &["config", "get", "cache"]))
+                    .map(PathBuf::from),
+            );
+        }
+
+        if wants(ProjectType::Python) {
+            caches.extend(
+                env_var("PIP_CACHE_DIR")
+                    .or_else(|| run("pip", &["cache", "dir"]))
+                    .map(PathBuf::from),
+            );
+        }
+
+        if wants(ProjectType::Go) {
+            caches.extend(
+                env_var("GOCACHE")
+                    .or_else(|| run("go", &["env", "GOCACHE"]))
+                    .map(PathBuf::from),
+            );
+        }
+
+        caches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_discover_from_env_vars() {
+        let vars: HashMap<&str, &str> = [
+            ("GOCACHE", "/home/dev/.cache/go-build"),
+            ("CARGO_HOME", "/opt/cargo"),
+            ("npm_config_cache", "/home/dev/.npm"),
+            ("PIP_CACHE_DIR", "/home/dev/.cache/pip"),
+        ]
+        .into_iter()
+        .collect();
+        let env_var = |name: &str| vars.get(name).map(|value| value.to_string());
+        // 环境变量已设置时不应调用外部命令
+        let run = |program: &str, _: &[&str]| -> Option<String> {
+            panic!("unexpected command: {}", program)
+        };
+
+        assert_eq!(
+            ToolCacheDiscovery::discover_with(&ProjectType::Go, env_var, run),
+            vec![PathBuf::from("/home/dev/.cache/go-build")]
+        );
+        assert_eq!(
+            ToolCacheDiscovery::discover_with(&ProjectType::Rust, env_var, run),
+            vec![
+                PathBuf::from("/opt/cargo/registry"),
+                PathBuf::from("/opt/cargo/git")
+            ]
+        );
+        assert_eq!(
+            ToolCacheDiscovery::discover_with(&ProjectType::Unknown, env_var, run).len(),
+            5
+        );
+        assert!(ToolCacheDiscovery::discover_with(&ProjectType::Java, env_var, run).is_empty());
+    }
+
+    #[test]
+    fn test_discover_falls_back_to_commands() {
+        let env_var = |name: &str| (name == "HOME").then(|| "/home/dev".to_string());
+        let run = |program: &str, args: &[&str]| match (program, args) {
+            ("go", ["env", "GOCACHE"]) => Some("/tmp/go-build".to_string()),
+            _ => None,
+        };
+
+        assert_eq!(
+            ToolCacheDiscovery::discover_with(&ProjectType::Go, env_var, run),
+            vec![PathBuf::from("/tmp/go-build")]
+        );
+        assert_eq!(
+            ToolCacheDiscovery::discover_with(&ProjectType::Rust, env_var, run),
+            vec![
+                PathBuf::from("/home/dev/.cargo/registry"),
+                PathBuf::from("/home/dev/.cargo/git")
+            ]
+        );
+        assert!(ToolCacheDiscovery::discover_with(&ProjectType::NodeJs, env_var, run).is_empty());
+    }
+}